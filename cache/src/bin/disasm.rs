@@ -0,0 +1,52 @@
+use cache::{Disassembler, ObjProvider, ScriptFile, ScriptProvider, COMPILER_VERSION};
+
+/// Prints the disassembly of scripts from a packed cache.
+///
+/// `disasm <script> [dir]` where the script is a name like `[proc,fib]` or
+/// `opheld1,bronze_dagger`, a bare trigger like `opheld1` to dump every script
+/// of that trigger, or a script id. `dir` defaults to `./data/pack`.
+fn main() {
+    let query: String = match std::env::args().nth(1) {
+        Some(query) => query,
+        None => {
+            eprintln!("Usage: disasm <script|trigger|id> [dir]");
+            std::process::exit(1);
+        }
+    };
+    let dir: String = std::env::args().nth(2).unwrap_or("./data/pack".to_string());
+
+    let scripts: ScriptProvider = ScriptProvider::io(&dir, COMPILER_VERSION.to_string());
+    let objs: ObjProvider = ObjProvider::io(&dir, true);
+    let disassembler: Disassembler = Disassembler::new(&scripts, &objs);
+
+    let found: Vec<&ScriptFile> = find(&scripts, &query);
+    if found.is_empty() {
+        eprintln!("No scripts found for: {}", query);
+        std::process::exit(1);
+    }
+    for script in found {
+        println!("{}", disassembler.disassemble(script));
+    }
+}
+
+fn find<'a>(scripts: &'a ScriptProvider, query: &str) -> Vec<&'a ScriptFile> {
+    if let Ok(id) = query.parse::<usize>() {
+        return scripts.get_by_id(id).into_iter().collect();
+    }
+    let name: &str = query.trim_start_matches('[').trim_end_matches(']');
+    if name.contains(',') {
+        return scripts
+            .get_by_name(&format!("[{}]", name))
+            .into_iter()
+            .collect();
+    }
+    let prefix: String = format!("[{},", name);
+    let mut found: Vec<&ScriptFile> = scripts
+        .scripts
+        .iter()
+        .flatten()
+        .filter(|script| script.info.name.starts_with(&prefix))
+        .collect();
+    found.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+    return found;
+}
@@ -1,22 +1,52 @@
-use crate::{ObjProvider, ScriptProvider};
+use std::rc::Rc;
+
+use crate::{ObjProvider, ScriptChanges, ScriptProvider, COMPILER_VERSION};
 
 pub struct CacheProvider {
-    pub script_provider: ScriptProvider,
+    pub script_provider: Rc<ScriptProvider>, // shared with runs that outlive a reload
     pub obj_provider: ObjProvider,
+    dir: String,
+    compiler_version: String,
 }
 
 impl CacheProvider {
     pub fn new(dir: &str, compiler_version: String, members: bool) -> CacheProvider {
         return CacheProvider {
-            script_provider: ScriptProvider::io(dir, compiler_version),
+            script_provider: Rc::new(ScriptProvider::io(dir, compiler_version.clone())),
             obj_provider: ObjProvider::io(dir, members),
+            dir: dir.to_string(),
+            compiler_version,
         };
     }
 
     pub fn mock() -> CacheProvider {
         return CacheProvider {
-            script_provider: ScriptProvider::mock(),
+            script_provider: Rc::new(ScriptProvider::mock()),
             obj_provider: ObjProvider::mock(),
+            dir: String::new(),
+            compiler_version: String::new(),
         };
     }
+
+    /// A mock without configs that loads and reloads its scripts from `dir`.
+    pub fn mock_scripts(dir: &str) -> CacheProvider {
+        return CacheProvider {
+            dir: dir.to_string(),
+            compiler_version: COMPILER_VERSION.to_string(),
+            ..CacheProvider::mock()
+        };
+    }
+
+    /// Reads `script.dat` again and swaps it in, keeping the current scripts
+    /// when it can't be loaded.
+    ///
+    /// A run borrows its scripts, a run that has to outlive the swap borrows them
+    /// from its own clone of `script_provider` and finishes on the old scripts,
+    /// which are dropped with the last clone.
+    pub fn reload_scripts(&mut self) -> Result<ScriptChanges, String> {
+        let provider: ScriptProvider = ScriptProvider::load(&self.dir, &self.compiler_version)?;
+        let changes: ScriptChanges = provider.changes(&self.script_provider);
+        self.script_provider = Rc::new(provider);
+        return Ok(changes);
+    }
 }
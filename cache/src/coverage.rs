@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::{ScriptFile, ScriptProvider};
+
+/// How often each instruction of a single script ran.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptHits {
+    pub name: String,
+    pub path: String,
+    pub hits: Vec<u64>, // indexed by pc
+    calls: u64,
+    pcs: Vec<i32>, // the line table of the script
+    lines: Vec<i32>,
+}
+
+impl ScriptHits {
+    fn new(script: &ScriptFile) -> ScriptHits {
+        let length: usize = script.codes.as_ref().map_or(0, |codes| codes.len());
+        return ScriptHits {
+            name: script.info.name.clone(),
+            path: script.info.path.clone(),
+            hits: vec![0; length],
+            calls: 0,
+            pcs: script.info.pcs.clone(),
+            lines: script.info.lines.clone(),
+        };
+    }
+
+    /// How often each source line ran, a line counts as often as its most
+    /// run instruction so a line that was only partly run is still hit.
+    pub fn line_hits(&self) -> BTreeMap<i32, u64> {
+        let mut lines: BTreeMap<i32, u64> = BTreeMap::new();
+        for (index, &start) in self.pcs.iter().enumerate() {
+            let end: usize = self
+                .pcs
+                .get(index + 1)
+                .map_or(self.hits.len(), |&end| (end as usize).min(self.hits.len()));
+            let start: usize = (start as usize).min(end);
+            let hits: u64 = self.hits[start..end].iter().copied().max().unwrap_or(0);
+            let line: &mut u64 = lines.entry(self.lines[index]).or_insert(0);
+            *line = (*line).max(hits);
+        }
+        return lines;
+    }
+
+    /// How often the script was entered, by a run or a gosub or jump into it.
+    pub fn calls(&self) -> u64 {
+        return self.calls;
+    }
+}
+
+/// Records which instructions run for scripts run by `ScriptState::execute_covered`,
+/// or by `execute` when the runner has coverage attached.
+///
+/// Scripts only show up once they run, `add_all` registers every loaded script
+/// so the ones that never ran are reported as missed.
+pub struct ScriptCoverage {
+    pub scripts: HashMap<usize, ScriptHits>, // by script id
+}
+
+impl ScriptCoverage {
+    pub fn new() -> ScriptCoverage {
+        return ScriptCoverage {
+            scripts: HashMap::new(),
+        };
+    }
+
+    pub fn reset(&mut self) {
+        self.scripts.clear();
+    }
+
+    /// Registers a script without running it.
+    pub fn add(&mut self, script: &ScriptFile) -> &mut ScriptHits {
+        return self
+            .scripts
+            .entry(script.id())
+            .or_insert_with(|| ScriptHits::new(script));
+    }
+
+    /// Registers every script of the provider.
+    pub fn add_all(&mut self, provider: &ScriptProvider) {
+        for script in provider.scripts.iter().flatten() {
+            self.add(script);
+        }
+    }
+
+    /// Counts a script being entered, by a run or a gosub or jump.
+    pub fn enter(&mut self, script: &ScriptFile) {
+        self.add(script).calls += 1;
+    }
+
+    /// Counts the instruction at `pc` of `script` as run.
+    pub fn hit(&mut self, script: &ScriptFile, pc: i32) {
+        let hits: &mut ScriptHits = self.add(script);
+        if let Some(count) = hits.hits.get_mut(pc as usize) {
+            *count += 1;
+        }
+    }
+
+    /// The coverage in the lcov tracefile format, one record per source file
+    /// with every script in it as a function.
+    pub fn lcov(&self) -> String {
+        let mut files: BTreeMap<&str, Vec<&ScriptHits>> = BTreeMap::new();
+        for hits in self.scripts.values() {
+            files.entry(&hits.path).or_default().push(hits);
+        }
+
+        let mut out: String = String::new();
+        for (path, mut scripts) in files {
+            scripts.sort_by(|a, b| a.name.cmp(&b.name));
+            let _ = writeln!(out, "TN:");
+            let _ = writeln!(out, "SF:{}", path);
+
+            let mut lines: BTreeMap<i32, u64> = BTreeMap::new();
+            for script in &scripts {
+                let first: i32 = script.lines.first().copied().unwrap_or(0);
+                let _ = writeln!(out, "FN:{},{}", first, script.name);
+                for (line, hits) in script.line_hits() {
+                    *lines.entry(line).or_insert(0) += hits;
+                }
+            }
+            for script in &scripts {
+                let _ = writeln!(out, "FNDA:{},{}", script.calls(), script.name);
+            }
+            let _ = writeln!(out, "FNF:{}", scripts.len());
+            let _ = writeln!(
+                out,
+                "FNH:{}",
+                scripts.iter().filter(|script| script.calls() > 0).count()
+            );
+
+            for (line, hits) in &lines {
+                let _ = writeln!(out, "DA:{},{}", line, hits);
+            }
+            let _ = writeln!(out, "LF:{}", lines.len());
+            let _ = writeln!(
+                out,
+                "LH:{}",
+                lines.values().filter(|&&hits| hits > 0).count()
+            );
+            let _ = writeln!(out, "end_of_record");
+        }
+        return out;
+    }
+}
+
+impl Default for ScriptCoverage {
+    fn default() -> ScriptCoverage {
+        return ScriptCoverage::new();
+    }
+}
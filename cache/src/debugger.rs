@@ -0,0 +1,341 @@
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+use crate::{ScriptFrame, ScriptState};
+
+/// Pauses a script before the first instruction of a source line.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Breakpoint {
+    pub script: String, // the script name, `[proc,foo]`
+    pub line: i32,
+}
+
+impl Breakpoint {
+    /// Parses a breakpoint written as `<script>:<line>`, like `[proc,fib]:2`.
+    pub fn parse(value: &str) -> Result<Breakpoint, String> {
+        let (script, line) = value
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Invalid breakpoint, expected <script>:<line>: {}", value))?;
+        return Ok(Breakpoint {
+            script: script.to_string(),
+            line: line
+                .parse()
+                .map_err(|_| format!("Invalid breakpoint line: {}", line))?,
+        });
+    }
+}
+
+/// Why the debugger stopped a script.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PauseReason {
+    Breakpoint(Breakpoint),
+    Step,
+}
+
+/// What the frontend wants the script to do after a pause.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DebugCommand {
+    Continue, // run until the next breakpoint
+    Step,     // run a single instruction
+    Next,     // run until the next source line, in this script or one it calls
+    Abort,
+}
+
+/// A line typed into a line based frontend like `ConsoleDebugHook`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DebugLine {
+    Command(DebugCommand),
+    Print,
+    Backtrace,
+    Break(Breakpoint),
+    Delete(Breakpoint),
+    Reload,
+    Invalid(String), // the error to show the frontend
+}
+
+/// What a frontend can ask for while no script is paused.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DebugRequest {
+    Pause,  // pause whichever script runs next
+    Reload, // reload the scripts at the end of the tick
+}
+
+impl DebugLine {
+    /// Commands are `c` continue, `s` step, `n` next line, `p` print the state,
+    /// `bt` stack trace, `b <script> <line>` and `d <script> <line>` to add and
+    /// delete breakpoints, `q` to abort the script and `reload` to reload the
+    /// scripts once no script is paused.
+    pub fn parse(line: &str) -> DebugLine {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        return match parts.as_slice() {
+            ["c"] | ["continue"] => DebugLine::Command(DebugCommand::Continue),
+            ["s"] | ["step"] => DebugLine::Command(DebugCommand::Step),
+            ["n"] | ["next"] => DebugLine::Command(DebugCommand::Next),
+            ["q"] | ["abort"] => DebugLine::Command(DebugCommand::Abort),
+            ["p"] | ["print"] => DebugLine::Print,
+            ["bt"] => DebugLine::Backtrace,
+            ["reload"] => DebugLine::Reload,
+            [kind @ ("b" | "d"), script, line] => match line.parse::<i32>() {
+                Ok(line) => {
+                    let breakpoint: Breakpoint = Breakpoint {
+                        script: script.to_string(),
+                        line,
+                    };
+                    if *kind == "b" {
+                        DebugLine::Break(breakpoint)
+                    } else {
+                        DebugLine::Delete(breakpoint)
+                    }
+                }
+                Err(_) => DebugLine::Invalid(format!("Invalid line: {}", line)),
+            },
+            _ => DebugLine::Invalid(format!("Unknown command: {}", line.trim())),
+        };
+    }
+
+    /// Adds or deletes the breakpoint of a `b` or `d` line.
+    ///
+    /// # Returns
+    ///
+    /// `false` when the line doesn't change breakpoints.
+    pub fn change(&self, breakpoints: &mut Vec<Breakpoint>) -> bool {
+        return match self {
+            DebugLine::Break(breakpoint) | DebugLine::Delete(breakpoint) => {
+                breakpoints.retain(|existing| existing != breakpoint);
+                if let DebugLine::Break(breakpoint) = self {
+                    breakpoints.push(breakpoint.clone());
+                }
+                true
+            }
+            _ => false,
+        };
+    }
+
+    /// Carries out the line for a paused script, writing anything it prints to `output`.
+    ///
+    /// # Returns
+    ///
+    /// The command to carry on with, or `None` to wait for another line.
+    pub fn respond(
+        self,
+        state: &ScriptState,
+        breakpoints: &mut Vec<Breakpoint>,
+        output: &mut dyn Write,
+    ) -> Option<DebugCommand> {
+        match &self {
+            DebugLine::Command(command) => return Some(command.clone()),
+            DebugLine::Print => {
+                let _ = write!(output, "{}", ScriptDebugger::inspect(state));
+            }
+            DebugLine::Backtrace => {
+                for frame in state.stack_trace() {
+                    let _ = writeln!(output, "{}", frame);
+                }
+            }
+            DebugLine::Break(_) | DebugLine::Delete(_) => {
+                self.change(breakpoints);
+            }
+            DebugLine::Reload => {
+                let _ = writeln!(output, "Scripts can't be reloaded while one is paused");
+            }
+            DebugLine::Invalid(error) => {
+                let _ = writeln!(output, "{}", error);
+            }
+        }
+        return None;
+    }
+}
+
+/// A frontend attached to the debugger, a console or a socket.
+///
+/// `on_pause` blocks the script until the frontend decides how to carry on,
+/// it can change the breakpoints while the script is paused.
+pub trait DebugHook {
+    fn on_pause(
+        &mut self,
+        state: &ScriptState,
+        reason: PauseReason,
+        breakpoints: &mut Vec<Breakpoint>,
+    ) -> DebugCommand;
+
+    /// Picks up what the frontend sent while no script was paused, called
+    /// between ticks.
+    fn poll(&mut self, _breakpoints: &mut Vec<Breakpoint>) -> Vec<DebugRequest> {
+        return Vec::new();
+    }
+}
+
+/// Breakpoints and stepping for scripts run by `ScriptState::execute_debug`,
+/// or by `execute` when the runner has a debugger attached.
+pub struct ScriptDebugger {
+    pub breakpoints: Vec<Breakpoint>,
+    stepping: Option<DebugCommand>,
+    hook: Box<dyn DebugHook>,
+}
+
+impl ScriptDebugger {
+    pub fn new(hook: Box<dyn DebugHook>) -> ScriptDebugger {
+        return ScriptDebugger {
+            breakpoints: Vec::new(),
+            stepping: None,
+            hook,
+        };
+    }
+
+    pub fn add_breakpoint(&mut self, script: &str, line: i32) {
+        let breakpoint: Breakpoint = Breakpoint {
+            script: script.to_string(),
+            line,
+        };
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, script: &str, line: i32) {
+        self.breakpoints
+            .retain(|breakpoint| breakpoint.script != script || breakpoint.line != line);
+    }
+
+    /// Pauses before the next instruction of whichever script runs next.
+    pub fn step(&mut self) {
+        self.stepping = Some(DebugCommand::Step);
+    }
+
+    /// Lets the hook pick up breakpoints and requests sent while nothing was paused.
+    ///
+    /// # Returns
+    ///
+    /// `true` when the frontend asked for the scripts to be reloaded.
+    pub fn poll(&mut self) -> bool {
+        let requests: Vec<DebugRequest> = self.hook.poll(&mut self.breakpoints);
+        if requests.contains(&DebugRequest::Pause) {
+            self.step();
+        }
+        return requests.contains(&DebugRequest::Reload);
+    }
+
+    /// Checks whether the script should pause before running its next
+    /// instruction, and if so hands it to the hook until it carries on.
+    ///
+    /// # Returns
+    ///
+    /// `false` when the hook aborted the script.
+    pub fn check(&mut self, state: &ScriptState) -> bool {
+        let reason: PauseReason = match self.pause_reason(state) {
+            Some(reason) => reason,
+            None => return true,
+        };
+        let command: DebugCommand = self.hook.on_pause(state, reason, &mut self.breakpoints);
+        return match command {
+            DebugCommand::Continue => {
+                self.stepping = None;
+                true
+            }
+            DebugCommand::Step | DebugCommand::Next => {
+                self.stepping = Some(command);
+                true
+            }
+            DebugCommand::Abort => {
+                self.stepping = None;
+                false
+            }
+        };
+    }
+
+    fn pause_reason(&self, state: &ScriptState) -> Option<PauseReason> {
+        let pc: i32 = state.pc + 1;
+        if self.stepping == Some(DebugCommand::Step) {
+            return Some(PauseReason::Step);
+        }
+        // everything else only pauses at the start of a line.
+        let pcs: &Vec<i32> = &state.script.info.pcs;
+        let index: usize = pcs.binary_search(&pc).ok()?;
+        if self.stepping == Some(DebugCommand::Next) {
+            return Some(PauseReason::Step);
+        }
+        let line: i32 = state.script.info.lines[index];
+        return self
+            .breakpoints
+            .iter()
+            .find(|breakpoint| {
+                breakpoint.line == line && breakpoint.script == state.script.info.name
+            })
+            .map(|breakpoint| PauseReason::Breakpoint(breakpoint.clone()));
+    }
+
+    /// Where a paused script will continue from, the instruction after `pc`.
+    pub fn location(state: &ScriptState) -> ScriptFrame {
+        let pc: i32 = state.pc + 1;
+        return ScriptFrame {
+            script: state.script.info.name.clone(),
+            path: state.script.info.path.clone(),
+            pc,
+            line: state.script.info.line(pc),
+        };
+    }
+
+    /// Describes a paused script for a frontend: where it is, the callers,
+    /// the values on both stacks, the locals and the active pointers.
+    pub fn inspect(state: &ScriptState) -> String {
+        let mut out: String = String::new();
+        let _ = writeln!(out, "at {}", ScriptDebugger::location(state));
+        for frame in state.stack_trace().iter().skip(1) {
+            let _ = writeln!(out, "from {}", frame);
+        }
+        let _ = writeln!(out, "int stack: {:?}", &state.int_stack[..state.isp]);
+        let _ = writeln!(out, "string stack: {:?}", &state.string_stack[..state.ssp]);
+        let _ = writeln!(out, "int locals: {:?}", state.int_locals);
+        let _ = writeln!(out, "string locals: {:?}", state.string_locals);
+        if !state.arrays.is_empty() {
+            let _ = writeln!(out, "arrays: {:?}", state.arrays);
+        }
+        let _ = writeln!(out, "pointers: {}", state.pointer_debug());
+        return out;
+    }
+}
+
+/// A line based frontend, reading commands from `input` and writing to `output`,
+/// see `DebugLine` for the commands.
+///
+/// Works over stdin and stdout or both halves of a `TcpStream`.
+pub struct ConsoleDebugHook<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> ConsoleDebugHook<R, W> {
+    pub fn new(input: R, output: W) -> ConsoleDebugHook<R, W> {
+        return ConsoleDebugHook { input, output };
+    }
+}
+
+impl<R: BufRead, W: Write> DebugHook for ConsoleDebugHook<R, W> {
+    fn on_pause(
+        &mut self,
+        state: &ScriptState,
+        reason: PauseReason,
+        breakpoints: &mut Vec<Breakpoint>,
+    ) -> DebugCommand {
+        let location: ScriptFrame = ScriptDebugger::location(state);
+        let _ = match reason {
+            PauseReason::Breakpoint(_) => writeln!(self.output, "breakpoint at {}", location),
+            PauseReason::Step => writeln!(self.output, "paused at {}", location),
+        };
+        loop {
+            let _ = write!(self.output, "> ");
+            let _ = self.output.flush();
+            let mut line: String = String::new();
+            // a closed frontend lets the script finish.
+            if self.input.read_line(&mut line).unwrap_or(0) == 0 {
+                breakpoints.clear();
+                return DebugCommand::Continue;
+            }
+            if let Some(command) =
+                DebugLine::parse(&line).respond(state, breakpoints, &mut self.output)
+            {
+                return command;
+            }
+        }
+    }
+}
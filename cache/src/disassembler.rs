@@ -0,0 +1,224 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::{ObjProvider, ScriptFile, ScriptOpcode, ScriptProvider};
+
+/// Turns a compiled script back into readable instructions.
+///
+/// Branch and switch targets are printed as labels, gosubs by the name of the
+/// script they call and obj constants by their debugname.
+pub struct Disassembler<'a> {
+    scripts: &'a ScriptProvider,
+    objs: &'a ObjProvider,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(scripts: &'a ScriptProvider, objs: &'a ObjProvider) -> Disassembler<'a> {
+        return Disassembler { scripts, objs };
+    }
+
+    /// Disassembles a script, one instruction per line.
+    ///
+    /// Each source line starts with a `// path:line` comment and switch
+    /// tables are listed under the instruction that uses them.
+    pub fn disassemble(&self, script: &ScriptFile) -> String {
+        let mut out: String = String::new();
+        let _ = writeln!(out, "{} ({})", script.info.name, script.info.path);
+        let _ = writeln!(
+            out,
+            "int args: {}, string args: {}, int locals: {}, string locals: {}",
+            script.int_arg_count,
+            script.string_arg_count,
+            script.int_local_count,
+            script.string_local_count
+        );
+
+        let codes: &Vec<Option<ScriptOpcode>> = match &script.codes {
+            Some(codes) => codes,
+            None => return out,
+        };
+
+        let labels: BTreeSet<i32> = Disassembler::labels(script, codes);
+        let objs: BTreeSet<usize> = Disassembler::obj_constants(codes);
+        for (pc, code) in codes.iter().enumerate() {
+            let code: &ScriptOpcode = match code {
+                Some(code) => code,
+                None => continue,
+            };
+            if labels.contains(&(pc as i32)) {
+                let _ = writeln!(out, "label_{}:", pc);
+            }
+            if let Ok(index) = script.info.pcs.binary_search(&(pc as i32)) {
+                let _ = writeln!(
+                    out,
+                    "    // {}:{}",
+                    script.info.path, script.info.lines[index]
+                );
+            }
+            let operand: i32 = script.int_operands[pc];
+            let _ = write!(out, "{:>6}  {:?}", pc, code);
+            match code {
+                ScriptOpcode::Branch
+                | ScriptOpcode::BranchNot
+                | ScriptOpcode::BranchEquals
+                | ScriptOpcode::BranchLessThan
+                | ScriptOpcode::BranchGreaterThan
+                | ScriptOpcode::BranchLessThanOrEquals
+                | ScriptOpcode::BranchGreaterThanOrEquals => {
+                    let _ = write!(out, " label_{}", pc as i32 + operand + 1);
+                }
+                ScriptOpcode::PushConstantString => {
+                    let _ = write!(out, " {:?}", script.string_operands[pc]);
+                }
+                ScriptOpcode::PushConstantInt if objs.contains(&pc) => {
+                    match self.objs.get_by_id(operand as usize) {
+                        Ok(obj) => {
+                            let name: &str = obj.debugname.as_deref().unwrap_or("?");
+                            let _ = write!(out, " {} ({})", operand, name);
+                        }
+                        Err(_) => {
+                            let _ = write!(out, " {}", operand);
+                        }
+                    }
+                }
+                ScriptOpcode::GoSubWithParams | ScriptOpcode::JumpWithParams => {
+                    match self.scripts.get_by_id(operand as usize) {
+                        Ok(target) => {
+                            let _ = write!(out, " {}", target.info.name);
+                        }
+                        Err(_) => {
+                            let _ = write!(out, " {} (missing)", operand);
+                        }
+                    }
+                }
+                ScriptOpcode::Switch => {
+                    let _ = write!(out, " {}", operand);
+                    if let Some(table) = script.switch_tables.get(operand as usize) {
+                        let mut cases: Vec<(&i32, &i32)> = table.iter().collect();
+                        cases.sort();
+                        for (key, offset) in cases {
+                            let _ = write!(
+                                out,
+                                "\n            case {}: label_{}",
+                                key,
+                                pc as i32 + offset + 1
+                            );
+                        }
+                    }
+                }
+                _ if ScriptFile::is_large_operand(code) || operand != 0 => {
+                    let _ = write!(out, " {}", operand);
+                }
+                _ => {}
+            }
+            out.push('\n');
+        }
+        return out;
+    }
+
+    /// Every pc a branch or switch can jump to.
+    fn labels(script: &ScriptFile, codes: &[Option<ScriptOpcode>]) -> BTreeSet<i32> {
+        let mut labels: BTreeSet<i32> = BTreeSet::new();
+        for (pc, code) in codes.iter().enumerate() {
+            let pc: i32 = pc as i32;
+            let operand: i32 = script.int_operands[pc as usize];
+            match code {
+                Some(ScriptOpcode::Branch)
+                | Some(ScriptOpcode::BranchNot)
+                | Some(ScriptOpcode::BranchEquals)
+                | Some(ScriptOpcode::BranchLessThan)
+                | Some(ScriptOpcode::BranchGreaterThan)
+                | Some(ScriptOpcode::BranchLessThanOrEquals)
+                | Some(ScriptOpcode::BranchGreaterThanOrEquals) => {
+                    labels.insert(pc + operand + 1);
+                }
+                Some(ScriptOpcode::Switch) => {
+                    if let Some(table) = script.switch_tables.get(operand as usize) {
+                        labels.extend(table.values().map(|offset| pc + offset + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        return labels;
+    }
+
+    /// The pcs of the int constants that are passed as objs.
+    ///
+    /// The bytecode has no types, so this only finds constants pushed directly
+    /// before a command that takes an obj, with nothing but other pushes between.
+    fn obj_constants(codes: &[Option<ScriptOpcode>]) -> BTreeSet<usize> {
+        let mut objs: BTreeSet<usize> = BTreeSet::new();
+        for (pc, code) in codes.iter().enumerate() {
+            let depths: &[usize] = match code {
+                Some(code) => Disassembler::obj_args(code),
+                None => continue,
+            };
+            for &depth in depths {
+                // walk back over the int pushes to the one `depth` from the top.
+                let mut remaining: usize = depth;
+                let mut index: usize = pc;
+                while index > 0 {
+                    index -= 1;
+                    match &codes[index] {
+                        Some(ScriptOpcode::PushConstantString)
+                        | Some(ScriptOpcode::PushStringLocal) => continue,
+                        Some(ScriptOpcode::PushConstantInt) if remaining == 0 => {
+                            objs.insert(index);
+                            break;
+                        }
+                        Some(ScriptOpcode::PushConstantInt)
+                        | Some(ScriptOpcode::PushIntLocal)
+                        | Some(ScriptOpcode::PushVarp)
+                        | Some(ScriptOpcode::PushVarn)
+                        | Some(ScriptOpcode::PushVarbit)
+                            if remaining > 0 =>
+                        {
+                            remaining -= 1;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+        return objs;
+    }
+
+    /// Where the obj arguments of a command are on the int stack, 0 being the top.
+    fn obj_args(code: &ScriptOpcode) -> &'static [usize] {
+        return match code {
+            ScriptOpcode::OcCategory
+            | ScriptOpcode::OcCert
+            | ScriptOpcode::OcCost
+            | ScriptOpcode::OcDebugname
+            | ScriptOpcode::OcDesc
+            | ScriptOpcode::OcMembers
+            | ScriptOpcode::OcName
+            | ScriptOpcode::OcStackable
+            | ScriptOpcode::OcTradeable
+            | ScriptOpcode::OcUncert
+            | ScriptOpcode::OcWearPos
+            | ScriptOpcode::OcWearPos2
+            | ScriptOpcode::OcWearPos3
+            | ScriptOpcode::OcWeight
+            | ScriptOpcode::ObjFind
+            | ScriptOpcode::InvStockBase
+            | ScriptOpcode::InvTotal => &[0],
+            ScriptOpcode::OcParam
+            | ScriptOpcode::IfSetObject
+            | ScriptOpcode::InvAdd
+            | ScriptOpcode::InvDel
+            | ScriptOpcode::InvMoveItem
+            | ScriptOpcode::InvMoveItemCert
+            | ScriptOpcode::InvMoveItemUncert
+            | ScriptOpcode::InvSetSlot => &[1],
+            ScriptOpcode::ObjAdd
+            | ScriptOpcode::ObjAddAll
+            | ScriptOpcode::InvDropItem
+            | ScriptOpcode::InvItemSpace
+            | ScriptOpcode::InvItemSpace2 => &[2],
+            ScriptOpcode::InvChangeSlot => &[1, 2],
+            _ => &[],
+        };
+    }
+}
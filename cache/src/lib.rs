@@ -1,20 +1,50 @@
 pub use cache::CacheProvider;
+pub use coverage::ScriptCoverage;
+pub use coverage::ScriptHits;
+pub use debugger::Breakpoint;
+pub use debugger::ConsoleDebugHook;
+pub use debugger::DebugCommand;
+pub use debugger::DebugHook;
+pub use debugger::DebugLine;
+pub use debugger::DebugRequest;
+pub use debugger::PauseReason;
+pub use debugger::ScriptDebugger;
+pub use disassembler::Disassembler;
+pub use limits::ScriptLimits;
+pub use limits::TriggerLimits;
 pub use obj::ObjProvider;
 pub use obj::ObjType;
 pub use param::decode_params;
 pub use param::ParamValue;
+pub use profiler::OpcodeMetrics;
+pub use profiler::ScriptMetrics;
+pub use profiler::ScriptProfiler;
+pub use profiler::ScriptRun;
+pub use script::ScriptChanges;
 pub use script::ScriptEngine;
+pub use script::ScriptError;
+pub use script::ScriptErrorKind;
 pub use script::ScriptExecutionState;
 pub use script::ScriptFile;
+pub use script::ScriptFrame;
 pub use script::ScriptInfo;
+pub use script::ScriptLoc;
 pub use script::ScriptOpcode;
 pub use script::ScriptPlayer;
 pub use script::ScriptPointer;
 pub use script::ScriptProvider;
 pub use script::ScriptRunner;
 pub use script::ScriptState;
+pub use script::COMPILER_VERSION;
+pub use verifier::ScriptVerifier;
 
 mod cache;
+mod coverage;
+mod debugger;
+mod disassembler;
+mod limits;
 mod obj;
 mod param;
+mod profiler;
 mod script;
+mod verifier;
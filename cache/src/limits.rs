@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::ScriptFile;
+
+/// How much a single script is allowed to use, going over any of these
+/// aborts the script with an error instead of taking down the tick.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScriptLimits {
+    pub instructions: i32,    // instructions over the whole script, across suspends
+    pub frames: usize,        // how deep gosubs can nest
+    pub int_stack: usize,     // ints on the stack at once
+    pub string_stack: usize,  // strings on the stack at once
+    pub string_length: usize, // the longest string a script can build, in bytes
+    pub array_size: usize,    // the most elements in a single array
+}
+
+impl ScriptLimits {
+    pub const DEFAULT: ScriptLimits = ScriptLimits {
+        instructions: 500_000,
+        frames: 50,
+        int_stack: 1000,
+        string_stack: 1000,
+        string_length: 5000,
+        array_size: 5000,
+    };
+}
+
+/// Limits chosen by the trigger of the script a run starts in, so `[debugproc,*]`
+/// and other admin scripts can be given a bigger budget than content.
+pub struct TriggerLimits {
+    pub default: ScriptLimits,
+    triggers: HashMap<String, ScriptLimits>,
+}
+
+impl TriggerLimits {
+    pub fn new(default: ScriptLimits) -> TriggerLimits {
+        return TriggerLimits {
+            default,
+            triggers: HashMap::new(),
+        };
+    }
+
+    /// Uses `limits` for scripts of `trigger`, the name as written in scripts like `debugproc`.
+    pub fn set(&mut self, trigger: &str, limits: ScriptLimits) {
+        self.triggers.insert(trigger.to_string(), limits);
+    }
+
+    /// The limits for a run starting in `script`.
+    pub fn get(&self, script: &ScriptFile) -> ScriptLimits {
+        let trigger: &str = script
+            .info
+            .name
+            .trim_start_matches('[')
+            .split(',')
+            .next()
+            .unwrap_or_default();
+        return *self.triggers.get(trigger).unwrap_or(&self.default);
+    }
+}
@@ -35,10 +35,22 @@ impl ObjProvider {
     ///
     /// The function will panic if either the `client/config` file or the `server/obj.dat` file
     /// cannot be read, or if they contain unexpected data that cannot be decoded.
-    #[rustfmt::skip]
     pub fn io(dir: &str, members: bool) -> ObjProvider {
+        return match ObjProvider::load(dir, members) {
+            Ok(provider) => provider,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    /// Loads obj data like `io`, returning an error naming the archive and the file in it
+    /// when `client/config` is missing or corrupt.
+    #[rustfmt::skip]
+    pub fn load(dir: &str, members: bool) -> Result<ObjProvider, String> {
         let start: Instant = Instant::now();
-        match JagFile::new(std::fs::read(format!("{}/client/config", dir)).unwrap()).read("obj.dat") {
+        let path: String = format!("{}/client/config", dir);
+        let bytes: Vec<u8> = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let config: JagFile = JagFile::try_new(bytes).map_err(|e| format!("client/config: {}", e))?;
+        match config.try_read("obj.dat").map_err(|e| format!("client/config: {}", e))? {
             Some(mut client) => {
                 let mut server: Packet = Packet::io(format!("{}/server/obj.dat", dir));
 
@@ -100,9 +112,9 @@ impl ObjProvider {
                     }
                 }
                 println!("Loaded objs in: {:?}", start.elapsed());
-                return ObjProvider { names, objs };
+                return Ok(ObjProvider { names, objs });
             }
-            _ => panic!("Could not load objs!"),
+            None => return Err("client/config has no obj.dat".to_string()),
         }
     }
 
@@ -390,6 +402,10 @@ impl ObjType {
         };
     }
 
+    pub fn mock(id: usize) -> ObjType {
+        return ObjType::new(id);
+    }
+
     #[rustfmt::skip]
     fn decode(&mut self, dat: &mut Packet) {
         while dat.remaining() > 0 {
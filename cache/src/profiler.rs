@@ -0,0 +1,192 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::ScriptOpcode;
+
+/// Totals for a single script, across every time it ran.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct ScriptMetrics {
+    pub calls: u64,        // runs plus gosubs and jumps into it
+    pub instructions: u64, // instructions run inside the script itself
+    pub time: Duration,    // time spent in its own instructions
+}
+
+/// Totals for a single opcode, across every script.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct OpcodeMetrics {
+    pub count: u64,
+    pub time: Duration,
+}
+
+/// One `execute` call, from the script it started in until it stopped.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptRun {
+    pub tick: u64,
+    pub script: String,
+    pub instructions: u64,
+    pub time: Duration,
+}
+
+/// Collects where script time goes for runs made by `ScriptState::execute_profiled`,
+/// or by `execute` when the runner has a profiler attached.
+///
+/// Everything adds up across ticks until `reset`, `end_tick` closes a tick and
+/// keeps its slowest runs for the last `WORST_TICKS` ticks.
+pub struct ScriptProfiler {
+    pub scripts: HashMap<String, ScriptMetrics>,
+    pub opcodes: HashMap<ScriptOpcode, OpcodeMetrics>,
+    pub stacks: HashMap<String, Duration>, // folded call stacks, `[proc,a];[proc,b]`
+    pub worst: VecDeque<Vec<ScriptRun>>, // the slowest runs of each recent tick, oldest tick first
+    pub ticks: u64,
+    runs: Vec<ScriptRun>, // the runs of the current tick
+}
+
+impl ScriptProfiler {
+    /// How many of the slowest runs are kept for each tick.
+    pub const WORST_RUNS: usize = 10;
+
+    /// How many ticks the slowest runs are kept for.
+    pub const WORST_TICKS: usize = 100;
+
+    pub fn new() -> ScriptProfiler {
+        return ScriptProfiler {
+            scripts: HashMap::new(),
+            opcodes: HashMap::new(),
+            stacks: HashMap::new(),
+            worst: VecDeque::new(),
+            ticks: 0,
+            runs: Vec::new(),
+        };
+    }
+
+    pub fn reset(&mut self) {
+        *self = ScriptProfiler::new();
+    }
+
+    /// Counts a script being entered, by a run or a gosub or jump.
+    pub fn enter(&mut self, script: &str) {
+        self.script(script).calls += 1;
+    }
+
+    /// Counts an instruction run by `script`, `stack` being its folded call stack.
+    pub fn instruction(&mut self, script: &str, stack: &str, code: &ScriptOpcode, time: Duration) {
+        let metrics: &mut ScriptMetrics = self.script(script);
+        metrics.instructions += 1;
+        metrics.time += time;
+
+        let opcode: &mut OpcodeMetrics = self.opcodes.entry(code.clone()).or_default();
+        opcode.count += 1;
+        opcode.time += time;
+
+        match self.stacks.get_mut(stack) {
+            Some(total) => *total += time,
+            None => {
+                self.stacks.insert(stack.to_string(), time);
+            }
+        }
+    }
+
+    /// Records a finished `execute` call in the current tick.
+    pub fn run(&mut self, script: &str, instructions: u64, time: Duration) {
+        self.runs.push(ScriptRun {
+            tick: self.ticks,
+            script: script.to_string(),
+            instructions,
+            time,
+        });
+    }
+
+    /// Ends the current tick, keeping its slowest runs and dropping those of
+    /// the oldest tick once `WORST_TICKS` ticks are kept.
+    pub fn end_tick(&mut self) {
+        let mut runs: Vec<ScriptRun> = std::mem::take(&mut self.runs);
+        runs.sort_by(|a, b| b.time.cmp(&a.time));
+        runs.truncate(ScriptProfiler::WORST_RUNS);
+        if self.worst.len() == ScriptProfiler::WORST_TICKS {
+            self.worst.pop_front();
+        }
+        self.worst.push_back(runs);
+        self.ticks += 1;
+    }
+
+    /// The slowest runs of the current tick so far, slowest first.
+    pub fn tick_runs(&self) -> Vec<&ScriptRun> {
+        let mut runs: Vec<&ScriptRun> = self.runs.iter().collect();
+        runs.sort_by(|a, b| b.time.cmp(&a.time));
+        return runs;
+    }
+
+    /// The slowest runs of the ticks that are still kept, slowest first.
+    pub fn worst_runs(&self) -> Vec<&ScriptRun> {
+        let mut runs: Vec<&ScriptRun> = self.worst.iter().flatten().collect();
+        runs.sort_by(|a, b| b.time.cmp(&a.time));
+        runs.truncate(ScriptProfiler::WORST_RUNS);
+        return runs;
+    }
+
+    /// A readable summary: scripts and opcodes by time spent, then the slowest
+    /// runs of the recent ticks.
+    pub fn report(&self) -> String {
+        let mut out: String = String::new();
+        let _ = writeln!(out, "Script profile over {} ticks", self.ticks);
+
+        let mut scripts: Vec<(&String, &ScriptMetrics)> = self.scripts.iter().collect();
+        scripts.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(b.0)));
+        let _ = writeln!(out, "\nscripts:");
+        for (name, metrics) in scripts {
+            let _ = writeln!(
+                out,
+                "  {:>12?} {:>10} instructions {:>8} calls  {}",
+                metrics.time, metrics.instructions, metrics.calls, name
+            );
+        }
+
+        let mut opcodes: Vec<(&ScriptOpcode, &OpcodeMetrics)> = self.opcodes.iter().collect();
+        opcodes.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(b.1.count.cmp(&a.1.count)));
+        let _ = writeln!(out, "\nopcodes:");
+        for (code, metrics) in opcodes {
+            let _ = writeln!(
+                out,
+                "  {:>12?} {:>10} calls  {:?}",
+                metrics.time, metrics.count, code
+            );
+        }
+
+        let _ = writeln!(out, "\nslowest runs:");
+        for run in self.worst_runs() {
+            let _ = writeln!(
+                out,
+                "  {:>12?} {:>10} instructions  tick {}  {}",
+                run.time, run.instructions, run.tick, run.script
+            );
+        }
+        return out;
+    }
+
+    /// The call stacks in the folded format flamegraph tools read,
+    /// one `[proc,a];[proc,b] <nanoseconds>` line per stack.
+    pub fn folded(&self) -> String {
+        let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().collect();
+        stacks.sort();
+        let mut out: String = String::new();
+        for (stack, time) in stacks {
+            let _ = writeln!(out, "{} {}", stack, time.as_nanos());
+        }
+        return out;
+    }
+
+    fn script(&mut self, script: &str) -> &mut ScriptMetrics {
+        if !self.scripts.contains_key(script) {
+            self.scripts
+                .insert(script.to_string(), ScriptMetrics::default());
+        }
+        return self.scripts.get_mut(script).unwrap();
+    }
+}
+
+impl Default for ScriptProfiler {
+    fn default() -> ScriptProfiler {
+        return ScriptProfiler::new();
+    }
+}
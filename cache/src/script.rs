@@ -2,841 +2,501 @@ use std::cell::{Ref, RefMut};
 use std::collections::HashMap;
 use std::time::Instant;
 
-use io::Packet;
+use io::{Packet, PacketReader};
 
 use crate::ObjType;
+use crate::ScriptCoverage;
+use crate::ScriptDebugger;
+use crate::ScriptLimits;
+use crate::ScriptProfiler;
+use crate::ScriptVerifier;
+
+/// Declares `ScriptOpcode` from one table of opcodes, their ids and, for commands,
+/// the name they are declared with in engine.rs2, so decoding `script.dat` and
+/// compiling scripts look opcodes up in the same place.
+macro_rules! script_opcodes {
+    ($($opcode:ident = $id:literal $(=> $command:literal)?,)*) => {
+        #[derive(PartialEq, Eq, Hash, PartialOrd, Clone, Debug)]
+        #[repr(u16)]
+        pub enum ScriptOpcode {
+            $($opcode = $id,)*
+        }
+
+        impl TryFrom<u16> for ScriptOpcode {
+            type Error = String;
+
+            fn try_from(code: u16) -> Result<ScriptOpcode, String> {
+                return Ok(match code {
+                    $($id => ScriptOpcode::$opcode,)*
+                    _ => return Err(format!("Invalid script opcode value: {}", code)),
+                });
+            }
+        }
 
-#[derive(PartialEq, PartialOrd, Clone, Debug)]
-#[repr(u16)]
-pub enum ScriptOpcode {
+        impl ScriptOpcode {
+            /// Looks up the opcode of a command by the name it is declared with
+            /// in engine.rs2, without the `.` of its secondary form.
+            pub fn from_command(name: &str) -> Option<ScriptOpcode> {
+                return match name {
+                    $($($command => Some(ScriptOpcode::$opcode),)?)*
+                    _ => None,
+                };
+            }
+        }
+    };
+}
+
+script_opcodes! {
     // Core language ops (0-99)
-    PushConstantInt = 0,    // official, see cs2
-    PushVarp = 1,           // official, see cs2
-    PopVarp = 2,            // official, see cs2
+    PushConstantInt = 0, // official, see cs2
+    PushVarp = 1, // official, see cs2
+    PopVarp = 2, // official, see cs2
     PushConstantString = 3, // official, see cs2
     PushVarn = 4,
     PopVarn = 5,
-    Branch = 6,             // official, see cs2
-    BranchNot = 7,          // official, see cs2
-    BranchEquals = 8,       // official, see cs2
-    BranchLessThan = 9,     // official, see cs2
+    Branch = 6, // official, see cs2
+    BranchNot = 7, // official, see cs2
+    BranchEquals = 8, // official, see cs2
+    BranchLessThan = 9, // official, see cs2
     BranchGreaterThan = 10, // official, see cs2
     PushVars = 11,
     PopVars = 12,
     Return = 21, // official, see cs2
-    GoSub = 22,
-    Jump = 23,
+    GoSub = 22 => "gosub",
+    Jump = 23 => "jump",
     Switch = 24,
     PushVarbit = 25,
     PopVarbit = 26,
-    BranchLessThanOrEquals = 31,    // official, see cs2
+    BranchLessThanOrEquals = 31, // official, see cs2
     BranchGreaterThanOrEquals = 32, // official, see cs2
-    PushIntLocal = 33,              // official, see cs2
-    PopIntLocal = 34,               // official, see cs2
-    PushStringLocal = 35,           // official, see cs2
-    PopStringLocal = 36,            // official, see cs2
-    JoinString = 37,                // official, see cs2
-    PopIntDiscard = 38,             // official, see cs2
-    PopStringDiscard = 39,          // official, see cs2
-    GoSubWithParams = 40,           // official, see cs2
-    JumpWithParams = 41,            // official, see cs2
+    PushIntLocal = 33, // official, see cs2
+    PopIntLocal = 34, // official, see cs2
+    PushStringLocal = 35, // official, see cs2
+    PopStringLocal = 36, // official, see cs2
+    JoinString = 37, // official, see cs2
+    PopIntDiscard = 38, // official, see cs2
+    PopStringDiscard = 39, // official, see cs2
+    GoSubWithParams = 40, // official, see cs2
+    JumpWithParams = 41, // official, see cs2
     PushVarcInt = 42,
     PopVarcInt = 43,
-    DefineArray = 44,  // official, see cs2
+    DefineArray = 44, // official, see cs2
     PushArrayInt = 45, // official, see cs2
-    PopArrayInt = 46,  // official, see cs2
+    PopArrayInt = 46, // official, see cs2
     EndCoreOps = 100,
     // Server ops (1000-1999)
-    CoordX = 1000, // official, see cs2
-    CoordY = 1001, // official, see cs2
-    CoordZ = 1002, // official, see cs2
-    Distance = 1003,
-    HuntAll = 1004,
-    HuntNext = 1005, // official
-    InZone = 1006,   // official
-    LineOfSight = 1007,
-    LineOfWalk = 1008,
-    MapBlocked = 1009, // official
-    MapIndoors = 1010,
-    MapClock = 1011,        // official
-    MapLocAddUnsafe = 1012, // official
-    MapMembers = 1013,      // official
-    MapPlayerCount = 1014,  // official, see giant dwarf cutscene
-    MapFindSquare = 1015,   // official
-    MoveCoord = 1016,       // official
-    PlayerCount = 1017,
-    ProjAnimMap = 1018,
-    ProjAnimNpc = 1019,
-    ProjAnimPl = 1020,
-    SeqLength = 1021, // official
-    SplitGet = 1022,
-    SplitGetAnim = 1023,
-    SplitInit = 1024, // official
-    SplitLineCount = 1025,
-    SplitPageCount = 1026, // official
-    SpotAnimMap = 1027,
-    StatRandom = 1028,
-    StructParam = 1029,
-    WorldDelay = 1030, // official
-    NpcsCount = 1031,
-    ZonesCount = 1032,
-    LocsCount = 1033,
-    ObjsCount = 1034,
-    MapMulti = 1035,
+    CoordX = 1000 => "coordx", // official, see cs2
+    CoordY = 1001 => "coordy", // official, see cs2
+    CoordZ = 1002 => "coordz", // official, see cs2
+    Distance = 1003 => "distance",
+    HuntAll = 1004 => "huntall",
+    HuntNext = 1005 => "huntnext", // official
+    InZone = 1006 => "inzone", // official
+    LineOfSight = 1007 => "lineofsight",
+    LineOfWalk = 1008 => "lineofwalk",
+    MapBlocked = 1009 => "map_blocked", // official
+    MapIndoors = 1010 => "map_indoors",
+    MapClock = 1011 => "map_clock", // official
+    MapLocAddUnsafe = 1012 => "map_locaddunsafe", // official
+    MapMembers = 1013 => "map_members", // official
+    MapPlayerCount = 1014 => "map_playercount", // official, see giant dwarf cutscene
+    MapFindSquare = 1015 => "map_findsquare", // official
+    MoveCoord = 1016 => "movecoord", // official
+    PlayerCount = 1017 => "playercount",
+    ProjAnimMap = 1018 => "projanim_map",
+    ProjAnimNpc = 1019 => "projanim_npc",
+    ProjAnimPl = 1020 => "projanim_pl",
+    SeqLength = 1021 => "seqlength", // official
+    SplitGet = 1022 => "split_get",
+    SplitGetAnim = 1023 => "split_getanim",
+    SplitInit = 1024 => "split_init", // official
+    SplitLineCount = 1025 => "split_linecount",
+    SplitPageCount = 1026 => "split_pagecount", // official
+    SpotAnimMap = 1027 => "spotanim_map",
+    StatRandom = 1028 => "stat_random",
+    StructParam = 1029 => "struct_param",
+    WorldDelay = 1030 => "world_delay", // official
+    NpcsCount = 1031 => "npccount",
+    ZonesCount = 1032 => "zonecount",
+    LocsCount = 1033 => "loccount",
+    ObjsCount = 1034 => "objcount",
+    MapMulti = 1035 => "map_multi",
     // Player ops (2000-2499)
-    AllowDesign = 2000,
-    Anim = 2001,
-    BasReadyAnim = 2002,
-    BasRunning = 2003,
-    BasTurnOnSpot = 2004,
-    BasWalkB = 2005,
-    BasWalkF = 2006,
-    BasWalkL = 2007,
-    BasWalkR = 2008,
-    BufferFull = 2009,      // official
-    BuildAppearance = 2010, // official
-    Busy = 2011,            // official
-    CamLookAt = 2012,       // official
-    CamMoveTo = 2013,       // official
-    CamReset = 2014,        // official
-    CamShake = 2015,        // official, see server packets
-    ClearQueue = 2016,      // official
-    ClearSoftTimer = 2017,
-    ClearTimer = 2018,
-    GetTimer = 2019,
-    Coord = 2020, // official
-    Damage = 2021,
-    Displayname = 2022, // official, joke reply
-    FaceSquare = 2023,  // official
-    FindUid = 2024,     // official
-    Gender = 2025,
-    GetQueue = 2026, // official
-    StatAdvance = 2027,
-    HeadiconsGet = 2028,
-    HeadiconsSet = 2029,
-    HealEnergy = 2030, // official
-    HintCoord = 2031,
-    HintNpc = 2032,
-    HintPlayer = 2033,
-    HintStop = 2034,
-    IfClose = 2035, // official
-    TutClose = 2036,
+    AllowDesign = 2000 => "allowdesign",
+    Anim = 2001 => "anim",
+    BasReadyAnim = 2002 => "bas_readyanim",
+    BasRunning = 2003 => "bas_running",
+    BasTurnOnSpot = 2004 => "bas_turnonspot",
+    BasWalkB = 2005 => "bas_walk_b",
+    BasWalkF = 2006 => "bas_walk_f",
+    BasWalkL = 2007 => "bas_walk_l",
+    BasWalkR = 2008 => "bas_walk_r",
+    BufferFull = 2009 => "buffer_full", // official
+    BuildAppearance = 2010 => "buildappearance", // official
+    Busy = 2011 => "busy", // official
+    CamLookAt = 2012 => "cam_lookat", // official
+    CamMoveTo = 2013 => "cam_moveto", // official
+    CamReset = 2014 => "cam_reset", // official
+    CamShake = 2015 => "cam_shake", // official, see server packets
+    ClearQueue = 2016 => "clearqueue", // official
+    ClearSoftTimer = 2017 => "clearsofttimer",
+    ClearTimer = 2018 => "cleartimer",
+    GetTimer = 2019 => "gettimer",
+    Coord = 2020 => "coord", // official
+    Damage = 2021 => "damage",
+    Displayname = 2022 => "displayname", // official, joke reply
+    FaceSquare = 2023 => "facesquare", // official
+    FindUid = 2024 => "finduid", // official
+    Gender = 2025 => "gender",
+    GetQueue = 2026 => "getqueue", // official
+    StatAdvance = 2027 => "stat_advance",
+    HeadiconsGet = 2028 => "headicons_get",
+    HeadiconsSet = 2029 => "headicons_set",
+    HealEnergy = 2030 => "healenergy", // official
+    HintCoord = 2031 => "hint_coord",
+    HintNpc = 2032 => "hint_npc",
+    HintPlayer = 2033 => "hint_player",
+    HintStop = 2034 => "hint_stop",
+    IfClose = 2035 => "if_close", // official
+    TutClose = 2036 => "tut_close",
     IfMultiZone = 2037,
-    IfOpenChat = 2038,
-    TutOpen = 2039,
-    IfOpenMain = 2040,
-    IfOpenMainSide = 2041,
-    IfOpenSide = 2042,
-    IfSetAnim = 2043,   // official
-    IfSetColour = 2044, // official
-    IfSetHide = 2045,   // official
-    IfSetModel = 2046,  // official
-    IfSetRecol = 2047,
-    IfSetNpcHead = 2048,    // official
-    IfSetObject = 2049,     // official
-    IfSetPlayerHead = 2050, // official
-    IfSetPosition = 2051,   // official
-    IfSetResumeButtons = 2052,
-    IfSetTab = 2053,
-    IfSetTabActive = 2054,
-    TutFlash = 2055,
-    IfSetText = 2056, // official
-    LastLoginInfo = 2057,
-    LastCom = 2058,
-    LastInt = 2059, // official
-    LastItem = 2060,
-    LastSlot = 2061, // official
-    LastTargetSlot = 2062,
-    LastUseItem = 2063,  // official
-    LastUseSlot = 2064,  // official
-    LongQueue = 2065,    // official
-    Mes = 2066,          // official
-    MidiJingle = 2067,   // official, see cs2
-    MidiSong = 2068,     // official, see cs2
-    Name = 2069,         // official, joke reply
-    PApRange = 2070,     // official
-    PArriveDelay = 2071, // official
-    PCountDialog = 2072, // official
-    PDelay = 2073,       // official
-    PExactMove = 2074,   // official
-    PFindUid = 2075,     // official
-    PLocMerge = 2076,    // official
-    PLogout = 2077,
-    POpHeld = 2078, // official
-    POpLoc = 2079,  // official
-    POpNpc = 2080,  // official
-    POpNpcT = 2081, // official
-    POpObj = 2082,
-    POpPlayer = 2083,
-    POpPlayerT = 2084,   // official
-    PPauseButton = 2085, // official
-    PStopAction = 2086,  // official
-    PTeleJump = 2087,    // official
-    PTeleport = 2088,
-    PWalk = 2089,             // official
-    PlayerFindAllZone = 2090, // todo: replace with huntall
-    PlayerFindNext = 2091,    // todo: replace with huntnext
-    Queue = 2092,             // official
-    Say = 2093,               // official
-    WalkTrigger = 2094,       // official
-    SetTimer = 2095,
-    SoftTimer = 2096,  // official
-    SoundSynth = 2097, // official, newspost
-    SpotAnimPl = 2098,
-    StaffModLevel = 2099, // official
-    Stat = 2100,          // official
-    StatAdd = 2101,
-    StatBase = 2102, // official
-    StatHeal = 2103, // official
-    StatSub = 2104,
-    StrongQueue = 2105,
-    Uid = 2106,       // official
-    WeakQueue = 2107, // official
+    IfOpenChat = 2038 => "if_openchat",
+    TutOpen = 2039 => "tut_open",
+    IfOpenMain = 2040 => "if_openmain",
+    IfOpenMainSide = 2041 => "if_openmain_side",
+    IfOpenSide = 2042 => "if_openside",
+    IfSetAnim = 2043 => "if_setanim", // official
+    IfSetColour = 2044 => "if_setcolour", // official
+    IfSetHide = 2045 => "if_sethide", // official
+    IfSetModel = 2046 => "if_setmodel", // official
+    IfSetRecol = 2047 => "if_setrecol",
+    IfSetNpcHead = 2048 => "if_setnpchead", // official
+    IfSetObject = 2049 => "if_setobject", // official
+    IfSetPlayerHead = 2050 => "if_setplayerhead", // official
+    IfSetPosition = 2051 => "if_setposition", // official
+    IfSetResumeButtons = 2052 => "if_setresumebuttons",
+    IfSetTab = 2053 => "if_settab",
+    IfSetTabActive = 2054 => "if_settabactive",
+    TutFlash = 2055 => "tut_flash",
+    IfSetText = 2056 => "if_settext", // official
+    LastLoginInfo = 2057 => "last_login_info",
+    LastCom = 2058 => "last_com",
+    LastInt = 2059 => "last_int", // official
+    LastItem = 2060 => "last_item",
+    LastSlot = 2061 => "last_slot", // official
+    LastTargetSlot = 2062 => "last_targetslot",
+    LastUseItem = 2063 => "last_useitem", // official
+    LastUseSlot = 2064 => "last_useslot", // official
+    LongQueue = 2065, // official
+    Mes = 2066 => "mes", // official
+    MidiJingle = 2067 => "midi_jingle", // official, see cs2
+    MidiSong = 2068 => "midi_song", // official, see cs2
+    Name = 2069 => "name", // official, joke reply
+    PApRange = 2070 => "p_aprange", // official
+    PArriveDelay = 2071 => "p_arrivedelay", // official
+    PCountDialog = 2072 => "p_countdialog", // official
+    PDelay = 2073 => "p_delay", // official
+    PExactMove = 2074 => "p_exactmove", // official
+    PFindUid = 2075 => "p_finduid", // official
+    PLocMerge = 2076 => "p_locmerge", // official
+    PLogout = 2077 => "p_logout",
+    POpHeld = 2078 => "p_opheld", // official
+    POpLoc = 2079 => "p_oploc", // official
+    POpNpc = 2080 => "p_opnpc", // official
+    POpNpcT = 2081 => "p_opnpct", // official
+    POpObj = 2082 => "p_opobj",
+    POpPlayer = 2083 => "p_opplayer",
+    POpPlayerT = 2084 => "p_opplayert", // official
+    PPauseButton = 2085 => "p_pausebutton", // official
+    PStopAction = 2086 => "p_stopaction", // official
+    PTeleJump = 2087 => "p_telejump", // official
+    PTeleport = 2088 => "p_teleport",
+    PWalk = 2089 => "p_walk", // official
+    PlayerFindAllZone = 2090 => "player_findallzone", // todo: replace with huntall
+    PlayerFindNext = 2091 => "player_findnext", // todo: replace with huntnext
+    Queue = 2092 => "queue", // official
+    Say = 2093 => "say", // official
+    WalkTrigger = 2094 => "walktrigger", // official
+    SetTimer = 2095 => "settimer",
+    SoftTimer = 2096 => "softtimer", // official
+    SoundSynth = 2097 => "sound_synth", // official, newspost
+    SpotAnimPl = 2098 => "spotanim_pl",
+    StaffModLevel = 2099 => "staffmodlevel", // official
+    Stat = 2100 => "stat", // official
+    StatAdd = 2101 => "stat_add",
+    StatBase = 2102 => "stat_base", // official
+    StatHeal = 2103 => "stat_heal", // official
+    StatSub = 2104 => "stat_sub",
+    StrongQueue = 2105 => "strongqueue",
+    Uid = 2106 => "uid", // official
+    WeakQueue = 2107 => "weakqueue", // official
     IfOpenMainOverlay = 2108,
-    AfkEvent = 2109,
-    LowMemory = 2110,
-    SetIdkit = 2111,
+    AfkEvent = 2109 => "afk_event",
+    LowMemory = 2110 => "lowmemory",
+    SetIdkit = 2111 => "setidkit",
     PClearPendingAction = 2112, // official
-    GetWalkTrigger = 2113,      // official
-    Busy2 = 2114,               // official
-    FindHero = 2115,            // official
-    BothHeroPoints = 2116,      // official
-    SetGender = 2117,
-    SetSkinColour = 2118,
-    PAnimProtect = 2119,
-    RunEnergy = 2120,
-    Weight = 2121,
-    LastCoord = 2122,
+    GetWalkTrigger = 2113 => "getwalktrigger", // official
+    Busy2 = 2114 => "busy2", // official
+    FindHero = 2115 => "findhero", // official
+    BothHeroPoints = 2116 => "both_heropoints", // official
+    SetGender = 2117 => "setgender",
+    SetSkinColour = 2118 => "setskincolour",
+    PAnimProtect = 2119 => "p_animprotect",
+    RunEnergy = 2120 => "runenergy",
+    Weight = 2121 => "weight",
+    LastCoord = 2122 => "last_coord",
     // Npc ops (2500-2999)
-    NpcAdd = 2500,      // official
-    NpcAnim = 2501,     // official, newspost
-    NpcBaseStat = 2502, // official
-    NpcCategory = 2503, // official
-    NpcChangeType = 2504,
-    NpcCoord = 2505, // official
-    NpcDamage = 2506,
-    NpcDel = 2507,        // official
-    NpcDelay = 2508,      // official
-    NpcFaceSquare = 2509, // official
-    NpcFind = 2510,       // official
-    NpcFindAllAny = 2511, // official
-    NpcFindAll = 2512,
-    NpcFindExact = 2513, // official
-    NpcFindHero = 2514,  // official
-    NpcFindAllZone = 2515,
-    NpcFindNext = 2516,
-    NpcFindUid = 2517,
-    NpcGetMode = 2518,
-    NpcHeroPoints = 2519, // official
-    NpcName = 2520,
-    NpcParam = 2521,   // official
-    NpcQueue = 2522,   // official
-    NpcRange = 2523,   // official
-    NpcSay = 2524,     // official
-    NpcHuntAll = 2525, // official
-    NpcHuntNext = 2526,
-    NpcSetHunt = 2527,     // official
-    NpcSetHuntMode = 2528, // official
-    NpcSetMode = 2529,     // official
-    NpcWalkTrigger = 2530, // official
-    NpcSetTimer = 2531,
-    NpcStat = 2532,
-    NpcStatAdd = 2533,
-    NpcStatHeal = 2534, // official
-    NpcStatSub = 2535,
-    NpcTele = 2536,
-    NpcType = 2537, // official
-    NpcUid = 2538,
-    SpotAnimNpc = 2539,
-    NpcWalk = 2540,
-    NpcAttackRange = 2541, // official
-    NpcHasOp = 2542,       // official
-    NpcArriveDelay = 2543,
+    NpcAdd = 2500 => "npc_add", // official
+    NpcAnim = 2501 => "npc_anim", // official, newspost
+    NpcBaseStat = 2502 => "npc_basestat", // official
+    NpcCategory = 2503 => "npc_category", // official
+    NpcChangeType = 2504 => "npc_changetype",
+    NpcCoord = 2505 => "npc_coord", // official
+    NpcDamage = 2506 => "npc_damage",
+    NpcDel = 2507 => "npc_del", // official
+    NpcDelay = 2508 => "npc_delay", // official
+    NpcFaceSquare = 2509 => "npc_facesquare", // official
+    NpcFind = 2510 => "npc_find", // official
+    NpcFindAllAny = 2511 => "npc_findallany", // official
+    NpcFindAll = 2512 => "npc_findall",
+    NpcFindExact = 2513 => "npc_findexact", // official
+    NpcFindHero = 2514 => "npc_findhero", // official
+    NpcFindAllZone = 2515 => "npc_findallzone",
+    NpcFindNext = 2516 => "npc_findnext",
+    NpcFindUid = 2517 => "npc_finduid",
+    NpcGetMode = 2518 => "npc_getmode",
+    NpcHeroPoints = 2519 => "npc_heropoints", // official
+    NpcName = 2520 => "npc_name",
+    NpcParam = 2521 => "npc_param", // official
+    NpcQueue = 2522 => "npc_queue", // official
+    NpcRange = 2523 => "npc_range", // official
+    NpcSay = 2524 => "npc_say", // official
+    NpcHuntAll = 2525 => "npc_huntall", // official
+    NpcHuntNext = 2526 => "npc_huntnext",
+    NpcSetHunt = 2527 => "npc_sethunt", // official
+    NpcSetHuntMode = 2528 => "npc_sethuntmode", // official
+    NpcSetMode = 2529 => "npc_setmode", // official
+    NpcWalkTrigger = 2530 => "npc_walktrigger", // official
+    NpcSetTimer = 2531 => "npc_settimer",
+    NpcStat = 2532 => "npc_stat",
+    NpcStatAdd = 2533 => "npc_statadd",
+    NpcStatHeal = 2534 => "npc_statheal", // official
+    NpcStatSub = 2535 => "npc_statsub",
+    NpcTele = 2536 => "npc_tele",
+    NpcType = 2537 => "npc_type", // official
+    NpcUid = 2538 => "npc_uid",
+    SpotAnimNpc = 2539 => "spotanim_npc",
+    NpcWalk = 2540 => "npc_walk",
+    NpcAttackRange = 2541 => "npc_attackrange", // official
+    NpcHasOp = 2542 => "npc_hasop", // official
+    NpcArriveDelay = 2543 => "npc_arrivedelay",
     // Loc ops (3000-3499)
-    LocAdd = 3000,      // official
-    LocAngle = 3001,    // official
-    LocAnim = 3002,     // official
-    LocCategory = 3003, // official
-    LocChange = 3004,
-    LocCoord = 3005,       // official
-    LocDel = 3006,         // official
-    LocFind = 3007,        // official
-    LocFindAllZone = 3008, // official
-    LocFindNext = 3009,    // official
-    LocName = 3010,
-    LocParam = 3011, // official
-    LocShape = 3012,
-    LocType = 3013, // official
+    LocAdd = 3000 => "loc_add", // official
+    LocAngle = 3001 => "loc_angle", // official
+    LocAnim = 3002 => "loc_anim", // official
+    LocCategory = 3003 => "loc_category", // official
+    LocChange = 3004 => "loc_change",
+    LocCoord = 3005 => "loc_coord", // official
+    LocDel = 3006 => "loc_del", // official
+    LocFind = 3007 => "loc_find", // official
+    LocFindAllZone = 3008 => "loc_findallzone", // official
+    LocFindNext = 3009 => "loc_findnext", // official
+    LocName = 3010 => "loc_name",
+    LocParam = 3011 => "loc_param", // official
+    LocShape = 3012 => "loc_shape",
+    LocType = 3013 => "loc_type", // official
     // Obj ops (3500-4000)
-    ObjAdd = 3500,
-    ObjAddAll = 3501,
-    ObjCoord = 3502,
-    ObjCount = 3503,
-    ObjDel = 3504,
-    ObjName = 3505,
-    ObjParam = 3506,
-    ObjTakeItem = 3507,
-    ObjType = 3508,
-    ObjFind = 3509,
+    ObjAdd = 3500 => "obj_add",
+    ObjAddAll = 3501 => "obj_addall",
+    ObjCoord = 3502 => "obj_coord",
+    ObjCount = 3503 => "obj_count",
+    ObjDel = 3504 => "obj_del",
+    ObjName = 3505 => "obj_name",
+    ObjParam = 3506 => "obj_param",
+    ObjTakeItem = 3507 => "obj_takeitem",
+    ObjType = 3508 => "obj_type",
+    ObjFind = 3509 => "obj_find",
     // Npc config ops (4000-4099)
-    NcCategory = 4000,
-    NcDebugname = 4001,
-    NcDesc = 4002,
-    NcName = 4003,
-    NcOp = 4004,
-    NcParam = 4005,
+    NcCategory = 4000 => "nc_category",
+    NcDebugname = 4001 => "nc_debugname",
+    NcDesc = 4002 => "nc_desc",
+    NcName = 4003 => "nc_name",
+    NcOp = 4004 => "nc_op",
+    NcParam = 4005 => "nc_param",
     // Loc config ops (4100-4199)
-    LcCategory = 4100,
-    LcDebugname = 4101,
-    LcDesc = 4102,
-    LcName = 4103,
+    LcCategory = 4100 => "lc_category",
+    LcDebugname = 4101 => "lc_debugname",
+    LcDesc = 4102 => "lc_desc",
+    LcName = 4103 => "lc_name",
     LcOp = 4104,
-    LcParam = 4105,
-    LcWidth = 4106,
-    LcLength = 4107,
+    LcParam = 4105 => "lc_param",
+    LcWidth = 4106 => "lc_width",
+    LcLength = 4107 => "lc_length",
     // Obj config ops (4200-4299)
-    OcCategory = 4200, // official
-    OcCert = 4201,     // official, see cs2
-    OcCost = 4202,     // official, see cs2
-    OcDebugname = 4203,
-    OcDesc = 4204,      // official
-    OcIop = 4205,       // official, see cs2
-    OcMembers = 4206,   // official
-    OcName = 4207,      // official
-    OcOp = 4208,        // official, see cs2
-    OcParam = 4209,     // official
-    OcStackable = 4210, // official, see cs2
-    OcTradeable = 4211,
-    OcUncert = 4212, // official, see cs2
-    OcWearPos2 = 4213,
-    OcWearPos3 = 4214,
-    OcWearPos = 4215,
-    OcWeight = 4216,
+    OcCategory = 4200 => "oc_category", // official
+    OcCert = 4201 => "oc_cert", // official, see cs2
+    OcCost = 4202 => "oc_cost", // official, see cs2
+    OcDebugname = 4203 => "oc_debugname",
+    OcDesc = 4204 => "oc_desc", // official
+    OcIop = 4205, // official, see cs2
+    OcMembers = 4206 => "oc_members", // official
+    OcName = 4207 => "oc_name", // official
+    OcOp = 4208, // official, see cs2
+    OcParam = 4209 => "oc_param", // official
+    OcStackable = 4210 => "oc_stackable", // official, see cs2
+    OcTradeable = 4211 => "oc_tradeable",
+    OcUncert = 4212 => "oc_uncert", // official, see cs2
+    OcWearPos2 = 4213 => "oc_wearpos2",
+    OcWearPos3 = 4214 => "oc_wearpos3",
+    OcWearPos = 4215 => "oc_wearpos",
+    OcWeight = 4216 => "oc_weight",
     // Inventory ops (4300-4399)
-    InvAllStock = 4300,
-    InvSize = 4301, // official
-    InvStockBase = 4302,
-    InvAdd = 4303,        // official
-    InvChangeSlot = 4304, // official
-    InvClear = 4305,
-    InvDel = 4306, // official
-    InvDelSlot = 4307,
-    InvDropItem = 4308,
-    InvDropSlot = 4309,
-    InvFreespace = 4310,
-    InvGetNum = 4311,
-    InvGetObj = 4312, // official
-    InvItemSpace = 4313,
-    InvItemSpace2 = 4314, // official
-    InvMoveFromSlot = 4315,
-    InvMoveToSlot = 4316,     // official
-    BothMoveInv = 4317,       // official
-    InvMoveItem = 4318,       // official
-    InvMoveItemCert = 4319,   // official
-    InvMoveItemUncert = 4320, // official
-    InvSetSlot = 4321,        // official
-    InvTotal = 4322,          // official
-    InvTotalCat = 4323,
-    InvTransmit = 4324,
-    InvOtherTransmit = 4325,
-    InvStopTransmit = 4326,
-    BothDropSlot = 4327,
-    InvDropAll = 4328,
-    InvTotalParam = 4329,      // official, see cs2
+    InvAllStock = 4300 => "inv_allstock",
+    InvSize = 4301 => "inv_size", // official
+    InvStockBase = 4302 => "inv_stockbase",
+    InvAdd = 4303 => "inv_add", // official
+    InvChangeSlot = 4304 => "inv_changeslot", // official
+    InvClear = 4305 => "inv_clear",
+    InvDel = 4306 => "inv_del", // official
+    InvDelSlot = 4307 => "inv_delslot",
+    InvDropItem = 4308 => "inv_dropitem",
+    InvDropSlot = 4309 => "inv_dropslot",
+    InvFreespace = 4310 => "inv_freespace",
+    InvGetNum = 4311 => "inv_getnum",
+    InvGetObj = 4312 => "inv_getobj", // official
+    InvItemSpace = 4313 => "inv_itemspace",
+    InvItemSpace2 = 4314 => "inv_itemspace2", // official
+    InvMoveFromSlot = 4315 => "inv_movefromslot",
+    InvMoveToSlot = 4316 => "inv_movetoslot", // official
+    BothMoveInv = 4317 => "both_moveinv", // official
+    InvMoveItem = 4318 => "inv_moveitem", // official
+    InvMoveItemCert = 4319 => "inv_moveitem_cert", // official
+    InvMoveItemUncert = 4320 => "inv_moveitem_uncert", // official
+    InvSetSlot = 4321 => "inv_setslot", // official
+    InvTotal = 4322 => "inv_total", // official
+    InvTotalCat = 4323 => "inv_totalcat",
+    InvTransmit = 4324 => "inv_transmit",
+    InvOtherTransmit = 4325 => "invother_transmit",
+    InvStopTransmit = 4326 => "inv_stoptransmit",
+    BothDropSlot = 4327 => "both_dropslot",
+    InvDropAll = 4328 => "inv_dropall",
+    InvTotalParam = 4329, // official, see cs2
     InvTotalParamStack = 4330, // official, see cs2
     // Enum ops (4400-4499)
-    Enum = 4400,               // official
-    EnumGetOutputCount = 4401, // official
+    Enum = 4400 => "enum", // official
+    EnumGetOutputCount = 4401 => "enum_getoutputcount", // official
     // String ops (4500-4599)
-    AppendNum = 4500,           // official, see cs2
-    Append = 4501,              // official, see cs2
-    AppendSignNum = 4502,       // official, see cs2
-    Lowercase = 4503,           // official, see cs2
-    TextGender = 4504,          // official, see cs2
-    ToString = 4505,            // official, see cs2
-    Compare = 4506,             // official, see cs2
-    TextSwitch = 4507,          // official, see cs2
-    AppendChar = 4508,          // official, see cs2
-    StringLength = 4509,        // official, see cs2
-    SubString = 4510,           // official, see cs2
-    StringIndexOfChar = 4511,   // official, see cs2
-    StringIndexOfString = 4512, // official, see cs2
+    AppendNum = 4500 => "append_num", // official, see cs2
+    Append = 4501 => "append", // official, see cs2
+    AppendSignNum = 4502 => "append_signnum", // official, see cs2
+    Lowercase = 4503 => "lowercase", // official, see cs2
+    TextGender = 4504 => "text_gender", // official, see cs2
+    ToString = 4505 => "tostring", // official, see cs2
+    Compare = 4506 => "compare", // official, see cs2
+    TextSwitch = 4507, // official, see cs2
+    AppendChar = 4508 => "append_char", // official, see cs2
+    StringLength = 4509 => "string_length", // official, see cs2
+    SubString = 4510 => "substring", // official, see cs2
+    StringIndexOfChar = 4511 => "string_indexof_char", // official, see cs2
+    StringIndexOfString = 4512 => "string_indexof_string", // official, see cs2
     // Number ops (4600-4699)
-    Add = 4600,              // official, see cs2
-    Sub = 4601,              // official, see cs2
-    Multiply = 4602,         // official, see cs2
-    Divide = 4603,           // official, see cs2
-    Random = 4604,           // official, see cs2
-    RandomInc = 4605,        // official, see cs2
-    Interpolate = 4606,      // official, see cs2
-    AddPercent = 4607,       // official, see cs2
-    SetBit = 4608,           // official, see cs2
-    ClearBit = 4609,         // official, see cs2
-    TestBit = 4610,          // official, see cs2
-    Modulo = 4611,           // official, see cs2
-    Pow = 4612,              // official, see cs2
-    InvPow = 4613,           // official, see cs2
-    And = 4614,              // official, see cs2
-    Or = 4615,               // official, see cs2
-    Min = 4616,              // official, see cs2
-    Max = 4617,              // official, see cs2
-    Scale = 4618,            // official, see cs2
-    BitCount = 4619,         // custom
-    ToggleBit = 4620,        // custom
-    SetBitRange = 4621,      // custom
-    ClearBitRange = 4622,    // custom
-    GetBitRange = 4623,      // custom
-    SetBitRangeToInt = 4624, // custom
-    SinDeg = 4625,           // custom
-    CosDeg = 4626,           // custom
-    Atan2Deg = 4627,         // custom
-    Abs = 4628,              // custom
+    Add = 4600 => "add", // official, see cs2
+    Sub = 4601 => "sub", // official, see cs2
+    Multiply = 4602 => "multiply", // official, see cs2
+    Divide = 4603 => "divide", // official, see cs2
+    Random = 4604 => "random", // official, see cs2
+    RandomInc = 4605 => "randominc", // official, see cs2
+    Interpolate = 4606 => "interpolate", // official, see cs2
+    AddPercent = 4607 => "addpercent", // official, see cs2
+    SetBit = 4608 => "setbit", // official, see cs2
+    ClearBit = 4609 => "clearbit", // official, see cs2
+    TestBit = 4610 => "testbit", // official, see cs2
+    Modulo = 4611 => "modulo", // official, see cs2
+    Pow = 4612 => "pow", // official, see cs2
+    InvPow = 4613 => "invpow", // official, see cs2
+    And = 4614 => "and", // official, see cs2
+    Or = 4615 => "or", // official, see cs2
+    Min = 4616 => "min", // official, see cs2
+    Max = 4617 => "max", // official, see cs2
+    Scale = 4618 => "scale", // official, see cs2
+    BitCount = 4619 => "bitcount", // custom
+    ToggleBit = 4620 => "togglebit", // custom
+    SetBitRange = 4621 => "setbit_range", // custom
+    ClearBitRange = 4622 => "clearbit_range", // custom
+    GetBitRange = 4623 => "getbit_range", // custom
+    SetBitRangeToInt = 4624 => "setbit_range_toint", // custom
+    SinDeg = 4625 => "sin_deg", // custom
+    CosDeg = 4626 => "cos_deg", // custom
+    Atan2Deg = 4627 => "atan2_deg", // custom
+    Abs = 4628 => "abs", // custom
     // DB ops (7500-7599)
-    DbFindWithCount = 7500,
-    DbFindNext = 7501,
-    DbGetField = 7502,
-    DbGetFieldCount = 7503,
-    DbListAllWithCount = 7504,
-    DbGetRowTable = 7505,
-    DbFindByIndex = 7506,
-    DbFindRefineWithCount = 7507,
-    DbFind = 7508,
-    DbFindRefine = 7509,
-    DbListAll = 7510,
+    DbFindWithCount = 7500 => "db_find_with_count",
+    DbFindNext = 7501 => "db_findnext",
+    DbGetField = 7502 => "db_getfield",
+    DbGetFieldCount = 7503 => "db_getfieldcount",
+    DbListAllWithCount = 7504 => "db_listall_with_count",
+    DbGetRowTable = 7505 => "db_getrowtable",
+    DbFindByIndex = 7506 => "db_findbyindex",
+    DbFindRefineWithCount = 7507 => "db_find_refine_with_count",
+    DbFind = 7508 => "db_find",
+    DbFindRefine = 7509 => "db_find_refine",
+    DbListAll = 7510 => "db_listall",
     // Debug ops (10000-11000)
-    Error = 10000,
-    MapProduction = 10001,
-    MapLastClock = 10002,        // custom
-    MapLastWorld = 10003,        // custom
-    MapLastClientIn = 10004,     // custom
-    MapLastNpc = 10005,          // custom
-    MapLastPlayer = 10006,       // custom
-    MapLastLogout = 10007,       // custom
-    MapLastLogin = 10008,        // custom
-    MapLastZone = 10009,         // custom
-    MapLastClientOut = 10010,    // custom
-    MapLastCleanup = 10011,      // custom
-    MapLastBandwidthIn = 10012,  // custom
-    MapLastBandwidthOut = 10013, // custom
+    Error = 10000 => "error",
+    MapProduction = 10001 => "map_production",
+    MapLastClock = 10002 => "map_lastclock", // custom
+    MapLastWorld = 10003 => "map_lastworld", // custom
+    MapLastClientIn = 10004 => "map_lastclientin", // custom
+    MapLastNpc = 10005 => "map_lastnpc", // custom
+    MapLastPlayer = 10006 => "map_lastplayer", // custom
+    MapLastLogout = 10007 => "map_lastlogout", // custom
+    MapLastLogin = 10008 => "map_lastlogin", // custom
+    MapLastZone = 10009 => "map_lastzone", // custom
+    MapLastClientOut = 10010 => "map_lastclientout", // custom
+    MapLastCleanup = 10011 => "map_lastcleanup", // custom
+    MapLastBandwidthIn = 10012 => "map_lastbandwidthin", // custom
+    MapLastBandwidthOut = 10013 => "map_lastbandwidthout", // custom
+}
+
+/// The scripts that differ between two loads of `script.dat`, by name.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct ScriptChanges {
+    pub added: Vec<String>,
+    pub changed: Vec<String>, // different code or moved to another id
+    pub removed: Vec<String>,
 }
 
-impl From<u16> for ScriptOpcode {
-    fn from(code: u16) -> ScriptOpcode {
-        match code {
-            // Core language ops (0-99)
-            0 => ScriptOpcode::PushConstantInt,
-            1 => ScriptOpcode::PushVarp,
-            2 => ScriptOpcode::PopVarp,
-            3 => ScriptOpcode::PushConstantString,
-            4 => ScriptOpcode::PushVarn,
-            5 => ScriptOpcode::PopVarn,
-            6 => ScriptOpcode::Branch,
-            7 => ScriptOpcode::BranchNot,
-            8 => ScriptOpcode::BranchEquals,
-            9 => ScriptOpcode::BranchLessThan,
-            10 => ScriptOpcode::BranchGreaterThan,
-            11 => ScriptOpcode::PushVars,
-            12 => ScriptOpcode::PopVars,
-            21 => ScriptOpcode::Return,
-            22 => ScriptOpcode::GoSub,
-            23 => ScriptOpcode::Jump,
-            24 => ScriptOpcode::Switch,
-            25 => ScriptOpcode::PushVarbit,
-            26 => ScriptOpcode::PopVarbit,
-            31 => ScriptOpcode::BranchLessThanOrEquals,
-            32 => ScriptOpcode::BranchGreaterThanOrEquals,
-            33 => ScriptOpcode::PushIntLocal,
-            34 => ScriptOpcode::PopIntLocal,
-            35 => ScriptOpcode::PushStringLocal,
-            36 => ScriptOpcode::PopStringLocal,
-            37 => ScriptOpcode::JoinString,
-            38 => ScriptOpcode::PopIntDiscard,
-            39 => ScriptOpcode::PopStringDiscard,
-            40 => ScriptOpcode::GoSubWithParams,
-            41 => ScriptOpcode::JumpWithParams,
-            42 => ScriptOpcode::PushVarcInt,
-            43 => ScriptOpcode::PopVarcInt,
-            44 => ScriptOpcode::DefineArray,
-            45 => ScriptOpcode::PushArrayInt,
-            46 => ScriptOpcode::PopArrayInt,
-            100 => ScriptOpcode::EndCoreOps,
-            // Server ops (1000-1999)
-            1000 => ScriptOpcode::CoordX,
-            1001 => ScriptOpcode::CoordY,
-            1002 => ScriptOpcode::CoordZ,
-            1003 => ScriptOpcode::Distance,
-            1004 => ScriptOpcode::HuntAll,
-            1005 => ScriptOpcode::HuntNext,
-            1006 => ScriptOpcode::InZone,
-            1007 => ScriptOpcode::LineOfSight,
-            1008 => ScriptOpcode::LineOfWalk,
-            1009 => ScriptOpcode::MapBlocked,
-            1010 => ScriptOpcode::MapIndoors,
-            1011 => ScriptOpcode::MapClock,
-            1012 => ScriptOpcode::MapLocAddUnsafe,
-            1013 => ScriptOpcode::MapMembers,
-            1014 => ScriptOpcode::MapPlayerCount,
-            1015 => ScriptOpcode::MapFindSquare,
-            1016 => ScriptOpcode::MoveCoord,
-            1017 => ScriptOpcode::PlayerCount,
-            1018 => ScriptOpcode::ProjAnimMap,
-            1019 => ScriptOpcode::ProjAnimNpc,
-            1020 => ScriptOpcode::ProjAnimPl,
-            1021 => ScriptOpcode::SeqLength,
-            1022 => ScriptOpcode::SplitGet,
-            1023 => ScriptOpcode::SplitGetAnim,
-            1024 => ScriptOpcode::SplitInit,
-            1025 => ScriptOpcode::SplitLineCount,
-            1026 => ScriptOpcode::SplitPageCount,
-            1027 => ScriptOpcode::SpotAnimMap,
-            1028 => ScriptOpcode::StatRandom,
-            1029 => ScriptOpcode::StructParam,
-            1030 => ScriptOpcode::WorldDelay,
-            1031 => ScriptOpcode::NpcsCount,
-            1032 => ScriptOpcode::ZonesCount,
-            1033 => ScriptOpcode::LocsCount,
-            1034 => ScriptOpcode::ObjsCount,
-            1035 => ScriptOpcode::MapMulti,
-            // Player ops (2000-2499)
-            2000 => ScriptOpcode::AllowDesign,
-            2001 => ScriptOpcode::Anim,
-            2002 => ScriptOpcode::BasReadyAnim,
-            2003 => ScriptOpcode::BasRunning,
-            2004 => ScriptOpcode::BasTurnOnSpot,
-            2005 => ScriptOpcode::BasWalkB,
-            2006 => ScriptOpcode::BasWalkF,
-            2007 => ScriptOpcode::BasWalkL,
-            2008 => ScriptOpcode::BasWalkR,
-            2009 => ScriptOpcode::BufferFull,
-            2010 => ScriptOpcode::BuildAppearance,
-            2011 => ScriptOpcode::Busy,
-            2012 => ScriptOpcode::CamLookAt,
-            2013 => ScriptOpcode::CamMoveTo,
-            2014 => ScriptOpcode::CamReset,
-            2015 => ScriptOpcode::CamShake,
-            2016 => ScriptOpcode::ClearQueue,
-            2017 => ScriptOpcode::ClearSoftTimer,
-            2018 => ScriptOpcode::ClearTimer,
-            2019 => ScriptOpcode::GetTimer,
-            2020 => ScriptOpcode::Coord,
-            2021 => ScriptOpcode::Damage,
-            2022 => ScriptOpcode::Displayname,
-            2023 => ScriptOpcode::FaceSquare,
-            2024 => ScriptOpcode::FindUid,
-            2025 => ScriptOpcode::Gender,
-            2026 => ScriptOpcode::GetQueue,
-            2027 => ScriptOpcode::StatAdvance,
-            2028 => ScriptOpcode::HeadiconsGet,
-            2029 => ScriptOpcode::HeadiconsSet,
-            2030 => ScriptOpcode::HealEnergy,
-            2031 => ScriptOpcode::HintCoord,
-            2032 => ScriptOpcode::HintNpc,
-            2033 => ScriptOpcode::HintPlayer,
-            2034 => ScriptOpcode::HintStop,
-            2035 => ScriptOpcode::IfClose,
-            2036 => ScriptOpcode::TutClose,
-            2037 => ScriptOpcode::IfMultiZone,
-            2038 => ScriptOpcode::IfOpenChat,
-            2039 => ScriptOpcode::TutOpen,
-            2040 => ScriptOpcode::IfOpenMain,
-            2041 => ScriptOpcode::IfOpenMainSide,
-            2042 => ScriptOpcode::IfOpenSide,
-            2043 => ScriptOpcode::IfSetAnim,
-            2044 => ScriptOpcode::IfSetColour,
-            2045 => ScriptOpcode::IfSetHide,
-            2046 => ScriptOpcode::IfSetModel,
-            2047 => ScriptOpcode::IfSetRecol,
-            2048 => ScriptOpcode::IfSetNpcHead,
-            2049 => ScriptOpcode::IfSetObject,
-            2050 => ScriptOpcode::IfSetPlayerHead,
-            2051 => ScriptOpcode::IfSetPosition,
-            2052 => ScriptOpcode::IfSetResumeButtons,
-            2053 => ScriptOpcode::IfSetTab,
-            2054 => ScriptOpcode::IfSetTabActive,
-            2055 => ScriptOpcode::TutFlash,
-            2056 => ScriptOpcode::IfSetText,
-            2057 => ScriptOpcode::LastLoginInfo,
-            2058 => ScriptOpcode::LastCom,
-            2059 => ScriptOpcode::LastInt,
-            2060 => ScriptOpcode::LastItem,
-            2061 => ScriptOpcode::LastSlot,
-            2062 => ScriptOpcode::LastTargetSlot,
-            2063 => ScriptOpcode::LastUseItem,
-            2064 => ScriptOpcode::LastUseSlot,
-            2065 => ScriptOpcode::LongQueue,
-            2066 => ScriptOpcode::Mes,
-            2067 => ScriptOpcode::MidiJingle,
-            2068 => ScriptOpcode::MidiSong,
-            2069 => ScriptOpcode::Name,
-            2070 => ScriptOpcode::PApRange,
-            2071 => ScriptOpcode::PArriveDelay,
-            2072 => ScriptOpcode::PCountDialog,
-            2073 => ScriptOpcode::PDelay,
-            2074 => ScriptOpcode::PExactMove,
-            2075 => ScriptOpcode::PFindUid,
-            2076 => ScriptOpcode::PLocMerge,
-            2077 => ScriptOpcode::PLogout,
-            2078 => ScriptOpcode::POpHeld,
-            2079 => ScriptOpcode::POpLoc,
-            2080 => ScriptOpcode::POpNpc,
-            2081 => ScriptOpcode::POpNpcT,
-            2082 => ScriptOpcode::POpObj,
-            2083 => ScriptOpcode::POpPlayer,
-            2084 => ScriptOpcode::POpPlayerT,
-            2085 => ScriptOpcode::PPauseButton,
-            2086 => ScriptOpcode::PStopAction,
-            2087 => ScriptOpcode::PTeleJump,
-            2088 => ScriptOpcode::PTeleport,
-            2089 => ScriptOpcode::PWalk,
-            2090 => ScriptOpcode::PlayerFindAllZone,
-            2091 => ScriptOpcode::PlayerFindNext,
-            2092 => ScriptOpcode::Queue,
-            2093 => ScriptOpcode::Say,
-            2094 => ScriptOpcode::WalkTrigger,
-            2095 => ScriptOpcode::SetTimer,
-            2096 => ScriptOpcode::SoftTimer,
-            2097 => ScriptOpcode::SoundSynth,
-            2098 => ScriptOpcode::SpotAnimPl,
-            2099 => ScriptOpcode::StaffModLevel,
-            2100 => ScriptOpcode::Stat,
-            2101 => ScriptOpcode::StatAdd,
-            2102 => ScriptOpcode::StatBase,
-            2103 => ScriptOpcode::StatHeal,
-            2104 => ScriptOpcode::StatSub,
-            2105 => ScriptOpcode::StrongQueue,
-            2106 => ScriptOpcode::Uid,
-            2107 => ScriptOpcode::WeakQueue,
-            2108 => ScriptOpcode::IfOpenMainOverlay,
-            2109 => ScriptOpcode::AfkEvent,
-            2110 => ScriptOpcode::LowMemory,
-            2111 => ScriptOpcode::SetIdkit,
-            2112 => ScriptOpcode::PClearPendingAction,
-            2113 => ScriptOpcode::GetWalkTrigger,
-            2114 => ScriptOpcode::Busy2,
-            2115 => ScriptOpcode::FindHero,
-            2116 => ScriptOpcode::BothHeroPoints,
-            2117 => ScriptOpcode::SetGender,
-            2118 => ScriptOpcode::SetSkinColour,
-            2119 => ScriptOpcode::PAnimProtect,
-            2120 => ScriptOpcode::RunEnergy,
-            2121 => ScriptOpcode::Weight,
-            2122 => ScriptOpcode::LastCoord,
-            // Npc ops (2500-2999)
-            2500 => ScriptOpcode::NpcAdd,
-            2501 => ScriptOpcode::NpcAnim,
-            2502 => ScriptOpcode::NpcBaseStat,
-            2503 => ScriptOpcode::NpcCategory,
-            2504 => ScriptOpcode::NpcChangeType,
-            2505 => ScriptOpcode::NpcCoord,
-            2506 => ScriptOpcode::NpcDamage,
-            2507 => ScriptOpcode::NpcDel,
-            2508 => ScriptOpcode::NpcDelay,
-            2509 => ScriptOpcode::NpcFaceSquare,
-            2510 => ScriptOpcode::NpcFind,
-            2511 => ScriptOpcode::NpcFindAllAny,
-            2512 => ScriptOpcode::NpcFindAll,
-            2513 => ScriptOpcode::NpcFindExact,
-            2514 => ScriptOpcode::NpcFindHero,
-            2515 => ScriptOpcode::NpcFindAllZone,
-            2516 => ScriptOpcode::NpcFindNext,
-            2517 => ScriptOpcode::NpcFindUid,
-            2518 => ScriptOpcode::NpcGetMode,
-            2519 => ScriptOpcode::NpcHeroPoints,
-            2520 => ScriptOpcode::NpcName,
-            2521 => ScriptOpcode::NpcParam,
-            2522 => ScriptOpcode::NpcQueue,
-            2523 => ScriptOpcode::NpcRange,
-            2524 => ScriptOpcode::NpcSay,
-            2525 => ScriptOpcode::NpcHuntAll,
-            2526 => ScriptOpcode::NpcHuntNext,
-            2527 => ScriptOpcode::NpcSetHunt,
-            2528 => ScriptOpcode::NpcSetHuntMode,
-            2529 => ScriptOpcode::NpcSetMode,
-            2530 => ScriptOpcode::NpcWalkTrigger,
-            2531 => ScriptOpcode::NpcSetTimer,
-            2532 => ScriptOpcode::NpcStat,
-            2533 => ScriptOpcode::NpcStatAdd,
-            2534 => ScriptOpcode::NpcStatHeal,
-            2535 => ScriptOpcode::NpcStatSub,
-            2536 => ScriptOpcode::NpcTele,
-            2537 => ScriptOpcode::NpcType,
-            2538 => ScriptOpcode::NpcUid,
-            2539 => ScriptOpcode::SpotAnimNpc,
-            2540 => ScriptOpcode::NpcWalk,
-            2541 => ScriptOpcode::NpcAttackRange,
-            2542 => ScriptOpcode::NpcHasOp,
-            2543 => ScriptOpcode::NpcArriveDelay,
-            // Loc ops (3000-3499)
-            3000 => ScriptOpcode::LocAdd,
-            3001 => ScriptOpcode::LocAngle,
-            3002 => ScriptOpcode::LocAnim,
-            3003 => ScriptOpcode::LocCategory,
-            3004 => ScriptOpcode::LocChange,
-            3005 => ScriptOpcode::LocCoord,
-            3006 => ScriptOpcode::LocDel,
-            3007 => ScriptOpcode::LocFind,
-            3008 => ScriptOpcode::LocFindAllZone,
-            3009 => ScriptOpcode::LocFindNext,
-            3010 => ScriptOpcode::LocName,
-            3011 => ScriptOpcode::LocParam,
-            3012 => ScriptOpcode::LocShape,
-            3013 => ScriptOpcode::LocType,
-            // Obj ops (3500-4000)
-            3500 => ScriptOpcode::ObjAdd,
-            3501 => ScriptOpcode::ObjAddAll,
-            3502 => ScriptOpcode::ObjCoord,
-            3503 => ScriptOpcode::ObjCount,
-            3504 => ScriptOpcode::ObjDel,
-            3505 => ScriptOpcode::ObjName,
-            3506 => ScriptOpcode::ObjParam,
-            3507 => ScriptOpcode::ObjTakeItem,
-            3508 => ScriptOpcode::ObjType,
-            3509 => ScriptOpcode::ObjFind,
-            // Npc config ops (4000-4099)
-            4000 => ScriptOpcode::NcCategory,
-            4001 => ScriptOpcode::NcDebugname,
-            4002 => ScriptOpcode::NcDesc,
-            4003 => ScriptOpcode::NcName,
-            4004 => ScriptOpcode::NcOp,
-            4005 => ScriptOpcode::NcParam,
-            // Loc config ops (4100-4199)
-            4100 => ScriptOpcode::LcCategory,
-            4101 => ScriptOpcode::LcDebugname,
-            4102 => ScriptOpcode::LcDesc,
-            4103 => ScriptOpcode::LcName,
-            4104 => ScriptOpcode::LcOp,
-            4105 => ScriptOpcode::LcParam,
-            4106 => ScriptOpcode::LcWidth,
-            4107 => ScriptOpcode::LcLength,
-            // Obj config ops (4200-4299)
-            4200 => ScriptOpcode::OcCategory,
-            4201 => ScriptOpcode::OcCert,
-            4202 => ScriptOpcode::OcCost,
-            4203 => ScriptOpcode::OcDebugname,
-            4204 => ScriptOpcode::OcDesc,
-            4205 => ScriptOpcode::OcIop,
-            4206 => ScriptOpcode::OcMembers,
-            4207 => ScriptOpcode::OcName,
-            4208 => ScriptOpcode::OcOp,
-            4209 => ScriptOpcode::OcParam,
-            4210 => ScriptOpcode::OcStackable,
-            4211 => ScriptOpcode::OcTradeable,
-            4212 => ScriptOpcode::OcUncert,
-            4213 => ScriptOpcode::OcWearPos2,
-            4214 => ScriptOpcode::OcWearPos3,
-            4215 => ScriptOpcode::OcWearPos,
-            4216 => ScriptOpcode::OcWeight,
-            // Inventory ops (4300-4399)
-            4300 => ScriptOpcode::InvAllStock,
-            4301 => ScriptOpcode::InvSize,
-            4302 => ScriptOpcode::InvStockBase,
-            4303 => ScriptOpcode::InvAdd,
-            4304 => ScriptOpcode::InvChangeSlot,
-            4305 => ScriptOpcode::InvClear,
-            4306 => ScriptOpcode::InvDel,
-            4307 => ScriptOpcode::InvDelSlot,
-            4308 => ScriptOpcode::InvDropItem,
-            4309 => ScriptOpcode::InvDropSlot,
-            4310 => ScriptOpcode::InvFreespace,
-            4311 => ScriptOpcode::InvGetNum,
-            4312 => ScriptOpcode::InvGetObj,
-            4313 => ScriptOpcode::InvItemSpace,
-            4314 => ScriptOpcode::InvItemSpace2,
-            4315 => ScriptOpcode::InvMoveFromSlot,
-            4316 => ScriptOpcode::InvMoveToSlot,
-            4317 => ScriptOpcode::BothMoveInv,
-            4318 => ScriptOpcode::InvMoveItem,
-            4319 => ScriptOpcode::InvMoveItemCert,
-            4320 => ScriptOpcode::InvMoveItemUncert,
-            4321 => ScriptOpcode::InvSetSlot,
-            4322 => ScriptOpcode::InvTotal,
-            4323 => ScriptOpcode::InvTotalCat,
-            4324 => ScriptOpcode::InvTransmit,
-            4325 => ScriptOpcode::InvOtherTransmit,
-            4326 => ScriptOpcode::InvStopTransmit,
-            4327 => ScriptOpcode::BothDropSlot,
-            4328 => ScriptOpcode::InvDropAll,
-            4329 => ScriptOpcode::InvTotalParam,
-            4330 => ScriptOpcode::InvTotalParamStack,
-            // Enum ops (4400-4499)
-            4400 => ScriptOpcode::Enum,
-            4401 => ScriptOpcode::EnumGetOutputCount,
-            // String ops (4500-4599)
-            4500 => ScriptOpcode::AppendNum,
-            4501 => ScriptOpcode::Append,
-            4502 => ScriptOpcode::AppendSignNum,
-            4503 => ScriptOpcode::Lowercase,
-            4504 => ScriptOpcode::TextGender,
-            4505 => ScriptOpcode::ToString,
-            4506 => ScriptOpcode::Compare,
-            4507 => ScriptOpcode::TextSwitch,
-            4508 => ScriptOpcode::AppendChar,
-            4509 => ScriptOpcode::StringLength,
-            4510 => ScriptOpcode::SubString,
-            4511 => ScriptOpcode::StringIndexOfChar,
-            4512 => ScriptOpcode::StringIndexOfString,
-            // Number ops (4600-4699)
-            4600 => ScriptOpcode::Add,
-            4601 => ScriptOpcode::Sub,
-            4602 => ScriptOpcode::Multiply,
-            4603 => ScriptOpcode::Divide,
-            4604 => ScriptOpcode::Random,
-            4605 => ScriptOpcode::RandomInc,
-            4606 => ScriptOpcode::Interpolate,
-            4607 => ScriptOpcode::AddPercent,
-            4608 => ScriptOpcode::SetBit,
-            4609 => ScriptOpcode::ClearBit,
-            4610 => ScriptOpcode::TestBit,
-            4611 => ScriptOpcode::Modulo,
-            4612 => ScriptOpcode::Pow,
-            4613 => ScriptOpcode::InvPow,
-            4614 => ScriptOpcode::And,
-            4615 => ScriptOpcode::Or,
-            4616 => ScriptOpcode::Min,
-            4617 => ScriptOpcode::Max,
-            4618 => ScriptOpcode::Scale,
-            4619 => ScriptOpcode::BitCount,
-            4620 => ScriptOpcode::ToggleBit,
-            4621 => ScriptOpcode::SetBitRange,
-            4622 => ScriptOpcode::ClearBitRange,
-            4623 => ScriptOpcode::GetBitRange,
-            4624 => ScriptOpcode::SetBitRangeToInt,
-            4625 => ScriptOpcode::SinDeg,
-            4626 => ScriptOpcode::CosDeg,
-            4627 => ScriptOpcode::Atan2Deg,
-            4628 => ScriptOpcode::Abs,
-            // DB ops (7500-7599)
-            7500 => ScriptOpcode::DbFindWithCount,
-            7501 => ScriptOpcode::DbFindNext,
-            7502 => ScriptOpcode::DbGetField,
-            7503 => ScriptOpcode::DbGetFieldCount,
-            7504 => ScriptOpcode::DbListAllWithCount,
-            7505 => ScriptOpcode::DbGetRowTable,
-            7506 => ScriptOpcode::DbFindByIndex,
-            7507 => ScriptOpcode::DbFindRefineWithCount,
-            7508 => ScriptOpcode::DbFind,
-            7509 => ScriptOpcode::DbFindRefine,
-            7510 => ScriptOpcode::DbListAll,
-            // Debug ops (10000-11000)
-            10000 => ScriptOpcode::Error,
-            10001 => ScriptOpcode::MapProduction,
-            10002 => ScriptOpcode::MapLastClock,
-            10003 => ScriptOpcode::MapLastWorld,
-            10004 => ScriptOpcode::MapLastClientIn,
-            10005 => ScriptOpcode::MapLastNpc,
-            10006 => ScriptOpcode::MapLastPlayer,
-            10007 => ScriptOpcode::MapLastLogout,
-            10008 => ScriptOpcode::MapLastLogin,
-            10009 => ScriptOpcode::MapLastZone,
-            10010 => ScriptOpcode::MapLastClientOut,
-            10011 => ScriptOpcode::MapLastCleanup,
-            10012 => ScriptOpcode::MapLastBandwidthIn,
-            10013 => ScriptOpcode::MapLastBandwidthOut,
-            _ => panic!("Invalid script opcode value: {}", code),
+impl ScriptChanges {
+    pub fn is_empty(&self) -> bool {
+        return self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty();
+    }
+}
+
+impl std::fmt::Display for ScriptChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} changed, {} removed",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len()
+        )?;
+        for (label, names) in [
+            ("added", &self.added),
+            ("changed", &self.changed),
+            ("removed", &self.removed),
+        ] {
+            for name in names {
+                write!(f, "\n    {} {}", label, name)?;
+            }
         }
+        return Ok(());
     }
 }
 
+/// The version the compiler writes to `script.dat`, scripts from any other version are refused.
+pub const COMPILER_VERSION: i32 = 19;
+
 #[derive(Clone)]
 pub struct ScriptProvider {
     pub names: HashMap<String, usize>,
@@ -846,16 +506,33 @@ pub struct ScriptProvider {
 
 impl ScriptProvider {
     pub fn io(dir: &str, compiler_version: String) -> ScriptProvider {
+        return match ScriptProvider::load(dir, &compiler_version) {
+            Ok(provider) => provider,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    /// Reads `script.dat` and `script.idx` like `io`, returning an error instead
+    /// of panicking when the files are missing or were built by another compiler
+    /// so a reload can keep the scripts it already has.
+    pub fn load(dir: &str, compiler_version: &str) -> Result<ScriptProvider, String> {
         let start: Instant = Instant::now();
-        let mut dat: Packet = Packet::io(format!("{}/server/script.dat", dir));
-        let mut idx: Packet = Packet::io(format!("{}/server/script.idx", dir));
+        let mut dat: Packet = ScriptProvider::read(format!("{}/server/script.dat", dir))?;
+        let mut idx: Packet = ScriptProvider::read(format!("{}/server/script.idx", dir))?;
+
+        if dat.len() < 6 || idx.len() < 2 {
+            return Err("script.dat or script.idx is empty".to_string());
+        }
 
         let count: usize = dat.g2() as usize;
         idx.pos += 2;
+        if idx.len() < 2 + count * 2 {
+            return Err("script.idx is shorter than its script count".to_string());
+        }
 
         let version: i32 = dat.g4s();
-        if !version.to_string().eq(&compiler_version) {
-            panic!("RuneScript compiler is out of date!");
+        if !version.to_string().eq(compiler_version) {
+            return Err("RuneScript compiler is out of date!".to_string());
         }
 
         let mut names: HashMap<String, usize> = HashMap::new();
@@ -870,33 +547,92 @@ impl ScriptProvider {
 
             let start: usize = dat.pos;
             let end: usize = start + length;
+            if end > dat.len() {
+                return Err(format!("script.dat is truncated at script {}", index));
+            }
 
-            let script: ScriptFile = ScriptFile::new(&mut dat, index, length);
-
-            let info: &ScriptInfo = &script.info;
-            names.insert(info.name.clone(), index);
-            // add the script to lookup table if the value isn't -1
-            if info.lookup != -1 {
-                lookups.insert(info.lookup, index);
+            match ScriptFile::new(&dat.data[start..end], index) {
+                Ok(script) => *option = Some(script),
+                Err(e) => println!("Rejected script {}: {}", index, e),
             }
 
-            *option = Some(script);
+            dat.pos = end;
+        }
 
-            if dat.pos > end {
-                panic!("Script {} has read past end!", index);
+        // gosub targets are checked against every script so this waits until all are loaded,
+        // and verifies again after rejecting any so the scripts calling them are rejected too.
+        loop {
+            let verifier: ScriptVerifier = ScriptVerifier::new(&scripts);
+            let rejected: Vec<usize> = scripts
+                .iter()
+                .enumerate()
+                .filter_map(|(index, option)| {
+                    let script: &ScriptFile = option.as_ref()?;
+                    return match verifier.verify(script) {
+                        Ok(()) => None,
+                        Err(e) => {
+                            println!("Rejected script {}", e);
+                            Some(index)
+                        }
+                    };
+                })
+                .collect();
+            if rejected.is_empty() {
+                break;
+            }
+            for index in rejected {
+                scripts[index] = None;
             }
+        }
 
-            dat.pos = end;
+        for (index, option) in scripts.iter().enumerate() {
+            if let Some(script) = option {
+                let info: &ScriptInfo = &script.info;
+                names.insert(info.name.clone(), index);
+                // add the script to lookup table if the value isn't -1
+                if info.lookup != -1 {
+                    lookups.insert(info.lookup, index);
+                }
+            }
         }
 
         println!("Loaded scripts in: {:?}", start.elapsed());
-        return ScriptProvider {
+        return Ok(ScriptProvider {
             names,
             scripts,
             lookups,
+        });
+    }
+
+    fn read(path: String) -> Result<Packet, String> {
+        return match std::fs::read(&path) {
+            Ok(bytes) => Ok(Packet::from(bytes)),
+            Err(e) => Err(format!("Failed to read {}: {}", path, e)),
         };
     }
 
+    /// Compares the scripts by name against `previous`, the provider being replaced.
+    pub fn changes(&self, previous: &ScriptProvider) -> ScriptChanges {
+        let mut changes: ScriptChanges = ScriptChanges::default();
+        for (name, &id) in &self.names {
+            let script: &ScriptFile = self.scripts[id].as_ref().unwrap();
+            match previous.get_by_name(name) {
+                Ok(old) if old.id == id && old.same_code(script) => {}
+                Ok(_) => changes.changed.push(name.clone()),
+                Err(_) => changes.added.push(name.clone()),
+            }
+        }
+        for name in previous.names.keys() {
+            if !self.names.contains_key(name) {
+                changes.removed.push(name.clone());
+            }
+        }
+        changes.added.sort();
+        changes.changed.sort();
+        changes.removed.sort();
+        return changes;
+    }
+
     pub fn mock() -> ScriptProvider {
         return ScriptProvider {
             names: HashMap::new(),
@@ -905,6 +641,17 @@ impl ScriptProvider {
         };
     }
 
+    /// A mock holding `scripts`, each given the id of its index like loading `script.dat` does.
+    pub fn mock_with(scripts: Vec<ScriptFile>) -> ScriptProvider {
+        let mut provider: ScriptProvider = ScriptProvider::mock();
+        for (id, mut script) in scripts.into_iter().enumerate() {
+            script.id = id;
+            provider.names.insert(script.info.name.clone(), id);
+            provider.scripts.push(Some(script));
+        }
+        return provider;
+    }
+
     /// Retrieves a script by its ID, invoking the provided callback functions
     /// based on whether the script is found or not.
     ///
@@ -1238,14 +985,26 @@ pub struct ScriptInfo {
     pub lines: Vec<i32>,
 }
 
+impl ScriptInfo {
+    /// Finds the source line of the instruction at `pc` using the line table,
+    /// the table only has an entry for the first instruction of every line.
+    pub fn line(&self, pc: i32) -> Option<i32> {
+        let index: usize = self.pcs.partition_point(|&start| start <= pc);
+        if index == 0 {
+            return None;
+        }
+        return Some(self.lines[index - 1]);
+    }
+}
+
 #[derive(Clone)]
 pub struct ScriptFile {
     id: usize,
-    int_local_count: u16,
-    string_local_count: u16,
+    pub int_local_count: u16,
+    pub string_local_count: u16,
     pub int_arg_count: u16,
     pub string_arg_count: u16,
-    pub switch_table: Option<HashMap<i32, i32>>,
+    pub switch_tables: Vec<HashMap<i32, i32>>, // indexed by the switch operand
     pub info: ScriptInfo,
     pub codes: Option<Vec<Option<ScriptOpcode>>>,
     pub int_operands: Vec<i32>,
@@ -1253,7 +1012,25 @@ pub struct ScriptFile {
 }
 
 impl ScriptFile {
-    fn is_large_operand(code: &ScriptOpcode) -> bool {
+    /// The id of the script, its index in `script.dat`.
+    pub fn id(&self) -> usize {
+        return self.id;
+    }
+
+    /// Whether both scripts compiled to the same instructions, ignoring the line table.
+    pub fn same_code(&self, other: &ScriptFile) -> bool {
+        return self.int_local_count == other.int_local_count
+            && self.string_local_count == other.string_local_count
+            && self.int_arg_count == other.int_arg_count
+            && self.string_arg_count == other.string_arg_count
+            && self.switch_tables == other.switch_tables
+            && self.codes == other.codes
+            && self.int_operands == other.int_operands
+            && self.string_operands == other.string_operands;
+    }
+
+    /// Whether the operand of an instruction is stored as 4 bytes instead of 1.
+    pub fn is_large_operand(code: &ScriptOpcode) -> bool {
         if code > &ScriptOpcode::EndCoreOps {
             return false;
         }
@@ -1267,61 +1044,65 @@ impl ScriptFile {
         };
     }
 
-    fn new(dat: &mut Packet, id: usize, length: usize) -> ScriptFile {
-        let start: usize = dat.pos;
-        let end: usize = start + length;
+    /// Decodes a script from its bytes in `script.dat`, every read is bounds
+    /// checked so a corrupt script is rejected instead of read past its end.
+    fn new(bytes: &[u8], id: usize) -> Result<ScriptFile, String> {
+        let length: usize = bytes.len();
+        let mut dat: PacketReader = PacketReader::new(bytes);
 
-        if end < 16 {
-            panic!("Invalid script file (minimum length) must be 16 bytes.");
+        if length < 16 {
+            return Err("Invalid script file (minimum length) must be 16 bytes.".to_string());
         }
 
-        dat.pos = end - 2;
+        dat.pos = length - 2;
 
-        let trailer_len: usize = dat.g2() as usize;
-        let trailer_pos: usize = end - trailer_len - 12 - 2;
-
-        if trailer_pos >= end {
-            panic!("Invalid script file (bad trailer pos).");
-        }
+        let trailer_len: usize = dat.try_g2()? as usize;
+        let trailer_pos: usize = match length.checked_sub(trailer_len + 12 + 2) {
+            Some(pos) => pos,
+            None => return Err("Invalid script file (bad trailer pos).".to_string()),
+        };
 
         dat.pos = trailer_pos;
 
-        let instructions: usize = dat.g4s() as usize;
-        let int_local_count: u16 = dat.g2();
-        let string_local_count: u16 = dat.g2();
-        let int_arg_count: u16 = dat.g2();
-        let string_arg_count: u16 = dat.g2();
-        let mut switch_table: Option<HashMap<i32, i32>> = None;
-
-        let switches: usize = dat.g1() as usize;
+        // every instruction takes at least 3 bytes, so this also bounds the allocations below.
+        let instructions: usize = match usize::try_from(dat.try_g4s()?) {
+            Ok(instructions) if instructions <= trailer_pos / 3 => instructions,
+            _ => return Err("Invalid script file (bad instruction count).".to_string()),
+        };
+        let int_local_count: u16 = dat.try_g2()?;
+        let string_local_count: u16 = dat.try_g2()?;
+        let int_arg_count: u16 = dat.try_g2()?;
+        let string_arg_count: u16 = dat.try_g2()?;
+        let switches: usize = dat.try_g1()? as usize;
+        let mut switch_tables: Vec<HashMap<i32, i32>> = Vec::with_capacity(switches);
         for _ in 0..switches {
-            let count: usize = dat.g2() as usize;
+            let count: usize = dat.try_g2()? as usize;
             let mut table: HashMap<i32, i32> = HashMap::new();
 
             for _ in 0..count {
-                table.insert(dat.g4s(), dat.g4s());
+                table.insert(dat.try_g4s()?, dat.try_g4s()?);
             }
 
-            switch_table = Some(table);
+            switch_tables.push(table);
+        }
+        if dat.pos > length - 2 {
+            return Err("Invalid script file (switch tables overrun the trailer).".to_string());
         }
 
-        dat.pos = start;
-        let name: String = dat.gjstr(0);
-        let path: String = dat.gjstr(0);
-        let lookup: i32 = dat.g4s();
+        dat.pos = 0;
+        let name: String = dat.try_gjstr(0)?;
+        let path: String = dat.try_gjstr(0)?;
+        let lookup: i32 = dat.try_g4s()?;
 
-        let params_count: usize = dat.g1() as usize;
-        let mut params: Vec<u8> = vec![0; params_count];
-        for param in params.iter_mut() {
-            *param = dat.g1();
-        }
+        let params_count: usize = dat.try_g1()? as usize;
+        let params: Vec<u8> = dat.try_gdata(params_count)?.to_vec();
 
-        let lines_count: usize = dat.g2() as usize;
+        let lines_count: usize = dat.try_g2()? as usize;
         let mut pcs: Vec<i32> = vec![0; lines_count];
         let mut lines: Vec<i32> = vec![0; lines_count];
         for index in 0..lines_count {
-            pcs[index] = dat.g4s();
-            lines[index] = dat.g4s();
+            pcs[index] = dat.try_g4s()?;
+            lines[index] = dat.try_g4s()?;
         }
 
         let info: ScriptInfo = ScriptInfo {
@@ -1339,32 +1120,50 @@ impl ScriptFile {
 
         let mut pc: usize = 0;
         while trailer_pos > dat.pos {
-            let code: ScriptOpcode = ScriptOpcode::from(dat.g2());
+            if pc >= instructions {
+                return Err(format!(
+                    "Invalid script file (more than {} instructions).",
+                    instructions
+                ));
+            }
+
+            let code: ScriptOpcode = ScriptOpcode::try_from(dat.try_g2()?)?;
 
             if code == ScriptOpcode::PushConstantString {
-                string_operands[pc] = dat.gjstr(0);
+                string_operands[pc] = dat.try_gjstr(0)?;
             } else if ScriptFile::is_large_operand(&code) {
-                int_operands[pc] = dat.g4s();
+                int_operands[pc] = dat.try_g4s()?;
             } else {
-                int_operands[pc] = dat.g1() as i32;
+                int_operands[pc] = dat.try_g1()? as i32;
             }
 
             opcodes[pc] = Some(code);
             pc += 1;
         }
 
-        return ScriptFile {
+        if dat.pos != trailer_pos {
+            return Err("Invalid script file (instructions overrun the trailer).".to_string());
+        }
+
+        if pc != instructions {
+            return Err(format!(
+                "Invalid script file (expected {} instructions, found {}).",
+                instructions, pc
+            ));
+        }
+
+        return Ok(ScriptFile {
             id,
             int_local_count,
             string_local_count,
             int_arg_count,
             string_arg_count,
-            switch_table,
+            switch_tables,
             info,
             codes: Some(opcodes),
             int_operands,
             string_operands,
-        };
+        });
     }
 
     pub fn mock() -> ScriptFile {
@@ -1374,7 +1173,7 @@ impl ScriptFile {
             string_local_count: 0,
             int_arg_count: 0,
             string_arg_count: 0,
-            switch_table: None,
+            switch_tables: Vec::new(),
             info: ScriptInfo {
                 name: String::new(),
                 path: String::new(),
@@ -1388,6 +1187,17 @@ impl ScriptFile {
             string_operands: Vec::new(),
         };
     }
+
+    /// A mock `[proc,test]` in `scripts/test.rs2` that runs `codes` with `int_operands`.
+    pub fn mock_with(codes: Vec<ScriptOpcode>, int_operands: Vec<i32>) -> ScriptFile {
+        let mut file: ScriptFile = ScriptFile::mock();
+        file.info.name = "[proc,test]".to_string();
+        file.info.path = "scripts/test.rs2".to_string();
+        file.codes = Some(codes.into_iter().map(Some).collect());
+        file.string_operands = vec![String::new(); int_operands.len()];
+        file.int_operands = int_operands;
+        return file;
+    }
 }
 
 struct GoSubFrame<'script> {
@@ -1395,6 +1205,7 @@ struct GoSubFrame<'script> {
     pc: i32, // program counter
     int_locals: Vec<i32>,
     string_locals: Vec<String>,
+    arrays: Vec<Vec<i32>>,
 }
 
 struct GoToFrame<'script> {
@@ -1402,6 +1213,121 @@ struct GoToFrame<'script> {
     pc: i32,
 }
 
+/// What went wrong while running a script.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ScriptErrorKind {
+    Unimplemented,  // the engine has no handler for the opcode yet
+    StackUnderflow, // popped more values than were pushed
+    StackOverflow,  // the int or string stack is full, or gosubs went too deep
+    RunawayScript,  // ran more than `ScriptLimits::instructions` instructions
+    BadPointer {
+        required: String,
+        current: String,
+    },
+    MissingConfig {
+        kind: &'static str,
+        id: i32,
+    },
+    MissingPlayer(i32),
+    InvalidPc(i32),
+    InvalidArray(i32), // an array id past the last array or not defined yet
+    ArrayIndex {
+        array: i32,
+        index: i32,
+        length: usize,
+    },
+    LimitExceeded {
+        limit: &'static str,
+        max: usize,
+    },
+    Runtime(String), // anything else a command rejects, like a bad char
+}
+
+impl std::fmt::Display for ScriptErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return match self {
+            ScriptErrorKind::Unimplemented => write!(f, "Unimplemented opcode"),
+            ScriptErrorKind::StackUnderflow => write!(f, "Stack underflow"),
+            ScriptErrorKind::StackOverflow => write!(f, "Stack overflow"),
+            ScriptErrorKind::RunawayScript => write!(f, "Too many instructions"),
+            ScriptErrorKind::BadPointer { required, current } => {
+                write!(f, "Required pointer: {}, current: {}", required, current)
+            }
+            ScriptErrorKind::MissingConfig { kind, id } => write!(f, "Missing {}: {}", kind, id),
+            ScriptErrorKind::MissingPlayer(uid) => write!(f, "Missing player: {}", uid),
+            ScriptErrorKind::InvalidPc(pc) => write!(f, "Invalid program counter: {}", pc),
+            ScriptErrorKind::InvalidArray(array) => write!(f, "Invalid array: {}", array),
+            ScriptErrorKind::ArrayIndex {
+                array,
+                index,
+                length,
+            } => write!(
+                f,
+                "Array index out of bounds: {} in array {} of length {}",
+                index, array, length
+            ),
+            ScriptErrorKind::LimitExceeded { limit, max } => {
+                write!(f, "Exceeded the {} limit of {}", limit, max)
+            }
+            ScriptErrorKind::Runtime(message) => write!(f, "{}", message),
+        };
+    }
+}
+
+/// One entry of a script stack trace, a script and the instruction it was at.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptFrame {
+    pub script: String, // the script name, `[proc,foo]`
+    pub path: String,
+    pub pc: i32,
+    pub line: Option<i32>,
+}
+
+impl ScriptFrame {
+    fn of(script: &ScriptFile, pc: i32) -> ScriptFrame {
+        return ScriptFrame {
+            script: script.info.name.clone(),
+            path: script.info.path.clone(),
+            pc,
+            line: script.info.line(pc),
+        };
+    }
+}
+
+impl std::fmt::Display for ScriptFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return match self.line {
+            Some(line) => write!(f, "{} ({}:{})", self.script, self.path, line),
+            None => write!(f, "{} ({}, pc {})", self.script, self.path, self.pc),
+        };
+    }
+}
+
+/// A script error along with where in the script it happened.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptError {
+    pub kind: ScriptErrorKind,
+    pub opcode: Option<ScriptOpcode>,
+    pub script: String, // the script name, `[proc,foo]`
+    pub path: String,
+    pub pc: i32,
+    pub line: Option<i32>,
+    pub trace: Vec<ScriptFrame>, // the failing script first, then its callers
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(opcode) = &self.opcode {
+            write!(f, " ({:?})", opcode)?;
+        }
+        for frame in &self.trace {
+            write!(f, "\n    at {}", frame)?;
+        }
+        return Ok(());
+    }
+}
+
 #[derive(PartialEq, Debug)]
 #[repr(i8)]
 pub enum ScriptExecutionState {
@@ -1450,6 +1376,15 @@ impl From<i32> for ScriptPointer {
     }
 }
 
+/// A loc on the map a script acts on, as it was added or found by the script.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScriptLoc {
+    pub coord: i32,
+    pub loc: i32,
+    pub shape: i32,
+    pub angle: i32,
+}
+
 pub struct ScriptState<'script> {
     pub script: &'script ScriptFile,
     pub execution_state: ScriptExecutionState,
@@ -1465,9 +1400,14 @@ pub struct ScriptState<'script> {
     pub ssp: usize, // string stack pointer
     pub int_locals: Vec<i32>,
     pub string_locals: Vec<String>,
-    pointers: i32, // state pointers
+    pub arrays: Vec<Vec<i32>>, // the int arrays of the current frame, by array id
+    pointers: i32,             // state pointers
     active_player: i32,
     active_player2: i32,
+    active_loc: Option<ScriptLoc>,
+    active_loc2: Option<ScriptLoc>,
+    pub limits: ScriptLimits,
+    scripts: Option<&'script ScriptProvider>, // what gosubs and jumps resolve against
 }
 
 impl<'script> ScriptState<'script> {
@@ -1477,6 +1417,9 @@ impl<'script> ScriptState<'script> {
     pub const ACTIVE_LOC: [ScriptPointer; 2] =
         [ScriptPointer::ActiveLoc, ScriptPointer::ActiveLoc2];
 
+    /// How many int arrays a single frame can define.
+    pub const MAX_ARRAYS: usize = 5;
+
     pub const ACTIVE_OBJ: [ScriptPointer; 2] =
         [ScriptPointer::ActiveObj, ScriptPointer::ActiveObj2];
 
@@ -1504,19 +1447,25 @@ impl<'script> ScriptState<'script> {
     ///
     /// Returns a new instance of `ScriptState` initialized with the provided arguments.
     ///
-    /// # Safety
-    ///
-    /// This function assumes that the `ScriptFile` provided has valid local counts for
-    /// integer and string variables. If the `script` is improperly initialized, it may cause
-    /// undefined behavior.
-    #[rustfmt::skip]
     pub fn new_with_args(
         script: &ScriptFile,
         int_args: Vec<i32>,
         string_args: Vec<String>,
-    ) -> ScriptState {
-        let mut int_locals: Vec<i32> = vec![0; script.int_local_count as usize];
-        let mut string_locals: Vec<String> = vec![String::new(); script.string_local_count as usize];
+    ) -> ScriptState<'_> {
+        return ScriptState::new_with_limits(script, int_args, string_args, ScriptLimits::DEFAULT);
+    }
+
+    /// Creates a new `ScriptState` like `new_with_args` that runs within `limits`,
+    /// the stacks are allocated to the sizes the limits allow.
+    #[rustfmt::skip]
+    pub fn new_with_limits(
+        script: &ScriptFile,
+        int_args: Vec<i32>,
+        string_args: Vec<String>,
+        limits: ScriptLimits,
+    ) -> ScriptState<'_> {
+        let mut int_locals: Vec<i32> = vec![0; (script.int_local_count as usize).max(int_args.len())];
+        let mut string_locals: Vec<String> = vec![String::new(); (script.string_local_count as usize).max(string_args.len())];
 
         int_locals[..int_args.len()].copy_from_slice(&int_args);
         string_locals[..string_args.len()].clone_from_slice(&string_args);
@@ -1526,23 +1475,28 @@ impl<'script> ScriptState<'script> {
             execution_state: ScriptExecutionState::Running,
             pc: -1,
             opcount: 0,
-            frame_stack: Vec::with_capacity(50),
+            frame_stack: Vec::with_capacity(limits.frames),
             fp: 0,
             goto_frame_stack: Vec::with_capacity(50),
             goto_fp: 0,
-            int_stack: vec![0; 1000],
+            int_stack: vec![0; limits.int_stack],
             isp: 0,
-            string_stack: vec![String::new(); 1000],
+            string_stack: vec![String::new(); limits.string_stack],
             ssp: 0,
             int_locals,
             string_locals,
+            arrays: Vec::new(),
             pointers: 0,
             active_player: -1,
             active_player2: -1,
+            active_loc: None,
+            active_loc2: None,
+            limits,
+            scripts: None,
         }
     }
 
-    pub fn mock(file: &ScriptFile) -> ScriptState {
+    pub fn mock(file: &ScriptFile) -> ScriptState<'_> {
         return ScriptState {
             script: file,
             execution_state: ScriptExecutionState::Running,
@@ -1558,57 +1512,259 @@ impl<'script> ScriptState<'script> {
             ssp: 0,
             int_locals: Vec::new(),
             string_locals: Vec::new(),
+            arrays: Vec::new(),
             pointers: 0,
             active_player: -1,
             active_player2: -1,
+            active_loc: None,
+            active_loc2: None,
+            limits: ScriptLimits {
+                int_stack: 100,
+                string_stack: 100,
+                ..ScriptLimits::DEFAULT
+            },
+            scripts: None,
+        };
+    }
+
+    /// Resolves gosubs and jumps against `scripts`, the provider `script` was taken
+    /// from, so a run started before a reload keeps calling the scripts it started
+    /// with. Without it they are resolved by the engine when they run.
+    pub fn with_scripts(mut self, scripts: &'script ScriptProvider) -> ScriptState<'script> {
+        self.scripts = Some(scripts);
+        return self;
+    }
+
+    /// The script a gosub or jump to `id` runs.
+    pub fn script_by_id(
+        &self,
+        engine: &'script impl ScriptEngine,
+        id: i32,
+    ) -> Result<&'script ScriptFile, ScriptErrorKind> {
+        return match self.scripts {
+            Some(scripts) => scripts
+                .get_by_id(id as usize)
+                .map_err(|_| ScriptErrorKind::MissingConfig { kind: "script", id }),
+            None => engine.pop_script(id),
         };
     }
 
-    pub fn execute(
+    /// Runs the script until it finishes, suspends or fails.
+    ///
+    /// A failing instruction aborts the script with a `ScriptError` pointing at the
+    /// instruction and its source line, the caller decides what to do with it.
+    /// Runs under the debugger, profiler and coverage the runner has attached,
+    /// any of them can be attached at once.
+    pub fn execute(&mut self, runner: &'script impl ScriptRunner) -> Result<(), ScriptError> {
+        let mut debugger: Option<RefMut<'_, ScriptDebugger>> = runner.debugger();
+        let mut profiler: Option<RefMut<'_, ScriptProfiler>> = runner.profiler();
+        let mut coverage: Option<RefMut<'_, ScriptCoverage>> = runner.coverage();
+        if debugger.is_some() || profiler.is_some() || coverage.is_some() {
+            self.execute_instrumented(
+                runner,
+                debugger.as_deref_mut(),
+                profiler.as_deref_mut(),
+                coverage.as_deref_mut(),
+            )?;
+        } else {
+            self.execution_state = ScriptExecutionState::Running;
+            while self.execution_state == ScriptExecutionState::Running {
+                self.step(runner)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Runs the script like `execute`, letting the debugger pause it before
+    /// any instruction for breakpoints and stepping.
+    pub fn execute_debug(
         &mut self,
         runner: &'script impl ScriptRunner,
-        benchmark: bool,
-    ) -> Result<(), String> {
-        self.execution_state = ScriptExecutionState::Running;
+        debugger: &mut ScriptDebugger,
+    ) -> Result<(), ScriptError> {
+        return self.execute_instrumented(runner, Some(debugger), None, None);
+    }
+
+    /// Runs the script like `execute`, timing every instruction into the profiler.
+    pub fn execute_profiled(
+        &mut self,
+        runner: &'script impl ScriptRunner,
+        profiler: &mut ScriptProfiler,
+    ) -> Result<(), ScriptError> {
+        return self.execute_instrumented(runner, None, Some(profiler), None);
+    }
+
+    /// Runs the script like `execute`, counting every instruction it runs into the coverage.
+    pub fn execute_covered(
+        &mut self,
+        runner: &'script impl ScriptRunner,
+        coverage: &mut ScriptCoverage,
+    ) -> Result<(), ScriptError> {
+        return self.execute_instrumented(runner, None, None, Some(coverage));
+    }
 
+    /// Runs the script under any of the debugger, profiler and coverage.
+    ///
+    /// The debugger pauses before an instruction is timed, so time spent paused
+    /// only shows up in the time of the whole run.
+    pub fn execute_instrumented(
+        &mut self,
+        runner: &'script impl ScriptRunner,
+        mut debugger: Option<&mut ScriptDebugger>,
+        mut profiler: Option<&mut ScriptProfiler>,
+        mut coverage: Option<&mut ScriptCoverage>,
+    ) -> Result<(), ScriptError> {
+        self.execution_state = ScriptExecutionState::Running;
         let start: Instant = Instant::now();
+        let opcount: i32 = self.opcount;
+        let root: &'script ScriptFile = self.script;
+        if self.pc == -1 {
+            if let Some(profiler) = profiler.as_deref_mut() {
+                profiler.enter(&root.info.name);
+            }
+            if let Some(coverage) = coverage.as_deref_mut() {
+                coverage.enter(root);
+            }
+        }
+
+        let mut stack: String = match profiler {
+            Some(_) => self.folded_stack(),
+            None => String::new(),
+        };
+        let mut result: Result<(), ScriptError> = Ok(());
         while self.execution_state == ScriptExecutionState::Running {
-            match &self.script.codes {
-                None => panic!("Invalid script did not contain any codes to run!"),
-                Some(codes) => {
-                    let pc: i32 = self.pc;
-                    let length: i32 = codes.len() as i32;
-                    if pc >= length || pc < -1 {
-                        panic!("Invalid program counter: {}, max expected: {}", pc, length);
-                    }
+            if let Some(debugger) = debugger.as_deref_mut() {
+                if !debugger.check(self) {
+                    let kind: ScriptErrorKind =
+                        ScriptErrorKind::Runtime("Aborted by debugger".to_string());
+                    result = Err(self.abort(kind, None));
+                    break;
+                }
+            }
+            if let Some(coverage) = coverage.as_deref_mut() {
+                coverage.hit(self.script, self.pc + 1);
+            }
 
-                    if self.opcount > 500_000 {
-                        panic!("Too many instructions! {}", self.script.info.name);
-                    }
+            let script: &'script ScriptFile = self.script;
+            let (fp, goto_fp): (usize, usize) = (self.fp, self.goto_fp);
+            let code: Option<ScriptOpcode> = script
+                .codes
+                .as_ref()
+                .and_then(|codes| codes.get((self.pc + 1) as usize))
+                .cloned()
+                .flatten();
+
+            let op_start: Instant = Instant::now();
+            result = self.step(runner);
+            if let (Some(profiler), Some(code)) = (profiler.as_deref_mut(), &code) {
+                profiler.instruction(&script.info.name, &stack, code, op_start.elapsed());
+            }
+            if result.is_err() {
+                break;
+            }
 
-                    self.opcount += 1;
-                    self.pc += 1;
-
-                    if let Some(Some(code)) = self
-                        .script
-                        .codes
-                        .as_ref()
-                        .and_then(|codes| codes.get(self.pc as usize))
-                    {
-                        if let Err(err) = runner.push_script(self, code) {
-                            self.execution_state = ScriptExecutionState::Aborted;
-                            return Err(err);
-                        }
+            if !std::ptr::eq(script, self.script) || fp != self.fp || goto_fp != self.goto_fp {
+                // a new gosub or jump frame, returns only go back to a script already entered.
+                let entered: bool = self.fp > fp || self.goto_fp > goto_fp;
+                if let Some(profiler) = profiler.as_deref_mut() {
+                    if entered {
+                        profiler.enter(&self.script.info.name);
+                    }
+                    stack = self.folded_stack();
+                }
+                if let Some(coverage) = coverage.as_deref_mut() {
+                    if entered {
+                        coverage.enter(self.script);
                     }
                 }
             }
         }
-        if !benchmark {
-            println!("Executed script in: {:?}", start.elapsed());
+        if let Some(profiler) = profiler {
+            profiler.run(
+                &root.info.name,
+                (self.opcount - opcount) as u64,
+                start.elapsed(),
+            );
+        }
+        return result;
+    }
+
+    /// The scripts on the stack from the outermost, joined by `;`.
+    fn folded_stack(&self) -> String {
+        let mut names: Vec<&str> =
+            Vec::with_capacity(1 + self.goto_frame_stack.len() + self.frame_stack.len());
+        names.extend(
+            self.goto_frame_stack
+                .iter()
+                .map(|frame| frame.script.info.name.as_str()),
+        );
+        names.extend(
+            self.frame_stack
+                .iter()
+                .map(|frame| frame.script.info.name.as_str()),
+        );
+        names.push(&self.script.info.name);
+        return names.join(";");
+    }
+
+    /// Runs the next instruction.
+    #[inline(always)]
+    fn step(&mut self, runner: &'script impl ScriptRunner) -> Result<(), ScriptError> {
+        if self.opcount >= self.limits.instructions {
+            return Err(self.abort(ScriptErrorKind::RunawayScript, None));
+        }
+
+        self.opcount += 1;
+        self.pc += 1;
+
+        let code: ScriptOpcode = match self
+            .script
+            .codes
+            .as_ref()
+            .and_then(|codes| codes.get(self.pc as usize))
+        {
+            Some(Some(code)) if self.pc >= 0 => code.clone(),
+            _ => return Err(self.abort(ScriptErrorKind::InvalidPc(self.pc), None)),
+        };
+
+        if let Err(kind) = runner.push_script(self, &code) {
+            return Err(self.abort(kind, Some(code)));
         }
         return Ok(());
     }
 
+    /// Marks the script as aborted and describes where it failed.
+    fn abort(&mut self, kind: ScriptErrorKind, opcode: Option<ScriptOpcode>) -> ScriptError {
+        self.execution_state = ScriptExecutionState::Aborted;
+        let info: &ScriptInfo = &self.script.info;
+        return ScriptError {
+            kind,
+            opcode,
+            script: info.name.clone(),
+            path: info.path.clone(),
+            pc: self.pc,
+            line: info.line(self.pc),
+            trace: self.stack_trace(),
+        };
+    }
+
+    /// Walks the frames from the running script back to the script that started it.
+    ///
+    /// The running script comes first, followed by the gosub frames that will be
+    /// returned to and then the scripts that jumped here, most recent first.
+    pub fn stack_trace(&self) -> Vec<ScriptFrame> {
+        let mut trace: Vec<ScriptFrame> =
+            Vec::with_capacity(1 + self.frame_stack.len() + self.goto_frame_stack.len());
+        trace.push(ScriptFrame::of(self.script, self.pc));
+        for frame in self.frame_stack.iter().rev() {
+            trace.push(ScriptFrame::of(frame.script, frame.pc));
+        }
+        for frame in self.goto_frame_stack.iter().rev() {
+            trace.push(ScriptFrame::of(frame.script, frame.pc));
+        }
+        return trace;
+    }
+
     // ---- ints
 
     #[inline(always)]
@@ -1619,30 +1775,42 @@ impl<'script> ScriptState<'script> {
     /// Pushes an `i32` value onto the integer stack.
     ///
     /// This method places the provided integer value at the current integer stack pointer
-    /// and then increments the pointer. It assumes that there is sufficient space on the stack.
-    /// When a `ScriptState` is created, it allocates the string stack with a capacity of 1000.
+    /// and then increments the pointer.
+    /// When a `ScriptState` is created, it allocates the integer stack with a capacity of 1000.
     ///
     /// # Parameters
     ///
     /// - `value`: The `i32` value to push onto the integer stack.
+    ///
+    /// # Return
+    ///
+    /// Returns `Err(ScriptErrorKind::StackOverflow)` if the stack is full.
     #[inline(always)]
-    pub fn push_int(&mut self, value: i32) {
+    pub fn push_int(&mut self, value: i32) -> Result<(), ScriptErrorKind> {
+        if self.isp >= self.int_stack.len() {
+            return Err(ScriptErrorKind::StackOverflow);
+        }
         self.int_stack[self.isp] = value;
         self.isp += 1;
+        return Ok(());
     }
 
     /// Pops the top integer value from the integer stack.
     ///
     /// This method decreases the integer stack pointer and retrieves the integer at the new top
-    /// of the stack. The caller should ensure there are values on the stack.
+    /// of the stack.
     ///
     /// # Return
     ///
-    /// Returns the top integer value as an `i32`.
+    /// Returns the top integer value as an `i32`, or `Err(ScriptErrorKind::StackUnderflow)`
+    /// if the stack is empty.
     #[inline(always)]
-    pub fn pop_int(&mut self) -> i32 {
+    pub fn pop_int(&mut self) -> Result<i32, ScriptErrorKind> {
+        if self.isp == 0 {
+            return Err(ScriptErrorKind::StackUnderflow);
+        }
         self.isp -= 1;
-        return self.int_stack[self.isp];
+        return Ok(self.int_stack[self.isp]);
     }
 
     // ---- strings
@@ -1655,30 +1823,103 @@ impl<'script> ScriptState<'script> {
     /// Pushes a `String` value onto the string stack.
     ///
     /// This method places the provided string value at the current string stack pointer
-    /// and then increments the pointer. It assumes that there is sufficient space on the stack.
+    /// and then increments the pointer.
     /// When a `ScriptState` is created, it allocates the string stack with a capacity of 1000.
     ///
     /// # Parameters
     ///
     /// - `value`: The `String` value to push onto the string stack.
+    ///
+    /// # Return
+    ///
+    /// Returns `Err(ScriptErrorKind::StackOverflow)` if the stack is full, or
+    /// `Err(ScriptErrorKind::LimitExceeded)` if `value` is longer than the string length limit.
     #[inline(always)]
-    pub fn push_string(&mut self, value: String) {
+    pub fn push_string(&mut self, value: String) -> Result<(), ScriptErrorKind> {
+        if self.ssp >= self.string_stack.len() {
+            return Err(ScriptErrorKind::StackOverflow);
+        }
+        if value.len() > self.limits.string_length {
+            return Err(ScriptErrorKind::LimitExceeded {
+                limit: "string length",
+                max: self.limits.string_length,
+            });
+        }
         self.string_stack[self.ssp] = value;
         self.ssp += 1;
+        return Ok(());
     }
 
     /// Pops the top string value from the string stack.
     ///
     /// This method decreases the string stack pointer and retrieves the string at the new top
-    /// of the stack. IThe caller should ensure there are values on the stack.
+    /// of the stack.
     ///
     /// # Return
     ///
-    /// Returns the top string value as a `String`.
+    /// Returns the top string value as a `String`, or `Err(ScriptErrorKind::StackUnderflow)`
+    /// if the stack is empty.
     #[inline(always)]
-    pub fn pop_string(&mut self) -> String {
+    pub fn pop_string(&mut self) -> Result<String, ScriptErrorKind> {
+        if self.ssp == 0 {
+            return Err(ScriptErrorKind::StackUnderflow);
+        }
         self.ssp -= 1;
-        return self.string_stack[self.ssp].clone();
+        return Ok(self.string_stack[self.ssp].clone());
+    }
+
+    // ---- arrays
+
+    /// Defines int array `array` of the current frame with `size` elements all
+    /// set to the default of `kind`, 0 for ints and booleans and -1 for the rest.
+    pub fn define_array(
+        &mut self,
+        array: i32,
+        kind: i32,
+        size: i32,
+    ) -> Result<(), ScriptErrorKind> {
+        if array < 0 || array as usize >= ScriptState::MAX_ARRAYS {
+            return Err(ScriptErrorKind::InvalidArray(array));
+        }
+        if size < 0 {
+            return Err(ScriptErrorKind::Runtime(format!(
+                "Invalid array size: {}",
+                size
+            )));
+        }
+        if size as usize > self.limits.array_size {
+            return Err(ScriptErrorKind::LimitExceeded {
+                limit: "array size",
+                max: self.limits.array_size,
+            });
+        }
+        let value: i32 = if kind == 'i' as i32 || kind == '1' as i32 {
+            0
+        } else {
+            -1
+        };
+        if self.arrays.len() <= array as usize {
+            self.arrays.resize(array as usize + 1, Vec::new());
+        }
+        self.arrays[array as usize] = vec![value; size as usize];
+        return Ok(());
+    }
+
+    /// The element at `index` of int array `array`, checking both are in bounds.
+    pub fn array_element(&mut self, array: i32, index: i32) -> Result<&mut i32, ScriptErrorKind> {
+        let elements: &mut Vec<i32> = match self.arrays.get_mut(array as usize) {
+            Some(elements) if array >= 0 => elements,
+            _ => return Err(ScriptErrorKind::InvalidArray(array)),
+        };
+        let length: usize = elements.len();
+        return match elements.get_mut(index as usize) {
+            Some(element) if index >= 0 => Ok(element),
+            _ => Err(ScriptErrorKind::ArrayIndex {
+                array,
+                index,
+                length,
+            }),
+        };
     }
 
     // ---- frames
@@ -1709,6 +1950,7 @@ impl<'script> ScriptState<'script> {
         self.pc = frame.pc;
         self.int_locals = frame.int_locals;
         self.string_locals = frame.string_locals;
+        self.arrays = frame.arrays;
     }
 
     /// Pushes a new subroutine frame onto the frame stack for the given script.
@@ -1737,30 +1979,54 @@ impl<'script> ScriptState<'script> {
     /// - Increments the frame pointer (`fp`) to reflect the new frame.
     /// - Resets the program counter (`pc`) to -1 to prepare for execution in the new subroutine.
     /// - Initializes local integer and string variables by popping them from the respective stacks.
-    pub fn gosub_frame(&mut self, script: &'script ScriptFile) {
+    ///
+    /// Returns `Err(ScriptErrorKind::StackUnderflow)` without touching any frame if the stacks
+    /// hold fewer values than `script` takes as arguments.
+    pub fn gosub_frame(&mut self, script: &'script ScriptFile) -> Result<(), ScriptErrorKind> {
+        self.check_args(script)?;
         self.frame_stack.push(GoSubFrame {
             script: self.script,
             pc: self.pc,
             int_locals: self.int_locals.clone(),
             string_locals: self.string_locals.clone(),
+            arrays: std::mem::take(&mut self.arrays),
         });
 
         self.fp += 1;
         self.pc = -1;
+        self.new_locals(script);
 
         if script.int_arg_count > 0 {
             for i in (0..script.int_arg_count as usize).rev() {
-                self.int_locals[i] = self.pop_int();
+                self.int_locals[i] = self.pop_int()?;
             }
         }
 
         if script.string_arg_count > 0 {
             for i in (0..script.string_arg_count as usize).rev() {
-                self.string_locals[i] = self.pop_string();
+                self.string_locals[i] = self.pop_string()?;
             }
         }
 
         self.script = script;
+        return Ok(());
+    }
+
+    /// Fails if the stacks don't hold the arguments `script` takes.
+    fn check_args(&self, script: &ScriptFile) -> Result<(), ScriptErrorKind> {
+        if self.isp < script.int_arg_count as usize || self.ssp < script.string_arg_count as usize {
+            return Err(ScriptErrorKind::StackUnderflow);
+        }
+        return Ok(());
+    }
+
+    /// Gives the script being entered its own locals, the arguments go in the first ones.
+    fn new_locals(&mut self, script: &ScriptFile) {
+        let int_count: u16 = script.int_local_count.max(script.int_arg_count);
+        let string_count: u16 = script.string_local_count.max(script.string_arg_count);
+        self.int_locals = vec![0; int_count as usize];
+        self.string_locals = vec![String::new(); string_count as usize];
+        self.arrays.clear();
     }
 
     /// Jumps to a new goto frame without saving the current frame's context.
@@ -1786,7 +2052,11 @@ impl<'script> ScriptState<'script> {
     /// - Clears the `frame_stack`, discarding any previously saved frames.
     /// - Resets the frame pointer (`fp`) and program counter (`pc`).
     /// - Initializes local integer and string variables by popping them from the respective stacks.
-    pub fn goto_frame(&mut self, script: &'script ScriptFile) {
+    ///
+    /// Returns `Err(ScriptErrorKind::StackUnderflow)` without touching any frame if the stacks
+    /// hold fewer values than `script` takes as arguments.
+    pub fn goto_frame(&mut self, script: &'script ScriptFile) -> Result<(), ScriptErrorKind> {
+        self.check_args(script)?;
         self.goto_frame_stack.push(GoToFrame {
             script: self.script,
             pc: self.pc,
@@ -1797,20 +2067,22 @@ impl<'script> ScriptState<'script> {
 
         self.fp = 0;
         self.pc = -1;
+        self.new_locals(script);
 
         if script.int_arg_count > 0 {
             for i in (0..script.int_arg_count as usize).rev() {
-                self.int_locals[i] = self.pop_int();
+                self.int_locals[i] = self.pop_int()?;
             }
         }
 
         if script.string_arg_count > 0 {
             for i in (0..script.string_arg_count as usize).rev() {
-                self.string_locals[i] = self.pop_string();
+                self.string_locals[i] = self.pop_string()?;
             }
         }
 
         self.script = script;
+        return Ok(());
     }
 
     // ---- pointers
@@ -1996,35 +2268,46 @@ impl<'script> ScriptState<'script> {
     ///
     /// - `pointers`: A slice of `ScriptPointer` values representing valid pointers for the current state.
     /// - `on_success`: A closure that is executed if the required pointer is valid. It receives a mutable reference
-    ///   to the current `ScriptState` and returns a `Result<(), String>`.
+    ///   to the current `ScriptState` and returns a `Result<(), ScriptErrorKind>`.
     ///
     /// # Return
     ///
     /// Returns `Ok(())` if the pointer check succeeds and the `on_success` closure executes successfully.
-    /// If the pointer is invalid or the closure returns an error, a `Result::Err` with a descriptive error message is returned.
+    /// If the pointer is invalid, or the operand doesn't pick one of `pointers`, `ScriptErrorKind::BadPointer`
+    /// is returned, or the closure's error if it fails.
     ///
     /// # Panics
     ///
-    /// This function does not panic, but it assumes that `pointers` has valid indices, which depends on the
-    /// operand value returned by `self.int_operand()`. Out-of-bounds indexing on `pointers` will result in a panic.
+    /// This function does not panic.
     ///
     /// # Side Effects
     ///
     /// - The state may be modified by the `on_success` closure. The closure has full access to the mutable state.
     /// - The method does not alter any state if the pointer check fails or if an error is returned.
-    pub fn protect<F>(&mut self, pointers: &[ScriptPointer], on_success: F) -> Result<(), String>
+    pub fn protect<F>(
+        &mut self,
+        pointers: &[ScriptPointer],
+        on_success: F,
+    ) -> Result<(), ScriptErrorKind>
     where
-        F: FnOnce(&mut ScriptState) -> Result<(), String>,
+        F: FnOnce(&mut ScriptState) -> Result<(), ScriptErrorKind>,
     {
-        let pointer: ScriptPointer = pointers[self.int_operand() as usize];
+        let pointer: ScriptPointer = match pointers.get(self.int_operand() as usize) {
+            Some(pointer) => *pointer,
+            None => {
+                return Err(ScriptErrorKind::BadPointer {
+                    required: format!("pointer {} of {}", self.int_operand(), pointers.len()),
+                    current: self.pointer_print(self.pointers),
+                })
+            }
+        };
         if self.pointer_check(pointer) {
             return on_success(self);
         }
-        return Err(format!(
-            "Required pointer: {}, current: {}",
-            self.pointer_print(1 << pointer as i32),
-            self.pointer_print(self.pointers)
-        ));
+        return Err(ScriptErrorKind::BadPointer {
+            required: self.pointer_print(1 << pointer as i32),
+            current: self.pointer_print(self.pointers),
+        });
     }
 
     /// Sets the active player in the script state based on the current operand.
@@ -2076,6 +2359,30 @@ impl<'script> ScriptState<'script> {
             self.active_player2
         };
     }
+
+    /// Sets the active loc in the script state based on the current operand, like `set_active_player`.
+    pub fn set_active_loc(&mut self, loc: ScriptLoc) {
+        if self.int_operand() == 0 {
+            self.active_loc = Some(loc);
+        } else {
+            self.active_loc2 = Some(loc);
+        }
+    }
+
+    /// Retrieves the active loc from the script state based on the current operand, like `get_active_player`.
+    ///
+    /// # Return
+    ///
+    /// Returns the loc, or `ScriptErrorKind::Runtime` if no loc was made active. Ops check
+    /// the `ACTIVE_LOC` pointer first with `protect`, so this only fails on a bad script.
+    pub fn get_active_loc(&self) -> Result<ScriptLoc, ScriptErrorKind> {
+        let loc: Option<ScriptLoc> = if self.int_operand() == 0 {
+            self.active_loc
+        } else {
+            self.active_loc2
+        };
+        return loc.ok_or_else(|| ScriptErrorKind::Runtime("No active loc".to_string()));
+    }
 }
 
 pub trait ScriptRunner: ScriptEngine {
@@ -2083,7 +2390,22 @@ pub trait ScriptRunner: ScriptEngine {
         &'script self,
         state: &mut ScriptState<'script>,
         code: &ScriptOpcode,
-    ) -> Result<(), String>;
+    ) -> Result<(), ScriptErrorKind>;
+
+    /// The debugger attached to the runner, if any, scripts run under it.
+    fn debugger(&self) -> Option<RefMut<'_, ScriptDebugger>> {
+        return None;
+    }
+
+    /// The profiler attached to the runner, if any, scripts are timed into it.
+    fn profiler(&self) -> Option<RefMut<'_, ScriptProfiler>> {
+        return None;
+    }
+
+    /// The coverage attached to the runner, if any, scripts record what they run into it.
+    fn coverage(&self) -> Option<RefMut<'_, ScriptCoverage>> {
+        return None;
+    }
 }
 
 /// It is important to note that these are not commands.
@@ -2101,8 +2423,8 @@ pub trait ScriptEngine {
     ///
     /// # Return
     ///
-    /// Returns `Ok(&ObjType)` if the object is successfully found in the cache. Otherwise, returns `Err(String)` with an error message
-    /// if the object could not be found.
+    /// Returns `Ok(&ObjType)` if the object is successfully found in the cache. Otherwise, returns
+    /// `Err(ScriptErrorKind::MissingConfig)` if the object could not be found.
     ///
     /// # Panics
     ///
@@ -2118,7 +2440,7 @@ pub trait ScriptEngine {
     ///
     /// - **Lookup overhead**: This method performs a lookup in the object provider using `get_by_id`, which typically runs in `O(1)` time.
     /// - The function is highly efficient for retrieving objects.
-    fn pop_obj(&self, id: i32) -> Result<&ObjType, String>;
+    fn pop_obj(&self, id: i32) -> Result<&ObjType, ScriptErrorKind>;
 
     /// Retrieves a script file (`ScriptFile`) by its ID from the cache.
     ///
@@ -2132,8 +2454,8 @@ pub trait ScriptEngine {
     ///
     /// # Return
     ///
-    /// Returns `Ok(&ScriptFile)` if the script is successfully found in the cache. Otherwise, returns `Err(String)` with an error message
-    /// if the script could not be found.
+    /// Returns `Ok(&ScriptFile)` if the script is successfully found in the cache. Otherwise, returns
+    /// `Err(ScriptErrorKind::MissingConfig)` if the script could not be found.
     ///
     /// # Panics
     ///
@@ -2149,7 +2471,7 @@ pub trait ScriptEngine {
     ///
     /// - **Lookup overhead**: This method performs a lookup in the script provider using `get_by_id`, which typically runs in `O(1)` time.
     /// - The function is efficient for retrieving scripts.
-    fn pop_script(&self, id: i32) -> Result<&ScriptFile, String>;
+    fn pop_script(&self, id: i32) -> Result<&ScriptFile, ScriptErrorKind>;
 
     fn line_of_sight(&self, from: i32, to: i32) -> bool;
 
@@ -2169,7 +2491,7 @@ pub trait ScriptEngine {
     /// # Return
     ///
     /// Returns `Ok(())` if the player is found and the closure executes successfully. If the player is not found or is not initialized,
-    /// a `Result::Err` is returned with a descriptive error message.
+    /// `Err(ScriptErrorKind::MissingPlayer)` is returned.
     ///
     /// # Panics
     ///
@@ -2187,7 +2509,7 @@ pub trait ScriptEngine {
     ///   constant overhead.
     /// - **Borrowing cost**: The `RefCell::borrow_mut()` call on the player adds some runtime overhead due to borrow checking. The cost
     ///   is generally low.
-    fn with_player_mut<F>(&self, uid: i32, on_found: F) -> Result<(), String>
+    fn with_player_mut<F>(&self, uid: i32, on_found: F) -> Result<(), ScriptErrorKind>
     where
         F: FnOnce(RefMut<dyn ScriptPlayer>);
 
@@ -2205,7 +2527,7 @@ pub trait ScriptEngine {
     /// # Return
     ///
     /// Returns `Ok(())` if the player is found and the closure executes successfully. If the player is not found or is not initialized,
-    /// a `Result::Err` is returned with a descriptive error message.
+    /// `Err(ScriptErrorKind::MissingPlayer)` is returned.
     ///
     /// # Panics
     ///
@@ -2222,7 +2544,7 @@ pub trait ScriptEngine {
     ///   an `O(1)` operation. Checking whether the player is initialized adds a small constant overhead.
     /// - **Borrowing cost**: The `RefCell::borrow()` call introduces a slight runtime cost for borrow checking, but it is relatively
     ///   cheap and typically incurs less overhead than mutable borrowing (`borrow_mut`).
-    fn with_player<F>(&self, uid: i32, on_found: F) -> Result<(), String>
+    fn with_player<F>(&self, uid: i32, on_found: F) -> Result<(), ScriptErrorKind>
     where
         F: FnOnce(Ref<dyn ScriptPlayer>);
 }
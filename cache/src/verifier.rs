@@ -0,0 +1,312 @@
+use crate::script::{ScriptFile, ScriptOpcode, ScriptState};
+
+/// How many ints and strings are on the stack before an instruction.
+///
+/// `None` once the depth can't be known anymore, after a command or a var
+/// since only the engine knows what they push.
+type Depth = Option<(i32, i32)>;
+
+/// Checks scripts when they are loaded so a broken `script.dat` is rejected
+/// up front instead of failing mid-tick.
+pub struct ScriptVerifier<'a> {
+    scripts: &'a [Option<ScriptFile>],
+}
+
+impl<'a> ScriptVerifier<'a> {
+    /// Creates a verifier that resolves gosub and jump targets against `scripts`.
+    pub fn new(scripts: &'a [Option<ScriptFile>]) -> ScriptVerifier<'a> {
+        return ScriptVerifier { scripts };
+    }
+
+    /// Verifies a single script.
+    ///
+    /// Checks that branch and switch targets are in range, locals are below the
+    /// local counts, gosub and jump targets exist, switch operands point to a
+    /// table, string joins have a count, active pointer operands pick the first
+    /// or second pointer, and the stack never underflows and is the same size
+    /// on every path into an instruction. The stack is only tracked through the
+    /// core language ops, a path stops being checked at the first command or var
+    /// it reaches.
+    ///
+    /// # Returns
+    ///
+    /// An error naming the script and listing every problem, one per line.
+    pub fn verify(&self, script: &ScriptFile) -> Result<(), String> {
+        let mut problems: Vec<String> = Vec::new();
+        match &script.codes {
+            Some(codes) if !codes.is_empty() => {
+                self.verify_operands(script, codes, &mut problems);
+                if problems.is_empty() {
+                    self.verify_stack(script, codes, &mut problems);
+                }
+            }
+            _ => problems.push("has no instructions".to_string()),
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        return Err(format!(
+            "{} ({}):\n    {}",
+            script.info.name,
+            script.info.path,
+            problems.join("\n    ")
+        ));
+    }
+
+    fn verify_operands(
+        &self,
+        script: &ScriptFile,
+        codes: &[Option<ScriptOpcode>],
+        problems: &mut Vec<String>,
+    ) {
+        let length: i32 = codes.len() as i32;
+        for (pc, code) in codes.iter().enumerate() {
+            let code: &ScriptOpcode = match code {
+                Some(code) => code,
+                None => {
+                    problems.push(format!("pc {}: missing instruction", pc));
+                    continue;
+                }
+            };
+            let operand: i32 = script.int_operands[pc];
+            let pc: i32 = pc as i32;
+            match code {
+                ScriptOpcode::Branch
+                | ScriptOpcode::BranchNot
+                | ScriptOpcode::BranchEquals
+                | ScriptOpcode::BranchLessThan
+                | ScriptOpcode::BranchGreaterThan
+                | ScriptOpcode::BranchLessThanOrEquals
+                | ScriptOpcode::BranchGreaterThanOrEquals => {
+                    let target: i32 = pc + operand + 1;
+                    if target < 0 || target >= length {
+                        problems.push(format!(
+                            "pc {} ({:?}): target {} is out of range",
+                            pc, code, target
+                        ));
+                    }
+                }
+                ScriptOpcode::PushIntLocal | ScriptOpcode::PopIntLocal => {
+                    if operand < 0 || operand >= script.int_local_count as i32 {
+                        problems.push(format!(
+                            "pc {} ({:?}): int local {} is out of range, the script has {}",
+                            pc, code, operand, script.int_local_count
+                        ));
+                    }
+                }
+                ScriptOpcode::PushStringLocal | ScriptOpcode::PopStringLocal => {
+                    if operand < 0 || operand >= script.string_local_count as i32 {
+                        problems.push(format!(
+                            "pc {} ({:?}): string local {} is out of range, the script has {}",
+                            pc, code, operand, script.string_local_count
+                        ));
+                    }
+                }
+                ScriptOpcode::DefineArray
+                | ScriptOpcode::PushArrayInt
+                | ScriptOpcode::PopArrayInt => {
+                    let array: i32 = if code == &ScriptOpcode::DefineArray {
+                        operand >> 16
+                    } else {
+                        operand
+                    };
+                    if array < 0 || array as usize >= ScriptState::MAX_ARRAYS {
+                        problems.push(format!(
+                            "pc {} ({:?}): array {} is out of range, scripts have {}",
+                            pc,
+                            code,
+                            array,
+                            ScriptState::MAX_ARRAYS
+                        ));
+                    }
+                }
+                ScriptOpcode::JoinString => {
+                    if operand < 0 {
+                        problems.push(format!("pc {} (JoinString): joins {} strings", pc, operand));
+                    }
+                }
+                ScriptOpcode::Anim
+                | ScriptOpcode::BasReadyAnim
+                | ScriptOpcode::FindUid
+                | ScriptOpcode::TextGender
+                | ScriptOpcode::LocAdd
+                | ScriptOpcode::LocChange
+                | ScriptOpcode::LocDel => {
+                    if operand < 0 || operand > 1 {
+                        problems.push(format!(
+                            "pc {} ({:?}): pointer {} is out of range, it must be 0 or 1",
+                            pc, code, operand
+                        ));
+                    }
+                }
+                ScriptOpcode::GoSubWithParams | ScriptOpcode::JumpWithParams => {
+                    if self.script(operand).is_none() {
+                        problems.push(format!(
+                            "pc {} ({:?}): script {} does not exist",
+                            pc, code, operand
+                        ));
+                    }
+                }
+                ScriptOpcode::Switch => {
+                    let table = match script.switch_tables.get(operand as usize) {
+                        Some(table) if operand >= 0 => table,
+                        _ => {
+                            problems.push(format!(
+                                "pc {} (Switch): switch table {} does not exist",
+                                pc, operand
+                            ));
+                            continue;
+                        }
+                    };
+                    for (key, offset) in table {
+                        let target: i32 = pc + offset + 1;
+                        if target < 0 || target >= length {
+                            problems.push(format!(
+                                "pc {} (Switch): case {} target {} is out of range",
+                                pc, key, target
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn verify_stack(
+        &self,
+        script: &ScriptFile,
+        codes: &[Option<ScriptOpcode>],
+        problems: &mut Vec<String>,
+    ) {
+        let length: usize = codes.len();
+        // `None` until an instruction is reached.
+        let mut depths: Vec<Option<Depth>> = vec![None; length];
+        let mut pending: Vec<usize> = vec![0];
+        depths[0] = Some(Some((0, 0)));
+
+        while let Some(pc) = pending.pop() {
+            let code: &ScriptOpcode = codes[pc].as_ref().unwrap();
+            let operand: i32 = script.int_operands[pc];
+            let depth: Depth = depths[pc].unwrap();
+
+            let after: Depth = match (depth, self.effect(code, operand)) {
+                (Some((ints, strings)), Some((pop_ints, pop_strings, pushed))) => {
+                    if ints < pop_ints || strings < pop_strings {
+                        problems.push(format!(
+                            "pc {} ({:?}): pops {} ints and {} strings but the stack has {} and {}",
+                            pc, code, pop_ints, pop_strings, ints, strings
+                        ));
+                        return;
+                    }
+                    pushed.map(|(push_ints, push_strings)| {
+                        (
+                            ints - pop_ints + push_ints,
+                            strings - pop_strings + push_strings,
+                        )
+                    })
+                }
+                _ => None,
+            };
+
+            let mut next: Vec<usize> = Vec::new();
+            match code {
+                ScriptOpcode::Return | ScriptOpcode::Jump | ScriptOpcode::JumpWithParams => {}
+                ScriptOpcode::Branch => next.push((pc as i32 + operand + 1) as usize),
+                ScriptOpcode::BranchNot
+                | ScriptOpcode::BranchEquals
+                | ScriptOpcode::BranchLessThan
+                | ScriptOpcode::BranchGreaterThan
+                | ScriptOpcode::BranchLessThanOrEquals
+                | ScriptOpcode::BranchGreaterThanOrEquals => {
+                    next.push(pc + 1);
+                    next.push((pc as i32 + operand + 1) as usize);
+                }
+                ScriptOpcode::Switch => {
+                    next.push(pc + 1);
+                    for offset in script.switch_tables[operand as usize].values() {
+                        next.push((pc as i32 + offset + 1) as usize);
+                    }
+                }
+                _ => next.push(pc + 1),
+            }
+
+            for target in next {
+                if target >= length {
+                    problems.push(format!(
+                        "pc {} ({:?}): runs past the end of the script",
+                        pc, code
+                    ));
+                    return;
+                }
+                match depths[target] {
+                    None => {
+                        depths[target] = Some(after);
+                        pending.push(target);
+                    }
+                    Some(Some(existing)) if after.is_some_and(|after| after != existing) => {
+                        let (ints, strings) = after.unwrap();
+                        problems.push(format!(
+                            "pc {}: reached with {} ints and {} strings from pc {} but {} ints and {} strings elsewhere",
+                            target, ints, strings, pc, existing.0, existing.1
+                        ));
+                        return;
+                    }
+                    Some(Some(_)) if after.is_none() => {
+                        depths[target] = Some(None);
+                        pending.push(target);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// The ints and strings an instruction pops and then pushes, `None` when
+    /// only the engine knows. Gosubs pop their arguments but what they return
+    /// isn't stored in the script.
+    #[rustfmt::skip]
+    fn effect(&self, code: &ScriptOpcode, operand: i32) -> Option<(i32, i32, Option<(i32, i32)>)> {
+        return match code {
+            ScriptOpcode::PushConstantInt => Some((0, 0, Some((1, 0)))),
+            ScriptOpcode::PushConstantString => Some((0, 0, Some((0, 1)))),
+            ScriptOpcode::Branch => Some((0, 0, Some((0, 0)))),
+            ScriptOpcode::BranchNot
+            | ScriptOpcode::BranchEquals
+            | ScriptOpcode::BranchLessThan
+            | ScriptOpcode::BranchGreaterThan
+            | ScriptOpcode::BranchLessThanOrEquals
+            | ScriptOpcode::BranchGreaterThanOrEquals => Some((2, 0, Some((0, 0)))),
+            ScriptOpcode::Return => Some((0, 0, Some((0, 0)))),
+            ScriptOpcode::Jump => Some((1, 0, Some((0, 0)))),
+            ScriptOpcode::Switch => Some((1, 0, Some((0, 0)))),
+            ScriptOpcode::PushIntLocal => Some((0, 0, Some((1, 0)))),
+            ScriptOpcode::PopIntLocal => Some((1, 0, Some((0, 0)))),
+            ScriptOpcode::PushStringLocal => Some((0, 0, Some((0, 1)))),
+            ScriptOpcode::PopStringLocal => Some((0, 1, Some((0, 0)))),
+            ScriptOpcode::JoinString => Some((0, operand, Some((0, 1)))),
+            ScriptOpcode::PopIntDiscard => Some((1, 0, Some((0, 0)))),
+            ScriptOpcode::PopStringDiscard => Some((0, 1, Some((0, 0)))),
+            ScriptOpcode::DefineArray => Some((1, 0, Some((0, 0)))),
+            ScriptOpcode::PushArrayInt => Some((1, 0, Some((1, 0)))),
+            ScriptOpcode::PopArrayInt => Some((2, 0, Some((0, 0)))),
+            ScriptOpcode::GoSubWithParams => {
+                let script: &ScriptFile = self.script(operand)?;
+                Some((script.int_arg_count as i32, script.string_arg_count as i32, None))
+            }
+            ScriptOpcode::JumpWithParams => {
+                let script: &ScriptFile = self.script(operand)?;
+                Some((script.int_arg_count as i32, script.string_arg_count as i32, Some((0, 0))))
+            }
+            _ => None,
+        };
+    }
+
+    fn script(&self, id: i32) -> Option<&ScriptFile> {
+        if id < 0 {
+            return None;
+        }
+        return self.scripts.get(id as usize)?.as_ref();
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use cache::{Disassembler, ObjProvider, ObjType, ScriptFile, ScriptOpcode, ScriptProvider};
+
+fn objs() -> ObjProvider {
+    let mut objs: ObjProvider = ObjProvider::mock();
+    let mut knife: ObjType = ObjType::mock(1);
+    knife.debugname = Some("knife".to_string());
+    objs.objs = vec![None, Some(knife)];
+    return objs;
+}
+
+#[test]
+fn test_disassemble() {
+    let mut done: ScriptFile = ScriptFile::mock_with(vec![ScriptOpcode::Return], vec![0]);
+    done.info.name = "[proc,done]".to_string();
+    let scripts: ScriptProvider = ScriptProvider::mock_with(vec![done]);
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::InvTotal,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchLessThan,
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::Mes,
+            ScriptOpcode::GoSubWithParams,
+            ScriptOpcode::Return,
+        ],
+        vec![93, 1, 0, 1, 2, 0, 0, 0, 0],
+    );
+    file.string_operands[5] = "You need a knife.".to_string();
+    file.info.pcs = vec![0, 5];
+    file.info.lines = vec![2, 3];
+
+    let objs: ObjProvider = objs();
+    let disassembler: Disassembler = Disassembler::new(&scripts, &objs);
+    assert_eq!(
+        "[proc,test] (scripts/test.rs2)
+int args: 0, string args: 0, int locals: 0, string locals: 0
+    // scripts/test.rs2:2
+     0  PushConstantInt 93
+     1  PushConstantInt 1 (knife)
+     2  InvTotal
+     3  PushConstantInt 1
+     4  BranchLessThan label_7
+    // scripts/test.rs2:3
+     5  PushConstantString \"You need a knife.\"
+     6  Mes
+label_7:
+     7  GoSubWithParams [proc,done]
+     8  Return
+",
+        disassembler.disassemble(&file)
+    );
+}
+
+#[test]
+fn test_disassemble_switch() {
+    let scripts: ScriptProvider = ScriptProvider::mock();
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Switch,
+            ScriptOpcode::Return,
+            ScriptOpcode::Return,
+        ],
+        vec![2, 0, 0, 0],
+    );
+    file.switch_tables.push(HashMap::from([(2, 1), (1, 1)]));
+
+    let objs: ObjProvider = objs();
+    let disassembler: Disassembler = Disassembler::new(&scripts, &objs);
+    let out: String = disassembler.disassemble(&file);
+    assert!(out
+        .contains("     1  Switch 0\n            case 1: label_3\n            case 2: label_3\n"));
+    assert!(out.contains("label_3:\n     3  Return\n"));
+}
+
+#[test]
+fn test_disassemble_obj_needs_constant() {
+    // the obj comes from a local so there is no constant to name.
+    let scripts: ScriptProvider = ScriptProvider::mock();
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::InvTotal,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 0, 0, 0],
+    );
+
+    let objs: ObjProvider = objs();
+    let disassembler: Disassembler = Disassembler::new(&scripts, &objs);
+    assert!(!disassembler.disassemble(&file).contains("(knife)"));
+}
@@ -0,0 +1,6 @@
+#[cfg(test)]
+mod disassembler;
+#[cfg(test)]
+mod obj;
+#[cfg(test)]
+mod verifier;
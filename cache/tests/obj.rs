@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use cache::ObjProvider;
+use io::JagFile;
+
+fn mock_dir(name: &str, config: Vec<u8>) -> PathBuf {
+    let dir: PathBuf = std::env::temp_dir().join(format!("obj_provider_{}", name));
+    std::fs::create_dir_all(dir.join("client")).unwrap();
+    std::fs::write(dir.join("client/config"), config).unwrap();
+    return dir;
+}
+
+fn load(dir: &PathBuf) -> Result<ObjProvider, String> {
+    return ObjProvider::load(dir.to_str().unwrap(), true);
+}
+
+#[test]
+fn test_load_missing_config() {
+    let dir: PathBuf = std::env::temp_dir().join("obj_provider_missing");
+    let error: String = load(&dir).err().unwrap();
+    assert!(error.contains("client/config"), "{}", error);
+}
+
+#[test]
+fn test_load_corrupt_archive() {
+    let mut config: Vec<u8> = JagFile::pack(&[("obj.dat", vec![0; 100])], true);
+    config[10] ^= 0xff;
+    let error: String = load(&mock_dir("corrupt_archive", config)).err().unwrap();
+    assert!(error.starts_with("client/config:"), "{}", error);
+}
+
+#[test]
+fn test_load_corrupt_entry() {
+    let mut config: Vec<u8> = JagFile::pack(&[("obj.dat", vec![0; 100])], false);
+    let length: usize = config.len();
+    config[length - 10] ^= 0xff;
+    let error: String = load(&mock_dir("corrupt_entry", config)).err().unwrap();
+    assert!(
+        error.starts_with("client/config: Failed to read obj.dat:"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_load_missing_entry() {
+    let config: Vec<u8> = JagFile::pack(&[("loc.dat", vec![0; 100])], false);
+    let error: String = load(&mock_dir("missing_entry", config)).err().unwrap();
+    assert_eq!("client/config has no obj.dat", error);
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cache::{ScriptFile, ScriptOpcode, ScriptProvider, ScriptVerifier, COMPILER_VERSION};
+use io::Packet;
+
+fn verify(file: &ScriptFile) -> Result<(), String> {
+    return ScriptVerifier::new(&[]).verify(file);
+}
+
+/// An opcode and its operand bytes, exactly as they are written to `script.dat`.
+type Instruction = (ScriptOpcode, Vec<u8>);
+
+fn int(code: ScriptOpcode, operand: i32) -> Instruction {
+    if ScriptFile::is_large_operand(&code) {
+        return (code, operand.to_be_bytes().to_vec());
+    }
+    return (code, vec![operand as u8]);
+}
+
+/// Encodes a script the way the compiler writes it, without switches or locals.
+fn encode(name: &str, code: Vec<Instruction>) -> Vec<u8> {
+    let mut buf: Packet = Packet::new(4096);
+    buf.pjstr(name, 0);
+    buf.pjstr("verifier.rs2", 0);
+    buf.p4(-1); // lookup
+    buf.p1(0); // params
+    buf.p2(1); // lines
+    buf.p4(0);
+    buf.p4(1);
+    for (opcode, operand) in &code {
+        buf.p2(opcode.clone() as i32);
+        buf.pdata(operand, 0, operand.len());
+    }
+    buf.p4(code.len() as i32);
+    buf.p2(0);
+    buf.p2(0);
+    buf.p2(0);
+    buf.p2(0);
+    buf.p1(0); // switches
+    buf.p2(1); // trailer
+    return buf.data[..buf.pos].to_vec();
+}
+
+/// Loads `scripts` from a `script.dat` holding them by id, `None` for an unused id.
+fn load(name: &str, scripts: &[Option<Vec<u8>>]) -> Result<ScriptProvider, String> {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("verifier_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("server")).unwrap();
+    let mut dat: Packet = Packet::new(4096);
+    let mut idx: Packet = Packet::new(4096);
+    dat.p2(scripts.len() as i32);
+    dat.p4(COMPILER_VERSION);
+    idx.p2(scripts.len() as i32);
+    for script in scripts {
+        let data: Vec<u8> = script.clone().unwrap_or_default();
+        dat.pdata(&data, 0, data.len());
+        idx.p2(data.len() as i32);
+    }
+    std::fs::write(dir.join("server/script.dat"), &dat.data[..dat.pos]).unwrap();
+    std::fs::write(dir.join("server/script.idx"), &idx.data[..idx.pos]).unwrap();
+    let provider: Result<ScriptProvider, String> =
+        ScriptProvider::load(dir.to_str().unwrap(), &COMPILER_VERSION.to_string());
+    std::fs::remove_dir_all(&dir).unwrap();
+    return provider;
+}
+
+#[test]
+fn test_verify_valid() {
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchLessThan,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Return,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 10, 2, 1, 0, 2, 0],
+    );
+    file.int_local_count = 1;
+    assert!(verify(&file).is_ok());
+}
+
+#[test]
+fn test_verify_no_instructions() {
+    assert!(verify(&ScriptFile::mock()).is_err());
+}
+
+#[test]
+fn test_verify_branch_out_of_range() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Branch, ScriptOpcode::Return], vec![5, 0]);
+    let error: String = verify(&file).unwrap_err();
+    assert_eq!(
+        "[proc,test] (scripts/test.rs2):\n    pc 0 (Branch): target 6 is out of range",
+        error
+    );
+}
+
+#[test]
+fn test_verify_local_out_of_range() {
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::PopStringLocal,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 0, 0],
+    );
+    file.int_local_count = 1;
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 0 (PushIntLocal): int local 1 is out of range"));
+    assert!(error.contains("pc 1 (PopStringLocal): string local 0 is out of range"));
+}
+
+#[test]
+fn test_verify_array_out_of_range() {
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::DefineArray,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushArrayInt,
+            ScriptOpcode::PopIntDiscard,
+            ScriptOpcode::Return,
+        ],
+        vec![3, (4 << 16) | 'i' as i32, 0, 5, 0, 0],
+    );
+    let error: String = verify(&file).unwrap_err();
+    assert_eq!(
+        "[proc,test] (scripts/test.rs2):\n    pc 3 (PushArrayInt): array 5 is out of range, scripts have 5",
+        error
+    );
+}
+
+#[test]
+fn test_verify_gosub_target() {
+    let file = ScriptFile::mock_with(
+        vec![ScriptOpcode::GoSubWithParams, ScriptOpcode::Return],
+        vec![1, 0],
+    );
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 0 (GoSubWithParams): script 1 does not exist"));
+
+    let scripts: Vec<Option<ScriptFile>> = vec![None, Some(ScriptFile::mock())];
+    assert!(ScriptVerifier::new(&scripts).verify(&file).is_ok());
+}
+
+#[test]
+fn test_verify_gosub_arguments() {
+    let mut callee = ScriptFile::mock();
+    callee.int_arg_count = 1;
+    let scripts: Vec<Option<ScriptFile>> = vec![Some(callee)];
+    let file = ScriptFile::mock_with(
+        vec![ScriptOpcode::GoSubWithParams, ScriptOpcode::Return],
+        vec![0, 0],
+    );
+    let error: String = ScriptVerifier::new(&scripts).verify(&file).unwrap_err();
+    assert!(error.contains("pops 1 ints and 0 strings but the stack has 0 and 0"));
+}
+
+#[test]
+fn test_verify_switch() {
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Switch,
+            ScriptOpcode::Return,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 0, 0, 0],
+    );
+    file.switch_tables.push(HashMap::from([(1, 1)]));
+    assert!(verify(&file).is_ok());
+
+    file.int_operands[1] = 1;
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 1 (Switch): switch table 1 does not exist"));
+
+    file.int_operands[1] = 0;
+    file.switch_tables[0].insert(2, 5);
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 1 (Switch): case 2 target 7 is out of range"));
+}
+
+#[test]
+fn test_verify_stack_underflow() {
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PopIntDiscard,
+            ScriptOpcode::PopIntDiscard,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 0, 0, 0],
+    );
+    let error: String = verify(&file).unwrap_err();
+    assert!(
+        error.contains("pc 2 (PopIntDiscard): pops 1 ints and 0 strings but the stack has 0 and 0")
+    );
+}
+
+#[test]
+fn test_verify_join_string_count() {
+    let negative = ScriptFile::mock_with(
+        vec![ScriptOpcode::JoinString, ScriptOpcode::Return],
+        vec![-1, 0],
+    );
+    let error: String = verify(&negative).unwrap_err();
+    assert_eq!(
+        "[proc,test] (scripts/test.rs2):\n    pc 0 (JoinString): joins -1 strings",
+        error
+    );
+
+    let deeper = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::JoinString,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 3, 0],
+    );
+    let error: String = verify(&deeper).unwrap_err();
+    assert!(
+        error.contains("pc 1 (JoinString): pops 0 ints and 3 strings but the stack has 0 and 1")
+    );
+}
+
+#[test]
+fn test_verify_pointer_out_of_range() {
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::FindUid,
+            ScriptOpcode::LocAdd,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 2, 0],
+    );
+    let error: String = verify(&file).unwrap_err();
+    assert_eq!(
+        "[proc,test] (scripts/test.rs2):\n    pc 1 (LocAdd): pointer 2 is out of range, it must be 0 or 1",
+        error
+    );
+
+    let negative = ScriptFile::mock_with(
+        vec![ScriptOpcode::FindUid, ScriptOpcode::Return],
+        vec![-1, 0],
+    );
+    let error: String = verify(&negative).unwrap_err();
+    assert!(error.contains("pc 0 (FindUid): pointer -1 is out of range"));
+
+    for code in [
+        ScriptOpcode::Anim,
+        ScriptOpcode::BasReadyAnim,
+        ScriptOpcode::TextGender,
+        ScriptOpcode::LocChange,
+        ScriptOpcode::LocDel,
+    ] {
+        let file = ScriptFile::mock_with(vec![code.clone(), ScriptOpcode::Return], vec![2, 0]);
+        let error: String = verify(&file).unwrap_err();
+        assert!(error.contains(&format!("pc 0 ({:?}): pointer 2 is out of range", code)));
+    }
+}
+
+#[test]
+fn test_verify_stack_mismatch() {
+    // pushes an int on only one path into the return.
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchEquals,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 2, 1, 3, 0],
+    );
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 4: reached with"));
+}
+
+#[test]
+fn test_verify_runs_past_end() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::PushConstantInt], vec![1]);
+    let error: String = verify(&file).unwrap_err();
+    assert!(error.contains("pc 0 (PushConstantInt): runs past the end of the script"));
+}
+
+#[test]
+fn test_verify_stops_tracking_at_commands() {
+    // the verifier doesn't know `stat` pushes an int.
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Stat,
+            ScriptOpcode::PopIntDiscard,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 0, 0, 0],
+    );
+    assert!(verify(&file).is_ok());
+}
+
+#[test]
+fn test_load_rejects_callers_of_rejected_scripts() {
+    let caller: Vec<u8> = encode(
+        "[proc,caller]",
+        vec![
+            int(ScriptOpcode::GoSubWithParams, 1),
+            int(ScriptOpcode::Return, 0),
+        ],
+    );
+    // branches past its own end, so the verifier rejects it.
+    let broken: Vec<u8> = encode(
+        "[proc,broken]",
+        vec![int(ScriptOpcode::Branch, 10), int(ScriptOpcode::Return, 0)],
+    );
+    let fine: Vec<u8> = encode("[proc,fine]", vec![int(ScriptOpcode::Return, 0)]);
+    let scripts: ScriptProvider =
+        load("callers", &[Some(caller), Some(broken), Some(fine)]).unwrap();
+    assert!(scripts.get_by_name("[proc,broken]").is_err());
+    assert!(scripts.get_by_name("[proc,caller]").is_err());
+    assert!(scripts.get_by_name("[proc,fine]").is_ok());
+}
+
+#[test]
+fn test_load_rejects_corrupt_scripts() {
+    let fine: Vec<u8> = encode("[proc,fine]", vec![int(ScriptOpcode::Return, 0)]);
+
+    // a negative instruction count.
+    let mut negative: Vec<u8> = encode("[proc,negative]", vec![int(ScriptOpcode::Return, 0)]);
+    let count: usize = negative.len() - 2 - 1 - 12;
+    negative[count..count + 4].copy_from_slice(&[0xff; 4]);
+
+    // a string operand without its terminator, so it runs into the next instruction.
+    let unterminated: Vec<u8> = encode(
+        "[proc,unterminated]",
+        vec![
+            (ScriptOpcode::PushConstantString, b"x".to_vec()),
+            int(ScriptOpcode::Return, 0),
+        ],
+    );
+
+    // a trailer length pointing before the start of the script.
+    let mut trailer: Vec<u8> = encode("[proc,trailer]", vec![int(ScriptOpcode::Return, 0)]);
+    let end: usize = trailer.len();
+    trailer[end - 2..].copy_from_slice(&[0xff, 0xff]);
+
+    let scripts: ScriptProvider = load(
+        "corrupt",
+        &[
+            Some(negative),
+            Some(unterminated),
+            Some(trailer),
+            Some(fine),
+        ],
+    )
+    .unwrap();
+    assert!(scripts.scripts[0].is_none());
+    assert!(scripts.scripts[1].is_none());
+    assert!(scripts.scripts[2].is_none());
+    assert!(scripts.get_by_name("[proc,fine]").is_ok());
+}
@@ -1,7 +1,7 @@
 use criterion::*;
 use criterion::measurement::WallTime;
 
-use cache::{CacheProvider, ScriptProvider, ScriptState};
+use cache::{CacheProvider, ScriptProvider, ScriptState, COMPILER_VERSION};
 use engine::engine::Engine;
 
 fn bench_fib(c: &mut Criterion) {
@@ -10,9 +10,9 @@ fn bench_fib(c: &mut Criterion) {
     // Define the throughput in operations (you can use 1 if it's per operation)
     group.throughput(Throughput::Elements(1)); // Measure as ops/second
 
-    let engine: Engine = Engine::new(CacheProvider::new("../data/pack", "19".to_string(), true));
+    let engine: Engine = Engine::new(CacheProvider::new("../data/pack", COMPILER_VERSION.to_string(), true));
 
-    let script_provider: ScriptProvider = ScriptProvider::io("../data/pack", "19".to_string());
+    let script_provider: ScriptProvider = ScriptProvider::io("../data/pack", COMPILER_VERSION.to_string());
 
     group.bench_function("fib", move |b| {
         b.iter_batched(
@@ -22,8 +22,8 @@ fn bench_fib(c: &mut Criterion) {
                     "[proc,fib]",
                     |script| {
                         let mut state: ScriptState =
-                            ScriptState::new_with_args(script, vec![45], Vec::new());
-                        let _ = state.execute(&engine, true);
+                            engine.script_state(&provider, script, vec![45], Vec::new());
+                        let _ = state.execute(&engine);
                     },
                     || {},
                 );
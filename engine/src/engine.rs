@@ -1,14 +1,21 @@
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::HashMap;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
 use cache::{
-    CacheProvider, ObjType, ScriptEngine, ScriptFile, ScriptOpcode, ScriptPlayer, ScriptRunner,
-    ScriptState,
+    CacheProvider, ObjType, ScriptChanges, ScriptCoverage, ScriptDebugger, ScriptEngine,
+    ScriptErrorKind, ScriptFile, ScriptLimits, ScriptOpcode, ScriptPlayer, ScriptProfiler,
+    ScriptProvider, ScriptRunner, ScriptState, TriggerLimits,
 };
 
+use io::{Packet, PacketPool};
+
 use crate::entity::player::Player;
+use crate::network::debug_server::DebugServer;
 use crate::script::script::Ops;
+use crate::zone::coord_grid::CoordGrid;
+use crate::zone::zone_map::ZoneMap;
 
 #[repr(u8)]
 pub enum EngineStat {
@@ -46,8 +53,17 @@ pub struct Engine {
     pub cache: CacheProvider,
     pub ops: Ops,
     pub players: Vec<Option<RefCell<Player>>>,
+    pub zones: RefCell<ZoneMap>,
+    pub map_crcs: HashMap<String, i32>,
+    pub packet_pool: PacketPool,
     pub stats: Vec<Duration>,
     pub last_stats: Vec<Duration>,
+    pub debugger: RefCell<Option<ScriptDebugger>>,
+    pub debug_server: Option<DebugServer>,
+    pub profiler: RefCell<Option<ScriptProfiler>>,
+    pub coverage: RefCell<Option<ScriptCoverage>>,
+    pub limits: TriggerLimits,
+    pub reload_requested: Cell<bool>,
 }
 
 impl Engine {
@@ -58,8 +74,17 @@ impl Engine {
             cache,
             ops: Ops::new(),
             players: vec![None; 2048],
+            zones: RefCell::new(ZoneMap::new()),
+            map_crcs: HashMap::new(),
+            packet_pool: PacketPool::new(),
             stats: vec![Duration::new(0, 0); 12],
             last_stats: vec![Duration::new(0, 0); 12],
+            debugger: RefCell::new(None),
+            debug_server: None,
+            profiler: RefCell::new(None),
+            coverage: RefCell::new(None),
+            limits: Engine::limits(),
+            reload_requested: Cell::new(false),
         };
     }
 
@@ -70,11 +95,110 @@ impl Engine {
             cache: CacheProvider::mock(),
             ops: Ops::new(),
             players: vec![None; 2048],
+            zones: RefCell::new(ZoneMap::new()),
+            map_crcs: HashMap::new(),
+            packet_pool: PacketPool::new(),
             stats: vec![Duration::new(0, 0); 12],
             last_stats: vec![Duration::new(0, 0); 12],
+            debugger: RefCell::new(None),
+            debug_server: None,
+            profiler: RefCell::new(None),
+            coverage: RefCell::new(None),
+            limits: Engine::limits(),
+            reload_requested: Cell::new(false),
         };
     }
 
+    /// The limits for every run, debug scripts get a bigger budget than content.
+    fn limits() -> TriggerLimits {
+        let mut limits: TriggerLimits = TriggerLimits::new(ScriptLimits::DEFAULT);
+        limits.set(
+            "debugproc",
+            ScriptLimits {
+                instructions: 5_000_000,
+                frames: 100,
+                ..ScriptLimits::DEFAULT
+            },
+        );
+        return limits;
+    }
+
+    /// Starts a run of `script`, taken from `scripts`, within the limits of its trigger.
+    /// Every run the engine starts should be created here rather than with
+    /// `ScriptState::new_with_args`, so its gosubs and jumps stay on `scripts` when
+    /// the run outlives a reload.
+    pub fn script_state<'script>(
+        &self,
+        scripts: &'script ScriptProvider,
+        script: &'script ScriptFile,
+        int_args: Vec<i32>,
+        string_args: Vec<String>,
+    ) -> ScriptState<'script> {
+        return ScriptState::new_with_limits(
+            script,
+            int_args,
+            string_args,
+            self.limits.get(script),
+        )
+        .with_scripts(scripts);
+    }
+
+    /// Runs every script under `debugger` from now on, replacing any attached before.
+    pub fn attach_debugger(&self, debugger: ScriptDebugger) {
+        *self.debugger.borrow_mut() = Some(debugger);
+    }
+
+    pub fn detach_debugger(&self) -> Option<ScriptDebugger> {
+        return self.debugger.borrow_mut().take();
+    }
+
+    /// Accepts debugger frontends on `addr`, each one that connects is attached
+    /// between ticks and replaces the debugger attached before.
+    pub fn listen_debugger(&mut self, addr: &str) -> std::io::Result<()> {
+        self.debug_server = Some(DebugServer::listen(addr)?);
+        return Ok(());
+    }
+
+    /// Times every script into `profiler` from now on, ticks end with each cycle.
+    pub fn attach_profiler(&self, profiler: ScriptProfiler) {
+        *self.profiler.borrow_mut() = Some(profiler);
+    }
+
+    pub fn detach_profiler(&self) -> Option<ScriptProfiler> {
+        return self.profiler.borrow_mut().take();
+    }
+
+    /// Records what every script runs into `coverage` from now on.
+    pub fn attach_coverage(&self, coverage: ScriptCoverage) {
+        *self.coverage.borrow_mut() = Some(coverage);
+    }
+
+    pub fn detach_coverage(&self) -> Option<ScriptCoverage> {
+        return self.coverage.borrow_mut().take();
+    }
+
+    /// Reloads `script.dat` at the end of the current tick, once no script is running.
+    /// A debugger frontend asks for this with `reload`, see `listen_debugger`.
+    pub fn request_reload(&self) {
+        self.reload_requested.set(true);
+    }
+
+    /// Swaps in a fresh `script.dat`, reporting which scripts changed.
+    ///
+    /// Runs started by `script_state` from a clone of `cache.script_provider`
+    /// finish on the scripts they started with, gosubs and jumps included. The
+    /// current scripts are kept if the new ones can't be loaded.
+    pub fn reload_scripts(&mut self) -> Result<ScriptChanges, String> {
+        let changes: ScriptChanges = self.cache.reload_scripts()?;
+        if let Some(coverage) = self.coverage.get_mut() {
+            // hits are by pc so they don't carry over to new code.
+            coverage.scripts.retain(|_, hits| {
+                !changes.changed.contains(&hits.name) && !changes.removed.contains(&hits.name)
+            });
+        }
+        return Ok(changes);
+    }
+
     pub fn start(&mut self, start_cycle: bool) {
         println!("Starting world...");
         // TODO load maps
@@ -85,96 +209,10 @@ impl Engine {
         }
     }
 
-    #[rustfmt::skip]
     fn cycle(&mut self) {
         loop {
             let start: Instant = Instant::now();
-
-            // world processing
-            // - world queue
-            // - calculate afk event readiness
-            // - npc spawn scripts
-            // - npc hunt
-            self.process_world();
-
-            // client input
-            // - decode packets
-            // - process pathfinding/following
-            self.process_in();
-
-            // npc processing (if npc is not busy)
-            // - resume suspended script
-            // - stat regen
-            // - timer
-            // - queue
-            // - movement
-            // - modes
-            self.process_npcs();
-
-            // player processing
-            // - resume suspended script
-            // - primary queue
-            // - weak queue
-            // - timers
-            // - soft timers
-            // - engine queue
-            // - interactions
-            // - movement
-            // - close interface if attempting to logout
-            self.process_players();
-
-            // player logout
-            self.process_logouts();
-
-            // player login, good spot for it (before packets so they immediately load but after processing so nothing hits them)
-            self.process_logins();
-
-            // process zones
-            // - build list of active zones around players
-            // - loc/obj despawn/respawn
-            // - compute shared buffer
-            self.process_zones();
-
-            // process movement directions
-            // - convert player movements
-            // - convert npc movements
-            self.process_movement_dirs();
-
-            // client output
-            // - map update
-            // - player info
-            // - npc info
-            // - zone updates
-            // - inv changes
-            // - stat changes
-            // - afk zones changes
-            // - flush packets
-            self.process_out();
-
-            // cleanup
-            // - reset zones
-            // - reset players
-            // - reset npcs
-            // - reset invs
-            self.process_cleanup();
-
-            // cycle the world now
-            self.tick.increment();
-            self.stats[EngineStat::Cycle as usize] = Instant::now() - start;
-
-            // update stats
-            self.last_stats[EngineStat::Cycle as usize] = self.stats[EngineStat::Cycle as usize];
-            self.last_stats[EngineStat::World as usize] = self.stats[EngineStat::World as usize];
-            self.last_stats[EngineStat::ClientsIn as usize] = self.stats[EngineStat::ClientsIn as usize];
-            self.last_stats[EngineStat::Npcs as usize] = self.stats[EngineStat::Npcs as usize];
-            self.last_stats[EngineStat::Players as usize] = self.stats[EngineStat::Players as usize];
-            self.last_stats[EngineStat::Logouts as usize] = self.stats[EngineStat::Logouts as usize];
-            self.last_stats[EngineStat::Logins as usize] = self.stats[EngineStat::Logins as usize];
-            self.last_stats[EngineStat::Zones as usize] = self.stats[EngineStat::Zones as usize];
-            self.last_stats[EngineStat::ClientsOut as usize] = self.stats[EngineStat::ClientsOut as usize];
-            self.last_stats[EngineStat::Cleanup as usize] = self.stats[EngineStat::Cleanup as usize];
-            self.last_stats[EngineStat::BandwidthIn as usize] = self.stats[EngineStat::BandwidthIn as usize];
-            self.last_stats[EngineStat::BandwidthOut as usize] = self.stats[EngineStat::BandwidthOut as usize];
+            self.process_cycle();
 
             println!(
                 "tick {} took {:?}",
@@ -182,11 +220,130 @@ impl Engine {
                 self.stats[EngineStat::Cycle as usize]
             );
             println!("----");
-            
+
             sleep(self.tick_rate.saturating_sub(Instant::now() - start));
         }
     }
 
+    /// Runs every phase of a single game tick.
+    #[rustfmt::skip]
+    pub fn process_cycle(&mut self) {
+        let start: Instant = Instant::now();
+
+        // debugger frontends, before any script runs this tick
+        if let Some(debugger) = self.debug_server.as_ref().and_then(DebugServer::accept) {
+            self.attach_debugger(debugger);
+        }
+        if self.debugger.get_mut().as_mut().is_some_and(ScriptDebugger::poll) {
+            self.request_reload();
+        }
+
+        self.process_tick();
+
+        // cleanup
+        // - reset zones
+        // - reset players
+        // - reset npcs
+        // - reset invs
+        self.process_cleanup();
+
+        if let Some(profiler) = self.profiler.get_mut() {
+            profiler.end_tick();
+        }
+
+        // script reload, between ticks so no script is running
+        if self.reload_requested.replace(false) {
+            match self.reload_scripts() {
+                Ok(changes) => println!("Reloaded scripts: {}", changes),
+                Err(e) => println!("Failed to reload scripts: {}", e),
+            }
+        }
+
+        // cycle the world now
+        self.tick.increment();
+        self.stats[EngineStat::Cycle as usize] = Instant::now() - start;
+
+        // update stats
+        self.last_stats[EngineStat::Cycle as usize] = self.stats[EngineStat::Cycle as usize];
+        self.last_stats[EngineStat::World as usize] = self.stats[EngineStat::World as usize];
+        self.last_stats[EngineStat::ClientsIn as usize] = self.stats[EngineStat::ClientsIn as usize];
+        self.last_stats[EngineStat::Npcs as usize] = self.stats[EngineStat::Npcs as usize];
+        self.last_stats[EngineStat::Players as usize] = self.stats[EngineStat::Players as usize];
+        self.last_stats[EngineStat::Logouts as usize] = self.stats[EngineStat::Logouts as usize];
+        self.last_stats[EngineStat::Logins as usize] = self.stats[EngineStat::Logins as usize];
+        self.last_stats[EngineStat::Zones as usize] = self.stats[EngineStat::Zones as usize];
+        self.last_stats[EngineStat::ClientsOut as usize] = self.stats[EngineStat::ClientsOut as usize];
+        self.last_stats[EngineStat::Cleanup as usize] = self.stats[EngineStat::Cleanup as usize];
+        self.last_stats[EngineStat::BandwidthIn as usize] = self.stats[EngineStat::BandwidthIn as usize];
+        self.last_stats[EngineStat::BandwidthOut as usize] = self.stats[EngineStat::BandwidthOut as usize];
+    }
+
+    /// Runs every phase of a tick up to and including client output, leaving this
+    /// tick's packets in each player's write queue until `process_cycle` cleans up.
+    #[rustfmt::skip]
+    pub fn process_tick(&mut self) {
+        // world processing
+        // - world queue
+        // - calculate afk event readiness
+        // - npc spawn scripts
+        // - npc hunt
+        self.process_world();
+
+        // client input
+        // - decode packets
+        // - process pathfinding/following
+        self.process_in();
+
+        // npc processing (if npc is not busy)
+        // - resume suspended script
+        // - stat regen
+        // - timer
+        // - queue
+        // - movement
+        // - modes
+        self.process_npcs();
+
+        // player processing
+        // - resume suspended script
+        // - primary queue
+        // - weak queue
+        // - timers
+        // - soft timers
+        // - engine queue
+        // - interactions
+        // - movement
+        // - close interface if attempting to logout
+        self.process_players();
+
+        // player logout
+        self.process_logouts();
+
+        // player login, good spot for it (before packets so they immediately load but after processing so nothing hits them)
+        self.process_logins();
+
+        // process zones
+        // - build list of active zones around players
+        // - loc/obj despawn/respawn
+        // - compute shared buffer
+        self.process_zones();
+
+        // process movement directions
+        // - convert player movements
+        // - convert npc movements
+        self.process_movement_dirs();
+
+        // client output
+        // - map update
+        // - player info
+        // - npc info
+        // - zone updates
+        // - inv changes
+        // - stat changes
+        // - afk zones changes
+        // - flush packets
+        self.process_out();
+    }
+
     // - world queue
     // - calculate afk event readiness
     // - npc spawn scripts
@@ -272,7 +429,15 @@ impl Engine {
     // - compute shared buffer
     fn process_zones(&mut self) {
         let start: Instant = Instant::now();
-        // TODO
+        // - build list of active zones around players
+        for player in self.players.iter().flatten() {
+            let mut player: RefMut<Player> = player.borrow_mut();
+            let coord: CoordGrid = player.coord;
+            player.build_area.update_active_zones(coord);
+        }
+        // - loc/obj despawn/respawn
+        // - compute shared buffer
+        self.zones.borrow_mut().process(self.tick.current_tick);
         self.stats[EngineStat::Zones as usize] = Instant::now() - start
     }
 
@@ -299,11 +464,29 @@ impl Engine {
     // - flush packets
     fn process_out(&mut self) {
         let start: Instant = Instant::now();
-        for player in &self.players {
-            if let Some(ref player) = player {
-                let _: Ref<Player> = player.borrow();
-                // TODO
+        let zones: Ref<ZoneMap> = self.zones.borrow();
+        for player in self.players.iter().flatten() {
+            let mut player: RefMut<Player> = player.borrow_mut();
+            let coord: CoordGrid = player.coord;
+            // - map update
+            if player.build_area.needs_rebuild(coord) {
+                let rebuild: Packet =
+                    player
+                        .build_area
+                        .rebuild(coord, &self.map_crcs, &mut self.packet_pool);
+                player.write(rebuild);
+                player.build_area.update_active_zones(coord);
+            }
+            // - player info
+            // - npc info
+            // - zone updates
+            for buf in player.build_area.write_zones(&zones, &mut self.packet_pool) {
+                player.write(buf);
             }
+            // - inv changes
+            // - stat changes
+            // - afk zones changes
+            // - flush packets
         }
         self.stats[EngineStat::ClientsOut as usize] = Instant::now() - start
     }
@@ -315,10 +498,16 @@ impl Engine {
     fn process_cleanup(&mut self) {
         let start: Instant = Instant::now();
         // - reset zones
+        self.zones.borrow_mut().reset();
         // - reset players
         for player in &self.players {
             if let Some(ref player) = player {
-                let _: Ref<Player> = player.borrow();
+                let mut player: RefMut<Player> = player.borrow_mut();
+                // TODO: players have no connection to flush to yet, so this tick's packets
+                // are dropped here unsent and only their buffers are reused.
+                for buf in player.write_queue.drain(..) {
+                    self.packet_pool.give(buf);
+                }
                 // TODO
             }
         }
@@ -345,12 +534,20 @@ impl Engine {
 /// It is important to note that these are not commands.
 /// This is specifically for interfacing commands<->engine.
 impl ScriptEngine for Engine {
-    fn pop_obj(&self, id: i32) -> Result<&ObjType, String> {
-        return self.cache.obj_provider.get_by_id(id as usize);
+    fn pop_obj(&self, id: i32) -> Result<&ObjType, ScriptErrorKind> {
+        return self
+            .cache
+            .obj_provider
+            .get_by_id(id as usize)
+            .map_err(|_| ScriptErrorKind::MissingConfig { kind: "obj", id });
     }
 
-    fn pop_script(&self, id: i32) -> Result<&ScriptFile, String> {
-        return self.cache.script_provider.get_by_id(id as usize);
+    fn pop_script(&self, id: i32) -> Result<&ScriptFile, ScriptErrorKind> {
+        return self
+            .cache
+            .script_provider
+            .get_by_id(id as usize)
+            .map_err(|_| ScriptErrorKind::MissingConfig { kind: "script", id });
     }
 
     fn line_of_sight(&self, from: i32, to: i32) -> bool {
@@ -359,11 +556,16 @@ impl ScriptEngine for Engine {
     }
 
     fn add_obj(&self, coord: i32, id: i32, count: i32, duration: i32) -> bool {
-        // TODO: zone stuff.
-        return false;
+        self.zones.borrow_mut().add_obj(
+            CoordGrid::from(coord),
+            id,
+            count,
+            self.tick.current_tick + duration,
+        );
+        return true;
     }
 
-    fn with_player_mut<F>(&self, uid: i32, on_found: F) -> Result<(), String>
+    fn with_player_mut<F>(&self, uid: i32, on_found: F) -> Result<(), ScriptErrorKind>
     where
         F: FnOnce(RefMut<dyn ScriptPlayer>),
     {
@@ -372,14 +574,11 @@ impl ScriptEngine for Engine {
                 on_found(player.borrow_mut()); // Call the closure on the found player
                 Ok(())
             }
-            _ => Err(format!(
-                "Player with uid {} not found or not initialized",
-                uid
-            )),
+            _ => Err(ScriptErrorKind::MissingPlayer(uid)),
         }
     }
 
-    fn with_player<F>(&self, uid: i32, on_found: F) -> Result<(), String>
+    fn with_player<F>(&self, uid: i32, on_found: F) -> Result<(), ScriptErrorKind>
     where
         F: FnOnce(Ref<dyn ScriptPlayer>),
     {
@@ -388,10 +587,7 @@ impl ScriptEngine for Engine {
                 on_found(player.borrow()); // Call the closure on the found player
                 Ok(())
             }
-            _ => Err(format!(
-                "Player with uid {} not found or not initialized",
-                uid
-            )),
+            _ => Err(ScriptErrorKind::MissingPlayer(uid)),
         }
     }
 }
@@ -401,7 +597,7 @@ impl ScriptRunner for Engine {
         &'script self,
         state: &mut ScriptState<'script>,
         code: &ScriptOpcode,
-    ) -> Result<(), String> {
+    ) -> Result<(), ScriptErrorKind> {
         // println!("{:?}", code);
         match code {
             // Core language ops (0-99)
@@ -460,7 +656,6 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::MapFindSquare
             | ScriptOpcode::MoveCoord
             | ScriptOpcode::PlayerCount
-            | ScriptOpcode::ProjAnimMap
             | ScriptOpcode::ProjAnimNpc
             | ScriptOpcode::ProjAnimPl
             | ScriptOpcode::SeqLength
@@ -469,7 +664,6 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::SplitInit
             | ScriptOpcode::SplitLineCount
             | ScriptOpcode::SplitPageCount
-            | ScriptOpcode::SpotAnimMap
             | ScriptOpcode::StatRandom
             | ScriptOpcode::StructParam
             | ScriptOpcode::WorldDelay
@@ -477,7 +671,10 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::ZonesCount
             | ScriptOpcode::LocsCount
             | ScriptOpcode::ObjsCount
-            | ScriptOpcode::MapMulti => Err("Not implemented".to_string()),
+            | ScriptOpcode::MapMulti => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::ProjAnimMap | ScriptOpcode::SpotAnimMap => {
+                self.ops.zone.push(self, state, code)
+            }
             // Player ops (2000-2499)
             ScriptOpcode::AllowDesign
             | ScriptOpcode::Anim
@@ -646,25 +843,25 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::NpcWalk
             | ScriptOpcode::NpcAttackRange
             | ScriptOpcode::NpcHasOp
-            | ScriptOpcode::NpcArriveDelay => Err("Not implemented".to_string()),
+            | ScriptOpcode::NpcArriveDelay => Err(ScriptErrorKind::Unimplemented),
             // Loc ops (3000-3499)
-            ScriptOpcode::LocAdd
-            | ScriptOpcode::LocAngle
+            ScriptOpcode::LocAdd | ScriptOpcode::LocChange | ScriptOpcode::LocDel => {
+                self.ops.zone.push(self, state, code)
+            }
+            ScriptOpcode::LocAngle
             | ScriptOpcode::LocAnim
             | ScriptOpcode::LocCategory
-            | ScriptOpcode::LocChange
             | ScriptOpcode::LocCoord
-            | ScriptOpcode::LocDel
             | ScriptOpcode::LocFind
             | ScriptOpcode::LocFindAllZone
             | ScriptOpcode::LocFindNext
             | ScriptOpcode::LocName
             | ScriptOpcode::LocParam
             | ScriptOpcode::LocShape
-            | ScriptOpcode::LocType => Err("Not implemented".to_string()),
+            | ScriptOpcode::LocType => Err(ScriptErrorKind::Unimplemented),
             // Obj ops (3500-4000)
-            ScriptOpcode::ObjAdd
-            | ScriptOpcode::ObjAddAll
+            ScriptOpcode::ObjAdd => self.ops.zone.push(self, state, code),
+            ScriptOpcode::ObjAddAll
             | ScriptOpcode::ObjCoord
             | ScriptOpcode::ObjCount
             | ScriptOpcode::ObjDel
@@ -672,14 +869,14 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::ObjParam
             | ScriptOpcode::ObjTakeItem
             | ScriptOpcode::ObjType
-            | ScriptOpcode::ObjFind => Err("Not implemented".to_string()),
+            | ScriptOpcode::ObjFind => Err(ScriptErrorKind::Unimplemented),
             // Npc config ops (4000-4099)
             ScriptOpcode::NcCategory
             | ScriptOpcode::NcDebugname
             | ScriptOpcode::NcDesc
             | ScriptOpcode::NcName
             | ScriptOpcode::NcOp
-            | ScriptOpcode::NcParam => Err("Not implemented".to_string()),
+            | ScriptOpcode::NcParam => Err(ScriptErrorKind::Unimplemented),
             // Loc config ops (4100-4199)
             ScriptOpcode::LcCategory
             | ScriptOpcode::LcDebugname
@@ -688,7 +885,7 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::LcOp
             | ScriptOpcode::LcParam
             | ScriptOpcode::LcWidth
-            | ScriptOpcode::LcLength => Err("Not implemented".to_string()),
+            | ScriptOpcode::LcLength => Err(ScriptErrorKind::Unimplemented),
             // Obj config ops (4200-4299)
             ScriptOpcode::OcCategory
             | ScriptOpcode::OcCert
@@ -738,10 +935,10 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::BothDropSlot
             | ScriptOpcode::InvDropAll
             | ScriptOpcode::InvTotalParam
-            | ScriptOpcode::InvTotalParamStack => Err("Not implemented".to_string()),
+            | ScriptOpcode::InvTotalParamStack => Err(ScriptErrorKind::Unimplemented),
             // Enum ops (4400-4499)
             ScriptOpcode::Enum | ScriptOpcode::EnumGetOutputCount => {
-                Err("Not implemented".to_string())
+                Err(ScriptErrorKind::Unimplemented)
             }
             // String ops (4500-4599)
             ScriptOpcode::AppendNum
@@ -798,7 +995,7 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::DbFindRefineWithCount
             | ScriptOpcode::DbFind
             | ScriptOpcode::DbFindRefine
-            | ScriptOpcode::DbListAll => Err("Not implemented".to_string()),
+            | ScriptOpcode::DbListAll => Err(ScriptErrorKind::Unimplemented),
             // Debug ops (10000-11000)
             ScriptOpcode::Error
             | ScriptOpcode::MapProduction
@@ -813,7 +1010,23 @@ impl ScriptRunner for Engine {
             | ScriptOpcode::MapLastClientOut
             | ScriptOpcode::MapLastCleanup
             | ScriptOpcode::MapLastBandwidthIn
-            | ScriptOpcode::MapLastBandwidthOut => Err("Not implemented".to_string()),
+            | ScriptOpcode::MapLastBandwidthOut => Err(ScriptErrorKind::Unimplemented),
         }
     }
+
+    fn debugger(&self) -> Option<RefMut<'_, ScriptDebugger>> {
+        // a script started from inside a debugged script runs without it.
+        let debugger: RefMut<'_, Option<ScriptDebugger>> = self.debugger.try_borrow_mut().ok()?;
+        return RefMut::filter_map(debugger, |debugger| debugger.as_mut()).ok();
+    }
+
+    fn profiler(&self) -> Option<RefMut<'_, ScriptProfiler>> {
+        let profiler: RefMut<'_, Option<ScriptProfiler>> = self.profiler.try_borrow_mut().ok()?;
+        return RefMut::filter_map(profiler, |profiler| profiler.as_mut()).ok();
+    }
+
+    fn coverage(&self) -> Option<RefMut<'_, ScriptCoverage>> {
+        let coverage: RefMut<'_, Option<ScriptCoverage>> = self.coverage.try_borrow_mut().ok()?;
+        return RefMut::filter_map(coverage, |coverage| coverage.as_mut()).ok();
+    }
 }
@@ -1,4 +1,8 @@
 use cache::ScriptPlayer;
+use io::Packet;
+
+use crate::zone::build_area::BuildArea;
+use crate::zone::coord_grid::CoordGrid;
 
 #[derive(Clone)]
 pub struct Player {
@@ -8,6 +12,9 @@ pub struct Player {
     pub anim_delay: i32,
     pub anim_protect: bool,
     pub bas_readyanim: i32,
+    pub coord: CoordGrid,
+    pub build_area: BuildArea,
+    pub write_queue: Vec<Packet>,
 }
 
 impl Player {
@@ -30,8 +37,15 @@ impl Player {
             anim_delay: -1,
             anim_protect: false,
             bas_readyanim: -1,
+            coord: CoordGrid::new(3222, 3222, 0),
+            build_area: BuildArea::new(),
+            write_queue: Vec::new(),
         };
     }
+
+    pub fn write(&mut self, buf: Packet) {
+        self.write_queue.push(buf);
+    }
 }
 
 impl ScriptPlayer for Player {
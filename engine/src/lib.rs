@@ -1,3 +1,5 @@
 pub mod engine;
 pub mod entity;
+pub mod network;
 pub mod script;
+pub mod zone;
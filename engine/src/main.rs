@@ -1,7 +1,10 @@
 use dotenv::dotenv;
 
-use cache::{CacheProvider, ScriptPointer, ScriptState};
+use cache::{
+    Breakpoint, CacheProvider, ConsoleDebugHook, ScriptDebugger, ScriptPointer, ScriptState,
+};
 use engine::engine::Engine;
+use engine::network::update_server::UpdateServer;
 
 fn main() {
     println!("Hello, world!");
@@ -10,10 +13,29 @@ fn main() {
 
     let mut engine: Engine = Engine::new(CacheProvider::new(
         "./data/pack",
-        std::env::var("COMPILER_VERSION").unwrap(),
+        cache::COMPILER_VERSION.to_string(),
         std::env::var("MEMBERS").unwrap() == "true",
     ));
 
+    // SCRIPT_BREAKPOINT=[proc,fib]:2 pauses scripts on the console.
+    if let Ok(breakpoint) = std::env::var("SCRIPT_BREAKPOINT") {
+        let breakpoint: Breakpoint = match Breakpoint::parse(&breakpoint) {
+            Ok(breakpoint) => breakpoint,
+            Err(e) => panic!("SCRIPT_BREAKPOINT: {}", e),
+        };
+        let hook = ConsoleDebugHook::new(std::io::stdin().lock(), std::io::stdout());
+        let mut debugger: ScriptDebugger = ScriptDebugger::new(Box::new(hook));
+        debugger.add_breakpoint(&breakpoint.script, breakpoint.line);
+        engine.attach_debugger(debugger);
+    }
+
+    // DEBUG_PORT=43600 lets a debugger frontend attach over telnet or netcat.
+    if let Ok(debug_port) = std::env::var("DEBUG_PORT") {
+        if let Err(e) = engine.listen_debugger(&format!("127.0.0.1:{}", debug_port)) {
+            println!("Failed to start debug server: {}", e);
+        }
+    }
+
     engine.cache.obj_provider.with_script_name(
         "christmas_cracker",
         |obj| {
@@ -27,18 +49,19 @@ fn main() {
     engine.cache.script_provider.with_script_name(
         "[proc,fib]",
         |script| {
-            let mut state: ScriptState = ScriptState::new_with_args(script, vec![45], Vec::new());
+            let mut state: ScriptState =
+                engine.script_state(&engine.cache.script_provider, script, vec![45], Vec::new());
             state.pointer_add(ScriptPointer::ProtectedActivePlayer);
-            match state.execute(&engine, false) {
+            match state.execute(&engine) {
                 Ok(()) => {
                     println!(
                         "fib: result={}, opcount={}, pointers={}",
-                        state.pop_int(),
+                        state.pop_int().unwrap_or_default(),
                         state.opcount,
                         state.pointer_debug()
                     );
                 }
-                Err(e) => panic!("{}", e),
+                Err(e) => println!("Script error: {}", e),
             };
         },
         || {},
@@ -60,17 +83,21 @@ fn main() {
             engine.cache.obj_provider.with_script_name(
                 "christmas_cracker",
                 |obj| {
-                    let mut state: ScriptState =
-                        ScriptState::new_with_args(script, vec![obj.id as i32], Vec::new());
-                    match state.execute(&engine, false) {
+                    let mut state: ScriptState = engine.script_state(
+                        &engine.cache.script_provider,
+                        script,
+                        vec![obj.id as i32],
+                        Vec::new(),
+                    );
+                    match state.execute(&engine) {
                         Ok(()) => {
                             println!(
                                 "get_obj_name: result={}, opcount={}",
-                                state.pop_string(),
+                                state.pop_string().unwrap_or_default(),
                                 state.opcount
                             );
                         }
-                        Err(e) => panic!("{}", e),
+                        Err(e) => println!("Script error: {}", e),
                     };
                 },
                 || {},
@@ -82,11 +109,32 @@ fn main() {
     engine.cache.script_provider.with_script_name(
         "[proc,test_jump]",
         |script| {
-            let mut state: ScriptState = ScriptState::new_with_args(script, Vec::new(), Vec::new());
-            let _ = state.execute(&engine, false);
+            let mut state: ScriptState = engine.script_state(
+                &engine.cache.script_provider,
+                script,
+                Vec::new(),
+                Vec::new(),
+            );
+            let _ = state.execute(&engine);
         },
         || {},
     );
 
+    let web_port: String = std::env::var("WEB_PORT").unwrap_or_else(|_| "80".to_string());
+    let ondemand_port: String =
+        std::env::var("ONDEMAND_PORT").unwrap_or_else(|_| "43595".to_string());
+    match UpdateServer::load("./data/pack") {
+        Ok(update_server) => {
+            engine.map_crcs = update_server.map_crcs.clone();
+            if let Err(e) = update_server.serve(
+                &format!("0.0.0.0:{}", web_port),
+                &format!("0.0.0.0:{}", ondemand_port),
+            ) {
+                println!("Failed to start update server: {}", e);
+            }
+        }
+        Err(e) => println!("Failed to load update server: {}", e),
+    }
+
     engine.start(true);
 }
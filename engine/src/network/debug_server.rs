@@ -0,0 +1,162 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use cache::{
+    Breakpoint, DebugCommand, DebugHook, DebugLine, DebugRequest, PauseReason, ScriptDebugger,
+    ScriptFrame, ScriptState,
+};
+
+/// Accepts script debugger frontends over TCP.
+///
+/// Connections are accepted on their own thread and handed to the engine over
+/// a channel, the engine attaches the newest one as its debugger between ticks
+/// since the debugger itself never leaves the world thread.
+pub struct DebugServer {
+    addr: SocketAddr,
+    connections: Receiver<TcpStream>,
+    pub pause_timeout: Duration, // how long a paused script waits on its frontend
+}
+
+impl DebugServer {
+    pub fn listen(addr: &str) -> std::io::Result<DebugServer> {
+        let listener: TcpListener = TcpListener::bind(addr)?;
+        let addr: SocketAddr = listener.local_addr()?;
+        let (sender, connections): (Sender<TcpStream>, Receiver<TcpStream>) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if sender.send(stream).is_err() {
+                    return;
+                }
+            }
+        });
+        println!("Debug server listening on {}", addr);
+        return Ok(DebugServer {
+            addr,
+            connections,
+            pause_timeout: TcpDebugHook::PAUSE_TIMEOUT,
+        });
+    }
+
+    /// The address frontends connect to, with the port picked when listening on port 0.
+    pub fn addr(&self) -> SocketAddr {
+        return self.addr;
+    }
+
+    /// A debugger for the newest frontend that connected since the last call, if any.
+    pub fn accept(&self) -> Option<ScriptDebugger> {
+        let mut debugger: Option<ScriptDebugger> = None;
+        while let Ok(stream) = self.connections.try_recv() {
+            match TcpDebugHook::new(stream, self.pause_timeout) {
+                Ok(hook) => debugger = Some(ScriptDebugger::new(Box::new(hook))),
+                Err(e) => println!("Failed to attach debugger: {}", e),
+            }
+        }
+        return debugger;
+    }
+}
+
+/// A line based frontend over a socket, see `DebugLine` for the commands.
+///
+/// Lines are read on their own thread and handed over a channel, so the world
+/// thread can pick up breakpoints sent while no script is paused without
+/// blocking on the socket.
+///
+/// A paused script holds up the world tick, so a frontend that goes quiet for
+/// `pause_timeout` or disconnects is detached and the script resumes.
+pub struct TcpDebugHook {
+    lines: Receiver<String>,
+    output: TcpStream,
+    pause_timeout: Duration,
+}
+
+impl TcpDebugHook {
+    pub const PAUSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    pub fn new(stream: TcpStream, pause_timeout: Duration) -> std::io::Result<TcpDebugHook> {
+        let reader: BufReader<TcpStream> = BufReader::new(stream.try_clone()?);
+        let (sender, lines): (Sender<String>, Receiver<String>) = channel();
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+        return Ok(TcpDebugHook {
+            lines,
+            output: stream,
+            pause_timeout,
+        });
+    }
+}
+
+impl DebugHook for TcpDebugHook {
+    fn on_pause(
+        &mut self,
+        state: &ScriptState,
+        reason: PauseReason,
+        breakpoints: &mut Vec<Breakpoint>,
+    ) -> DebugCommand {
+        let location: ScriptFrame = ScriptDebugger::location(state);
+        let _ = match reason {
+            PauseReason::Breakpoint(_) => writeln!(self.output, "breakpoint at {}", location),
+            PauseReason::Step => writeln!(self.output, "paused at {}", location),
+        };
+        loop {
+            let _ = write!(self.output, "> ");
+            let _ = self.output.flush();
+            // a closed or idle frontend is detached and lets the script finish.
+            let line: String = match self.lines.recv_timeout(self.pause_timeout) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = writeln!(self.output, "Timed out, detaching and resuming");
+                    let _ = self.output.shutdown(Shutdown::Both);
+                    breakpoints.clear();
+                    return DebugCommand::Continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    breakpoints.clear();
+                    return DebugCommand::Continue;
+                }
+            };
+            if let Some(command) =
+                DebugLine::parse(&line).respond(state, breakpoints, &mut self.output)
+            {
+                return command;
+            }
+        }
+    }
+
+    fn poll(&mut self, breakpoints: &mut Vec<Breakpoint>) -> Vec<DebugRequest> {
+        let mut requests: Vec<DebugRequest> = Vec::new();
+        loop {
+            let line: String = match self.lines.try_recv() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => return requests,
+                Err(TryRecvError::Disconnected) => {
+                    breakpoints.clear();
+                    return requests;
+                }
+            };
+            match DebugLine::parse(&line) {
+                DebugLine::Command(DebugCommand::Step) | DebugLine::Command(DebugCommand::Next) => {
+                    requests.push(DebugRequest::Pause);
+                }
+                DebugLine::Reload => {
+                    let _ = writeln!(self.output, "Reloading scripts at the end of the tick");
+                    requests.push(DebugRequest::Reload);
+                }
+                DebugLine::Invalid(error) => {
+                    let _ = writeln!(self.output, "{}", error);
+                }
+                line if line.change(breakpoints) => {}
+                _ => {
+                    let _ = writeln!(self.output, "No script is paused");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod debug_server;
+pub mod server_prot;
+pub mod update_server;
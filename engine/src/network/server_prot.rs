@@ -0,0 +1,49 @@
+/// Server -> client packets for the 225 protocol.
+///
+/// Only the packets the engine currently writes are listed here.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(u8)]
+pub enum ServerProt {
+    // zones
+    UpdateZoneFullFollows = 7,
+    UpdateZonePartialFollows = 135,
+    UpdateZonePartialEnclosed = 162,
+
+    // zone protocol
+    LocMerge = 23,
+    LocAnim = 42,
+    ObjDel = 49,
+    ObjReveal = 50,
+    LocAddChange = 59,
+    MapProjAnim = 69,
+    LocDel = 76,
+    ObjCount = 151,
+    MapAnim = 191,
+    ObjAdd = 223,
+
+    // map
+    RebuildNormal = 237,
+}
+
+impl ServerProt {
+    /// The fixed length of this packet, or `-1` for a var byte
+    /// and `-2` for a var short sized packet.
+    pub fn length(&self) -> i32 {
+        return match self {
+            ServerProt::UpdateZoneFullFollows => 2,
+            ServerProt::UpdateZonePartialFollows => 2,
+            ServerProt::UpdateZonePartialEnclosed => -2,
+            ServerProt::LocMerge => 14,
+            ServerProt::LocAnim => 4,
+            ServerProt::ObjDel => 3,
+            ServerProt::ObjReveal => 7,
+            ServerProt::LocAddChange => 4,
+            ServerProt::MapProjAnim => 15,
+            ServerProt::LocDel => 2,
+            ServerProt::ObjCount => 7,
+            ServerProt::MapAnim => 6,
+            ServerProt::ObjAdd => 5,
+            ServerProt::RebuildNormal => -2,
+        };
+    }
+}
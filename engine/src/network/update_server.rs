@@ -0,0 +1,464 @@
+use std::cmp;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::ReadDir;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use io::{crc32, Packet};
+
+/// The jag archives a 225 client downloads on startup, in crc table order.
+/// Index 0 is reserved for the crc table itself.
+pub const ARCHIVES: [&str; 9] = [
+    "crc",
+    "title",
+    "config",
+    "interface",
+    "media",
+    "models",
+    "textures",
+    "wordenc",
+    "sounds",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[repr(u8)]
+pub enum OnDemandKind {
+    Land = 0,
+    Loc = 1,
+    Model = 2,
+    Midi = 3,
+}
+
+impl OnDemandKind {
+    pub fn from(value: u8) -> Option<OnDemandKind> {
+        return match value {
+            0 => Some(OnDemandKind::Land),
+            1 => Some(OnDemandKind::Loc),
+            2 => Some(OnDemandKind::Model),
+            3 => Some(OnDemandKind::Midi),
+            _ => None,
+        };
+    }
+
+    /// Maps are needed before the player can see anything so they go first,
+    /// models next, and music whenever there is nothing else to send.
+    pub fn priority(&self) -> u8 {
+        return match self {
+            OnDemandKind::Land | OnDemandKind::Loc => 2,
+            OnDemandKind::Model => 1,
+            OnDemandKind::Midi => 0,
+        };
+    }
+
+    fn dir(&self) -> &'static str {
+        return match self {
+            OnDemandKind::Land | OnDemandKind::Loc => "maps",
+            OnDemandKind::Model => "models",
+            OnDemandKind::Midi => "songs",
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OnDemandRequest {
+    pub kind: OnDemandKind,
+    pub name: String,
+    pub priority: u8,
+    seq: u64, // keeps requests of the same priority in order
+}
+
+impl Ord for OnDemandRequest {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        return self
+            .priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq));
+    }
+}
+
+impl PartialOrd for OnDemandRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+/// Pending file requests for a single connection, highest priority first.
+///
+/// The queue is closed once the client stops sending requests, so the thread
+/// answering them can finish what is left and stop.
+pub struct OnDemandQueue {
+    requests: BinaryHeap<OnDemandRequest>,
+    seq: u64,
+    closed: bool,
+}
+
+impl OnDemandQueue {
+    pub fn new() -> OnDemandQueue {
+        return OnDemandQueue {
+            requests: BinaryHeap::new(),
+            seq: 0,
+            closed: false,
+        };
+    }
+
+    pub fn push(&mut self, kind: OnDemandKind, name: String) {
+        self.push_with_priority(kind, name, kind.priority());
+    }
+
+    pub fn push_with_priority(&mut self, kind: OnDemandKind, name: String, priority: u8) {
+        self.requests.push(OnDemandRequest {
+            kind,
+            name,
+            priority,
+            seq: self.seq,
+        });
+        self.seq += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<OnDemandRequest> {
+        return self.requests.pop();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.requests.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.requests.is_empty();
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        return self.closed;
+    }
+}
+
+impl Default for OnDemandQueue {
+    fn default() -> OnDemandQueue {
+        return OnDemandQueue::new();
+    }
+}
+
+/// Serves the client cache out of `data/pack/client`.
+///
+/// The jag archives are held in memory and served over http, the client
+/// appends the expected crc to each path which is ignored here. Maps, models
+/// and midis are read from disk when requested over http or the ondemand socket.
+pub struct UpdateServer {
+    pub dir: String,
+    pub archives: HashMap<String, Vec<u8>>,
+    pub map_crcs: HashMap<String, i32>, // by map file name, `m50_50`
+}
+
+impl UpdateServer {
+    /// The most connections answered at once, any more are closed straight away.
+    pub const MAX_CONNECTIONS: usize = 64;
+    /// How long a connection gets to send its request and take the response.
+    pub const TIMEOUT: Duration = Duration::from_secs(10);
+    /// The most bytes read of a request, the request line is all that is needed.
+    pub const MAX_REQUEST: usize = 8192;
+    pub const CHUNK_SIZE: usize = 500;
+
+    pub fn io(dir: &str) -> UpdateServer {
+        return match UpdateServer::load(dir) {
+            Ok(server) => server,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    /// Reads the archives like `io`, failing only when `<dir>/client` can't be read.
+    ///
+    /// `pack` doesn't write every archive yet, so a missing one is warned about,
+    /// answered with a 404 and written as 0 in the crc table.
+    pub fn load(dir: &str) -> Result<UpdateServer, String> {
+        let start: Instant = Instant::now();
+        if let Err(e) = std::fs::read_dir(format!("{}/client", dir)) {
+            return Err(format!("Update server can't read {}/client: {}", dir, e));
+        }
+        let mut archives: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut missing: Vec<&str> = Vec::new();
+        for name in ARCHIVES.iter().filter(|name| **name != "crc") {
+            match std::fs::read(format!("{}/client/{}", dir, name)) {
+                Ok(data) => {
+                    archives.insert(name.to_string(), data);
+                }
+                Err(_) => missing.push(name),
+            }
+        }
+        if !missing.is_empty() {
+            println!(
+                "Update server is missing archives in {}/client, clients won't finish loading: {}",
+                dir,
+                missing.join(", ")
+            );
+        }
+        // always built from the archives served, a `crc` left on disk by an
+        // older pack would have clients loop on a checksum mismatch.
+        archives.insert("crc".to_string(), UpdateServer::crc_table(&archives));
+        let map_crcs: HashMap<String, i32> = UpdateServer::map_crcs(dir);
+        println!("Loaded update server in: {:?}", start.elapsed());
+        return Ok(UpdateServer {
+            dir: dir.to_string(),
+            archives,
+            map_crcs,
+        });
+    }
+
+    /// The `crc` archive, the crc of every other archive as a `p4` in `ARCHIVES`
+    /// order. The crc table itself and missing archives are written as 0.
+    pub fn crc_table(archives: &HashMap<String, Vec<u8>>) -> Vec<u8> {
+        let mut buf: Packet = Packet::new(ARCHIVES.len() * 4);
+        for name in ARCHIVES {
+            match archives.get(name) {
+                Some(data) if name != "crc" => buf.p4(crc32(data)),
+                _ => buf.p4(0),
+            }
+        }
+        return buf.data;
+    }
+
+    /// The crc of every map file in `<dir>/client/maps`, as sent in rebuild packets.
+    pub fn map_crcs(dir: &str) -> HashMap<String, i32> {
+        let mut crcs: HashMap<String, i32> = HashMap::new();
+        let entries: ReadDir = match std::fs::read_dir(format!("{}/client/maps", dir)) {
+            Ok(entries) => entries,
+            Err(_) => return crcs,
+        };
+        for entry in entries.flatten() {
+            if let Ok(data) = std::fs::read(entry.path()) {
+                crcs.insert(
+                    entry.file_name().to_string_lossy().to_string(),
+                    crc32(&data),
+                );
+            }
+        }
+        return crcs;
+    }
+
+    /// Strips the crc the client appends to archive paths, `/config-1494247427` -> `config`.
+    pub fn archive_name(path: &str) -> &str {
+        return path
+            .trim_start_matches('/')
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '-');
+    }
+
+    pub fn archive(&self, name: &str) -> Option<&Vec<u8>> {
+        return self.archives.get(name);
+    }
+
+    /// Reads an on-demand file, maps are named `m50_50`/`l50_50`,
+    /// models by id and midis by their song name.
+    pub fn file(&self, kind: OnDemandKind, name: &str) -> Option<Vec<u8>> {
+        if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
+            return None;
+        }
+        let file: String = match kind {
+            OnDemandKind::Midi => format!("{}.mid", name),
+            _ => name.to_string(),
+        };
+        return std::fs::read(format!("{}/client/{}/{}", self.dir, kind.dir(), file)).ok();
+    }
+
+    /// Builds the full http response for a raw request.
+    pub fn http_response(&self, request: &str) -> Vec<u8> {
+        let path: Option<&str> = request.lines().next().and_then(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("GET"), Some(path)) => Some(path),
+                _ => None,
+            }
+        });
+
+        let body: Option<Vec<u8>> = path.and_then(|path| {
+            let trimmed: &str = path.trim_start_matches('/');
+            if let Some(song) = trimmed.strip_suffix(".mid") {
+                // songs are requested as `/name_crc.mid`
+                let name: &str = song.rsplit_once('_').map(|(name, _)| name).unwrap_or(song);
+                return self.file(OnDemandKind::Midi, name);
+            }
+            return self.archive(UpdateServer::archive_name(path)).cloned();
+        });
+
+        return match body {
+            Some(body) => {
+                let mut response: Vec<u8> = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&body);
+                response
+            }
+            None => {
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+            }
+        };
+    }
+
+    /// Splits a file into the chunks sent over the ondemand socket.
+    ///
+    /// Each chunk is `p1 kind, jstr name, p4 total length, p2 chunk, p2 length, data`.
+    /// An empty file is sent as a single empty chunk so the client knows it is missing.
+    pub fn chunks(kind: OnDemandKind, name: &str, data: &Vec<u8>) -> Vec<Packet> {
+        let mut chunks: Vec<Packet> = Vec::new();
+        let mut offset: usize = 0;
+        let mut chunk: usize = 0;
+        loop {
+            let length: usize = (data.len() - offset).min(UpdateServer::CHUNK_SIZE);
+            let mut buf: Packet = Packet::new(1 + name.len() + 1 + 4 + 2 + 2 + length);
+            buf.p1(kind as i32);
+            buf.pjstr(name, 10);
+            buf.p4(data.len() as i32);
+            buf.p2(chunk as i32);
+            buf.p2(length as i32);
+            buf.pdata(data, offset, length);
+            chunks.push(buf);
+
+            offset += length;
+            chunk += 1;
+            if offset >= data.len() {
+                break;
+            }
+        }
+        return chunks;
+    }
+
+    /// Starts the http and ondemand listeners on their own threads, answering
+    /// each connection on a thread of its own up to `MAX_CONNECTIONS` at once
+    /// per listener.
+    ///
+    /// # Returns
+    ///
+    /// The http and ondemand addresses, with the ports picked when listening on port 0.
+    pub fn serve(
+        self,
+        http_addr: &str,
+        ondemand_addr: &str,
+    ) -> std::io::Result<(SocketAddr, SocketAddr)> {
+        let server: Arc<UpdateServer> = Arc::new(self);
+        let http: TcpListener = TcpListener::bind(http_addr)?;
+        let ondemand: TcpListener = TcpListener::bind(ondemand_addr)?;
+        let addrs: (SocketAddr, SocketAddr) = (http.local_addr()?, ondemand.local_addr()?);
+        UpdateServer::accept(http, Arc::clone(&server), UpdateServer::handle_http);
+        UpdateServer::accept(ondemand, server, UpdateServer::handle_ondemand);
+        println!("Update server listening on {} and {}", addrs.0, addrs.1);
+        return Ok(addrs);
+    }
+
+    fn accept(
+        listener: TcpListener,
+        server: Arc<UpdateServer>,
+        handle: fn(&UpdateServer, TcpStream) -> std::io::Result<()>,
+    ) {
+        let connections: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // dropping the stream closes it
+                if connections.fetch_add(1, Ordering::SeqCst) >= UpdateServer::MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                let server: Arc<UpdateServer> = Arc::clone(&server);
+                let connections: Arc<AtomicUsize> = Arc::clone(&connections);
+                thread::spawn(move || {
+                    let _ = handle(&server, stream);
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+    }
+
+    fn handle_http(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(UpdateServer::TIMEOUT))?;
+        stream.set_write_timeout(Some(UpdateServer::TIMEOUT))?;
+        let mut request: Vec<u8> = Vec::new();
+        let mut buf: [u8; 1024] = [0; 1024];
+        while request.len() < UpdateServer::MAX_REQUEST
+            && !request.windows(4).any(|window| window == b"\r\n\r\n")
+        {
+            let read: usize = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            let length: usize = read.min(UpdateServer::MAX_REQUEST - request.len());
+            request.extend_from_slice(&buf[..length]);
+        }
+        let response: Vec<u8> = self.http_response(&String::from_utf8_lossy(&request));
+        stream.write_all(&response)?;
+        return stream.flush();
+    }
+
+    /// Each request is `p1 kind, p1 priority, jstr name`. Requests are read on
+    /// their own thread and answered from the queue in priority order.
+    ///
+    /// The client keeps the socket open between requests, so only writes time out.
+    fn handle_ondemand(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_write_timeout(Some(UpdateServer::TIMEOUT))?;
+        let queue: Arc<(Mutex<OnDemandQueue>, Condvar)> =
+            Arc::new((Mutex::new(OnDemandQueue::new()), Condvar::new()));
+
+        let reader_queue = Arc::clone(&queue);
+        let mut reader: BufReader<TcpStream> = BufReader::new(stream.try_clone()?);
+        thread::spawn(move || {
+            let mut header: [u8; 2] = [0; 2];
+            let mut name: Vec<u8> = Vec::new();
+            loop {
+                name.clear();
+                if reader.read_exact(&mut header).is_err()
+                    || (&mut reader)
+                        .take(UpdateServer::MAX_REQUEST as u64)
+                        .read_until(10, &mut name)
+                        .unwrap_or(0)
+                        == 0
+                {
+                    break;
+                }
+                name.pop();
+                if let Some(kind) = OnDemandKind::from(header[0]) {
+                    let (lock, signal) = &*reader_queue;
+                    if let Ok(mut pending) = lock.lock() {
+                        pending.push_with_priority(
+                            kind,
+                            String::from_utf8_lossy(&name).to_string(),
+                            header[1],
+                        );
+                        signal.notify_one();
+                    }
+                }
+            }
+            // closed under the same lock the writer waits on, so the wakeup can't be missed
+            let (lock, signal) = &*reader_queue;
+            if let Ok(mut pending) = lock.lock() {
+                pending.close();
+            }
+            signal.notify_one();
+        });
+
+        let (lock, signal) = &*queue;
+        loop {
+            let request: OnDemandRequest = {
+                let mut pending = signal
+                    .wait_while(lock.lock().unwrap(), |pending| {
+                        pending.is_empty() && !pending.is_closed()
+                    })
+                    .unwrap();
+                match pending.pop() {
+                    Some(request) => request,
+                    None => return Ok(()),
+                }
+            };
+            let data: Vec<u8> = self.file(request.kind, &request.name).unwrap_or_default();
+            for chunk in UpdateServer::chunks(request.kind, &request.name, &data) {
+                stream.write_all(&chunk.data[..chunk.pos])?;
+            }
+            stream.flush()?;
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use cache::{ScriptEngine, ScriptExecutionState, ScriptOpcode, ScriptState};
+use std::collections::HashMap;
+
+use cache::{ScriptEngine, ScriptErrorKind, ScriptExecutionState, ScriptOpcode, ScriptState};
 
 pub struct CoreOps;
 
@@ -12,7 +14,7 @@ impl CoreOps {
         engine: &'script impl ScriptEngine,
         state: &mut ScriptState<'script>,
         code: &ScriptOpcode,
-    ) -> Result<(), String> {
+    ) -> Result<(), ScriptErrorKind> {
         match code {
             ScriptOpcode::PushConstantInt => self.push_constant_int(state),
             ScriptOpcode::PushVarp => self.push_varp(engine, state),
@@ -49,52 +51,52 @@ impl CoreOps {
             ScriptOpcode::DefineArray => self.define_array(state),
             ScriptOpcode::PushArrayInt => self.push_array_int(state),
             ScriptOpcode::PopArrayInt => self.pop_array_int(state),
-            _ => Err(format!("Unrecognised core ops code: {:?}", code)),
+            _ => Err(ScriptErrorKind::Unimplemented),
         }
     }
 
     #[inline(always)]
-    fn push_constant_int(&self, state: &mut ScriptState) -> Result<(), String> {
-        state.push_int(state.int_operand());
+    fn push_constant_int(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        state.push_int(state.int_operand())?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn push_varp(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_varp(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_varp(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_varp(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn push_constant_string(&self, state: &mut ScriptState) -> Result<(), String> {
-        state.push_string(state.string_operand());
+    fn push_constant_string(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        state.push_string(state.string_operand())?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn push_varn(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_varn(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_varn(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_varn(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn branch(&self, state: &mut ScriptState) -> Result<(), String> {
+    fn branch(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
         state.pc += state.int_operand();
         return Ok(());
     }
 
     #[inline(always)]
-    fn branch_not(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_not(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a != b {
             state.pc += state.int_operand();
         }
@@ -102,9 +104,9 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn branch_equals(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_equals(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a == b {
             state.pc += state.int_operand();
         }
@@ -112,9 +114,9 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn branch_less_than(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_less_than(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a < b {
             state.pc += state.int_operand();
         }
@@ -122,9 +124,9 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn branch_greater_than(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_greater_than(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a > b {
             state.pc += state.int_operand();
         }
@@ -132,17 +134,17 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn push_vars(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_vars(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_vars(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_vars(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn _return(&self, state: &mut ScriptState) -> Result<(), String> {
+    fn _return(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
         if state.fp == 0 {
             state.execution_state = ScriptExecutionState::Finished;
             return Ok(());
@@ -156,12 +158,12 @@ impl CoreOps {
         &self,
         engine: &'script impl ScriptEngine,
         state: &mut ScriptState<'script>,
-    ) -> Result<(), String> {
-        if state.fp >= 50 {
-            return Err("stack overflow!".to_string());
+    ) -> Result<(), ScriptErrorKind> {
+        if state.fp >= state.limits.frames {
+            return Err(ScriptErrorKind::StackOverflow);
         }
-        let script: i32 = state.pop_int();
-        state.gosub_frame(engine.pop_script(script)?);
+        let script: i32 = state.pop_int()?;
+        state.gosub_frame(state.script_by_id(engine, script)?)?;
         return Ok(());
     }
 
@@ -170,39 +172,50 @@ impl CoreOps {
         &self,
         engine: &'script impl ScriptEngine,
         state: &mut ScriptState<'script>,
-    ) -> Result<(), String> {
-        let script: i32 = state.pop_int();
-        state.goto_frame(engine.pop_script(script)?);
+    ) -> Result<(), ScriptErrorKind> {
+        let script: i32 = state.pop_int()?;
+        state.goto_frame(state.script_by_id(engine, script)?)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn switch(&self, state: &mut ScriptState) -> Result<(), String> {
-        if let Some(result) = state
+    fn switch(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let key: i32 = state.pop_int()?;
+        let table: &HashMap<i32, i32> = state
             .script
-            .switch_table
-            .as_ref()
-            .and_then(|table| table.get(&state.pop_int()))
-        {
+            .switch_tables
+            .get(state.int_operand() as usize)
+            .ok_or_else(|| {
+                ScriptErrorKind::Runtime(format!("Missing switch table: {}", state.int_operand()))
+            })?;
+        if let Some(result) = table.get(&key) {
             state.pc += result;
         }
         return Ok(());
     }
 
     #[inline(always)]
-    fn push_varbit(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_varbit(
+        &self,
+        _: &impl ScriptEngine,
+        _: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_varbit(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_varbit(
+        &self,
+        _: &impl ScriptEngine,
+        _: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn branch_less_than_or_equals(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_less_than_or_equals(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a <= b {
             state.pc += state.int_operand();
         }
@@ -210,9 +223,12 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn branch_greater_than_or_equals(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn branch_greater_than_or_equals(
+        &self,
+        state: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a >= b {
             state.pc += state.int_operand();
         }
@@ -220,48 +236,48 @@ impl CoreOps {
     }
 
     #[inline(always)]
-    fn push_int_local(&self, state: &mut ScriptState) -> Result<(), String> {
-        state.push_int(state.int_locals[state.int_operand() as usize]);
+    fn push_int_local(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        state.push_int(state.int_locals[state.int_operand() as usize])?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn pop_int_local(&self, state: &mut ScriptState) -> Result<(), String> {
+    fn pop_int_local(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
         let operand: usize = state.int_operand() as usize;
-        state.int_locals[operand] = state.pop_int();
+        state.int_locals[operand] = state.pop_int()?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn push_string_local(&self, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_string_local(&self, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_string_local(&self, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_string_local(&self, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn join_string(&self, state: &mut ScriptState) -> Result<(), String> {
+    fn join_string(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
         let count: usize = state.int_operand() as usize;
         let mut result: String = String::new();
         for _ in 0..count {
-            result = state.pop_string() + &result;
+            result = state.pop_string()? + &result;
         }
-        state.push_string(result);
+        state.push_string(result)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn pop_int_discard(&self, state: &mut ScriptState) -> Result<(), String> {
-        state.isp -= 1;
+    fn pop_int_discard(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        state.pop_int()?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn pop_string_discard(&self, state: &mut ScriptState) -> Result<(), String> {
-        state.ssp -= 1;
+    fn pop_string_discard(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        state.pop_string()?;
         return Ok(());
     }
 
@@ -270,11 +286,11 @@ impl CoreOps {
         &self,
         engine: &'script impl ScriptEngine,
         state: &mut ScriptState<'script>,
-    ) -> Result<(), String> {
-        if state.fp >= 50 {
-            return Err("stack overflow!".to_string());
+    ) -> Result<(), ScriptErrorKind> {
+        if state.fp >= state.limits.frames {
+            return Err(ScriptErrorKind::StackOverflow);
         }
-        state.gosub_frame(engine.pop_script(state.int_operand())?);
+        state.gosub_frame(state.script_by_id(engine, state.int_operand())?)?;
         return Ok(());
     }
 
@@ -283,33 +299,42 @@ impl CoreOps {
         &self,
         engine: &'script impl ScriptEngine,
         state: &mut ScriptState<'script>,
-    ) -> Result<(), String> {
-        state.goto_frame(engine.pop_script(state.int_operand())?);
+    ) -> Result<(), ScriptErrorKind> {
+        state.goto_frame(state.script_by_id(engine, state.int_operand())?)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn push_varc(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_varc(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn pop_varc(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_varc(&self, _: &impl ScriptEngine, _: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        return Err(ScriptErrorKind::Unimplemented);
     }
 
     #[inline(always)]
-    fn define_array(&self, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn define_array(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let array: i32 = state.int_operand() >> 16;
+        let kind: i32 = state.int_operand() & 0xffff;
+        let size: i32 = state.pop_int()?;
+        return state.define_array(array, kind, size);
     }
 
     #[inline(always)]
-    fn push_array_int(&self, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn push_array_int(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let index: i32 = state.pop_int()?;
+        let value: i32 = *state.array_element(state.int_operand(), index)?;
+        state.push_int(value)?;
+        return Ok(());
     }
 
     #[inline(always)]
-    fn pop_array_int(&self, _: &mut ScriptState) -> Result<(), String> {
-        return Err("Not implemented".to_string());
+    fn pop_array_int(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let value: i32 = state.pop_int()?;
+        let index: i32 = state.pop_int()?;
+        *state.array_element(state.int_operand(), index)? = value;
+        return Ok(());
     }
 }
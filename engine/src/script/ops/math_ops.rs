@@ -1,6 +1,6 @@
 use rand::random;
 
-use cache::{ScriptOpcode, ScriptState};
+use cache::{ScriptErrorKind, ScriptOpcode, ScriptState};
 use math::bits::Bits;
 use math::trig::Trig;
 
@@ -17,7 +17,11 @@ impl MathOps {
         };
     }
 
-    pub fn push(&self, state: &mut ScriptState, code: &ScriptOpcode) -> Result<(), String> {
+    pub fn push(
+        &self,
+        state: &mut ScriptState,
+        code: &ScriptOpcode,
+    ) -> Result<(), ScriptErrorKind> {
         match code {
             ScriptOpcode::Add => self.add(state),
             ScriptOpcode::Sub => self.sub(state),
@@ -48,260 +52,284 @@ impl MathOps {
             ScriptOpcode::CosDeg => self.cos_deg(state),
             ScriptOpcode::Atan2Deg => self.atan2_deg(state),
             ScriptOpcode::Abs => self.abs(state),
-            _ => Err(format!("Unrecognised math ops code: {:?}", code)),
+            _ => Err(ScriptErrorKind::Unimplemented),
         }
     }
 
     #[inline(always)]
-    fn add(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_add(b));
+    fn add(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.wrapping_add(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn sub(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_sub(b));
+    fn sub(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.wrapping_sub(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn multiply(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_mul(b));
+    fn multiply(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.wrapping_mul(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn divide(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_div(b));
+    fn divide(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        if b == 0 {
+            return Err(ScriptErrorKind::Runtime("Division by zero".to_string()));
+        }
+        state.push_int(a.wrapping_div(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn random(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: f64 = state.pop_int() as f64;
-        state.push_int((random::<f64>() * a) as i32);
+    fn random(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: f64 = state.pop_int()? as f64;
+        state.push_int((random::<f64>() * a) as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn randominc(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: f64 = state.pop_int().wrapping_add(1) as f64;
-        state.push_int((random::<f64>() * a) as i32);
+    fn randominc(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: f64 = state.pop_int()?.wrapping_add(1) as f64;
+        state.push_int((random::<f64>() * a) as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn interpolate(&self, state: &mut ScriptState) -> Result<(), String> {
-        let x: i32 = state.pop_int();
-        let x1: i32 = state.pop_int();
-        let x0: i32 = state.pop_int();
-        let y1: i32 = state.pop_int();
-        let y0: i32 = state.pop_int();
+    fn interpolate(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let x: i32 = state.pop_int()?;
+        let x1: i32 = state.pop_int()?;
+        let x0: i32 = state.pop_int()?;
+        let y1: i32 = state.pop_int()?;
+        let y0: i32 = state.pop_int()?;
         let floor: f64 = (y1.wrapping_sub(y0) as f64 / x1.wrapping_sub(x0) as f64).floor();
-        state.push_int(((floor * x.wrapping_sub(x0) as f64) + y0 as f64) as i32);
+        state.push_int(((floor * x.wrapping_sub(x0) as f64) + y0 as f64) as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn addpercent(&self, state: &mut ScriptState) -> Result<(), String> {
-        let percent: i32 = state.pop_int();
-        let num: i32 = state.pop_int();
+    fn addpercent(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let percent: i32 = state.pop_int()?;
+        let num: i32 = state.pop_int()?;
         state.push_int(
             num.wrapping_mul(percent)
                 .wrapping_div(100)
                 .wrapping_add(num),
-        );
+        )?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn setbit(&self, state: &mut ScriptState) -> Result<(), String> {
-        let bit: i32 = state.pop_int();
-        let value: i32 = state.pop_int();
-        state.push_int(value | (1i32.wrapping_shl(bit as u32)));
+    fn setbit(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let bit: i32 = state.pop_int()?;
+        let value: i32 = state.pop_int()?;
+        state.push_int(value | (1i32.wrapping_shl(bit as u32)))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn clearbit(&self, state: &mut ScriptState) -> Result<(), String> {
-        let bit: i32 = state.pop_int();
-        let value: i32 = state.pop_int();
-        state.push_int(value & !1i32.wrapping_shl(bit as u32));
+    fn clearbit(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let bit: i32 = state.pop_int()?;
+        let value: i32 = state.pop_int()?;
+        state.push_int(value & !1i32.wrapping_shl(bit as u32))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn testbit(&self, state: &mut ScriptState) -> Result<(), String> {
-        let bit: i32 = state.pop_int();
-        let value: i32 = state.pop_int();
-        state.push_int(((value & (1i32.wrapping_shl(bit as u32))) != 0) as i32);
+    fn testbit(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let bit: i32 = state.pop_int()?;
+        let value: i32 = state.pop_int()?;
+        state.push_int(((value & (1i32.wrapping_shl(bit as u32))) != 0) as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn modulo(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_rem(b));
+    fn modulo(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        if b == 0 {
+            return Err(ScriptErrorKind::Runtime("Division by zero".to_string()));
+        }
+        state.push_int(a.wrapping_rem(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn pow(&self, state: &mut ScriptState) -> Result<(), String> {
-        let exponent: i32 = state.pop_int();
-        let base: i32 = state.pop_int();
-        state.push_int(base.wrapping_pow(exponent as u32));
+    fn pow(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let exponent: i32 = state.pop_int()?;
+        let base: i32 = state.pop_int()?;
+        state.push_int(base.wrapping_pow(exponent as u32))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn invpow(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
+    fn invpow(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
         if a == 0 || b == 0 {
-            state.push_int(0);
+            state.push_int(0)?;
         } else {
             match b {
-                1 => state.push_int(a),
-                2 => state.push_int((a as f64).sqrt() as i32),
-                3 => state.push_int((a as f64).cbrt() as i32),
-                4 => state.push_int((a as f64).sqrt().sqrt() as i32),
-                _ => state.push_int(a.pow((1.0 / b as f64) as u32)),
+                1 => state.push_int(a)?,
+                2 => state.push_int((a as f64).sqrt() as i32)?,
+                3 => state.push_int((a as f64).cbrt() as i32)?,
+                4 => state.push_int((a as f64).sqrt().sqrt() as i32)?,
+                _ => state.push_int(a.pow((1.0 / b as f64) as u32))?,
             }
         }
         return Ok(());
     }
 
     #[inline(always)]
-    fn and(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a & b);
+    fn and(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a & b)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn or(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a | b);
+    fn or(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a | b)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn min(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.min(b));
+    fn min(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.min(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn max(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.max(b));
+    fn max(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.max(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn scale(&self, state: &mut ScriptState) -> Result<(), String> {
-        let c: i32 = state.pop_int();
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(a.wrapping_mul(c).wrapping_div(b));
+    fn scale(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let c: i32 = state.pop_int()?;
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        if b == 0 {
+            return Err(ScriptErrorKind::Runtime("Division by zero".to_string()));
+        }
+        state.push_int(a.wrapping_mul(c).wrapping_div(b))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn bitcount(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: i32 = state.pop_int();
-        state.push_int(Bits::bitcount(a));
+    fn bitcount(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: i32 = state.pop_int()?;
+        state.push_int(Bits::bitcount(a))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn togglebit(&self, state: &mut ScriptState) -> Result<(), String> {
-        let bit: i32 = state.pop_int();
-        let value: i32 = state.pop_int();
-        state.push_int(value ^ (1i32.wrapping_shl(bit as u32)));
+    fn togglebit(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let bit: i32 = state.pop_int()?;
+        let value: i32 = state.pop_int()?;
+        state.push_int(value ^ (1i32.wrapping_shl(bit as u32)))?;
+        return Ok(());
+    }
+
+    /// Fails unless `start..=end` is a range of bits within an int.
+    fn check_bit_range(start: i32, end: i32) -> Result<(), ScriptErrorKind> {
+        if start < 0 || start > end || end > 31 {
+            return Err(ScriptErrorKind::Runtime(format!(
+                "Invalid bit range: {} to {}",
+                start, end
+            )));
+        }
         return Ok(());
     }
 
     #[inline(always)]
-    fn setbit_range(&self, state: &mut ScriptState) -> Result<(), String> {
-        let end: i32 = state.pop_int();
-        let start: i32 = state.pop_int();
-        let num: i32 = state.pop_int();
-        state.push_int(self.bits.setbit_range(num, start, end));
+    fn setbit_range(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let end: i32 = state.pop_int()?;
+        let start: i32 = state.pop_int()?;
+        Self::check_bit_range(start, end)?;
+        let num: i32 = state.pop_int()?;
+        state.push_int(self.bits.setbit_range(num, start, end))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn clearbit_range(&self, state: &mut ScriptState) -> Result<(), String> {
-        let end: i32 = state.pop_int();
-        let start: i32 = state.pop_int();
-        let num: i32 = state.pop_int();
-        state.push_int(self.bits.clearbit_range(num, start, end));
+    fn clearbit_range(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let end: i32 = state.pop_int()?;
+        let start: i32 = state.pop_int()?;
+        Self::check_bit_range(start, end)?;
+        let num: i32 = state.pop_int()?;
+        state.push_int(self.bits.clearbit_range(num, start, end))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn getbit_range(&self, state: &mut ScriptState) -> Result<(), String> {
-        let end: i32 = state.pop_int();
-        let start: i32 = state.pop_int();
-        let num: i32 = state.pop_int();
+    fn getbit_range(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let end: i32 = state.pop_int()?;
+        let start: i32 = state.pop_int()?;
+        Self::check_bit_range(start, end)?;
+        let num: i32 = state.pop_int()?;
         let r: i32 = 31i32.wrapping_sub(end);
         state.push_int(
             ((num.wrapping_shl(r as u32) as u32) >> (start.wrapping_add(r) as u32)) as i32,
-        );
+        )?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn setbit_range_toint(&self, state: &mut ScriptState) -> Result<(), String> {
-        let end: i32 = state.pop_int();
-        let start: i32 = state.pop_int();
-        let value: i32 = state.pop_int();
-        let num: i32 = state.pop_int();
-        state.push_int(self.bits.setbit_range_toint(num, value, start, end));
+    fn setbit_range_toint(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let end: i32 = state.pop_int()?;
+        let start: i32 = state.pop_int()?;
+        Self::check_bit_range(start, end)?;
+        let value: i32 = state.pop_int()?;
+        let num: i32 = state.pop_int()?;
+        state.push_int(self.bits.setbit_range_toint(num, value, start, end))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn sin_deg(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: i32 = state.pop_int();
-        state.push_int(self.trig.sin(a));
+    fn sin_deg(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: i32 = state.pop_int()?;
+        state.push_int(self.trig.sin(a))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn cos_deg(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: i32 = state.pop_int();
-        state.push_int(self.trig.cos(a));
+    fn cos_deg(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: i32 = state.pop_int()?;
+        state.push_int(self.trig.cos(a))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn atan2_deg(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: i32 = state.pop_int();
-        let a: i32 = state.pop_int();
-        state.push_int(Trig::atan2(b, a));
+    fn atan2_deg(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: i32 = state.pop_int()?;
+        let a: i32 = state.pop_int()?;
+        state.push_int(Trig::atan2(b, a))?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn abs(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: i32 = state.pop_int();
-        state.push_int(a.abs());
+    fn abs(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: i32 = state.pop_int()?;
+        state.push_int(a.wrapping_abs())?;
         return Ok(());
     }
 }
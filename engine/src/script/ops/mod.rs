@@ -3,3 +3,4 @@ pub mod math_ops;
 pub mod oc_ops;
 pub mod player_ops;
 pub mod string_ops;
+pub mod zone_ops;
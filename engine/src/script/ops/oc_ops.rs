@@ -1,4 +1,4 @@
-use cache::{ObjType, ScriptEngine, ScriptOpcode, ScriptState};
+use cache::{ObjType, ScriptEngine, ScriptErrorKind, ScriptOpcode, ScriptState};
 
 pub struct OcOps;
 
@@ -12,39 +12,43 @@ impl OcOps {
         engine: &impl ScriptEngine,
         state: &mut ScriptState,
         code: &ScriptOpcode,
-    ) -> Result<(), String> {
+    ) -> Result<(), ScriptErrorKind> {
         match code {
-            ScriptOpcode::OcCategory => panic!("Not implemented"),
-            ScriptOpcode::OcCert => panic!("Not implemented"),
-            ScriptOpcode::OcCost => panic!("Not implemented"),
-            ScriptOpcode::OcDebugname => panic!("Not implemented"),
-            ScriptOpcode::OcDesc => panic!("Not implemented"),
-            ScriptOpcode::OcIop => panic!("Not implemented"),
-            ScriptOpcode::OcMembers => panic!("Not implemented"),
+            ScriptOpcode::OcCategory => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcCert => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcCost => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcDebugname => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcDesc => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcIop => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcMembers => Err(ScriptErrorKind::Unimplemented),
             ScriptOpcode::OcName => self.oc_name(engine, state),
-            ScriptOpcode::OcOp => panic!("Not implemented"),
-            ScriptOpcode::OcParam => panic!("Not implemented"),
-            ScriptOpcode::OcStackable => panic!("Not implemented"),
-            ScriptOpcode::OcTradeable => panic!("Not implemented"),
-            ScriptOpcode::OcUncert => panic!("Not implemented"),
-            ScriptOpcode::OcWearPos2 => panic!("Not implemented"),
-            ScriptOpcode::OcWearPos3 => panic!("Not implemented"),
-            ScriptOpcode::OcWearPos => panic!("Not implemented"),
-            ScriptOpcode::OcWeight => panic!("Not implemented"),
-            _ => Err(format!("Unrecognised oc ops code: {:?}", code)),
+            ScriptOpcode::OcOp => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcParam => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcStackable => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcTradeable => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcUncert => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcWearPos2 => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcWearPos3 => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcWearPos => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::OcWeight => Err(ScriptErrorKind::Unimplemented),
+            _ => Err(ScriptErrorKind::Unimplemented),
         }
     }
 
     #[inline(always)]
-    fn oc_name(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), String> {
-        let obj: &ObjType = engine.pop_obj(state.pop_int())?;
+    fn oc_name(
+        &self,
+        engine: &impl ScriptEngine,
+        state: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        let obj: &ObjType = engine.pop_obj(state.pop_int()?)?;
         state.push_string(
             obj.name
                 .as_ref()
                 .or(obj.debugname.as_ref())
                 .unwrap_or(&String::new())
                 .clone(),
-        );
+        )?;
         return Ok(());
     }
 }
@@ -1,4 +1,4 @@
-use cache::{ScriptEngine, ScriptOpcode, ScriptState};
+use cache::{ScriptEngine, ScriptErrorKind, ScriptOpcode, ScriptState};
 
 pub struct PlayerOps;
 
@@ -12,146 +12,150 @@ impl PlayerOps {
         engine: &impl ScriptEngine,
         state: &mut ScriptState,
         code: &ScriptOpcode,
-    ) -> Result<(), String> {
+    ) -> Result<(), ScriptErrorKind> {
         match code {
-            ScriptOpcode::AllowDesign => Err("Not implemented".to_string()),
+            ScriptOpcode::AllowDesign => Err(ScriptErrorKind::Unimplemented),
             ScriptOpcode::Anim => state.protect(&ScriptState::ACTIVE_PLAYER, |state| {
                 self.anim(engine, state)
             }),
             ScriptOpcode::BasReadyAnim => self.bas_readyanim(engine, state),
-            ScriptOpcode::BasRunning => Err("Not implemented".to_string()),
-            ScriptOpcode::BasTurnOnSpot => Err("Not implemented".to_string()),
-            ScriptOpcode::BasWalkB => Err("Not implemented".to_string()),
-            ScriptOpcode::BasWalkF => Err("Not implemented".to_string()),
-            ScriptOpcode::BasWalkL => Err("Not implemented".to_string()),
-            ScriptOpcode::BasWalkR => Err("Not implemented".to_string()),
-            ScriptOpcode::BufferFull => Err("Not implemented".to_string()),
-            ScriptOpcode::BuildAppearance => Err("Not implemented".to_string()),
-            ScriptOpcode::Busy => Err("Not implemented".to_string()),
-            ScriptOpcode::CamLookAt => Err("Not implemented".to_string()),
-            ScriptOpcode::CamMoveTo => Err("Not implemented".to_string()),
-            ScriptOpcode::CamReset => Err("Not implemented".to_string()),
-            ScriptOpcode::CamShake => Err("Not implemented".to_string()),
-            ScriptOpcode::ClearQueue => Err("Not implemented".to_string()),
-            ScriptOpcode::ClearSoftTimer => Err("Not implemented".to_string()),
-            ScriptOpcode::ClearTimer => Err("Not implemented".to_string()),
-            ScriptOpcode::GetTimer => Err("Not implemented".to_string()),
-            ScriptOpcode::Coord => Err("Not implemented".to_string()),
-            ScriptOpcode::Damage => Err("Not implemented".to_string()),
-            ScriptOpcode::Displayname => Err("Not implemented".to_string()),
-            ScriptOpcode::FaceSquare => Err("Not implemented".to_string()),
+            ScriptOpcode::BasRunning => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BasTurnOnSpot => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BasWalkB => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BasWalkF => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BasWalkL => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BasWalkR => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BufferFull => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BuildAppearance => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Busy => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::CamLookAt => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::CamMoveTo => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::CamReset => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::CamShake => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::ClearQueue => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::ClearSoftTimer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::ClearTimer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::GetTimer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Coord => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Damage => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Displayname => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::FaceSquare => Err(ScriptErrorKind::Unimplemented),
             ScriptOpcode::FindUid => self.find_uid(engine, state),
-            ScriptOpcode::Gender => Err("Not implemented".to_string()),
-            ScriptOpcode::GetQueue => Err("Not implemented".to_string()),
-            ScriptOpcode::StatAdvance => Err("Not implemented".to_string()),
-            ScriptOpcode::HeadiconsGet => Err("Not implemented".to_string()),
-            ScriptOpcode::HeadiconsSet => Err("Not implemented".to_string()),
-            ScriptOpcode::HealEnergy => Err("Not implemented".to_string()),
-            ScriptOpcode::HintCoord => Err("Not implemented".to_string()),
-            ScriptOpcode::HintNpc => Err("Not implemented".to_string()),
-            ScriptOpcode::HintPlayer => Err("Not implemented".to_string()),
-            ScriptOpcode::HintStop => Err("Not implemented".to_string()),
-            ScriptOpcode::IfClose => Err("Not implemented".to_string()),
-            ScriptOpcode::TutClose => Err("Not implemented".to_string()),
-            ScriptOpcode::IfMultiZone => Err("Not implemented".to_string()),
-            ScriptOpcode::IfOpenChat => Err("Not implemented".to_string()),
-            ScriptOpcode::TutOpen => Err("Not implemented".to_string()),
-            ScriptOpcode::IfOpenMain => Err("Not implemented".to_string()),
-            ScriptOpcode::IfOpenMainSide => Err("Not implemented".to_string()),
-            ScriptOpcode::IfOpenSide => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetAnim => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetColour => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetHide => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetModel => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetRecol => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetNpcHead => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetObject => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetPlayerHead => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetPosition => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetResumeButtons => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetTab => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetTabActive => Err("Not implemented".to_string()),
-            ScriptOpcode::TutFlash => Err("Not implemented".to_string()),
-            ScriptOpcode::IfSetText => Err("Not implemented".to_string()),
-            ScriptOpcode::LastLoginInfo => Err("Not implemented".to_string()),
-            ScriptOpcode::LastCom => Err("Not implemented".to_string()),
-            ScriptOpcode::LastInt => Err("Not implemented".to_string()),
-            ScriptOpcode::LastItem => Err("Not implemented".to_string()),
-            ScriptOpcode::LastSlot => Err("Not implemented".to_string()),
-            ScriptOpcode::LastTargetSlot => Err("Not implemented".to_string()),
-            ScriptOpcode::LastUseItem => Err("Not implemented".to_string()),
-            ScriptOpcode::LastUseSlot => Err("Not implemented".to_string()),
-            ScriptOpcode::LongQueue => Err("Not implemented".to_string()),
+            ScriptOpcode::Gender => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::GetQueue => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StatAdvance => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HeadiconsGet => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HeadiconsSet => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HealEnergy => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HintCoord => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HintNpc => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HintPlayer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::HintStop => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfClose => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::TutClose => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfMultiZone => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfOpenChat => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::TutOpen => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfOpenMain => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfOpenMainSide => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfOpenSide => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetAnim => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetColour => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetHide => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetModel => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetRecol => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetNpcHead => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetObject => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetPlayerHead => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetPosition => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetResumeButtons => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetTab => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetTabActive => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::TutFlash => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfSetText => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastLoginInfo => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastCom => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastInt => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastItem => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastSlot => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastTargetSlot => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastUseItem => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastUseSlot => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LongQueue => Err(ScriptErrorKind::Unimplemented),
             ScriptOpcode::Mes => {
-                state.pop_string();
-                //println!("{}", state.pop_string());
+                state.pop_string()?;
+                //println!("{}", state.pop_string()?);
                 return Ok(());
             }
-            ScriptOpcode::MidiJingle => Err("Not implemented".to_string()),
-            ScriptOpcode::MidiSong => Err("Not implemented".to_string()),
-            ScriptOpcode::Name => Err("Not implemented".to_string()),
-            ScriptOpcode::PApRange => Err("Not implemented".to_string()),
-            ScriptOpcode::PArriveDelay => Err("Not implemented".to_string()),
-            ScriptOpcode::PCountDialog => Err("Not implemented".to_string()),
-            ScriptOpcode::PDelay => Err("Not implemented".to_string()),
-            ScriptOpcode::PExactMove => Err("Not implemented".to_string()),
-            ScriptOpcode::PFindUid => Err("Not implemented".to_string()),
-            ScriptOpcode::PLocMerge => Err("Not implemented".to_string()),
-            ScriptOpcode::PLogout => Err("Not implemented".to_string()),
-            ScriptOpcode::POpHeld => Err("Not implemented".to_string()),
-            ScriptOpcode::POpLoc => Err("Not implemented".to_string()),
-            ScriptOpcode::POpNpc => Err("Not implemented".to_string()),
-            ScriptOpcode::POpNpcT => Err("Not implemented".to_string()),
-            ScriptOpcode::POpObj => Err("Not implemented".to_string()),
-            ScriptOpcode::POpPlayer => Err("Not implemented".to_string()),
-            ScriptOpcode::POpPlayerT => Err("Not implemented".to_string()),
-            ScriptOpcode::PPauseButton => Err("Not implemented".to_string()),
-            ScriptOpcode::PStopAction => Err("Not implemented".to_string()),
-            ScriptOpcode::PTeleJump => Err("Not implemented".to_string()),
-            ScriptOpcode::PTeleport => Err("Not implemented".to_string()),
-            ScriptOpcode::PWalk => Err("Not implemented".to_string()),
-            ScriptOpcode::PlayerFindAllZone => Err("Not implemented".to_string()),
-            ScriptOpcode::PlayerFindNext => Err("Not implemented".to_string()),
-            ScriptOpcode::Queue => Err("Not implemented".to_string()),
-            ScriptOpcode::Say => Err("Not implemented".to_string()),
-            ScriptOpcode::WalkTrigger => Err("Not implemented".to_string()),
-            ScriptOpcode::SetTimer => Err("Not implemented".to_string()),
-            ScriptOpcode::SoftTimer => Err("Not implemented".to_string()),
-            ScriptOpcode::SoundSynth => Err("Not implemented".to_string()),
-            ScriptOpcode::SpotAnimPl => Err("Not implemented".to_string()),
-            ScriptOpcode::StaffModLevel => Err("Not implemented".to_string()),
-            ScriptOpcode::Stat => Err("Not implemented".to_string()),
-            ScriptOpcode::StatAdd => Err("Not implemented".to_string()),
-            ScriptOpcode::StatBase => Err("Not implemented".to_string()),
-            ScriptOpcode::StatHeal => Err("Not implemented".to_string()),
-            ScriptOpcode::StatSub => Err("Not implemented".to_string()),
-            ScriptOpcode::StrongQueue => Err("Not implemented".to_string()),
-            ScriptOpcode::Uid => Err("Not implemented".to_string()),
-            ScriptOpcode::WeakQueue => Err("Not implemented".to_string()),
-            ScriptOpcode::IfOpenMainOverlay => Err("Not implemented".to_string()),
-            ScriptOpcode::AfkEvent => Err("Not implemented".to_string()),
-            ScriptOpcode::LowMemory => Err("Not implemented".to_string()),
-            ScriptOpcode::SetIdkit => Err("Not implemented".to_string()),
-            ScriptOpcode::PClearPendingAction => Err("Not implemented".to_string()),
-            ScriptOpcode::GetWalkTrigger => Err("Not implemented".to_string()),
-            ScriptOpcode::Busy2 => Err("Not implemented".to_string()),
-            ScriptOpcode::FindHero => Err("Not implemented".to_string()),
-            ScriptOpcode::BothHeroPoints => Err("Not implemented".to_string()),
-            ScriptOpcode::SetGender => Err("Not implemented".to_string()),
-            ScriptOpcode::SetSkinColour => Err("Not implemented".to_string()),
-            ScriptOpcode::PAnimProtect => Err("Not implemented".to_string()),
-            ScriptOpcode::RunEnergy => Err("Not implemented".to_string()),
-            ScriptOpcode::Weight => Err("Not implemented".to_string()),
-            ScriptOpcode::LastCoord => Err("Not implemented".to_string()),
-            _ => Err(format!("Unrecognised player ops code: {:?}", code)),
+            ScriptOpcode::MidiJingle => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::MidiSong => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Name => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PApRange => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PArriveDelay => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PCountDialog => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PDelay => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PExactMove => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PFindUid => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PLocMerge => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PLogout => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpHeld => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpLoc => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpNpc => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpNpcT => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpObj => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpPlayer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::POpPlayerT => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PPauseButton => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PStopAction => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PTeleJump => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PTeleport => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PWalk => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PlayerFindAllZone => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PlayerFindNext => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Queue => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Say => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::WalkTrigger => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SetTimer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SoftTimer => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SoundSynth => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SpotAnimPl => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StaffModLevel => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Stat => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StatAdd => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StatBase => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StatHeal => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StatSub => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::StrongQueue => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Uid => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::WeakQueue => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::IfOpenMainOverlay => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::AfkEvent => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LowMemory => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SetIdkit => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PClearPendingAction => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::GetWalkTrigger => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Busy2 => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::FindHero => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::BothHeroPoints => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SetGender => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::SetSkinColour => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::PAnimProtect => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::RunEnergy => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::Weight => Err(ScriptErrorKind::Unimplemented),
+            ScriptOpcode::LastCoord => Err(ScriptErrorKind::Unimplemented),
+            _ => Err(ScriptErrorKind::Unimplemented),
         }
     }
 
     // https://x.com/JagexAsh/status/1806246992797921391
     #[inline(always)]
-    fn anim(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), String> {
-        let delay: i32 = state.pop_int();
-        let seq: i32 = state.pop_int();
+    fn anim(
+        &self,
+        engine: &impl ScriptEngine,
+        state: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        let delay: i32 = state.pop_int()?;
+        let seq: i32 = state.pop_int()?;
         return engine.with_player_mut(state.get_active_player(), |mut player| {
             player.play_animation(seq, delay);
         });
@@ -159,8 +163,8 @@ impl PlayerOps {
 
     #[rustfmt::skip]
     #[inline(always)]
-    fn bas_readyanim(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), String> {
-        let seq: i32 = state.pop_int();
+    fn bas_readyanim(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let seq: i32 = state.pop_int()?;
         return engine.with_player_mut(state.get_active_player(), |mut player| {
             player.set_bas_readyanim(seq);
         });
@@ -168,15 +172,13 @@ impl PlayerOps {
 
     #[rustfmt::skip]
     #[inline(always)]
-    fn find_uid(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), String> {
-        let uid: i32 = state.pop_int();
-        return engine.with_player(uid, |player| {
-            state.set_active_player(uid);
-            state.pointer_add(ScriptState::ACTIVE_PLAYER[state.int_operand() as usize]);
-            state.push_int(1);
-        }).or_else(|_| {
-            state.push_int(0);
-            Ok(())
-        });
+    fn find_uid(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let uid: i32 = state.pop_int()?;
+        if engine.with_player(uid, |_| {}).is_err() {
+            return state.push_int(0);
+        }
+        state.set_active_player(uid);
+        state.pointer_add(ScriptState::ACTIVE_PLAYER[state.int_operand() as usize]);
+        return state.push_int(1);
     }
 }
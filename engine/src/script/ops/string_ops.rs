@@ -1,4 +1,4 @@
-use cache::{ScriptEngine, ScriptOpcode, ScriptState};
+use cache::{ScriptEngine, ScriptErrorKind, ScriptOpcode, ScriptState};
 
 pub struct StringOps;
 
@@ -12,7 +12,7 @@ impl StringOps {
         engine: &impl ScriptEngine,
         state: &mut ScriptState,
         code: &ScriptOpcode,
-    ) -> Result<(), String> {
+    ) -> Result<(), ScriptErrorKind> {
         match code {
             ScriptOpcode::AppendNum => self.append_num(state),
             ScriptOpcode::Append => self.append(state),
@@ -27,70 +27,68 @@ impl StringOps {
             ScriptOpcode::SubString => self.substring(state),
             ScriptOpcode::StringIndexOfChar => self.string_indexof_char(state),
             ScriptOpcode::StringIndexOfString => self.string_indexof_string(state),
-            _ => Err(format!("Unrecognised string ops code: {:?}", code)),
+            _ => Err(ScriptErrorKind::Unimplemented),
         }
     }
 
     #[inline(always)]
-    fn append_num(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: i32 = state.pop_int();
-        state.push_string(b + &a.to_string());
+    fn append_num(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: i32 = state.pop_int()?;
+        state.push_string(b + &a.to_string())?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn append(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: String = state.pop_string();
-        state.push_string(a + &b);
+    fn append(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: String = state.pop_string()?;
+        state.push_string(a + &b)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn append_signnum(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: i32 = state.pop_int();
+    fn append_signnum(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: i32 = state.pop_int()?;
         if a >= 0 {
-            state.push_string(b + "+" + &a.to_string());
+            state.push_string(b + "+" + &a.to_string())?;
         } else {
-            state.push_string(b + &a.to_string());
+            state.push_string(b + &a.to_string())?;
         }
         return Ok(());
     }
 
     #[inline(always)]
-    fn lowercase(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: String = state.pop_string().to_ascii_lowercase();
-        state.push_string(a);
+    fn lowercase(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: String = state.pop_string()?.to_ascii_lowercase();
+        state.push_string(a)?;
         return Ok(());
     }
 
     #[rustfmt::skip]
     #[inline(always)]
-    fn text_gender(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), String> {
-        let female: String = state.pop_string();
-        let male: String = state.pop_string();
-        return engine.with_player(state.get_active_player(), |player| {
-            if player.get_gender() == 0 {
-                state.push_string(male);
-            } else {
-                state.push_string(female);
-            }
-        });
+    fn text_gender(&self, engine: &impl ScriptEngine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let female: String = state.pop_string()?;
+        let male: String = state.pop_string()?;
+        let mut gender: u8 = 0;
+        engine.with_player(state.get_active_player(), |player| {
+            gender = player.get_gender();
+        })?;
+        return state.push_string(if gender == 0 { male } else { female });
     }
 
     #[inline(always)]
-    fn to_string(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: i32 = state.pop_int();
-        state.push_string(a.to_string());
+    fn to_string(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: i32 = state.pop_int()?;
+        state.push_string(a.to_string())?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn compare(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: String = state.pop_string();
+    fn compare(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: String = state.pop_string()?;
         let len1: usize = a.len();
         let len2: usize = b.len();
         let limit: usize = len1.min(len2);
@@ -101,80 +99,93 @@ impl StringOps {
             let code1: i32 = c1 as i32;
             let code2: i32 = c2 as i32;
             if code1 != code2 {
-                state.push_int(code1 - code2);
+                state.push_int(code1 - code2)?;
                 return Ok(());
             }
         }
-        state.push_int((len1 - len2) as i32);
+        state.push_int((len1 - len2) as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn text_switch(&self, state: &mut ScriptState) -> Result<(), String> {
-        let c: i32 = state.pop_int();
-        let b: String = state.pop_string();
-        let a: String = state.pop_string();
+    fn text_switch(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let c: i32 = state.pop_int()?;
+        let b: String = state.pop_string()?;
+        let a: String = state.pop_string()?;
         if c == 1 {
-            state.push_string(a);
+            state.push_string(a)?;
         } else {
-            state.push_string(b);
+            state.push_string(b)?;
         }
         return Ok(());
     }
 
     #[inline(always)]
-    fn append_char(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: i32 = state.pop_int();
+    fn append_char(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: i32 = state.pop_int()?;
         if a == -1 {
-            return Err("null char".to_string());
+            return Err(ScriptErrorKind::Runtime("null char".to_string()));
         }
         if let Some(char) = std::char::from_u32((a & 0xffff) as u32) {
-            state.push_string(b + &char.to_string());
+            state.push_string(b + &char.to_string())?;
             return Ok(());
         }
-        return Err("bad char".to_string());
+        return Err(ScriptErrorKind::Runtime("bad char".to_string()));
     }
 
     #[inline(always)]
-    fn string_length(&self, state: &mut ScriptState) -> Result<(), String> {
-        let a: String = state.pop_string();
-        state.push_int(a.len() as i32);
+    fn string_length(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let a: String = state.pop_string()?;
+        state.push_int(a.len() as i32)?;
         return Ok(());
     }
 
     #[inline(always)]
-    fn substring(&self, state: &mut ScriptState) -> Result<(), String> {
-        let string: String = state.pop_string();
-        let end: usize = state.pop_int() as usize;
-        let start: usize = state.pop_int() as usize;
-        state.push_string(string[start..end].to_string());
-        return Ok(());
+    fn substring(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let string: String = state.pop_string()?;
+        let end: i32 = state.pop_int()?;
+        let start: i32 = state.pop_int()?;
+        if start < 0 || start > end || end as usize > string.len() {
+            return Err(ScriptErrorKind::Runtime(format!(
+                "Invalid substring range: {}..{} of {}",
+                start,
+                end,
+                string.len()
+            )));
+        }
+        return match string.get(start as usize..end as usize) {
+            Some(substring) => state.push_string(substring.to_string()),
+            None => Err(ScriptErrorKind::Runtime(format!(
+                "Substring range {}..{} is not on a char boundary",
+                start, end
+            ))),
+        };
     }
 
     #[inline(always)]
-    fn string_indexof_char(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: i32 = state.pop_int();
+    fn string_indexof_char(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: i32 = state.pop_int()?;
         if a == -1 {
-            return Err("null char".to_string());
+            return Err(ScriptErrorKind::Runtime("null char".to_string()));
         }
         if let Some(char) = std::char::from_u32((a & 0xffff) as u32) {
             state.push_int(
                 b.chars()
                     .position(|c| c == char)
                     .map_or(-1, |index| index as i32), // return -1 if not found.
-            );
+            )?;
             return Ok(());
         }
-        return Err("bad char".to_string());
+        return Err(ScriptErrorKind::Runtime("bad char".to_string()));
     }
 
     #[inline(always)]
-    fn string_indexof_string(&self, state: &mut ScriptState) -> Result<(), String> {
-        let b: String = state.pop_string();
-        let a: String = state.pop_string();
-        state.push_int(b.find(&a).map_or(-1, |index| index as i32)); // return -1 if not found.
+    fn string_indexof_string(&self, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let b: String = state.pop_string()?;
+        let a: String = state.pop_string()?;
+        state.push_int(b.find(&a).map_or(-1, |index| index as i32))?; // return -1 if not found.
         return Ok(());
     }
 }
@@ -0,0 +1,180 @@
+use cache::{ScriptEngine, ScriptErrorKind, ScriptLoc, ScriptOpcode, ScriptState};
+
+use crate::engine::Engine;
+use crate::zone::coord_grid::CoordGrid;
+use crate::zone::zone::LocRevert;
+use crate::zone::zone_event::ProjAnim;
+
+/// The ops that change what players see in a zone, they are sent to every
+/// observer of the zone at the end of the tick.
+pub struct ZoneOps;
+
+impl ZoneOps {
+    pub fn new() -> ZoneOps {
+        return ZoneOps;
+    }
+
+    pub fn push(
+        &self,
+        engine: &Engine,
+        state: &mut ScriptState,
+        code: &ScriptOpcode,
+    ) -> Result<(), ScriptErrorKind> {
+        match code {
+            ScriptOpcode::SpotAnimMap => self.spotanim_map(engine, state),
+            ScriptOpcode::ProjAnimMap => self.projanim_map(engine, state),
+            ScriptOpcode::LocAdd => self.loc_add(engine, state),
+            ScriptOpcode::LocChange => state.protect(&ScriptState::ACTIVE_LOC, |state| {
+                self.loc_change(engine, state)
+            }),
+            ScriptOpcode::LocDel => state.protect(&ScriptState::ACTIVE_LOC, |state| {
+                self.loc_del(engine, state)
+            }),
+            ScriptOpcode::ObjAdd => self.obj_add(engine, state),
+            _ => Err(ScriptErrorKind::Unimplemented),
+        }
+    }
+
+    // spotanim_map(spotanim, coord, height, delay)
+    #[inline(always)]
+    fn spotanim_map(
+        &self,
+        engine: &Engine,
+        state: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        let delay: i32 = state.pop_int()?;
+        let height: i32 = state.pop_int()?;
+        let coord: i32 = state.pop_int()?;
+        let spotanim: i32 = state.pop_int()?;
+        engine
+            .zones
+            .borrow_mut()
+            .anim_map(CoordGrid::from(coord), spotanim, height, delay);
+        return Ok(());
+    }
+
+    // projanim_map(from, to, spotanim, src_height, dst_height, delay, duration, peak, arc)
+    #[inline(always)]
+    fn projanim_map(
+        &self,
+        engine: &Engine,
+        state: &mut ScriptState,
+    ) -> Result<(), ScriptErrorKind> {
+        let arc: i32 = state.pop_int()?;
+        let peak: i32 = state.pop_int()?;
+        let duration: i32 = state.pop_int()?;
+        let delay: i32 = state.pop_int()?;
+        let dst_height: i32 = state.pop_int()?;
+        let src_height: i32 = state.pop_int()?;
+        let spotanim: i32 = state.pop_int()?;
+        let to: i32 = state.pop_int()?;
+        let from: i32 = state.pop_int()?;
+        engine.zones.borrow_mut().proj_anim(ProjAnim {
+            from: CoordGrid::from(from),
+            to: CoordGrid::from(to),
+            target: 0,
+            spotanim,
+            src_height,
+            dst_height,
+            start_delay: delay,
+            end_delay: delay + duration,
+            peak,
+            arc,
+        });
+        return Ok(());
+    }
+
+    // loc_add(coord, loc, angle, shape, duration)
+    #[inline(always)]
+    fn loc_add(&self, engine: &Engine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let duration: i32 = state.pop_int()?;
+        let shape: i32 = state.pop_int()?;
+        let angle: i32 = state.pop_int()?;
+        let loc: i32 = state.pop_int()?;
+        let coord: i32 = state.pop_int()?;
+        let added: ScriptLoc = ScriptLoc {
+            coord,
+            loc,
+            shape,
+            angle,
+        };
+        // zones don't know the static map, so an add where the zone has no loc yet
+        // is removed once its duration is up.
+        let revert: LocRevert = LocRevert {
+            tick: engine.tick.current_tick + duration,
+            loc: -1,
+            angle,
+        };
+        engine
+            .zones
+            .borrow_mut()
+            .change_loc(CoordGrid::from(coord), loc, shape, angle, revert);
+        state.set_active_loc(added);
+        state.pointer_add(ScriptState::ACTIVE_LOC[state.int_operand() as usize]);
+        return Ok(());
+    }
+
+    // loc_change(loc, duration), on the active loc
+    #[inline(always)]
+    fn loc_change(&self, engine: &Engine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let duration: i32 = state.pop_int()?;
+        let loc: i32 = state.pop_int()?;
+        let active: ScriptLoc = state.get_active_loc()?;
+        engine.zones.borrow_mut().change_loc(
+            CoordGrid::from(active.coord),
+            loc,
+            active.shape,
+            active.angle,
+            Self::revert_to(engine, &active, duration),
+        );
+        state.set_active_loc(ScriptLoc { loc, ..active });
+        return Ok(());
+    }
+
+    // loc_del(duration), on the active loc
+    #[inline(always)]
+    fn loc_del(&self, engine: &Engine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let duration: i32 = state.pop_int()?;
+        let active: ScriptLoc = state.get_active_loc()?;
+        engine.zones.borrow_mut().del_loc(
+            CoordGrid::from(active.coord),
+            active.shape,
+            active.angle,
+            Self::revert_to(engine, &active, duration),
+        );
+        return Ok(());
+    }
+
+    /// Reverts to the active loc once `duration` ticks are up.
+    fn revert_to(engine: &Engine, active: &ScriptLoc, duration: i32) -> LocRevert {
+        return LocRevert {
+            tick: engine.tick.current_tick + duration,
+            loc: active.loc,
+            angle: active.angle,
+        };
+    }
+
+    // obj_add(coord, obj, count, duration)
+    #[inline(always)]
+    fn obj_add(&self, engine: &Engine, state: &mut ScriptState) -> Result<(), ScriptErrorKind> {
+        let duration: i32 = state.pop_int()?;
+        let count: i32 = state.pop_int()?;
+        let obj: i32 = state.pop_int()?;
+        let coord: i32 = state.pop_int()?;
+        if count <= 0 {
+            return Err(ScriptErrorKind::Runtime(format!(
+                "obj_add count out of range: {}",
+                count
+            )));
+        }
+        engine.pop_obj(obj)?;
+        engine.add_obj(coord, obj, count, duration);
+        return Ok(());
+    }
+}
+
+impl Default for ZoneOps {
+    fn default() -> ZoneOps {
+        return ZoneOps::new();
+    }
+}
@@ -3,6 +3,7 @@ use crate::script::ops::math_ops::MathOps;
 use crate::script::ops::oc_ops::OcOps;
 use crate::script::ops::player_ops::PlayerOps;
 use crate::script::ops::string_ops::StringOps;
+use crate::script::ops::zone_ops::ZoneOps;
 
 pub struct Ops {
     pub core: CoreOps,
@@ -10,6 +11,7 @@ pub struct Ops {
     pub oc: OcOps,
     pub player: PlayerOps,
     pub string: StringOps,
+    pub zone: ZoneOps,
 }
 
 impl Ops {
@@ -20,6 +22,7 @@ impl Ops {
             oc: OcOps::new(),
             player: PlayerOps::new(),
             string: StringOps::new(),
+            zone: ZoneOps::new(),
         };
     }
 }
@@ -0,0 +1,230 @@
+use std::collections::{HashMap, HashSet};
+
+use io::{Packet, PacketPool};
+
+use crate::network::server_prot::ServerProt;
+use crate::zone::coord_grid::CoordGrid;
+use crate::zone::zone::Zone;
+use crate::zone::zone_event::ZoneEvent;
+use crate::zone::zone_map::ZoneMap;
+
+/// The 13x13 zone area the client has loaded around a player.
+#[derive(Clone)]
+pub struct BuildArea {
+    pub origin: Option<CoordGrid>, // where the area was last rebuilt, none until login
+    pub active_zones: HashSet<i32>,
+    pub loaded_zones: HashSet<i32>,
+}
+
+impl BuildArea {
+    // zones loaded either side of the origin zone.
+    const BUILD_RADIUS: i32 = 6;
+    // zones either side of the player that receive updates.
+    const VIEW_RADIUS: i32 = 3;
+
+    pub fn new() -> BuildArea {
+        return BuildArea {
+            origin: None,
+            active_zones: HashSet::new(),
+            loaded_zones: HashSet::new(),
+        };
+    }
+
+    /// Whether the client needs a new area built around `coord`.
+    ///
+    /// The client can only render 104x104 tiles around the origin, so a new area
+    /// is needed when the player walks within 16 tiles of the edge, or on login.
+    pub fn needs_rebuild(&self, coord: CoordGrid) -> bool {
+        return match self.origin {
+            None => true,
+            Some(origin) => {
+                let left_x: i32 = (origin.zone_x() - 4) << 3;
+                let right_x: i32 = (origin.zone_x() + 5) << 3;
+                let bottom_z: i32 = (origin.zone_z() - 4) << 3;
+                let top_z: i32 = (origin.zone_z() + 5) << 3;
+                coord.x() < left_x
+                    || coord.z() < bottom_z
+                    || coord.x() > right_x - 1
+                    || coord.z() > top_z - 1
+            }
+        };
+    }
+
+    /// Moves the origin to `coord` and forgets every zone the client had loaded,
+    /// then writes the rebuild packet for the new area.
+    pub fn rebuild(
+        &mut self,
+        coord: CoordGrid,
+        map_crcs: &HashMap<String, i32>,
+        pool: &mut PacketPool,
+    ) -> Packet {
+        self.origin = Some(coord);
+        self.active_zones.clear();
+        self.loaded_zones.clear();
+        return BuildArea::rebuild_normal(coord, map_crcs, pool);
+    }
+
+    /// Every mapsquare the client needs to build the area centred on `coord`.
+    pub fn mapsquares(coord: CoordGrid) -> Vec<(i32, i32)> {
+        let min_x: i32 = (coord.zone_x() - BuildArea::BUILD_RADIUS) >> 3;
+        let max_x: i32 = (coord.zone_x() + BuildArea::BUILD_RADIUS) >> 3;
+        let min_z: i32 = (coord.zone_z() - BuildArea::BUILD_RADIUS) >> 3;
+        let max_z: i32 = (coord.zone_z() + BuildArea::BUILD_RADIUS) >> 3;
+
+        let mut mapsquares: Vec<(i32, i32)> = Vec::new();
+        for x in min_x..=max_x {
+            for z in min_z..=max_z {
+                mapsquares.push((x, z));
+            }
+        }
+        return mapsquares;
+    }
+
+    /// Writes `rebuild_normal`, the centre zone followed by the land and loc
+    /// crcs for every mapsquare in the area. Unknown maps are sent with a crc of 0.
+    pub fn rebuild_normal(
+        coord: CoordGrid,
+        map_crcs: &HashMap<String, i32>,
+        pool: &mut PacketPool,
+    ) -> Packet {
+        let mapsquares: Vec<(i32, i32)> = BuildArea::mapsquares(coord);
+        let size: usize = 4 + mapsquares.len() * 10;
+
+        let mut buf: Packet = pool.take(1 + 2 + size);
+        buf.p1(ServerProt::RebuildNormal as i32);
+        buf.pos += 2;
+        buf.p2(coord.zone_x());
+        buf.p2(coord.zone_z());
+        for (x, z) in mapsquares {
+            buf.p1(x);
+            buf.p1(z);
+            buf.p4(*map_crcs.get(&format!("m{}_{}", x, z)).unwrap_or(&0));
+            buf.p4(*map_crcs.get(&format!("l{}_{}", x, z)).unwrap_or(&0));
+        }
+        buf.psize2(size as u16);
+        return buf;
+    }
+
+    /// Recalculates which zones around `coord` the player can see.
+    /// Zones that left view are unloaded so re-entering them sends a full update.
+    pub fn update_active_zones(&mut self, coord: CoordGrid) {
+        self.active_zones.clear();
+        let origin: CoordGrid = match self.origin {
+            Some(origin) => origin,
+            None => return,
+        };
+
+        let left_x: i32 = origin.zone_x() - BuildArea::BUILD_RADIUS;
+        let right_x: i32 = origin.zone_x() + BuildArea::BUILD_RADIUS;
+        let bottom_z: i32 = origin.zone_z() - BuildArea::BUILD_RADIUS;
+        let top_z: i32 = origin.zone_z() + BuildArea::BUILD_RADIUS;
+
+        for x in
+            (coord.zone_x() - BuildArea::VIEW_RADIUS)..=(coord.zone_x() + BuildArea::VIEW_RADIUS)
+        {
+            for z in (coord.zone_z() - BuildArea::VIEW_RADIUS)
+                ..=(coord.zone_z() + BuildArea::VIEW_RADIUS)
+            {
+                if x < left_x || x > right_x || z < bottom_z || z > top_z {
+                    continue;
+                }
+                self.active_zones.insert(ZoneMap::zone_index(CoordGrid::new(
+                    x << 3,
+                    z << 3,
+                    coord.level(),
+                )));
+            }
+        }
+
+        let active: &HashSet<i32> = &self.active_zones;
+        self.loaded_zones.retain(|index| active.contains(index));
+    }
+
+    /// Writes the updates for every active zone. Newly seen zones get a full
+    /// snapshot, zones that were already loaded get this tick's shared buffer.
+    /// The packets are checked out of `pool` and go back to it once sent.
+    pub fn write_zones(&mut self, zones: &ZoneMap, pool: &mut PacketPool) -> Vec<Packet> {
+        let mut packets: Vec<Packet> = Vec::new();
+        let origin: CoordGrid = match self.origin {
+            Some(origin) => origin,
+            None => return packets,
+        };
+
+        let mut active: Vec<i32> = self.active_zones.iter().copied().collect();
+        active.sort_unstable();
+
+        for index in active {
+            let zone: Option<&Zone> = zones.get(index);
+            let coord: CoordGrid = ZoneMap::unpack_index(index);
+
+            if self.loaded_zones.insert(index) {
+                packets.push(BuildArea::zone_header(
+                    ServerProt::UpdateZoneFullFollows,
+                    origin,
+                    coord,
+                    pool,
+                ));
+                if let Some(zone) = zone {
+                    for event in zone.full_events() {
+                        packets.push(BuildArea::zone_event(&event, pool));
+                    }
+                }
+            } else if let Some(shared) = zone.and_then(|zone| zone.shared.as_ref()) {
+                packets.push(BuildArea::zone_enclosed(origin, coord, shared, pool));
+            }
+        }
+        return packets;
+    }
+
+    /// The zone packets address the south west tile of a zone relative to the build area.
+    fn local_zone(origin: CoordGrid, coord: CoordGrid) -> (i32, i32) {
+        return (
+            (coord.zone_x() - (origin.zone_x() - BuildArea::BUILD_RADIUS)) << 3,
+            (coord.zone_z() - (origin.zone_z() - BuildArea::BUILD_RADIUS)) << 3,
+        );
+    }
+
+    fn zone_header(
+        prot: ServerProt,
+        origin: CoordGrid,
+        coord: CoordGrid,
+        pool: &mut PacketPool,
+    ) -> Packet {
+        let (x, z) = BuildArea::local_zone(origin, coord);
+        let mut buf: Packet = pool.take(3);
+        buf.p1(prot as i32);
+        buf.p1(x);
+        buf.p1(z);
+        return buf;
+    }
+
+    fn zone_event(event: &ZoneEvent, pool: &mut PacketPool) -> Packet {
+        let mut buf: Packet = pool.take(event.size());
+        event.encode(&mut buf);
+        return buf;
+    }
+
+    fn zone_enclosed(
+        origin: CoordGrid,
+        coord: CoordGrid,
+        shared: &Packet,
+        pool: &mut PacketPool,
+    ) -> Packet {
+        let (x, z) = BuildArea::local_zone(origin, coord);
+        let size: usize = 2 + shared.len();
+        let mut buf: Packet = pool.take(1 + 2 + size);
+        buf.p1(ServerProt::UpdateZonePartialEnclosed as i32);
+        buf.pos += 2;
+        buf.p1(x);
+        buf.p1(z);
+        buf.pdata(&shared.data, 0, shared.len());
+        buf.psize2(size as u16);
+        return buf;
+    }
+}
+
+impl Default for BuildArea {
+    fn default() -> BuildArea {
+        return BuildArea::new();
+    }
+}
@@ -0,0 +1,66 @@
+/// A packed world coordinate, matching the RuneScript `coord` type.
+///
+/// The level is stored in bits 28-29, the x coordinate in bits 14-27 and
+/// the z coordinate in bits 0-13.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CoordGrid {
+    pub packed: i32,
+}
+
+impl CoordGrid {
+    pub fn new(x: i32, z: i32, level: i32) -> CoordGrid {
+        return CoordGrid {
+            packed: ((level & 0x3) << 28) | ((x & 0x3fff) << 14) | (z & 0x3fff),
+        };
+    }
+
+    pub fn from(packed: i32) -> CoordGrid {
+        return CoordGrid { packed };
+    }
+
+    #[inline(always)]
+    pub fn x(&self) -> i32 {
+        return (self.packed >> 14) & 0x3fff;
+    }
+
+    #[inline(always)]
+    pub fn z(&self) -> i32 {
+        return self.packed & 0x3fff;
+    }
+
+    #[inline(always)]
+    pub fn level(&self) -> i32 {
+        return (self.packed >> 28) & 0x3;
+    }
+
+    /// The x coordinate of the 8x8 zone this coord is in.
+    #[inline(always)]
+    pub fn zone_x(&self) -> i32 {
+        return self.x() >> 3;
+    }
+
+    /// The z coordinate of the 8x8 zone this coord is in.
+    #[inline(always)]
+    pub fn zone_z(&self) -> i32 {
+        return self.z() >> 3;
+    }
+
+    /// The x coordinate of the 64x64 mapsquare this coord is in.
+    #[inline(always)]
+    pub fn mapsquare_x(&self) -> i32 {
+        return self.x() >> 6;
+    }
+
+    /// The z coordinate of the 64x64 mapsquare this coord is in.
+    #[inline(always)]
+    pub fn mapsquare_z(&self) -> i32 {
+        return self.z() >> 6;
+    }
+
+    /// The position of this coord inside of its zone, packed the way
+    /// the zone protocol expects it: `(x & 7) << 4 | (z & 7)`.
+    #[inline(always)]
+    pub fn packed_zone_coord(&self) -> i32 {
+        return ((self.x() & 0x7) << 4) | (self.z() & 0x7);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod build_area;
+pub mod coord_grid;
+pub mod zone;
+pub mod zone_event;
+pub mod zone_map;
@@ -0,0 +1,283 @@
+use io::Packet;
+
+use crate::zone::coord_grid::CoordGrid;
+use crate::zone::zone_event::{ProjAnim, ZoneEvent};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZoneObj {
+    pub coord: CoordGrid,
+    pub obj: i32,
+    pub count: i32,
+    pub despawn: i32, // tick to despawn on, -1 for never
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZoneLoc {
+    pub coord: CoordGrid,
+    pub loc: i32, // -1 if the loc was deleted
+    pub shape: i32,
+    pub angle: i32,
+    pub revert: Option<LocRevert>,
+}
+
+/// What a loc goes back to once a timed add, change or delete is up.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LocRevert {
+    pub tick: i32, // tick to revert on
+    pub loc: i32,  // -1 if there was no loc
+    pub angle: i32,
+}
+
+/// An 8x8 area of the map on a single level.
+///
+/// Zones keep track of the dynamic objs and locs inside of them so a
+/// full snapshot can be sent when a player first sees the zone, and
+/// collect the events that happened this tick so they can be encoded
+/// once into a shared buffer for every observer.
+#[derive(Clone)]
+pub struct Zone {
+    pub index: i32,
+    pub objs: Vec<ZoneObj>,
+    pub locs: Vec<ZoneLoc>,
+    pub events: Vec<ZoneEvent>,
+    pub shared: Option<Packet>,
+}
+
+impl Zone {
+    pub fn new(index: i32) -> Zone {
+        return Zone {
+            index,
+            objs: Vec::new(),
+            locs: Vec::new(),
+            events: Vec::new(),
+            shared: None,
+        };
+    }
+
+    pub fn add_obj(&mut self, coord: CoordGrid, obj: i32, count: i32, despawn: i32) {
+        self.objs.push(ZoneObj {
+            coord,
+            obj,
+            count,
+            despawn,
+        });
+        self.events.push(ZoneEvent::ObjAdd { coord, obj, count });
+    }
+
+    pub fn del_obj(&mut self, coord: CoordGrid, obj: i32) -> bool {
+        return match self
+            .objs
+            .iter()
+            .position(|zone_obj| zone_obj.coord == coord && zone_obj.obj == obj)
+        {
+            Some(index) => {
+                self.objs.remove(index);
+                self.events.push(ZoneEvent::ObjDel { coord, obj });
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn change_obj_count(&mut self, coord: CoordGrid, obj: i32, count: i32) -> bool {
+        return match self
+            .objs
+            .iter_mut()
+            .find(|zone_obj| zone_obj.coord == coord && zone_obj.obj == obj)
+        {
+            Some(zone_obj) => {
+                let old_count: i32 = zone_obj.count;
+                zone_obj.count = count;
+                self.events.push(ZoneEvent::ObjCount {
+                    coord,
+                    obj,
+                    old_count,
+                    new_count: count,
+                });
+                true
+            }
+            None => false,
+        };
+    }
+
+    /// Shows `loc` until `revert.tick`. `revert` holds the loc that was there
+    /// before, unless the zone already has an earlier change waiting to revert.
+    pub fn change_loc(
+        &mut self,
+        coord: CoordGrid,
+        loc: i32,
+        shape: i32,
+        angle: i32,
+        revert: LocRevert,
+    ) {
+        self.set_loc(coord, loc, shape, angle, Some(revert));
+        self.events.push(ZoneEvent::LocAddChange {
+            coord,
+            loc,
+            shape,
+            angle,
+        });
+    }
+
+    /// Deletes the loc until `revert.tick`, like `change_loc`.
+    pub fn del_loc(&mut self, coord: CoordGrid, shape: i32, angle: i32, revert: LocRevert) {
+        self.set_loc(coord, -1, shape, angle, Some(revert));
+        self.events.push(ZoneEvent::LocDel {
+            coord,
+            shape,
+            angle,
+        });
+    }
+
+    pub fn anim_map(&mut self, coord: CoordGrid, spotanim: i32, height: i32, delay: i32) {
+        self.events.push(ZoneEvent::MapAnim {
+            coord,
+            spotanim,
+            height,
+            delay,
+        });
+    }
+
+    pub fn proj_anim(&mut self, proj: ProjAnim) {
+        self.events.push(ZoneEvent::MapProjAnim(proj));
+    }
+
+    /// Despawns any objs that have reached their despawn tick and reverts
+    /// any locs that have reached their revert tick.
+    pub fn process(&mut self, tick: i32) {
+        let mut index: usize = 0;
+        while index < self.objs.len() {
+            let zone_obj: &ZoneObj = &self.objs[index];
+            if zone_obj.despawn != -1 && zone_obj.despawn <= tick {
+                let coord: CoordGrid = zone_obj.coord;
+                let obj: i32 = zone_obj.obj;
+                self.objs.remove(index);
+                self.events.push(ZoneEvent::ObjDel { coord, obj });
+            } else {
+                index += 1;
+            }
+        }
+
+        let mut reverted: Vec<ZoneLoc> = Vec::new();
+        for zone_loc in &self.locs {
+            if let Some(revert) = zone_loc.revert {
+                if revert.tick <= tick {
+                    reverted.push(zone_loc.clone());
+                }
+            }
+        }
+        for zone_loc in reverted {
+            let revert: LocRevert = zone_loc.revert.unwrap();
+            self.set_loc(
+                zone_loc.coord,
+                revert.loc,
+                zone_loc.shape,
+                revert.angle,
+                None,
+            );
+            if revert.loc == -1 {
+                self.events.push(ZoneEvent::LocDel {
+                    coord: zone_loc.coord,
+                    shape: zone_loc.shape,
+                    angle: revert.angle,
+                });
+            } else {
+                self.events.push(ZoneEvent::LocAddChange {
+                    coord: zone_loc.coord,
+                    loc: revert.loc,
+                    shape: zone_loc.shape,
+                    angle: revert.angle,
+                });
+            }
+        }
+    }
+
+    /// Encodes every event from this tick into one buffer, which is then
+    /// sent as-is to every player observing this zone.
+    pub fn compute_shared(&mut self) {
+        if self.events.is_empty() {
+            self.shared = None;
+            return;
+        }
+        let size: usize = self.events.iter().map(|event| event.size()).sum();
+        let mut buf: Packet = Packet::new(size);
+        for event in &self.events {
+            event.encode(&mut buf);
+        }
+        self.shared = Some(buf);
+    }
+
+    /// Every event a player needs to rebuild this zone from a clean state.
+    pub fn full_events(&self) -> Vec<ZoneEvent> {
+        let mut events: Vec<ZoneEvent> = Vec::with_capacity(self.locs.len() + self.objs.len());
+        for zone_loc in &self.locs {
+            if zone_loc.loc == -1 {
+                events.push(ZoneEvent::LocDel {
+                    coord: zone_loc.coord,
+                    shape: zone_loc.shape,
+                    angle: zone_loc.angle,
+                });
+            } else {
+                events.push(ZoneEvent::LocAddChange {
+                    coord: zone_loc.coord,
+                    loc: zone_loc.loc,
+                    shape: zone_loc.shape,
+                    angle: zone_loc.angle,
+                });
+            }
+        }
+        for zone_obj in &self.objs {
+            events.push(ZoneEvent::ObjAdd {
+                coord: zone_obj.coord,
+                obj: zone_obj.obj,
+                count: zone_obj.count,
+            });
+        }
+        return events;
+    }
+
+    pub fn reset(&mut self) {
+        self.events.clear();
+        self.shared = None;
+    }
+
+    /// A loc already changed by the zone reverts to what it showed before the
+    /// change, or to the original loc if an earlier timed change is still pending.
+    fn set_loc(
+        &mut self,
+        coord: CoordGrid,
+        loc: i32,
+        shape: i32,
+        angle: i32,
+        revert: Option<LocRevert>,
+    ) {
+        match self
+            .locs
+            .iter_mut()
+            .find(|zone_loc| zone_loc.coord == coord && zone_loc.shape == shape)
+        {
+            Some(zone_loc) => {
+                zone_loc.revert = revert.map(|revert| match zone_loc.revert {
+                    Some(pending) => LocRevert {
+                        tick: revert.tick,
+                        ..pending
+                    },
+                    None => LocRevert {
+                        tick: revert.tick,
+                        loc: zone_loc.loc,
+                        angle: zone_loc.angle,
+                    },
+                });
+                zone_loc.loc = loc;
+                zone_loc.angle = angle;
+            }
+            None => self.locs.push(ZoneLoc {
+                coord,
+                loc,
+                shape,
+                angle,
+                revert,
+            }),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use io::Packet;
+
+use crate::network::server_prot::ServerProt;
+use crate::zone::coord_grid::CoordGrid;
+
+/// A projectile flying from one coord to another, sent to the zone it starts in.
+///
+/// `target` is the uid of the pathing entity it follows, 0 for none. The delays are
+/// in client cycles, the projectile appears at `start_delay` and lands at `end_delay`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProjAnim {
+    pub from: CoordGrid,
+    pub to: CoordGrid,
+    pub target: i32,
+    pub spotanim: i32,
+    pub src_height: i32,
+    pub dst_height: i32,
+    pub start_delay: i32,
+    pub end_delay: i32,
+    pub peak: i32,
+    pub arc: i32,
+}
+
+/// A single change inside of a zone that every observer of the zone
+/// needs to be told about.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ZoneEvent {
+    ObjAdd {
+        coord: CoordGrid,
+        obj: i32,
+        count: i32,
+    },
+    ObjDel {
+        coord: CoordGrid,
+        obj: i32,
+    },
+    ObjCount {
+        coord: CoordGrid,
+        obj: i32,
+        old_count: i32,
+        new_count: i32,
+    },
+    LocAddChange {
+        coord: CoordGrid,
+        loc: i32,
+        shape: i32,
+        angle: i32,
+    },
+    LocDel {
+        coord: CoordGrid,
+        shape: i32,
+        angle: i32,
+    },
+    MapAnim {
+        coord: CoordGrid,
+        spotanim: i32,
+        height: i32,
+        delay: i32,
+    },
+    MapProjAnim(ProjAnim),
+}
+
+impl ZoneEvent {
+    pub fn prot(&self) -> ServerProt {
+        return match self {
+            ZoneEvent::ObjAdd { .. } => ServerProt::ObjAdd,
+            ZoneEvent::ObjDel { .. } => ServerProt::ObjDel,
+            ZoneEvent::ObjCount { .. } => ServerProt::ObjCount,
+            ZoneEvent::LocAddChange { .. } => ServerProt::LocAddChange,
+            ZoneEvent::LocDel { .. } => ServerProt::LocDel,
+            ZoneEvent::MapAnim { .. } => ServerProt::MapAnim,
+            ZoneEvent::MapProjAnim(_) => ServerProt::MapProjAnim,
+        };
+    }
+
+    /// The number of bytes this event takes up when written with its opcode.
+    pub fn size(&self) -> usize {
+        return 1 + self.prot().length() as usize;
+    }
+
+    /// Writes the opcode followed by the payload of this event.
+    /// This is the layout used both for standalone zone packets and
+    /// for the enclosed zone buffer.
+    pub fn encode(&self, buf: &mut Packet) {
+        buf.p1(self.prot() as i32);
+        match self {
+            ZoneEvent::ObjAdd { coord, obj, count } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p2(*obj);
+                buf.p2((*count).min(65535));
+            }
+            ZoneEvent::ObjDel { coord, obj } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p2(*obj);
+            }
+            ZoneEvent::ObjCount {
+                coord,
+                obj,
+                old_count,
+                new_count,
+            } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p2(*obj);
+                buf.p2((*old_count).min(65535));
+                buf.p2((*new_count).min(65535));
+            }
+            ZoneEvent::LocAddChange {
+                coord,
+                loc,
+                shape,
+                angle,
+            } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p1((shape << 2) | (angle & 0x3));
+                buf.p2(*loc);
+            }
+            ZoneEvent::LocDel {
+                coord,
+                shape,
+                angle,
+            } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p1((shape << 2) | (angle & 0x3));
+            }
+            ZoneEvent::MapAnim {
+                coord,
+                spotanim,
+                height,
+                delay,
+            } => {
+                buf.p1(coord.packed_zone_coord());
+                buf.p2(*spotanim);
+                buf.p1(*height);
+                buf.p2(*delay);
+            }
+            ZoneEvent::MapProjAnim(proj) => {
+                buf.p1(proj.from.packed_zone_coord());
+                buf.p1(proj.to.x() - proj.from.x());
+                buf.p1(proj.to.z() - proj.from.z());
+                buf.p2(proj.target);
+                buf.p2(proj.spotanim);
+                buf.p1(proj.src_height);
+                buf.p1(proj.dst_height);
+                buf.p2(proj.start_delay);
+                buf.p2(proj.end_delay);
+                buf.p1(proj.peak);
+                buf.p1(proj.arc);
+            }
+        }
+    }
+}
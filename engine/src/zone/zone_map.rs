@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::zone::coord_grid::CoordGrid;
+use crate::zone::zone::{LocRevert, Zone};
+use crate::zone::zone_event::ProjAnim;
+
+pub struct ZoneMap {
+    pub zones: HashMap<i32, Zone>,
+}
+
+impl ZoneMap {
+    pub fn new() -> ZoneMap {
+        return ZoneMap {
+            zones: HashMap::new(),
+        };
+    }
+
+    /// Packs the zone coordinates and level of a coord into a single index.
+    pub fn zone_index(coord: CoordGrid) -> i32 {
+        return (coord.zone_x() & 0x7ff)
+            | ((coord.zone_z() & 0x7ff) << 11)
+            | ((coord.level() & 0x3) << 22);
+    }
+
+    /// Unpacks a zone index back into the coord of the south west corner of the zone.
+    pub fn unpack_index(index: i32) -> CoordGrid {
+        return CoordGrid::new(
+            (index & 0x7ff) << 3,
+            ((index >> 11) & 0x7ff) << 3,
+            (index >> 22) & 0x3,
+        );
+    }
+
+    pub fn get(&self, index: i32) -> Option<&Zone> {
+        return self.zones.get(&index);
+    }
+
+    pub fn zone_mut(&mut self, coord: CoordGrid) -> &mut Zone {
+        let index: i32 = ZoneMap::zone_index(coord);
+        return self.zones.entry(index).or_insert_with(|| Zone::new(index));
+    }
+
+    pub fn add_obj(&mut self, coord: CoordGrid, obj: i32, count: i32, despawn: i32) {
+        self.zone_mut(coord).add_obj(coord, obj, count, despawn);
+    }
+
+    pub fn del_obj(&mut self, coord: CoordGrid, obj: i32) -> bool {
+        return self.zone_mut(coord).del_obj(coord, obj);
+    }
+
+    pub fn change_obj_count(&mut self, coord: CoordGrid, obj: i32, count: i32) -> bool {
+        return self.zone_mut(coord).change_obj_count(coord, obj, count);
+    }
+
+    pub fn change_loc(
+        &mut self,
+        coord: CoordGrid,
+        loc: i32,
+        shape: i32,
+        angle: i32,
+        revert: LocRevert,
+    ) {
+        self.zone_mut(coord)
+            .change_loc(coord, loc, shape, angle, revert);
+    }
+
+    pub fn del_loc(&mut self, coord: CoordGrid, shape: i32, angle: i32, revert: LocRevert) {
+        self.zone_mut(coord).del_loc(coord, shape, angle, revert);
+    }
+
+    pub fn anim_map(&mut self, coord: CoordGrid, spotanim: i32, height: i32, delay: i32) {
+        self.zone_mut(coord)
+            .anim_map(coord, spotanim, height, delay);
+    }
+
+    /// Projectiles are sent from the zone they start in.
+    pub fn proj_anim(&mut self, proj: ProjAnim) {
+        self.zone_mut(proj.from).proj_anim(proj);
+    }
+
+    // - loc/obj despawn/respawn
+    // - compute shared buffer
+    pub fn process(&mut self, tick: i32) {
+        for zone in self.zones.values_mut() {
+            zone.process(tick);
+            zone.compute_shared();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for zone in self.zones.values_mut() {
+            zone.reset();
+        }
+    }
+}
+
+impl Default for ZoneMap {
+    fn default() -> ZoneMap {
+        return ZoneMap::new();
+    }
+}
@@ -1,2 +1,6 @@
 #[cfg(test)]
+mod network;
+#[cfg(test)]
 mod script;
+#[cfg(test)]
+mod zone;
@@ -0,0 +1 @@
+mod update_server;
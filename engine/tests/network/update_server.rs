@@ -0,0 +1,240 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+
+use engine::network::update_server::{OnDemandKind, OnDemandQueue, UpdateServer, ARCHIVES};
+use io::{crc32, Packet};
+
+fn mock_dir(name: &str) -> PathBuf {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("update_server_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("client/maps")).unwrap();
+    std::fs::create_dir_all(dir.join("client/songs")).unwrap();
+    // every archive holds its own name, but config which is checked against a known crc
+    for name in ARCHIVES {
+        std::fs::write(dir.join("client").join(name), name).unwrap();
+    }
+    std::fs::write(dir.join("client/crc"), vec![0, 0, 0, 1]).unwrap();
+    std::fs::write(dir.join("client/config"), vec![1, 2, 3]).unwrap();
+    std::fs::write(dir.join("client/maps/m50_50"), vec![4; 1200]).unwrap();
+    std::fs::write(dir.join("client/songs/scape_main.mid"), vec![5, 6]).unwrap();
+    return dir;
+}
+
+fn body(response: &[u8]) -> &[u8] {
+    let end: usize = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .unwrap();
+    return &response[end + 4..];
+}
+
+#[test]
+fn test_archive_name() {
+    assert_eq!("config", UpdateServer::archive_name("/config-1494247427"));
+    assert_eq!("crc", UpdateServer::archive_name("/crc123456"));
+    assert_eq!("title", UpdateServer::archive_name("/title"));
+}
+
+#[test]
+fn test_http_archive() {
+    let dir: PathBuf = mock_dir("http_archive");
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+
+    let response: Vec<u8> = server.http_response("GET /config-1494247427 HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+    assert_eq!(&[1, 2, 3], body(&response));
+
+    let response: Vec<u8> = server.http_response("GET /crc9912 HTTP/1.1\r\n\r\n");
+    assert_eq!(server.archive("crc").unwrap().as_slice(), body(&response));
+}
+
+#[test]
+fn test_crc_table() {
+    // the stale crc written by mock_dir is ignored.
+    let dir: PathBuf = mock_dir("crc_table");
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+
+    let mut crc: Packet = Packet::from(server.archive("crc").unwrap().clone());
+    assert_eq!(ARCHIVES.len() * 4, crc.len());
+    assert_eq!(0, crc.g4s()); // crc
+    assert_eq!(crc32(b"title"), crc.g4s());
+    assert_eq!(0x55bc801d, crc.g4s()); // config
+    assert_eq!(crc32(b"interface"), crc.g4s());
+}
+
+#[test]
+fn test_map_crcs() {
+    let dir: PathBuf = mock_dir("map_crcs");
+    std::fs::write(dir.join("client/maps/l50_50"), vec![1, 2, 3]).unwrap();
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+
+    assert_eq!(2, server.map_crcs.len());
+    assert_eq!(Some(&-162091), server.map_crcs.get("m50_50"));
+    assert_eq!(Some(&0x55bc801d), server.map_crcs.get("l50_50"));
+}
+
+#[test]
+fn test_http_midi() {
+    let dir: PathBuf = mock_dir("http_midi");
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+
+    let response: Vec<u8> = server.http_response("GET /scape_main_12345.mid HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+    assert_eq!(&[5, 6], body(&response));
+}
+
+#[test]
+fn test_missing_archives() {
+    let dir: PathBuf = mock_dir("missing_archives");
+    std::fs::remove_file(dir.join("client/media")).unwrap();
+    std::fs::remove_file(dir.join("client/sounds")).unwrap();
+    let server: UpdateServer = UpdateServer::load(dir.to_str().unwrap()).unwrap();
+
+    assert!(server.archive("media").is_none());
+    assert!(server
+        .http_response("GET /media HTTP/1.1\r\n\r\n")
+        .starts_with(b"HTTP/1.1 404"));
+    let mut crc: Packet = Packet::from(server.archive("crc").unwrap().clone());
+    crc.pos = 4 * 4;
+    assert_eq!(0, crc.g4s()); // media
+    assert_eq!(crc32(b"models"), crc.g4s());
+}
+
+#[test]
+fn test_missing_client_dir() {
+    let dir: PathBuf = std::env::temp_dir().join("update_server_missing_client_dir");
+    assert!(UpdateServer::load(dir.to_str().unwrap()).is_err());
+}
+
+#[test]
+fn test_queue_priority() {
+    let mut queue: OnDemandQueue = OnDemandQueue::new();
+    queue.push(OnDemandKind::Midi, "scape_main".to_string());
+    queue.push(OnDemandKind::Model, "1".to_string());
+    queue.push(OnDemandKind::Land, "m50_50".to_string());
+    queue.push(OnDemandKind::Loc, "l50_50".to_string());
+    queue.push(OnDemandKind::Model, "2".to_string());
+    assert_eq!(5, queue.len());
+
+    let order: Vec<String> = std::iter::from_fn(|| queue.pop())
+        .map(|request| request.name)
+        .collect();
+    assert_eq!(vec!["m50_50", "l50_50", "1", "2", "scape_main"], order);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_queue_close() {
+    let mut queue: OnDemandQueue = OnDemandQueue::new();
+    queue.push(OnDemandKind::Model, "1".to_string());
+    queue.close();
+    assert!(queue.is_closed());
+    assert_eq!("1", queue.pop().unwrap().name);
+    assert!(queue.pop().is_none());
+}
+
+#[test]
+fn test_chunks() {
+    let dir: PathBuf = mock_dir("chunks");
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+    let data: Vec<u8> = server.file(OnDemandKind::Land, "m50_50").unwrap();
+
+    let mut chunks: Vec<Packet> = UpdateServer::chunks(OnDemandKind::Land, "m50_50", &data);
+    assert_eq!(3, chunks.len());
+
+    let last: &mut Packet = &mut chunks[2];
+    last.pos = 0;
+    assert_eq!(OnDemandKind::Land as u8, last.g1());
+    assert_eq!("m50_50", last.gjstr(10));
+    assert_eq!(1200, last.g4s());
+    assert_eq!(2, last.g2());
+    assert_eq!(200, last.g2());
+}
+
+#[test]
+fn test_chunks_missing_file() {
+    let chunks: Vec<Packet> = UpdateServer::chunks(OnDemandKind::Model, "0", &Vec::new());
+    assert_eq!(1, chunks.len());
+    assert_eq!(1 + 2 + 4 + 2 + 2, chunks[0].len());
+}
+
+#[test]
+fn test_http_not_found() {
+    let dir: PathBuf = mock_dir("http_not_found");
+    let server: UpdateServer = UpdateServer::io(dir.to_str().unwrap());
+
+    assert!(server
+        .http_response("GET /maps123 HTTP/1.1\r\n\r\n")
+        .starts_with(b"HTTP/1.1 404"));
+    assert!(server
+        .http_response("POST /config HTTP/1.1\r\n\r\n")
+        .starts_with(b"HTTP/1.1 404"));
+    assert!(server
+        .http_response("GET /.._12.mid HTTP/1.1\r\n\r\n")
+        .starts_with(b"HTTP/1.1 404"));
+    assert!(server.file(OnDemandKind::Midi, "../config").is_none());
+    assert!(server
+        .file(OnDemandKind::Land, "../client/config")
+        .is_none());
+}
+
+#[test]
+fn test_serve() {
+    let dir: PathBuf = mock_dir("serve");
+    let (addr, _): (SocketAddr, SocketAddr) = UpdateServer::io(dir.to_str().unwrap())
+        .serve("127.0.0.1:0", "127.0.0.1:0")
+        .unwrap();
+
+    let mut client: TcpStream = TcpStream::connect(addr).unwrap();
+    client
+        .write_all(b"GET /config-1494247427 HTTP/1.1\r\n\r\n")
+        .unwrap();
+    let mut response: Vec<u8> = Vec::new();
+    client.read_to_end(&mut response).unwrap();
+    assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+    assert_eq!(&[1, 2, 3], body(&response));
+}
+
+#[test]
+fn test_serve_ondemand() {
+    let dir: PathBuf = mock_dir("serve_ondemand");
+    let (_, addr): (SocketAddr, SocketAddr) = UpdateServer::io(dir.to_str().unwrap())
+        .serve("127.0.0.1:0", "127.0.0.1:0")
+        .unwrap();
+
+    let mut client: TcpStream = TcpStream::connect(addr).unwrap();
+    client.write_all(&[OnDemandKind::Land as u8, 2]).unwrap();
+    client.write_all(b"m50_50\n").unwrap();
+    client.shutdown(std::net::Shutdown::Write).unwrap();
+    let mut response: Vec<u8> = Vec::new();
+    client.read_to_end(&mut response).unwrap();
+    // three chunks of headers, then the whole map
+    assert_eq!(3 * (1 + 7 + 4 + 2 + 2) + 1200, response.len());
+
+    let mut first: Packet = Packet::from(response);
+    assert_eq!(OnDemandKind::Land as u8, first.g1());
+    assert_eq!("m50_50", first.gjstr(10));
+    assert_eq!(1200, first.g4s());
+    assert_eq!(0, first.g2());
+    assert_eq!(500, first.g2());
+}
+
+#[test]
+fn test_serve_connection_limit() {
+    let dir: PathBuf = mock_dir("serve_connection_limit");
+    let (addr, _): (SocketAddr, SocketAddr) = UpdateServer::io(dir.to_str().unwrap())
+        .serve("127.0.0.1:0", "127.0.0.1:0")
+        .unwrap();
+
+    // idle connections hold every slot until they time out.
+    let idle: Vec<TcpStream> = (0..UpdateServer::MAX_CONNECTIONS)
+        .map(|_| TcpStream::connect(addr).unwrap())
+        .collect();
+    let mut client: TcpStream = TcpStream::connect(addr).unwrap();
+    client.write_all(b"GET /config HTTP/1.1\r\n\r\n").unwrap();
+    let mut response: Vec<u8> = Vec::new();
+    let _ = client.read_to_end(&mut response);
+    assert!(response.is_empty());
+    drop(idle);
+}
@@ -1,4 +1,8 @@
-use cache::{ScriptExecutionState, ScriptFile, ScriptOpcode, ScriptRunner, ScriptState};
+use std::collections::HashMap;
+
+use cache::{
+    ScriptErrorKind, ScriptExecutionState, ScriptFile, ScriptOpcode, ScriptRunner, ScriptState,
+};
 use engine::engine::Engine;
 
 #[test]
@@ -12,7 +16,7 @@ fn test_push_constant_int() {
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::PushConstantInt);
-    assert_eq!(1, state.pop_int());
+    assert_eq!(1, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -27,7 +31,7 @@ fn test_push_constant_string() {
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::PushConstantString);
-    assert_eq!("Hello World!", state.pop_string());
+    assert_eq!("Hello World!", state.pop_string().unwrap());
     assert!(result.is_ok());
 }
 
@@ -54,8 +58,8 @@ fn test_branch_not_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchNot);
@@ -70,8 +74,8 @@ fn test_branch_not_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(1);
+    state.push_int(1).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchNot);
@@ -86,8 +90,8 @@ fn test_branch_equals_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchEquals);
@@ -103,8 +107,8 @@ fn test_branch_equals_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(1);
+    state.push_int(1).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchEquals);
@@ -120,8 +124,8 @@ fn test_branch_less_than_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchLessThan);
@@ -136,8 +140,8 @@ fn test_branch_less_than_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(1);
+    state.push_int(1).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchLessThan);
@@ -152,8 +156,8 @@ fn test_branch_greater_than_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchGreaterThan);
@@ -169,8 +173,8 @@ fn test_branch_greater_than_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(2);
-    state.push_int(1);
+    state.push_int(2).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchGreaterThan);
@@ -202,7 +206,7 @@ fn test_return_running() {
 
     state.pc += 1; // emulate starting the script program.
     let script2 = ScriptFile::mock();
-    state.gosub_frame(&script2);
+    state.gosub_frame(&script2).unwrap();
     assert_eq!(-1, state.pc);
     assert_eq!(1, state.fp);
 
@@ -221,8 +225,8 @@ fn test_branch_less_than_or_equals_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(1);
+    state.push_int(1).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchLessThanOrEquals);
@@ -237,8 +241,8 @@ fn test_branch_less_than_or_equals_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(2);
-    state.push_int(1);
+    state.push_int(2).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchLessThanOrEquals);
@@ -253,8 +257,8 @@ fn test_branch_greater_than_or_equals_1() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchGreaterThanOrEquals);
@@ -270,8 +274,8 @@ fn test_branch_greater_than_or_equals_0() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(2);
-    state.push_int(1);
+    state.push_int(2).unwrap();
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::BranchGreaterThanOrEquals);
@@ -291,7 +295,7 @@ fn test_push_int_local() {
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::PushIntLocal);
-    assert_eq!(1, state.pop_int());
+    assert_eq!(1, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -304,7 +308,7 @@ fn test_pop_int_local() {
 
     state.int_locals.push(0);
     state.pc += 1; // emulate starting the script program.
-    state.push_int(1);
+    state.push_int(1).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::PopIntLocal);
@@ -320,11 +324,195 @@ fn test_join_string() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_string("Hello".to_string());
-    state.push_string("World!".to_string());
+    state.push_string("Hello".to_string()).unwrap();
+    state.push_string("World!".to_string()).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::JoinString);
-    assert_eq!("HelloWorld!", state.pop_string());
+    assert_eq!("HelloWorld!", state.pop_string().unwrap());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_switch() {
+    let mut file = ScriptFile::mock();
+    file.switch_tables.push(HashMap::from([(1, 5)]));
+    file.switch_tables.push(HashMap::from([(1, 2), (3, 7)]));
+    file.int_operands.push(1); // the second table.
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(3).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::Switch);
+    assert_eq!(7, state.pc);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_switch_first_table() {
+    let mut file = ScriptFile::mock();
+    file.switch_tables.push(HashMap::from([(1, 5)]));
+    file.switch_tables.push(HashMap::from([(1, 2), (3, 7)]));
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(1).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::Switch);
+    assert_eq!(5, state.pc);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_switch_no_case() {
+    let mut file = ScriptFile::mock();
+    file.switch_tables.push(HashMap::from([(1, 5)]));
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(2).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::Switch);
+    assert_eq!(0, state.pc);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_switch_missing_table() {
+    let mut file = ScriptFile::mock();
+    file.switch_tables.push(HashMap::from([(1, 5)]));
+    file.int_operands.push(1);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(1).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::Switch);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_define_array() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push((1 << 16) | 'i' as i32);
+    file.int_operands.push((2 << 16) | 'o' as i32);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(3).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::DefineArray);
     assert!(result.is_ok());
+    state.pc += 1;
+    state.push_int(2).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::DefineArray);
+    assert!(result.is_ok());
+    assert_eq!(vec![vec![], vec![0, 0, 0], vec![-1, -1]], state.arrays);
+}
+
+#[test]
+fn test_define_array_invalid() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push((5 << 16) | 'i' as i32);
+    file.int_operands.push('i' as i32);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(3).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::DefineArray);
+    assert_eq!(Err(ScriptErrorKind::InvalidArray(5)), result);
+
+    state.pc += 1;
+    state.push_int(5001).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::DefineArray);
+    assert_eq!(
+        Err(ScriptErrorKind::LimitExceeded {
+            limit: "array size",
+            max: 5000
+        }),
+        result
+    );
+}
+
+#[test]
+fn test_pop_and_push_array_int() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push('i' as i32);
+    file.int_operands.push(0);
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(4).unwrap();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::DefineArray)
+        .is_ok());
+    state.pc += 1;
+    state.push_int(2).unwrap(); // index
+    state.push_int(42).unwrap(); // value
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::PopArrayInt)
+        .is_ok());
+    state.pc += 1;
+    state.push_int(2).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::PushArrayInt);
+    assert!(result.is_ok());
+    assert_eq!(42, state.pop_int().unwrap());
+    assert_eq!(vec![vec![0, 0, 42, 0]], state.arrays);
+}
+
+#[test]
+fn test_push_array_int_out_of_bounds() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    file.int_operands.push(1);
+    let mut state = ScriptState::mock(&file);
+    state.define_array(0, 'i' as i32, 4).unwrap();
+
+    state.pc += 1; // emulate starting the script program.
+
+    let engine = Engine::mock();
+    state.push_int(4).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::PushArrayInt);
+    assert_eq!(
+        Err(ScriptErrorKind::ArrayIndex {
+            array: 0,
+            index: 4,
+            length: 4
+        }),
+        result
+    );
+
+    state.pc += 1;
+    state.push_int(0).unwrap();
+    let result = engine.push_script(&mut state, &ScriptOpcode::PushArrayInt);
+    assert_eq!(Err(ScriptErrorKind::InvalidArray(1)), result);
+}
+
+#[test]
+fn test_arrays_across_frames() {
+    let caller = ScriptFile::mock();
+    let callee = ScriptFile::mock();
+    let mut state = ScriptState::mock(&caller);
+    state.define_array(0, 'i' as i32, 2).unwrap();
+    *state.array_element(0, 1).unwrap() = 7;
+
+    state.gosub_frame(&callee).unwrap();
+    assert!(state.arrays.is_empty());
+    state.define_array(0, 'i' as i32, 1).unwrap();
+
+    state.pop_frame();
+    assert_eq!(vec![vec![0, 7]], state.arrays);
 }
@@ -0,0 +1,126 @@
+use cache::{ScriptCoverage, ScriptFile, ScriptOpcode, ScriptProfiler, ScriptState};
+use engine::engine::Engine;
+
+use super::fixtures::{engine, get, script};
+
+#[test]
+fn test_coverage_hits() {
+    let engine: Engine = engine();
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    let mut coverage: ScriptCoverage = ScriptCoverage::new();
+    let mut state = ScriptState::mock(file);
+    assert!(state.execute_covered(&engine, &mut coverage).is_ok());
+
+    assert_eq!(vec![1, 1, 0, 1, 1, 1], coverage.scripts[&1].hits);
+    assert_eq!(vec![1, 1], coverage.scripts[&0].hits);
+    assert_eq!(
+        vec![(1, 1), (2, 1), (3, 0), (4, 1), (5, 1)],
+        coverage.scripts[&1]
+            .line_hits()
+            .into_iter()
+            .collect::<Vec<(i32, u64)>>()
+    );
+}
+
+#[test]
+fn test_coverage_attached() {
+    let engine: Engine = engine();
+    engine.attach_coverage(ScriptCoverage::new());
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    for _ in 0..2 {
+        let mut state = ScriptState::mock(file);
+        assert!(state.execute(&engine).is_ok());
+    }
+
+    let coverage: ScriptCoverage = engine.detach_coverage().unwrap();
+    assert_eq!(vec![2, 2, 0, 2, 2, 2], coverage.scripts[&1].hits);
+    assert_eq!(2, coverage.scripts[&0].calls());
+}
+
+#[test]
+fn test_coverage_lcov() {
+    let engine: Engine = engine();
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    let mut coverage: ScriptCoverage = ScriptCoverage::new();
+    coverage.add(get(&engine, "[proc,unused]"));
+    let mut state = ScriptState::mock(file);
+    assert!(state.execute_covered(&engine, &mut coverage).is_ok());
+
+    assert_eq!(
+        "TN:\n\
+         SF:scripts/test.rs2\n\
+         FN:10,[proc,callee]\n\
+         FN:1,[proc,root]\n\
+         FNDA:1,[proc,callee]\n\
+         FNDA:1,[proc,root]\n\
+         FNF:2\n\
+         FNH:2\n\
+         DA:1,1\n\
+         DA:2,1\n\
+         DA:3,0\n\
+         DA:4,1\n\
+         DA:5,1\n\
+         DA:10,1\n\
+         DA:11,1\n\
+         LF:7\n\
+         LH:6\n\
+         end_of_record\n\
+         TN:\n\
+         SF:scripts/unused.rs2\n\
+         FN:1,[proc,unused]\n\
+         FNDA:0,[proc,unused]\n\
+         FNF:1\n\
+         FNH:0\n\
+         DA:1,0\n\
+         LF:1\n\
+         LH:0\n\
+         end_of_record\n",
+        coverage.lcov()
+    );
+}
+
+#[test]
+fn test_coverage_with_profiler() {
+    let engine: Engine = engine();
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    engine.attach_coverage(ScriptCoverage::new());
+    engine.attach_profiler(ScriptProfiler::new());
+    let mut state = ScriptState::mock(file);
+    assert!(state.execute(&engine).is_ok());
+
+    let coverage: ScriptCoverage = engine.detach_coverage().unwrap();
+    let profiler: ScriptProfiler = engine.detach_profiler().unwrap();
+    assert_eq!(vec![1, 1, 0, 1, 1, 1], coverage.scripts[&1].hits);
+    assert_eq!(1, profiler.scripts["[proc,callee]"].calls);
+    assert_eq!(1, profiler.tick_runs().len());
+}
+
+#[test]
+fn test_coverage_calls_loop() {
+    // counts local 0 up to 3, branching back to pc 0 each time.
+    let mut file: ScriptFile = script(
+        "[proc,loop]",
+        vec![
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchGreaterThanOrEquals,
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Add,
+            ScriptOpcode::PopIntLocal,
+            ScriptOpcode::Branch,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 3, 5, 0, 1, 0, 0, -8, 0],
+        vec![1, 1, 1, 2, 2, 2, 2, 2, 3],
+    );
+    file.int_local_count = 1;
+    let engine: Engine = Engine::mock();
+    let mut coverage: ScriptCoverage = ScriptCoverage::new();
+    let mut state = ScriptState::new_with_args(&file, vec![0], Vec::new());
+    assert!(state.execute_covered(&engine, &mut coverage).is_ok());
+
+    let hits = &coverage.scripts[&file.id()];
+    assert_eq!(4, hits.hits[0]);
+    assert_eq!(1, hits.calls());
+}
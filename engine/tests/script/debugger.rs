@@ -0,0 +1,282 @@
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::TcpStream;
+use std::rc::Rc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use cache::{
+    Breakpoint, ConsoleDebugHook, DebugCommand, DebugHook, DebugLine, PauseReason, ScriptDebugger,
+    ScriptErrorKind, ScriptExecutionState, ScriptFile, ScriptOpcode, ScriptState,
+};
+use engine::engine::Engine;
+
+/// Answers every pause with the next command and records where it paused.
+struct ScriptedHook {
+    commands: Vec<DebugCommand>,
+    pauses: Rc<RefCell<Vec<(PauseReason, i32)>>>,
+}
+
+impl DebugHook for ScriptedHook {
+    fn on_pause(
+        &mut self,
+        state: &ScriptState,
+        reason: PauseReason,
+        _: &mut Vec<Breakpoint>,
+    ) -> DebugCommand {
+        self.pauses.borrow_mut().push((reason, state.pc + 1));
+        if self.commands.is_empty() {
+            return DebugCommand::Continue;
+        }
+        return self.commands.remove(0);
+    }
+}
+
+fn debugger(commands: Vec<DebugCommand>) -> (ScriptDebugger, Rc<RefCell<Vec<(PauseReason, i32)>>>) {
+    let pauses: Rc<RefCell<Vec<(PauseReason, i32)>>> = Rc::new(RefCell::new(Vec::new()));
+    let hook: ScriptedHook = ScriptedHook {
+        commands,
+        pauses: pauses.clone(),
+    };
+    return (ScriptDebugger::new(Box::new(hook)), pauses);
+}
+
+/// `1 + 2` on line 2, `return` on line 3.
+fn script() -> ScriptFile {
+    let mut file: ScriptFile = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Add,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 2, 0, 0],
+    );
+    file.info.pcs = vec![0, 3];
+    file.info.lines = vec![2, 3];
+    return file;
+}
+
+#[test]
+fn test_breakpoint() {
+    let file: ScriptFile = script();
+    let (mut debugger, pauses) = debugger(Vec::new());
+    debugger.add_breakpoint("[proc,test]", 3);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    assert!(state.execute_debug(&engine, &mut debugger).is_ok());
+    assert_eq!(ScriptExecutionState::Finished, state.execution_state);
+    assert_eq!(3, state.pop_int().unwrap());
+    assert_eq!(
+        vec![(
+            PauseReason::Breakpoint(Breakpoint {
+                script: "[proc,test]".to_string(),
+                line: 3,
+            }),
+            3
+        )],
+        *pauses.borrow()
+    );
+}
+
+#[test]
+fn test_step() {
+    let file: ScriptFile = script();
+    let (mut debugger, pauses) = debugger(vec![DebugCommand::Step, DebugCommand::Step]);
+    debugger.step();
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    assert!(state.execute_debug(&engine, &mut debugger).is_ok());
+    let pcs: Vec<i32> = pauses.borrow().iter().map(|(_, pc)| *pc).collect();
+    assert_eq!(vec![0, 1, 2], pcs);
+}
+
+#[test]
+fn test_next_line() {
+    let file: ScriptFile = script();
+    let (mut debugger, pauses) = debugger(vec![DebugCommand::Next]);
+    debugger.step();
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    assert!(state.execute_debug(&engine, &mut debugger).is_ok());
+    let pcs: Vec<i32> = pauses.borrow().iter().map(|(_, pc)| *pc).collect();
+    assert_eq!(vec![0, 3], pcs);
+}
+
+#[test]
+fn test_abort() {
+    let file: ScriptFile = script();
+    let (mut debugger, _) = debugger(vec![DebugCommand::Abort]);
+    debugger.add_breakpoint("[proc,test]", 2);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error = state.execute_debug(&engine, &mut debugger).unwrap_err();
+    assert_eq!(
+        ScriptErrorKind::Runtime("Aborted by debugger".to_string()),
+        error.kind
+    );
+    assert_eq!(ScriptExecutionState::Aborted, state.execution_state);
+}
+
+#[test]
+fn test_attached_debugger() {
+    let file: ScriptFile = script();
+    let (mut debugger, pauses) = debugger(Vec::new());
+    debugger.add_breakpoint("[proc,test]", 2);
+    let engine = Engine::mock();
+    engine.attach_debugger(debugger);
+    let mut state = ScriptState::mock(&file);
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(1, pauses.borrow().len());
+
+    assert!(engine.detach_debugger().is_some());
+    let mut state = ScriptState::mock(&file);
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(1, pauses.borrow().len());
+}
+
+#[test]
+fn test_console_hook() {
+    let file: ScriptFile = script();
+    let input: Cursor<&[u8]> = Cursor::new(b"s\ns\np\nb [proc,test] 3\nc\nc\n");
+    let output: Rc<RefCell<Vec<u8>>> = Rc::new(RefCell::new(Vec::new()));
+    let hook = ConsoleDebugHook::new(input, SharedOutput(output.clone()));
+    let mut debugger: ScriptDebugger = ScriptDebugger::new(Box::new(hook));
+    debugger.step();
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    assert!(state.execute_debug(&engine, &mut debugger).is_ok());
+
+    let output: String = String::from_utf8(output.borrow().clone()).unwrap();
+    assert!(output.starts_with("paused at [proc,test] (scripts/test.rs2:2)\n> "));
+    assert!(output.contains("int stack: [1, 2]\n"));
+    assert!(output.contains("breakpoint at [proc,test] (scripts/test.rs2:3)\n"));
+}
+
+#[test]
+fn test_breakpoint_parse() {
+    assert_eq!(
+        Ok(Breakpoint {
+            script: "[proc,fib]".to_string(),
+            line: 2,
+        }),
+        Breakpoint::parse("[proc,fib]:2")
+    );
+    assert!(Breakpoint::parse("[proc,fib]:two").is_err());
+    assert!(Breakpoint::parse("[proc,fib]").is_err());
+}
+
+#[test]
+fn test_debug_line_parse() {
+    assert_eq!(
+        DebugLine::Command(DebugCommand::Next),
+        DebugLine::parse("n\n")
+    );
+    assert_eq!(
+        DebugLine::Delete(Breakpoint {
+            script: "[proc,test]".to_string(),
+            line: 3,
+        }),
+        DebugLine::parse("d [proc,test] 3")
+    );
+    assert_eq!(DebugLine::Reload, DebugLine::parse("reload"));
+    assert_eq!(
+        DebugLine::Invalid("Invalid line: three".to_string()),
+        DebugLine::parse("b [proc,test] three")
+    );
+}
+
+#[test]
+fn test_debug_server() {
+    let file: ScriptFile = script();
+    let mut engine = Engine::mock();
+    engine.listen_debugger("127.0.0.1:0").unwrap();
+    let mut client: TcpStream =
+        TcpStream::connect(engine.debug_server.as_ref().unwrap().addr()).unwrap();
+    client.write_all(b"b [proc,test] 3\n").unwrap();
+
+    // the frontend is attached and its breakpoint picked up between ticks.
+    for _ in 0..100 {
+        engine.process_cycle();
+        let debugger = engine.debugger.borrow();
+        if debugger
+            .as_ref()
+            .is_some_and(|debugger| !debugger.breakpoints.is_empty())
+        {
+            break;
+        }
+        drop(debugger);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let frontend: JoinHandle<Vec<String>> = std::thread::spawn(move || {
+        let mut reader: BufReader<TcpStream> = BufReader::new(client.try_clone().unwrap());
+        let mut lines: Vec<String> = Vec::new();
+        let mut line: String = String::new();
+        while !line.contains("int stack") {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            if line.starts_with("breakpoint at") {
+                client.write_all(b"p\nc\n").unwrap();
+            }
+            lines.push(line.trim().to_string());
+        }
+        return lines;
+    });
+
+    let mut state = ScriptState::mock(&file);
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(3, state.pop_int().unwrap());
+    let lines: Vec<String> = frontend.join().unwrap();
+    assert_eq!("breakpoint at [proc,test] (scripts/test.rs2:3)", lines[0]);
+    assert_eq!("int stack: [3]", lines[lines.len() - 1]);
+}
+
+#[test]
+fn test_debug_server_idle_frontend() {
+    let file: ScriptFile = script();
+    let mut engine = Engine::mock();
+    engine.listen_debugger("127.0.0.1:0").unwrap();
+    engine.debug_server.as_mut().unwrap().pause_timeout = Duration::from_millis(50);
+    let mut client: TcpStream =
+        TcpStream::connect(engine.debug_server.as_ref().unwrap().addr()).unwrap();
+    client.write_all(b"b [proc,test] 3\n").unwrap();
+    for _ in 0..100 {
+        engine.process_cycle();
+        let debugger = engine.debugger.borrow();
+        if debugger
+            .as_ref()
+            .is_some_and(|debugger| !debugger.breakpoints.is_empty())
+        {
+            break;
+        }
+        drop(debugger);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // the frontend never answers, the script resumes once the pause times out.
+    let mut state = ScriptState::mock(&file);
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(3, state.pop_int().unwrap());
+    assert!(engine
+        .debugger
+        .borrow()
+        .as_ref()
+        .is_some_and(|debugger| debugger.breakpoints.is_empty()));
+    let mut lines: String = String::new();
+    BufReader::new(client).read_to_string(&mut lines).unwrap();
+    assert!(lines.starts_with("breakpoint at [proc,test] (scripts/test.rs2:3)"));
+    assert!(lines.contains("Timed out, detaching and resuming"));
+}
+
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        return self.0.borrow_mut().write(buf);
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
@@ -0,0 +1,206 @@
+use cache::{
+    ScriptError, ScriptErrorKind, ScriptExecutionState, ScriptFile, ScriptFrame, ScriptOpcode,
+    ScriptState,
+};
+use engine::engine::Engine;
+
+#[test]
+fn test_execute_finishes() {
+    let file = ScriptFile::mock_with(
+        vec![ScriptOpcode::PushConstantInt, ScriptOpcode::Return],
+        vec![5, 0],
+    );
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(ScriptExecutionState::Finished, state.execution_state);
+    assert_eq!(5, state.pop_int().unwrap());
+}
+
+#[test]
+fn test_execute_unimplemented() {
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushVarp,
+            ScriptOpcode::Return,
+        ],
+        vec![5, 0, 0],
+    );
+    file.info.pcs = vec![0, 1];
+    file.info.lines = vec![3, 4];
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::Unimplemented, error.kind);
+    assert_eq!(Some(ScriptOpcode::PushVarp), error.opcode);
+    assert_eq!("[proc,test]", error.script);
+    assert_eq!(1, error.pc);
+    assert_eq!(Some(4), error.line);
+    assert_eq!(
+        "Unimplemented opcode (PushVarp)\n    at [proc,test] (scripts/test.rs2:4)",
+        error.to_string()
+    );
+    assert_eq!(ScriptExecutionState::Aborted, state.execution_state);
+}
+
+#[test]
+fn test_execute_stack_underflow() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Add, ScriptOpcode::Return], vec![0, 0]);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackUnderflow, error.kind);
+    assert_eq!(0, error.pc);
+}
+
+#[test]
+fn test_execute_join_string_underflow() {
+    // a negative count has to fail on the empty stack instead of popping forever.
+    let file = ScriptFile::mock_with(
+        vec![ScriptOpcode::JoinString, ScriptOpcode::Return],
+        vec![-1, 0],
+    );
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackUnderflow, error.kind);
+    assert_eq!(1, state.opcount);
+}
+
+#[test]
+fn test_execute_stack_overflow() {
+    // pushes forever, the mock stack only holds 100 ints.
+    let file = ScriptFile::mock_with(
+        vec![ScriptOpcode::PushConstantInt, ScriptOpcode::Branch],
+        vec![1, -2],
+    );
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackOverflow, error.kind);
+}
+
+#[test]
+fn test_execute_division_by_zero() {
+    for (code, operands) in [
+        (ScriptOpcode::Divide, vec![7, 0]),
+        (ScriptOpcode::Modulo, vec![7, 0]),
+        (ScriptOpcode::Scale, vec![7, 0, 3]),
+    ] {
+        let mut codes: Vec<ScriptOpcode> = vec![ScriptOpcode::PushConstantInt; operands.len()];
+        codes.push(code);
+        codes.push(ScriptOpcode::Return);
+        let mut int_operands: Vec<i32> = operands;
+        int_operands.extend([0, 0]);
+        let file = ScriptFile::mock_with(codes, int_operands);
+        let mut state = ScriptState::mock(&file);
+        let engine = Engine::mock();
+        let error: ScriptError = state.execute(&engine).unwrap_err();
+        assert_eq!(
+            ScriptErrorKind::Runtime("Division by zero".to_string()),
+            error.kind
+        );
+        assert_eq!(ScriptExecutionState::Aborted, state.execution_state);
+    }
+}
+
+#[test]
+fn test_execute_runaway_script() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Branch], vec![-1]);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::RunawayScript, error.kind);
+    assert_eq!(state.limits.instructions, state.opcount);
+}
+
+#[test]
+fn test_execute_invalid_pc() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Branch], vec![5]);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::InvalidPc(6), error.kind);
+    assert_eq!(None, error.opcode);
+
+    let empty = ScriptFile::mock();
+    let mut state = ScriptState::mock(&empty);
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::InvalidPc(0), error.kind);
+}
+
+#[test]
+fn test_execute_bad_pointer() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Anim], vec![0]);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert!(matches!(error.kind, ScriptErrorKind::BadPointer { .. }));
+
+    // an operand past the second pointer is an error rather than a panic.
+    for code in [
+        ScriptOpcode::Anim,
+        ScriptOpcode::LocChange,
+        ScriptOpcode::LocDel,
+    ] {
+        let file = ScriptFile::mock_with(vec![code], vec![2]);
+        let mut state = ScriptState::mock(&file);
+        let error: ScriptError = state.execute(&engine).unwrap_err();
+        assert!(matches!(error.kind, ScriptErrorKind::BadPointer { .. }));
+    }
+}
+
+#[test]
+fn test_execute_stack_trace() {
+    let mut caller = ScriptFile::mock_with(
+        vec![ScriptOpcode::PushConstantInt, ScriptOpcode::GoSubWithParams],
+        vec![0, 0],
+    );
+    caller.info.name = "[label,caller]".to_string();
+    caller.info.path = "scripts/caller.rs2".to_string();
+    caller.info.pcs = vec![0, 1];
+    caller.info.lines = vec![7, 8];
+    let mut callee = ScriptFile::mock_with(vec![ScriptOpcode::PushVarp], vec![0]);
+    callee.info.pcs = vec![0];
+    callee.info.lines = vec![20];
+
+    let mut state = ScriptState::mock(&caller);
+    state.pc = 1;
+    state.gosub_frame(&callee).unwrap();
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(
+        vec![
+            ScriptFrame {
+                script: "[proc,test]".to_string(),
+                path: "scripts/test.rs2".to_string(),
+                pc: 0,
+                line: Some(20),
+            },
+            ScriptFrame {
+                script: "[label,caller]".to_string(),
+                path: "scripts/caller.rs2".to_string(),
+                pc: 1,
+                line: Some(8),
+            },
+        ],
+        error.trace
+    );
+    assert_eq!(
+        "Unimplemented opcode (PushVarp)\n    at [proc,test] (scripts/test.rs2:20)\n    at [label,caller] (scripts/caller.rs2:8)",
+        error.to_string()
+    );
+}
+
+#[test]
+fn test_script_info_line() {
+    let mut file = ScriptFile::mock();
+    file.info.pcs = vec![0, 3, 7];
+    file.info.lines = vec![10, 12, 15];
+    assert_eq!(None, file.info.line(-1));
+    assert_eq!(Some(10), file.info.line(0));
+    assert_eq!(Some(10), file.info.line(2));
+    assert_eq!(Some(12), file.info.line(3));
+    assert_eq!(Some(15), file.info.line(100));
+}
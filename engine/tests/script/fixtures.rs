@@ -0,0 +1,56 @@
+use std::rc::Rc;
+
+use cache::{ScriptFile, ScriptOpcode, ScriptProvider};
+use engine::engine::Engine;
+
+/// A script named `name` with one line per instruction.
+pub fn script(
+    name: &str,
+    codes: Vec<ScriptOpcode>,
+    int_operands: Vec<i32>,
+    lines: Vec<i32>,
+) -> ScriptFile {
+    let mut file: ScriptFile = ScriptFile::mock_with(codes, int_operands);
+    file.info.name = name.to_string();
+    file.info.pcs = (0..lines.len() as i32).collect();
+    file.info.lines = lines;
+    return file;
+}
+
+/// `[proc,root]` calls `[proc,callee]`, branches over its own line 3 and adds 1 to the
+/// 7 the callee returns. `[proc,unused]` is in another file and never called.
+pub fn engine() -> Engine {
+    let mut engine = Engine::mock();
+    let callee: ScriptFile = script(
+        "[proc,callee]",
+        vec![ScriptOpcode::PushConstantInt, ScriptOpcode::Return],
+        vec![7, 0],
+        vec![10, 11],
+    );
+    let root: ScriptFile = script(
+        "[proc,root]",
+        vec![
+            ScriptOpcode::GoSubWithParams,
+            ScriptOpcode::Branch,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Add,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 1, 5, 1, 0, 0],
+        vec![1, 2, 3, 4, 4, 5],
+    );
+    let mut unused: ScriptFile = script(
+        "[proc,unused]",
+        vec![ScriptOpcode::Return],
+        vec![0],
+        vec![1],
+    );
+    unused.info.path = "scripts/unused.rs2".to_string();
+    engine.cache.script_provider = Rc::new(ScriptProvider::mock_with(vec![callee, root, unused]));
+    return engine;
+}
+
+pub fn get<'a>(engine: &'a Engine, name: &str) -> &'a ScriptFile {
+    return engine.cache.script_provider.get_by_name(name).unwrap();
+}
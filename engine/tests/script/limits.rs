@@ -0,0 +1,146 @@
+use cache::{
+    ScriptError, ScriptErrorKind, ScriptFile, ScriptLimits, ScriptOpcode, ScriptState,
+    TriggerLimits,
+};
+use engine::engine::Engine;
+
+fn state<'a>(file: &'a ScriptFile, limits: ScriptLimits) -> ScriptState<'a> {
+    return ScriptState::new_with_limits(file, Vec::new(), Vec::new(), limits);
+}
+
+#[test]
+fn test_limits_instructions() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::Branch], vec![-1]);
+    let limits = ScriptLimits {
+        instructions: 10,
+        ..ScriptLimits::DEFAULT
+    };
+    let mut state = state(&file, limits);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::RunawayScript, error.kind);
+    assert_eq!(10, state.opcount);
+}
+
+#[test]
+fn test_limits_frames() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::GoSubWithParams], vec![0]);
+    let limits = ScriptLimits {
+        frames: 0,
+        ..ScriptLimits::DEFAULT
+    };
+    let mut state = state(&file, limits);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackOverflow, error.kind);
+}
+
+#[test]
+fn test_limits_int_stack() {
+    let file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Return,
+        ],
+        vec![1, 2, 0],
+    );
+    let limits = ScriptLimits {
+        int_stack: 1,
+        ..ScriptLimits::DEFAULT
+    };
+    let mut state = state(&file, limits);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackOverflow, error.kind);
+    assert_eq!(1, error.pc);
+}
+
+#[test]
+fn test_limits_string_length() {
+    let mut file = ScriptFile::mock_with(
+        vec![
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::JoinString,
+            ScriptOpcode::Return,
+        ],
+        vec![0, 0, 2, 0],
+    );
+    file.string_operands[0] = "abc".to_string();
+    file.string_operands[1] = "def".to_string();
+    let limits = ScriptLimits {
+        string_length: 5,
+        ..ScriptLimits::DEFAULT
+    };
+    let mut state = state(&file, limits);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(
+        ScriptErrorKind::LimitExceeded {
+            limit: "string length",
+            max: 5
+        },
+        error.kind
+    );
+    assert_eq!(2, error.pc);
+    assert!(error
+        .to_string()
+        .starts_with("Exceeded the string length limit of 5 (JoinString)"));
+}
+
+#[test]
+fn test_limits_discard_underflow() {
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::PopIntDiscard], vec![0]);
+    let mut state = ScriptState::mock(&file);
+    let engine = Engine::mock();
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackUnderflow, error.kind);
+
+    let file = ScriptFile::mock_with(vec![ScriptOpcode::PopStringDiscard], vec![0]);
+    let mut state = ScriptState::mock(&file);
+    let error: ScriptError = state.execute(&engine).unwrap_err();
+    assert_eq!(ScriptErrorKind::StackUnderflow, error.kind);
+}
+
+#[test]
+fn test_limits_by_trigger() {
+    let debug = ScriptLimits {
+        instructions: 1_000_000,
+        ..ScriptLimits::DEFAULT
+    };
+    let mut limits = TriggerLimits::new(ScriptLimits::DEFAULT);
+    limits.set("debugproc", debug);
+
+    let mut file = ScriptFile::mock();
+    file.info.name = "[debugproc,fib]".to_string();
+    assert_eq!(debug, limits.get(&file));
+    file.info.name = "[proc,fib]".to_string();
+    assert_eq!(ScriptLimits::DEFAULT, limits.get(&file));
+}
+
+#[test]
+fn test_engine_script_state_limits() {
+    let mut engine = Engine::mock();
+    let debug = ScriptLimits {
+        instructions: 1_000_000,
+        ..ScriptLimits::DEFAULT
+    };
+    engine.limits.set("debugproc", debug);
+
+    let mut file = ScriptFile::mock();
+    file.info.name = "[debugproc,fib]".to_string();
+    assert_eq!(
+        debug,
+        engine
+            .script_state(&engine.cache.script_provider, &file, Vec::new(), Vec::new())
+            .limits
+    );
+    file.info.name = "[proc,fib]".to_string();
+    assert_eq!(
+        ScriptLimits::DEFAULT,
+        engine
+            .script_state(&engine.cache.script_provider, &file, Vec::new(), Vec::new())
+            .limits
+    );
+}
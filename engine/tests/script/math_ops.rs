@@ -1,16 +1,16 @@
-use cache::{ScriptFile, ScriptOpcode, ScriptState};
+use cache::{ScriptErrorKind, ScriptFile, ScriptOpcode, ScriptState};
 use engine::script::ops::math_ops::MathOps;
 
 #[test]
 fn test_add() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Add);
-    assert_eq!(3, state.pop_int());
+    assert_eq!(3, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -18,12 +18,12 @@ fn test_add() {
 fn test_sub() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Sub);
-    assert_eq!(-1, state.pop_int());
+    assert_eq!(-1, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -31,12 +31,12 @@ fn test_sub() {
 fn test_multiply() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(1);
-    state.push_int(2);
+    state.push_int(1).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Multiply);
-    assert_eq!(2, state.pop_int());
+    assert_eq!(2, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -44,12 +44,12 @@ fn test_multiply() {
 fn test_divide() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(50);
-    state.push_int(5);
+    state.push_int(50).unwrap();
+    state.push_int(5).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Divide);
-    assert_eq!(10, state.pop_int());
+    assert_eq!(10, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -57,11 +57,11 @@ fn test_divide() {
 fn test_random() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(100);
+    state.push_int(100).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Random);
-    let rand = state.pop_int();
+    let rand = state.pop_int().unwrap();
     assert!(rand >= 0 && rand <= 99);
     assert!(result.is_ok());
 }
@@ -70,11 +70,11 @@ fn test_random() {
 fn test_randominc() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(100);
+    state.push_int(100).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::RandomInc);
-    let rand = state.pop_int();
+    let rand = state.pop_int().unwrap();
     assert!(rand >= 0 && rand <= 100);
     assert!(result.is_ok());
 }
@@ -83,12 +83,12 @@ fn test_randominc() {
 fn test_addpercent() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(50);
-    state.push_int(6);
+    state.push_int(50).unwrap();
+    state.push_int(6).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::AddPercent);
-    assert_eq!(53, state.pop_int());
+    assert_eq!(53, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -96,12 +96,12 @@ fn test_addpercent() {
 fn test_setbit() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(11);
-    state.push_int(2);
+    state.push_int(11).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::SetBit);
-    assert_eq!(15, state.pop_int());
+    assert_eq!(15, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -109,12 +109,12 @@ fn test_setbit() {
 fn test_clearbit() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(15);
-    state.push_int(2);
+    state.push_int(15).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::ClearBit);
-    assert_eq!(11, state.pop_int());
+    assert_eq!(11, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -122,12 +122,12 @@ fn test_clearbit() {
 fn test_testbit_1() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(15);
-    state.push_int(2);
+    state.push_int(15).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::TestBit);
-    assert_eq!(1, state.pop_int());
+    assert_eq!(1, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -135,12 +135,12 @@ fn test_testbit_1() {
 fn test_testbit_0() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(11);
-    state.push_int(2);
+    state.push_int(11).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::TestBit);
-    assert_eq!(0, state.pop_int());
+    assert_eq!(0, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -148,12 +148,12 @@ fn test_testbit_0() {
 fn test_modulo() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(41);
-    state.push_int(6);
+    state.push_int(41).unwrap();
+    state.push_int(6).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Modulo);
-    assert_eq!(5, state.pop_int());
+    assert_eq!(5, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -161,12 +161,12 @@ fn test_modulo() {
 fn test_pow() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(100);
-    state.push_int(2);
+    state.push_int(100).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Pow);
-    assert_eq!(10000, state.pop_int());
+    assert_eq!(10000, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -174,12 +174,12 @@ fn test_pow() {
 fn test_invpow() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(100);
-    state.push_int(2);
+    state.push_int(100).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::InvPow);
-    assert_eq!(10, state.pop_int());
+    assert_eq!(10, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -187,12 +187,12 @@ fn test_invpow() {
 fn test_and() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(46);
-    state.push_int(33);
+    state.push_int(46).unwrap();
+    state.push_int(33).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::And);
-    assert_eq!(32, state.pop_int());
+    assert_eq!(32, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -200,12 +200,12 @@ fn test_and() {
 fn test_or() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(46);
-    state.push_int(533);
+    state.push_int(46).unwrap();
+    state.push_int(533).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Or);
-    assert_eq!(575, state.pop_int());
+    assert_eq!(575, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -213,12 +213,12 @@ fn test_or() {
 fn test_min() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(46);
-    state.push_int(533);
+    state.push_int(46).unwrap();
+    state.push_int(533).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Min);
-    assert_eq!(46, state.pop_int());
+    assert_eq!(46, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -226,12 +226,12 @@ fn test_min() {
 fn test_max() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(46);
-    state.push_int(533);
+    state.push_int(46).unwrap();
+    state.push_int(533).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Max);
-    assert_eq!(533, state.pop_int());
+    assert_eq!(533, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -239,13 +239,13 @@ fn test_max() {
 fn test_scale() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(46);
-    state.push_int(533);
-    state.push_int(69);
+    state.push_int(46).unwrap();
+    state.push_int(533).unwrap();
+    state.push_int(69).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Scale);
-    assert_eq!(5, state.pop_int());
+    assert_eq!(5, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -253,11 +253,11 @@ fn test_scale() {
 fn test_bitcount() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(15);
+    state.push_int(15).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::BitCount);
-    assert_eq!(4, state.pop_int());
+    assert_eq!(4, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -265,12 +265,12 @@ fn test_bitcount() {
 fn test_togglebit() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(11);
-    state.push_int(2);
+    state.push_int(11).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::ToggleBit);
-    assert_eq!(15, state.pop_int());
+    assert_eq!(15, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -278,13 +278,13 @@ fn test_togglebit() {
 fn test_setbit_range() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(0);
-    state.push_int(1);
-    state.push_int(3);
+    state.push_int(0).unwrap();
+    state.push_int(1).unwrap();
+    state.push_int(3).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::SetBitRange);
-    assert_eq!(14, state.pop_int());
+    assert_eq!(14, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -292,13 +292,13 @@ fn test_setbit_range() {
 fn test_clearbit_range() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(15);
-    state.push_int(1);
-    state.push_int(3);
+    state.push_int(15).unwrap();
+    state.push_int(1).unwrap();
+    state.push_int(3).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::ClearBitRange);
-    assert_eq!(1, state.pop_int());
+    assert_eq!(1, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -306,13 +306,13 @@ fn test_clearbit_range() {
 fn test_getbit_range() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(15);
-    state.push_int(0);
-    state.push_int(2);
+    state.push_int(15).unwrap();
+    state.push_int(0).unwrap();
+    state.push_int(2).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::GetBitRange);
-    assert_eq!(7, state.pop_int());
+    assert_eq!(7, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -320,26 +320,47 @@ fn test_getbit_range() {
 fn test_setbit_range_toint() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(0);
-    state.push_int(3);
-    state.push_int(1);
-    state.push_int(3);
+    state.push_int(0).unwrap();
+    state.push_int(3).unwrap();
+    state.push_int(1).unwrap();
+    state.push_int(3).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::SetBitRangeToInt);
-    assert_eq!(6, state.pop_int());
+    assert_eq!(6, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_bit_range_invalid() {
+    let file = ScriptFile::mock();
+    let ops = MathOps::new();
+    for code in [
+        ScriptOpcode::SetBitRange,
+        ScriptOpcode::ClearBitRange,
+        ScriptOpcode::GetBitRange,
+        ScriptOpcode::SetBitRangeToInt,
+    ] {
+        for (start, end) in [(5, 2), (-1, 3), (0, 32)] {
+            let mut state = ScriptState::mock(&file);
+            for value in [0, 1, start, end] {
+                state.push_int(value).unwrap();
+            }
+            let result = ops.push(&mut state, &code);
+            assert!(matches!(result, Err(ScriptErrorKind::Runtime(_))));
+        }
+    }
+}
+
 #[test]
 fn test_sin_deg() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(std::f64::consts::PI as i32);
+    state.push_int(std::f64::consts::PI as i32).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::SinDeg);
-    assert_eq!(18, state.pop_int());
+    assert_eq!(18, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -347,11 +368,11 @@ fn test_sin_deg() {
 fn test_cos_deg() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(std::f64::consts::PI as i32);
+    state.push_int(std::f64::consts::PI as i32).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::CosDeg);
-    assert_eq!(16383, state.pop_int());
+    assert_eq!(16383, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -359,12 +380,12 @@ fn test_cos_deg() {
 fn test_atan2_deg() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(-1);
-    state.push_int(1);
+    state.push_int(-1).unwrap();
+    state.push_int(1).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Atan2Deg);
-    assert_eq!(6144, state.pop_int());
+    assert_eq!(6144, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
 
@@ -372,10 +393,22 @@ fn test_atan2_deg() {
 fn test_abs() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(-136);
+    state.push_int(-136).unwrap();
+
+    let ops = MathOps::new();
+    let result = ops.push(&mut state, &ScriptOpcode::Abs);
+    assert_eq!(136, state.pop_int().unwrap());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_abs_min() {
+    let file = ScriptFile::mock();
+    let mut state = ScriptState::mock(&file);
+    state.push_int(i32::MIN).unwrap();
 
     let ops = MathOps::new();
     let result = ops.push(&mut state, &ScriptOpcode::Abs);
-    assert_eq!(136, state.pop_int());
+    assert_eq!(i32::MIN, state.pop_int().unwrap());
     assert!(result.is_ok());
 }
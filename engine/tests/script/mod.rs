@@ -1,5 +1,13 @@
 mod core_ops;
+mod coverage;
+mod debugger;
+mod execute;
+mod fixtures;
+mod limits;
 mod math_ops;
 mod oc_ops;
 mod player_ops;
+mod profiler;
+mod reload;
 mod string_ops;
+mod zone_ops;
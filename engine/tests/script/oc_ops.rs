@@ -8,7 +8,7 @@ fn test_oc_name() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(0);
+    state.push_int(0).unwrap();
 
     let mut engine = Engine::mock();
     engine.cache.obj_provider.objs.push(Some(ObjType {
@@ -59,6 +59,6 @@ fn test_oc_name() {
         debugname: None,
     }));
     let result = engine.push_script(&mut state, &ScriptOpcode::OcName);
-    assert_eq!("Hello World!", state.pop_string());
+    assert_eq!("Hello World!", state.pop_string().unwrap());
     assert!(result.is_ok());
 }
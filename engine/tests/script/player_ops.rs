@@ -10,7 +10,7 @@ pub fn test_bas_readyanim() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(69);
+    state.push_int(69).unwrap();
 
     let mut engine = Engine::mock();
     engine.add_player(0, Player::new());
@@ -42,8 +42,8 @@ pub fn test_anim_with_access() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(69);
-    state.push_int(420);
+    state.push_int(69).unwrap();
+    state.push_int(420).unwrap();
 
     let mut engine = Engine::mock();
     engine.add_player(0, Player::new());
@@ -61,14 +61,14 @@ pub fn test_find_uid_true() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(0);
+    state.push_int(0).unwrap();
 
     let mut engine = Engine::mock();
     engine.add_player(0, Player::new());
     let result = engine.push_script(&mut state, &ScriptOpcode::FindUid);
     assert!(result.is_ok());
     assert_eq!(0, state.get_active_player());
-    assert_eq!(1, state.pop_int());
+    assert_eq!(1, state.pop_int().unwrap());
 }
 
 #[test]
@@ -79,11 +79,11 @@ pub fn test_find_uid_false() {
     assert_eq!(-1, state.pc);
 
     state.pc += 1; // emulate starting the script program.
-    state.push_int(0);
+    state.push_int(0).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::FindUid);
     assert!(result.is_ok());
     assert_eq!(-1, state.get_active_player());
-    assert_eq!(0, state.pop_int());
+    assert_eq!(0, state.pop_int().unwrap());
 }
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use cache::{ScriptFile, ScriptOpcode, ScriptProfiler, ScriptState};
+use engine::engine::Engine;
+
+use super::fixtures::{engine, get};
+
+#[test]
+fn test_profile_script() {
+    let engine: Engine = engine();
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    let mut profiler: ScriptProfiler = ScriptProfiler::new();
+    let mut state = ScriptState::mock(file);
+    assert!(state.execute_profiled(&engine, &mut profiler).is_ok());
+    assert_eq!(8, state.pop_int().unwrap());
+
+    let root = &profiler.scripts["[proc,root]"];
+    assert_eq!(1, root.calls);
+    assert_eq!(5, root.instructions);
+    let callee = &profiler.scripts["[proc,callee]"];
+    assert_eq!(1, callee.calls);
+    assert_eq!(2, callee.instructions);
+
+    assert_eq!(2, profiler.opcodes[&ScriptOpcode::PushConstantInt].count);
+    assert_eq!(2, profiler.opcodes[&ScriptOpcode::Return].count);
+    assert_eq!(1, profiler.opcodes[&ScriptOpcode::Add].count);
+
+    let mut stacks: Vec<&String> = profiler.stacks.keys().collect();
+    stacks.sort();
+    assert_eq!(vec!["[proc,root]", "[proc,root];[proc,callee]"], stacks);
+
+    let folded: String = profiler.folded();
+    let lines: Vec<&str> = folded.lines().collect();
+    assert_eq!(2, lines.len());
+    assert!(lines[1].starts_with("[proc,root];[proc,callee] "));
+}
+
+#[test]
+fn test_profile_ticks() {
+    let engine: Engine = engine();
+    let file: &ScriptFile = get(&engine, "[proc,root]");
+    engine.attach_profiler(ScriptProfiler::new());
+    for _ in 0..3 {
+        let mut state = ScriptState::mock(file);
+        assert!(state.execute(&engine).is_ok());
+    }
+
+    let mut profiler: ScriptProfiler = engine.detach_profiler().unwrap();
+    assert_eq!(3, profiler.scripts["[proc,root]"].calls);
+    assert_eq!(3, profiler.tick_runs().len());
+    assert_eq!(7, profiler.tick_runs()[0].instructions);
+    assert!(profiler.worst.is_empty());
+
+    profiler.end_tick();
+    assert_eq!(1, profiler.ticks);
+    assert!(profiler.tick_runs().is_empty());
+    assert_eq!(1, profiler.worst.len());
+    assert_eq!(3, profiler.worst[0].len());
+    assert!(profiler.worst[0][0].time >= profiler.worst[0][2].time);
+    assert_eq!(0, profiler.worst[0][0].tick);
+
+    let report: String = profiler.report();
+    assert!(report.starts_with("Script profile over 1 ticks\n"));
+    assert!(report.contains("[proc,callee]"));
+    assert!(report.contains("PushConstantInt"));
+
+    profiler.reset();
+    assert!(profiler.scripts.is_empty());
+}
+
+#[test]
+fn test_profile_worst_ticks() {
+    let mut profiler: ScriptProfiler = ScriptProfiler::new();
+    for tick in 0..=ScriptProfiler::WORST_TICKS as u64 {
+        for run in 0..=ScriptProfiler::WORST_RUNS as u64 {
+            profiler.run("[proc,root]", run, Duration::from_micros(tick + run));
+        }
+        profiler.end_tick();
+    }
+
+    // the first tick has dropped out, every other kept its slowest runs.
+    assert_eq!(ScriptProfiler::WORST_TICKS, profiler.worst.len());
+    assert_eq!(1, profiler.worst[0][0].tick);
+    assert_eq!(ScriptProfiler::WORST_RUNS, profiler.worst[0].len());
+    assert_eq!(
+        ScriptProfiler::WORST_RUNS as u64,
+        profiler.worst[0][0].instructions
+    );
+    assert_eq!(
+        ScriptProfiler::WORST_TICKS as u64,
+        profiler.worst_runs()[0].tick
+    );
+}
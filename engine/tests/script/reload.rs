@@ -0,0 +1,133 @@
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use cache::{
+    CacheProvider, ScriptFile, ScriptOpcode, ScriptProvider, ScriptState, COMPILER_VERSION,
+};
+use engine::engine::Engine;
+use io::Packet;
+
+/// Writes a `script.dat` and `script.idx` holding `scripts`, each a name and its instructions.
+fn write_scripts(dir: &PathBuf, scripts: &[(&str, Vec<(ScriptOpcode, i32)>)]) {
+    std::fs::create_dir_all(dir.join("server")).unwrap();
+    let mut dat: Packet = Packet::new(4096);
+    let mut idx: Packet = Packet::new(4096);
+    dat.p2(scripts.len() as i32);
+    dat.p4(COMPILER_VERSION);
+    idx.p2(scripts.len() as i32);
+    for (name, instructions) in scripts {
+        let start: usize = dat.pos;
+        dat.pjstr(name, 0);
+        dat.pjstr("scripts/reload.rs2", 0);
+        dat.p4(-1); // lookup
+        dat.p1(0); // params
+        dat.p2(0); // lines
+        for (code, operand) in instructions {
+            dat.p2(code.clone() as i32);
+            if ScriptFile::is_large_operand(code) {
+                dat.p4(*operand);
+            } else {
+                dat.p1(*operand);
+            }
+        }
+        dat.p4(instructions.len() as i32);
+        dat.p2(0);
+        dat.p2(0);
+        dat.p2(0);
+        dat.p2(0);
+        dat.p1(0); // switches
+        dat.p2(1); // trailer
+        idx.p2((dat.pos - start) as i32);
+    }
+    std::fs::write(dir.join("server/script.dat"), &dat.data[..dat.pos]).unwrap();
+    std::fs::write(dir.join("server/script.idx"), &idx.data[..idx.pos]).unwrap();
+}
+
+/// A script that returns `value`.
+fn returns(value: i32) -> Vec<(ScriptOpcode, i32)> {
+    return vec![
+        (ScriptOpcode::PushConstantInt, value),
+        (ScriptOpcode::Return, 0),
+    ];
+}
+
+/// `[proc,old]` returns what script 1, `[proc,callee]`, returns.
+fn old_scripts() -> Vec<(&'static str, Vec<(ScriptOpcode, i32)>)> {
+    return vec![
+        (
+            "[proc,old]",
+            vec![
+                (ScriptOpcode::GoSubWithParams, 1),
+                (ScriptOpcode::Return, 0),
+            ],
+        ),
+        ("[proc,callee]", returns(1)),
+    ];
+}
+
+/// Script 1 is now `[proc,other]`, which returns something else.
+fn new_scripts() -> Vec<(&'static str, Vec<(ScriptOpcode, i32)>)> {
+    return vec![("[proc,new]", returns(0)), ("[proc,other]", returns(2))];
+}
+
+fn engine(name: &str) -> (Engine, PathBuf) {
+    let dir: PathBuf = std::env::temp_dir().join(format!("reload_{}_{}", name, std::process::id()));
+    write_scripts(&dir, &old_scripts());
+    let mut engine: Engine = Engine::new(CacheProvider::mock_scripts(dir.to_str().unwrap()));
+    engine.reload_scripts().unwrap();
+    return (engine, dir);
+}
+
+fn loaded(engine: &Engine, name: &str) -> bool {
+    return engine.cache.script_provider.get_by_name(name).is_ok();
+}
+
+#[test]
+fn test_engine_reload_between_ticks() {
+    let (mut engine, dir) = engine("tick");
+    let old: Rc<ScriptProvider> = engine.cache.script_provider.clone();
+    let file: &ScriptFile = old.get_by_name("[proc,old]").unwrap();
+    let mut state: ScriptState = engine.script_state(&old, file, Vec::new(), Vec::new());
+
+    write_scripts(&dir, &new_scripts());
+    engine.request_reload();
+    engine.process_cycle();
+    assert!(!engine.reload_requested.get());
+    assert!(loaded(&engine, "[proc,new]"));
+    assert!(!loaded(&engine, "[proc,old]"));
+
+    // a run started before the reload finishes on the scripts it started with,
+    // gosubs included even though script 1 is another script now.
+    assert!(state.execute(&engine).is_ok());
+    assert_eq!(1, state.pop_int().unwrap());
+}
+
+#[test]
+fn test_engine_reload_keeps_scripts_without_request() {
+    let (mut engine, dir) = engine("no_request");
+    write_scripts(&dir, &new_scripts());
+    engine.process_cycle();
+    assert!(loaded(&engine, "[proc,old]"));
+}
+
+#[test]
+fn test_debug_server_reload() {
+    let (mut engine, dir) = engine("debug_server");
+    write_scripts(&dir, &new_scripts());
+    engine.listen_debugger("127.0.0.1:0").unwrap();
+    let mut client: TcpStream =
+        TcpStream::connect(engine.debug_server.as_ref().unwrap().addr()).unwrap();
+    client.write_all(b"reload\n").unwrap();
+
+    for _ in 0..100 {
+        engine.process_cycle();
+        if loaded(&engine, "[proc,new]") {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("the frontend's reload was never picked up");
+}
@@ -1,14 +1,42 @@
-use cache::{ScriptFile, ScriptOpcode, ScriptRunner, ScriptState};
+use cache::{ScriptErrorKind, ScriptFile, ScriptOpcode, ScriptRunner, ScriptState};
 use engine::engine::Engine;
 
 #[test]
 fn test_to_string() {
     let file = ScriptFile::mock();
     let mut state = ScriptState::mock(&file);
-    state.push_int(420);
+    state.push_int(420).unwrap();
 
     let engine = Engine::mock();
     let result = engine.push_script(&mut state, &ScriptOpcode::ToString);
-    assert_eq!("420", state.pop_string());
+    assert_eq!("420", state.pop_string().unwrap());
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_substring() {
+    let file = ScriptFile::mock();
+    let mut state = ScriptState::mock(&file);
+    state.push_int(1).unwrap();
+    state.push_int(3).unwrap();
+    state.push_string("abcd".to_string()).unwrap();
+
+    let engine = Engine::mock();
+    let result = engine.push_script(&mut state, &ScriptOpcode::SubString);
+    assert_eq!("bc", state.pop_string().unwrap());
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_substring_invalid_range() {
+    let file = ScriptFile::mock();
+    let engine = Engine::mock();
+    for (start, end, string) in [(0, 10, "abc"), (-1, 2, "abc"), (2, 1, "abc"), (0, 1, "éa")] {
+        let mut state = ScriptState::mock(&file);
+        state.push_int(start).unwrap();
+        state.push_int(end).unwrap();
+        state.push_string(string.to_string()).unwrap();
+        let result = engine.push_script(&mut state, &ScriptOpcode::SubString);
+        assert!(matches!(result, Err(ScriptErrorKind::Runtime(_))));
+    }
+}
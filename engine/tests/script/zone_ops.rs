@@ -0,0 +1,254 @@
+use cache::{
+    ScriptErrorKind, ScriptFile, ScriptLoc, ScriptOpcode, ScriptPointer, ScriptRunner, ScriptState,
+};
+use engine::engine::Engine;
+use engine::zone::coord_grid::CoordGrid;
+use engine::zone::zone::{LocRevert, Zone, ZoneLoc};
+use engine::zone::zone_event::{ProjAnim, ZoneEvent};
+use engine::zone::zone_map::ZoneMap;
+
+fn zone(engine: &Engine, coord: CoordGrid) -> Zone {
+    return engine
+        .zones
+        .borrow()
+        .get(ZoneMap::zone_index(coord))
+        .unwrap()
+        .clone();
+}
+
+#[test]
+pub fn test_spotanim_map() {
+    let file = ScriptFile::mock();
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let coord = CoordGrid::new(3222, 3218, 0);
+    state.push_int(5).unwrap();
+    state.push_int(coord.packed).unwrap();
+    state.push_int(100).unwrap();
+    state.push_int(30).unwrap();
+
+    let engine = Engine::mock();
+    let result = engine.push_script(&mut state, &ScriptOpcode::SpotAnimMap);
+    assert!(result.is_ok());
+    assert_eq!(
+        vec![ZoneEvent::MapAnim {
+            coord,
+            spotanim: 5,
+            height: 100,
+            delay: 30,
+        }],
+        zone(&engine, coord).events
+    );
+}
+
+#[test]
+pub fn test_projanim_map() {
+    let file = ScriptFile::mock();
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let from = CoordGrid::new(3222, 3218, 0);
+    let to = CoordGrid::new(3225, 3216, 0);
+    for value in [from.packed, to.packed, 10, 40, 36, 30, 20, 16, 64] {
+        state.push_int(value).unwrap();
+    }
+
+    let engine = Engine::mock();
+    let result = engine.push_script(&mut state, &ScriptOpcode::ProjAnimMap);
+    assert!(result.is_ok());
+    assert_eq!(
+        vec![ZoneEvent::MapProjAnim(ProjAnim {
+            from,
+            to,
+            target: 0,
+            spotanim: 10,
+            src_height: 40,
+            dst_height: 36,
+            start_delay: 30,
+            end_delay: 50,
+            peak: 16,
+            arc: 64,
+        })],
+        zone(&engine, from).events
+    );
+}
+
+#[test]
+pub fn test_loc_add_change_del() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let coord = CoordGrid::new(3222, 3218, 0);
+    for value in [coord.packed, 1530, 2, 10, 100] {
+        state.push_int(value).unwrap();
+    }
+
+    let engine = Engine::mock();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocAdd)
+        .is_ok());
+    assert!(state.pointer_get(ScriptPointer::ActiveLoc));
+    assert_eq!(
+        Ok(ScriptLoc {
+            coord: coord.packed,
+            loc: 1530,
+            shape: 10,
+            angle: 2,
+        }),
+        state.get_active_loc()
+    );
+
+    state.push_int(1531).unwrap();
+    state.push_int(100).unwrap();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocChange)
+        .is_ok());
+    assert_eq!(
+        vec![ZoneLoc {
+            coord,
+            loc: 1531,
+            shape: 10,
+            angle: 2,
+            revert: Some(LocRevert {
+                tick: 100,
+                loc: -1,
+                angle: 2,
+            }),
+        }],
+        zone(&engine, coord).locs
+    );
+
+    state.push_int(100).unwrap();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocDel)
+        .is_ok());
+    let zone: Zone = zone(&engine, coord);
+    assert_eq!(-1, zone.locs[0].loc);
+    assert_eq!(
+        ZoneEvent::LocDel {
+            coord,
+            shape: 10,
+            angle: 2,
+        },
+        zone.events[2]
+    );
+}
+
+#[test]
+pub fn test_loc_add_underflow() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let coord = CoordGrid::new(3222, 3218, 0);
+    for value in [1530, 2, 10, 100] {
+        state.push_int(value).unwrap();
+    }
+
+    let engine = Engine::mock();
+    assert_eq!(
+        Err(ScriptErrorKind::StackUnderflow),
+        engine.push_script(&mut state, &ScriptOpcode::LocAdd)
+    );
+    assert!(!state.pointer_get(ScriptPointer::ActiveLoc));
+    let zones = engine.zones.borrow();
+    assert!(zones.get(ZoneMap::zone_index(coord)).is_none());
+    assert!(zones
+        .get(ZoneMap::zone_index(CoordGrid::from(10)))
+        .is_none());
+}
+
+#[test]
+pub fn test_loc_add_reverts() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let coord = CoordGrid::new(3222, 3218, 0);
+    for value in [coord.packed, 1530, 2, 10, 5] {
+        state.push_int(value).unwrap();
+    }
+
+    let engine = Engine::mock();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocAdd)
+        .is_ok());
+    engine.zones.borrow_mut().process(4);
+    assert_eq!(1530, zone(&engine, coord).locs[0].loc);
+
+    engine.zones.borrow_mut().process(5);
+    let zone: Zone = zone(&engine, coord);
+    assert_eq!(-1, zone.locs[0].loc);
+    assert_eq!(None, zone.locs[0].revert);
+    assert_eq!(
+        Some(&ZoneEvent::LocDel {
+            coord,
+            shape: 10,
+            angle: 2,
+        }),
+        zone.events.last()
+    );
+}
+
+#[test]
+pub fn test_loc_change_reverts() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+
+    let coord = CoordGrid::new(3222, 3218, 0);
+    state.set_active_loc(ScriptLoc {
+        coord: coord.packed,
+        loc: 1530,
+        shape: 10,
+        angle: 2,
+    });
+    state.pointer_add(ScriptPointer::ActiveLoc);
+    state.push_int(1531).unwrap();
+    state.push_int(3).unwrap();
+
+    let engine = Engine::mock();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocChange)
+        .is_ok());
+    state.push_int(10).unwrap();
+    assert!(engine
+        .push_script(&mut state, &ScriptOpcode::LocDel)
+        .is_ok());
+
+    // the delete pushed the revert back, but still reverts to the original loc.
+    engine.zones.borrow_mut().process(3);
+    assert_eq!(-1, zone(&engine, coord).locs[0].loc);
+    engine.zones.borrow_mut().process(10);
+    let zone: Zone = zone(&engine, coord);
+    assert_eq!(1530, zone.locs[0].loc);
+    assert_eq!(
+        Some(&ZoneEvent::LocAddChange {
+            coord,
+            loc: 1530,
+            shape: 10,
+            angle: 2,
+        }),
+        zone.events.last()
+    );
+}
+
+#[test]
+pub fn test_loc_del_no_access() {
+    let mut file = ScriptFile::mock();
+    file.int_operands.push(0);
+    let mut state = ScriptState::mock(&file);
+    state.pc += 1; // emulate starting the script program.
+    state.push_int(100).unwrap();
+
+    let engine = Engine::mock();
+    let result = engine.push_script(&mut state, &ScriptOpcode::LocDel);
+    assert!(result.is_err());
+    assert!(engine.zones.borrow().zones.is_empty());
+}
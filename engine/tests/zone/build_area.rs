@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use engine::engine::Engine;
+use engine::entity::player::Player;
+use engine::network::server_prot::ServerProt;
+use engine::zone::build_area::BuildArea;
+use engine::zone::coord_grid::CoordGrid;
+use io::{Packet, PacketPool};
+
+#[test]
+fn test_needs_rebuild_on_login() {
+    let build_area = BuildArea::new();
+    assert!(build_area.needs_rebuild(CoordGrid::new(3222, 3222, 0)));
+}
+
+#[test]
+fn test_needs_rebuild_near_edge() {
+    let mut build_area = BuildArea::new();
+    build_area.rebuild(
+        CoordGrid::new(3222, 3222, 0),
+        &HashMap::new(),
+        &mut PacketPool::new(),
+    );
+    assert!(!build_area.needs_rebuild(CoordGrid::new(3222, 3222, 0)));
+    assert!(!build_area.needs_rebuild(CoordGrid::new(3255, 3222, 0)));
+    assert!(build_area.needs_rebuild(CoordGrid::new(3256, 3222, 0)));
+    assert!(build_area.needs_rebuild(CoordGrid::new(3222, 3183, 0)));
+}
+
+#[test]
+fn test_rebuild_normal() {
+    let mut map_crcs: HashMap<String, i32> = HashMap::new();
+    map_crcs.insert("m50_50".to_string(), 1234);
+    map_crcs.insert("l50_50".to_string(), 5678);
+
+    let mut buf: Packet = BuildArea::rebuild_normal(
+        CoordGrid::new(3222, 3222, 0),
+        &map_crcs,
+        &mut PacketPool::new(),
+    );
+    buf.pos = 0; // Resetting the packet for showing test case.
+    assert_eq!(ServerProt::RebuildNormal as u8, buf.g1());
+    assert_eq!(4 + 9 * 10, buf.g2());
+    assert_eq!(402, buf.g2()); // zone x
+    assert_eq!(402, buf.g2()); // zone z
+    for x in 49..=51 {
+        for z in 49..=51 {
+            assert_eq!(x, buf.g1());
+            assert_eq!(z, buf.g1());
+            if x == 50 && z == 50 {
+                assert_eq!(1234, buf.g4s());
+                assert_eq!(5678, buf.g4s());
+            } else {
+                assert_eq!(0, buf.g4s());
+                assert_eq!(0, buf.g4s());
+            }
+        }
+    }
+    assert_eq!(0, buf.remaining());
+}
+
+#[test]
+fn test_rebuild_on_first_cycle() {
+    let mut engine = Engine::mock();
+    engine.add_player(0, Player::new());
+    engine.process_tick();
+
+    let player = engine.get_player(0).unwrap();
+    assert_eq!(
+        ServerProt::RebuildNormal as u8,
+        player.write_queue[0].data[0]
+    );
+    // every zone in view has been sent as a full update.
+    assert_eq!(49, player.build_area.active_zones.len());
+    assert_eq!(49, player.build_area.loaded_zones.len());
+    assert_eq!(
+        49,
+        player
+            .write_queue
+            .iter()
+            .filter(|buf| buf.data[0] == ServerProt::UpdateZoneFullFollows as u8)
+            .count()
+    );
+}
+
+#[test]
+fn test_no_rebuild_when_standing_still() {
+    let mut engine = Engine::mock();
+    engine.add_player(0, Player::new());
+    engine.process_cycle();
+    engine.process_tick();
+
+    // the first tick's rebuild has been sent, the second tick doesn't write another.
+    let player = engine.get_player(0).unwrap();
+    assert_eq!(
+        0,
+        player
+            .write_queue
+            .iter()
+            .filter(|buf| buf.data[0] == ServerProt::RebuildNormal as u8)
+            .count()
+    );
+}
+
+#[test]
+fn test_cleanup_reuses_packets() {
+    let mut engine = Engine::mock();
+    engine.add_player(0, Player::new());
+    engine.process_cycle();
+
+    assert!(engine.get_player(0).unwrap().write_queue.is_empty());
+    let allocations: usize = engine.packet_pool.allocations();
+    assert_eq!(allocations, engine.packet_pool.available());
+
+    // a second login in the same area is sent the same packets, all from reused buffers.
+    engine.add_player(1, Player::new());
+    engine.process_cycle();
+    assert_eq!(allocations, engine.packet_pool.allocations());
+    assert_eq!(allocations, engine.packet_pool.reuses());
+}
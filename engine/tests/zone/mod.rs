@@ -0,0 +1,2 @@
+mod build_area;
+mod zone_map;
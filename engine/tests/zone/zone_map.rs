@@ -0,0 +1,138 @@
+use cache::ScriptEngine;
+use engine::engine::Engine;
+use engine::entity::player::Player;
+use engine::network::server_prot::ServerProt;
+use engine::zone::coord_grid::CoordGrid;
+use engine::zone::zone_event::ZoneEvent;
+use engine::zone::zone_map::ZoneMap;
+use io::Packet;
+
+#[test]
+fn test_coord_grid() {
+    let coord = CoordGrid::new(3222, 3218, 1);
+    assert_eq!(3222, coord.x());
+    assert_eq!(3218, coord.z());
+    assert_eq!(1, coord.level());
+    assert_eq!(402, coord.zone_x());
+    assert_eq!(50, coord.mapsquare_x());
+    assert_eq!(0x62, coord.packed_zone_coord());
+}
+
+#[test]
+fn test_zone_index() {
+    let coord = CoordGrid::new(3222, 3218, 1);
+    let index: i32 = ZoneMap::zone_index(coord);
+    assert_eq!(CoordGrid::new(3216, 3216, 1), ZoneMap::unpack_index(index));
+}
+
+#[test]
+fn test_obj_add_encode() {
+    let event = ZoneEvent::ObjAdd {
+        coord: CoordGrid::new(3222, 3218, 0),
+        obj: 995,
+        count: 100,
+    };
+    let mut buf: Packet = Packet::new(event.size());
+    event.encode(&mut buf);
+    assert_eq!(
+        vec![ServerProt::ObjAdd as u8, 0x62, 0x03, 0xe3, 0x00, 0x64],
+        buf.data
+    );
+}
+
+#[test]
+fn test_shared_buffer() {
+    let coord = CoordGrid::new(3222, 3218, 0);
+    let mut zones = ZoneMap::new();
+    zones.add_obj(coord, 995, 100, -1);
+    zones.anim_map(coord, 1, 2, 3);
+    zones.process(0);
+
+    let shared: &Packet = zones
+        .get(ZoneMap::zone_index(coord))
+        .and_then(|zone| zone.shared.as_ref())
+        .unwrap();
+    assert_eq!(6 + 7, shared.len());
+    assert_eq!(ServerProt::ObjAdd as u8, shared.data[0]);
+    assert_eq!(ServerProt::MapAnim as u8, shared.data[6]);
+
+    zones.reset();
+    assert!(zones
+        .get(ZoneMap::zone_index(coord))
+        .unwrap()
+        .shared
+        .is_none());
+}
+
+#[test]
+fn test_obj_despawn() {
+    let coord = CoordGrid::new(3222, 3218, 0);
+    let mut zones = ZoneMap::new();
+    zones.add_obj(coord, 995, 100, 5);
+    zones.reset();
+    zones.process(4);
+    assert_eq!(1, zones.get(ZoneMap::zone_index(coord)).unwrap().objs.len());
+    zones.process(5);
+    let zone = zones.get(ZoneMap::zone_index(coord)).unwrap();
+    assert!(zone.objs.is_empty());
+    assert_eq!(ZoneEvent::ObjDel { coord, obj: 995 }, zone.events[0]);
+}
+
+#[test]
+fn test_full_snapshot_on_entry() {
+    let mut engine = Engine::mock();
+    let mut player = Player::new();
+    player.coord = CoordGrid::new(3222, 3218, 0);
+    engine.add_player(0, player);
+    assert!(engine.add_obj(CoordGrid::new(3222, 3218, 0).packed, 995, 100, 200));
+    engine.process_tick();
+
+    let player = engine.get_player(0).unwrap();
+    let obj_adds: Vec<&Packet> = player
+        .write_queue
+        .iter()
+        .filter(|buf| buf.data[0] == ServerProt::ObjAdd as u8)
+        .collect();
+    assert_eq!(1, obj_adds.len());
+    assert!(!player
+        .write_queue
+        .iter()
+        .any(|buf| buf.data[0] == ServerProt::UpdateZonePartialEnclosed as u8));
+}
+
+#[test]
+fn test_partial_enclosed_after_entry() {
+    let mut engine = Engine::mock();
+    let mut player = Player::new();
+    player.coord = CoordGrid::new(3222, 3218, 0);
+    engine.add_player(0, player);
+    engine.process_cycle();
+
+    assert!(engine.add_obj(CoordGrid::new(3222, 3218, 0).packed, 995, 100, 200));
+    engine.process_tick();
+
+    let player = engine.get_player(0).unwrap();
+    let enclosed: Vec<&Packet> = player
+        .write_queue
+        .iter()
+        .filter(|buf| buf.data[0] == ServerProt::UpdateZonePartialEnclosed as u8)
+        .collect();
+    assert_eq!(1, enclosed.len());
+    // opcode, size, local zone x, local zone z, then the shared buffer.
+    assert_eq!(
+        vec![
+            ServerProt::UpdateZonePartialEnclosed as u8,
+            0,
+            8,
+            48,
+            48,
+            ServerProt::ObjAdd as u8,
+            0x62,
+            0x03,
+            0xe3,
+            0x00,
+            0x64
+        ],
+        enclosed[0].data
+    );
+}
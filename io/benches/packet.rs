@@ -1,7 +1,7 @@
 use criterion::*;
 use criterion::measurement::WallTime;
 
-use io::Packet;
+use io::{Packet, PacketPool};
 
 fn bench_pbit(c: &mut Criterion) {
     let mut group: BenchmarkGroup<WallTime> = c.benchmark_group("packet");
@@ -686,6 +686,95 @@ fn bench_gdata(c: &mut Criterion) {
     group.finish();
 }
 
+// a full world, every player gets this every tick
+const WORLD_PLAYERS: usize = 2048;
+const PLAYER_INFO: usize = 5000;
+const NPC_INFO: usize = 5000;
+const ZONE_HEADERS: usize = 8;
+const ZONE_EVENTS: usize = 8;
+const ZONE_ENCLOSED: usize = 4;
+
+// writes the output of one tick for every player, each packet from `take`
+fn world_tick(queue: &mut Vec<Packet>, mut take: impl FnMut(usize) -> Packet) {
+    for _ in 0..WORLD_PLAYERS {
+        let mut buf: Packet = take(PLAYER_INFO);
+        buf.p1(184);
+        queue.push(buf);
+        let mut buf: Packet = take(NPC_INFO);
+        buf.p1(1);
+        queue.push(buf);
+        for _ in 0..ZONE_HEADERS {
+            let mut buf: Packet = take(3);
+            buf.p1(59);
+            queue.push(buf);
+        }
+        for _ in 0..ZONE_EVENTS {
+            let mut buf: Packet = take(7);
+            buf.p1(223);
+            queue.push(buf);
+        }
+        for _ in 0..ZONE_ENCLOSED {
+            let mut buf: Packet = take(40);
+            buf.p1(135);
+            queue.push(buf);
+        }
+    }
+}
+
+fn bench_world_new(c: &mut Criterion) {
+    let mut group: BenchmarkGroup<WallTime> = c.benchmark_group("packet");
+
+    // Define the throughput in ticks of a full world
+    group.throughput(Throughput::Elements(1));
+
+    let mut queue: Vec<Packet> = Vec::new();
+
+    group.bench_function("world_new", move |b| {
+        b.iter(|| {
+            world_tick(&mut queue, Packet::new);
+            // sent, the buffers are freed
+            queue.clear();
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_world_pool(c: &mut Criterion) {
+    let mut group: BenchmarkGroup<WallTime> = c.benchmark_group("packet");
+
+    // Define the throughput in ticks of a full world
+    group.throughput(Throughput::Elements(1));
+
+    let mut pool: PacketPool = PacketPool::new();
+    let mut queue: Vec<Packet> = Vec::new();
+
+    // the first tick allocates every buffer, every tick after reuses them
+    for _ in 0..2 {
+        world_tick(&mut queue, |size| pool.take(size));
+        for buf in queue.drain(..) {
+            pool.give(buf);
+        }
+    }
+    println!(
+        "world: {} packets per tick, {} allocated by the pool over 2 ticks",
+        WORLD_PLAYERS * (2 + ZONE_HEADERS + ZONE_EVENTS + ZONE_ENCLOSED),
+        pool.allocations()
+    );
+
+    group.bench_function("world_pool", move |b| {
+        b.iter(|| {
+            world_tick(&mut queue, |size| pool.take(size));
+            // sent, the buffers go back in cleanup
+            for buf in queue.drain(..) {
+                pool.give(buf);
+            }
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_pbit,
@@ -713,7 +802,9 @@ criterion_group!(
     bench_psmarts_2,
     bench_gsmarts_2,
     bench_pdata,
-    bench_gdata
+    bench_gdata,
+    bench_world_new,
+    bench_world_pool
 );
 
 criterion_main!(benches);
@@ -0,0 +1,34 @@
+use io::RsaKeyPair;
+
+/// Prints the modulus and exponent a patched client needs to log in with our key.
+///
+/// `rsakey [pem]` reads the key, `pem` defaults to `./data/config/private.pem`.
+/// `rsakey generate <bits> <pem>` writes a new key to `pem` first.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let key: Result<RsaKeyPair, String> = match args.as_slice() {
+        [command, bits, path] if command == "generate" => generate(bits, path),
+        [path] => RsaKeyPair::io(path),
+        [] => RsaKeyPair::io("./data/config/private.pem"),
+        _ => Err("Usage: rsakey [pem] | rsakey generate <bits> <pem>".to_string()),
+    };
+    match key {
+        Ok(key) => {
+            println!("modulus: {}", key.client_modulus());
+            println!("exponent: {}", key.client_exponent());
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn generate(bits: &str, path: &str) -> Result<RsaKeyPair, String> {
+    let bits: usize = bits
+        .parse()
+        .map_err(|_| format!("Invalid bits: {}", bits))?;
+    let key: RsaKeyPair = RsaKeyPair::generate(bits)?;
+    std::fs::write(path, key.to_pem()?).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    return Ok(key);
+}
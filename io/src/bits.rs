@@ -0,0 +1,255 @@
+use crate::Packet;
+
+// the most bytes a writer can be given without its limit in bits overflowing
+const UNLIMITED: usize = usize::MAX >> 3;
+
+/// Writes bit fields of up to 32 bits into a `Packet`, like the client's `pBit`.
+///
+/// The writer starts at the packet's byte position and the packet is back in byte
+/// mode, positioned after the last partly written byte, once the writer is finished
+/// or dropped. The buffer grows when a field doesn't fit, though the byte writes
+/// after it still need room of their own. A writer can also be given a capacity for
+/// how many bytes the packet may take up, so a caller like player info can check
+/// `remaining` and stop adding to it before the packet is full.
+///
+/// # Example
+///
+/// ```rust
+/// use io::{BitWriter, Packet};
+///
+/// let mut packet: Packet = Packet::new(3);
+/// let mut bits: BitWriter = BitWriter::new(&mut packet);
+/// bits.pbit(1, 1);
+/// bits.pbit(11, 2047);
+/// bits.finish();
+/// packet.p1(255);
+/// assert_eq!(vec![255, 240, 255], packet.data);
+/// ```
+pub struct BitWriter<'a> {
+    packet: &'a mut Packet,
+    bit_pos: usize,
+    limit: usize,
+}
+
+impl<'a> BitWriter<'a> {
+    /// Starts writing at the byte position of `packet`, growing it as needed.
+    pub fn new(packet: &'a mut Packet) -> BitWriter<'a> {
+        return BitWriter::with_capacity(packet, UNLIMITED);
+    }
+
+    /// Starts writing at the byte position of `packet`, with room for `capacity` bytes
+    /// in total, counted from the start of the packet.
+    pub fn with_capacity(packet: &'a mut Packet, capacity: usize) -> BitWriter<'a> {
+        let bit_pos: usize = packet.pos << 3;
+        return BitWriter {
+            packet,
+            bit_pos,
+            limit: capacity << 3,
+        };
+    }
+
+    /// Continues writing at the bit position of `packet`, as left by `Packet::bits` or
+    /// an earlier writer, growing it as needed.
+    pub fn resume(packet: &'a mut Packet) -> BitWriter<'a> {
+        let bit_pos: usize = packet.bit_pos;
+        return BitWriter {
+            packet,
+            bit_pos,
+            limit: UNLIMITED << 3,
+        };
+    }
+
+    /// The number of bits that can still be written.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        return self.limit.saturating_sub(self.bit_pos);
+    }
+
+    #[inline(always)]
+    pub fn bit_pos(&self) -> usize {
+        return self.bit_pos;
+    }
+
+    /// Writes the lowest `n` bits of `value`, most significant first.
+    ///
+    /// # Returns
+    ///
+    /// An error when `n` is more than 32 or there is no capacity left for it, in which
+    /// case nothing is written.
+    pub fn try_pbit(&mut self, n: usize, value: u32) -> Result<(), String> {
+        if n > 32 {
+            return Err(format!("Can't write {} bits at once", n));
+        }
+        if n > self.remaining() {
+            return Err(format!(
+                "Can't write {} bits, {} bits remaining",
+                n,
+                self.remaining()
+            ));
+        }
+
+        let end: usize = (self.bit_pos + n).div_ceil(8);
+        if end > self.packet.data.len() {
+            let size: usize = end.max(self.packet.data.len() * 2);
+            self.packet.data.resize(size, 0);
+        }
+
+        let mut n: usize = n;
+        while n > 0 {
+            let byte_pos: usize = self.bit_pos >> 3;
+            let free: usize = 8 - (self.bit_pos & 7);
+            let count: usize = free.min(n);
+            let shift: usize = free - count;
+            let mask: u8 = (((1u32 << count) - 1) << shift) as u8;
+            let bits: u8 = ((value >> (n - count)) as u8) << shift;
+            let byte: &mut u8 = &mut self.packet.data[byte_pos];
+            *byte = (*byte & !mask) | (bits & mask);
+            self.bit_pos += count;
+            n -= count;
+        }
+        return Ok(());
+    }
+
+    /// Writes the lowest `n` bits of `value`, most significant first.
+    ///
+    /// # Panics
+    ///
+    /// When `n` is more than 32 or there is no capacity left for it.
+    pub fn pbit(&mut self, n: usize, value: u32) {
+        if let Err(e) = self.try_pbit(n, value) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Switches the packet back to byte mode. This also happens when the writer is dropped.
+    pub fn finish(self) {}
+
+    fn flush(&mut self) {
+        self.packet.bit_pos = self.bit_pos;
+        self.packet.pos = self.bit_pos.div_ceil(8);
+    }
+}
+
+impl Drop for BitWriter<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Reads bit fields of up to 32 bits from a `Packet`, like the client's `gBit`.
+///
+/// The reader starts at the packet's byte position and every read is bounds checked.
+/// The packet is back in byte mode, positioned after the last partly read byte, once
+/// the reader is finished or dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use io::{BitReader, Packet};
+///
+/// let mut packet: Packet = Packet::from(vec![255, 240, 255]);
+/// let mut bits: BitReader = BitReader::new(&mut packet);
+/// assert_eq!(1, bits.gbit(1));
+/// assert_eq!(2047, bits.gbit(11));
+/// bits.finish();
+/// assert_eq!(255, packet.g1());
+/// ```
+pub struct BitReader<'a> {
+    packet: &'a mut Packet,
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Starts reading at the byte position of `packet`.
+    pub fn new(packet: &'a mut Packet) -> BitReader<'a> {
+        let bit_pos: usize = packet.pos << 3;
+        return BitReader { packet, bit_pos };
+    }
+
+    /// Continues reading at the bit position of `packet`, as left by `Packet::bits` or
+    /// an earlier reader.
+    pub fn resume(packet: &'a mut Packet) -> BitReader<'a> {
+        let bit_pos: usize = packet.bit_pos;
+        return BitReader { packet, bit_pos };
+    }
+
+    /// The number of bits left to read.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        return (self.packet.data.len() << 3).saturating_sub(self.bit_pos);
+    }
+
+    #[inline(always)]
+    pub fn bit_pos(&self) -> usize {
+        return self.bit_pos;
+    }
+
+    /// Reads `n` bits, most significant first.
+    ///
+    /// # Returns
+    ///
+    /// The bits, or an error when `n` is more than 32 or fewer than `n` bits are left,
+    /// in which case the position stays where it was.
+    pub fn try_gbit(&mut self, n: usize) -> Result<u32, String> {
+        return read_bits(&self.packet.data, &mut self.bit_pos, n);
+    }
+
+    /// Reads `n` bits, most significant first.
+    ///
+    /// # Panics
+    ///
+    /// When `n` is more than 32 or fewer than `n` bits are left.
+    pub fn gbit(&mut self, n: usize) -> u32 {
+        return match self.try_gbit(n) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    /// Switches the packet back to byte mode. This also happens when the reader is dropped.
+    pub fn finish(self) {}
+
+    fn flush(&mut self) {
+        self.packet.bit_pos = self.bit_pos;
+        self.packet.pos = self.bit_pos.div_ceil(8);
+    }
+}
+
+impl Drop for BitReader<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Reads `n` bits from `data` at `bit_pos`, most significant first, and moves `bit_pos`
+/// past them. Shared by `BitReader` and `PacketReader`.
+///
+/// # Returns
+///
+/// The bits, or an error when `n` is more than 32 or fewer than `n` bits are left,
+/// in which case `bit_pos` stays where it was.
+pub fn read_bits(data: &[u8], bit_pos: &mut usize, n: usize) -> Result<u32, String> {
+    if n > 32 {
+        return Err(format!("Can't read {} bits at once", n));
+    }
+    let remaining: usize = (data.len() << 3).saturating_sub(*bit_pos);
+    if n > remaining {
+        return Err(format!(
+            "Can't read {} bits, {} bits remaining",
+            n, remaining
+        ));
+    }
+
+    let mut value: u32 = 0;
+    let mut n: usize = n;
+    while n > 0 {
+        let byte: u8 = data[*bit_pos >> 3];
+        let free: usize = 8 - (*bit_pos & 7);
+        let count: usize = free.min(n);
+        let bits: u32 = ((byte >> (free - count)) as u32) & ((1 << count) - 1);
+        value = (value << count) | bits;
+        *bit_pos += count;
+        n -= count;
+    }
+    return Ok(value);
+}
@@ -1,6 +1,7 @@
 use std::io::Read;
 
-use bzip2::read::{BzDecoder, BzEncoder};
+use bzip2::read::BzEncoder;
+use bzip2::{Decompress, Status};
 
 /// Decompresses a Bzip2 compressed `Vec<u8>` into a decompressed `Vec<u8>`.
 ///
@@ -12,6 +13,8 @@ use bzip2::read::{BzDecoder, BzEncoder};
 ///
 /// * `bytes` - A vector containing the compressed Bzip2 data.
 /// * `decompress_length` - The expected length of the decompressed data, in bytes.
+/// * `prepend_header` - Whether the data is missing its `BZh1` header, as it is stored inside of a `JagFile`.
+/// * `offset` - Where the compressed data starts in `bytes` when `prepend_header` is set.
 ///
 /// # Return
 ///
@@ -19,37 +22,114 @@ use bzip2::read::{BzDecoder, BzEncoder};
 ///
 /// # Panics
 ///
-/// This function will panic if the decompressed data's size does not match the expected length,
-/// as indicated by `decompress_length`. Ensure the correct value is provided for `decompress_length`
-/// to avoid this panic.
-///
-/// # Safety
-///
-/// This function does not use `unsafe` code, but callers must ensure that the provided
-/// `decompress_length` accurately reflects the actual size of the decompressed data. If the length
-/// is incorrect, the function will panic with an error message.
+/// This function will panic if the data can't be decompressed or the decompressed data's size does
+/// not match the expected length, as indicated by `decompress_length`. Use `bz2_try_decompress`
+/// for data that may be corrupt.
 pub fn bz2_decompress(
     bytes: Vec<u8>,
     decompress_length: usize,
     prepend_header: bool,
     offset: usize,
 ) -> Vec<u8> {
-    let compressed: Vec<u8> = if prepend_header {
-        let mut header = Vec::with_capacity(4 + bytes.len() - offset);
-        header.extend_from_slice(b"BZh1");
-        header.extend_from_slice(&bytes[offset..]);
-        header
+    let compressed: &[u8] = if prepend_header {
+        &bytes[offset..]
     } else {
-        bytes
+        &bytes
     };
+    return match bz2_try_decompress(compressed, decompress_length, prepend_header) {
+        Ok(decompressed) => decompressed,
+        Err(e) => panic!("Bzip2 could not decompress! {}", e),
+    };
+}
+
+/// Decompresses Bzip2 data like `bz2_decompress`, returning an error instead of panicking.
+///
+/// # Arguments
+///
+/// * `bytes` - The compressed Bzip2 data.
+/// * `decompress_length` - The expected length of the decompressed data, in bytes.
+/// * `prepend_header` - Whether the data is missing its `BZh1` header, as it is stored inside of a `JagFile`.
+///
+/// # Return
+///
+/// Returns the decompressed data, or an error when the data is corrupt or decompresses to
+/// more or fewer than `decompress_length` bytes.
+pub fn bz2_try_decompress(
+    bytes: &[u8],
+    decompress_length: usize,
+    prepend_header: bool,
+) -> Result<Vec<u8>, String> {
     let mut decompressed: Vec<u8> = vec![0u8; decompress_length];
-    BzDecoder::new(&compressed[..])
-        .read_exact(&mut decompressed)
-        .expect("Bzip2 could not decompress!");
-    if decompress_length != decompressed.len() {
-        panic!("Decompressed data size does not match expected length.");
+    bz2_decompress_into(bytes, &mut decompressed, prepend_header)?;
+    return Ok(decompressed);
+}
+
+/// Decompresses Bzip2 data into `out`, which must be exactly as long as the decompressed data.
+///
+/// The data is streamed into `out` without copying it or the compressed data first. Data of
+/// more than one block decompresses like any other, and streams compressed one after the other
+/// are decompressed one after the other into `out`.
+///
+/// # Arguments
+///
+/// * `bytes` - The compressed Bzip2 data.
+/// * `out` - Where the decompressed data is written.
+/// * `prepend_header` - Whether the data is missing its `BZh1` header, as it is stored inside of a `JagFile`.
+///
+/// # Return
+///
+/// Returns an error when the data is corrupt, ends before `out` is full, or has more data than fits in `out`.
+pub fn bz2_decompress_into(
+    bytes: &[u8],
+    out: &mut [u8],
+    prepend_header: bool,
+) -> Result<(), String> {
+    let mut input: &[u8] = bytes;
+    let mut written: usize = 0;
+    let mut header: bool = prepend_header;
+    while written < out.len() || !input.is_empty() {
+        if input.is_empty() {
+            return Err(format!(
+                "Bzip2 data is truncated, decompressed {} of {} bytes",
+                written,
+                out.len()
+            ));
+        }
+
+        let mut stream: Decompress = Decompress::new(false);
+        if header {
+            stream
+                .decompress(b"BZh1", &mut [])
+                .map_err(|e| format!("Bzip2 could not decompress: {}", e))?;
+            header = false;
+        }
+        loop {
+            let (read, wrote): (u64, u64) = (stream.total_in(), stream.total_out());
+            let status: Status = stream
+                .decompress(input, &mut out[written..])
+                .map_err(|e| format!("Bzip2 could not decompress at {}: {}", written, e))?;
+            let read: usize = (stream.total_in() - read) as usize;
+            let wrote: usize = (stream.total_out() - wrote) as usize;
+            input = &input[read..];
+            written += wrote;
+
+            if status == Status::StreamEnd {
+                break;
+            }
+            if read == 0 && wrote == 0 {
+                return Err(if written == out.len() {
+                    format!("Bzip2 data decompresses to more than {} bytes", out.len())
+                } else {
+                    format!(
+                        "Bzip2 data is truncated, decompressed {} of {} bytes",
+                        written,
+                        out.len()
+                    )
+                });
+            }
+        }
     }
-    return decompressed;
+    return Ok(());
 }
 
 /// Compresses a `Vec<u8>` into a Bzip2 compressed `Vec<u8>`.
@@ -82,3 +162,20 @@ pub fn bz2_compress(bytes: Vec<u8>) -> Vec<u8> {
         .expect("Bzip2 could not compress!");
     return compressed;
 }
+
+/// Compresses data the way it is stored inside of a `JagFile`, with 100k blocks like the client
+/// archives and without the 4-byte `BZh1` header, which the client prepends before decompressing.
+///
+/// This is the inverse of `bz2_decompress` with `prepend_header` set.
+///
+/// # Panics
+///
+/// This function will panic if the compression process fails, like `bz2_compress`.
+pub fn bz2_compress_headerless(bytes: Vec<u8>) -> Vec<u8> {
+    let mut compressed: Vec<u8> = Vec::new();
+    BzEncoder::new(&bytes[..], bzip2::Compression::new(1))
+        .read_to_end(&mut compressed)
+        .expect("Bzip2 could not compress!");
+    compressed.drain(..4);
+    return compressed;
+}
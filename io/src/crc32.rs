@@ -0,0 +1,77 @@
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table: [u32; 256] = [0; 256];
+    let mut index: usize = 0;
+    while index < 256 {
+        let mut crc: u32 = index as u32;
+        let mut bit: usize = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    return table;
+}
+
+/// A running CRC-32 checksum, the same as `java.util.zip.CRC32`.
+///
+/// # Example
+///
+/// ```rust
+/// use io::Crc32;
+///
+/// let mut crc: Crc32 = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(0xcbf43926, crc.value());
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Crc32 {
+        return Crc32 { crc: 0 };
+    }
+
+    /// Adds `bytes` to the checksum.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut crc: u32 = !self.crc;
+        for &byte in bytes {
+            crc = TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.crc = !crc;
+    }
+
+    /// The checksum of everything added so far, like `getValue`.
+    pub fn value(&self) -> u32 {
+        return self.crc;
+    }
+
+    pub fn reset(&mut self) {
+        self.crc = 0;
+    }
+}
+
+/// Calculates the CRC-32 of `bytes` as the client keeps it, `(int) crc.getValue()`.
+///
+/// # Example
+///
+/// ```rust
+/// use io::crc32;
+///
+/// assert_eq!(-873187034, crc32(b"123456789"));
+/// ```
+pub fn crc32(bytes: &[u8]) -> i32 {
+    let mut crc: Crc32 = Crc32::new();
+    crc.update(bytes);
+    return crc.value() as i32;
+}
@@ -0,0 +1,74 @@
+use std::io::{Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::{Compression, GzBuilder};
+
+/// Compresses `bytes` into a gzip file.
+///
+/// Gzip is the format of the on-demand files in later clients. The 225 client has
+/// no on-demand files: its archives, maps and midis are all bzip2, so neither the
+/// packer nor the update server uses gzip yet.
+///
+/// # Arguments
+///
+/// * `bytes` - The data to be compressed.
+/// * `deterministic` - Whether to leave the modification time out of the header, so the
+///   same data always compresses to the same bytes. Packed files should always use this.
+///
+/// # Example
+///
+/// ```rust
+/// use io::{gzip_compress, gzip_decompress};
+///
+/// let compressed: Vec<u8> = gzip_compress(b"Hello world!", true);
+/// assert_eq!(b"Hello world!".to_vec(), gzip_decompress(&compressed, 12).unwrap());
+/// ```
+pub fn gzip_compress(bytes: &[u8], deterministic: bool) -> Vec<u8> {
+    let mtime: u32 = if deterministic {
+        0
+    } else {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs() as u32)
+    };
+
+    // os 0, what java's GZIPOutputStream writes
+    let mut encoder: GzEncoder<Vec<u8>> = GzBuilder::new().mtime(mtime).operating_system(0).write(
+        Vec::with_capacity(bytes.len() / 2 + 18),
+        Compression::best(),
+    );
+    return match encoder.write_all(bytes).and_then(|_| encoder.finish()) {
+        Ok(compressed) => compressed,
+        Err(e) => panic!("Gzip could not compress! {}", e),
+    };
+}
+
+/// Decompresses a gzip file.
+///
+/// # Arguments
+///
+/// * `bytes` - The gzip file.
+/// * `max_length` - The most bytes it may decompress to, so a small file can't be used
+///   to exhaust memory.
+///
+/// # Returns
+///
+/// The decompressed data, or an error when the header is not gzip, the compressed data
+/// is malformed or cut short, the crc or length in the trailer doesn't match, or it
+/// decompresses to more than `max_length` bytes.
+pub fn gzip_decompress(bytes: &[u8], max_length: usize) -> Result<Vec<u8>, String> {
+    let mut data: Vec<u8> = Vec::new();
+    GzDecoder::new(bytes)
+        .take(max_length as u64 + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Gzip could not decompress! {}", e))?;
+    if data.len() > max_length {
+        return Err(format!(
+            "Gzip decompresses to more than {} bytes",
+            max_length
+        ));
+    }
+    return Ok(data);
+}
@@ -0,0 +1,183 @@
+use std::io::{Read, Write};
+
+use crate::Isaac;
+
+/// One direction of the opcode cipher of a game connection.
+///
+/// Every packet opcode is sent with the next number of an `Isaac` stream added to
+/// it, the payload itself is never encrypted. Both ends seed a cipher for each
+/// direction from the four ints the client sends in its login block.
+#[derive(Clone)]
+pub struct IsaacCipher {
+    isaac: Isaac,
+}
+
+impl IsaacCipher {
+    /// How much every seed int is offset by for the server to client direction.
+    pub const SERVER_SEED_OFFSET: i32 = 50;
+
+    pub fn new(seed: [i32; 4]) -> IsaacCipher {
+        return IsaacCipher {
+            isaac: Isaac::new(seed.to_vec()),
+        };
+    }
+
+    /// The ciphers for both directions of a connection from its login seed.
+    ///
+    /// # Returns
+    ///
+    /// The client to server cipher, seeded with `seed` as is, then the server to
+    /// client cipher, seeded with `SERVER_SEED_OFFSET` added to every int.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use io::IsaacCipher;
+    ///
+    /// let (mut client, _) = IsaacCipher::login([1, 2, 3, 4]);
+    /// let (mut server, _) = IsaacCipher::login([1, 2, 3, 4]);
+    /// assert_eq!(4, server.decrypt(client.encrypt(4)));
+    /// ```
+    pub fn login(seed: [i32; 4]) -> (IsaacCipher, IsaacCipher) {
+        let server: [i32; 4] =
+            seed.map(|value| value.wrapping_add(IsaacCipher::SERVER_SEED_OFFSET));
+        return (IsaacCipher::new(seed), IsaacCipher::new(server));
+    }
+
+    #[inline(always)]
+    pub fn encrypt(&mut self, opcode: u8) -> u8 {
+        return opcode.wrapping_add(self.isaac.next() as u8);
+    }
+
+    #[inline(always)]
+    pub fn decrypt(&mut self, opcode: u8) -> u8 {
+        return opcode.wrapping_sub(self.isaac.next() as u8);
+    }
+}
+
+/// Frames packets on a byte stream, encrypting the opcodes going out and
+/// decrypting the ones coming in.
+///
+/// A packet is its opcode followed by its payload, with a one or two byte
+/// length before the payload when the opcode doesn't have a fixed length.
+pub struct IsaacCodec<S> {
+    stream: S,
+    decoder: IsaacCipher,
+    encoder: IsaacCipher,
+}
+
+impl<S> IsaacCodec<S> {
+    /// The length of a packet with a one byte length before its payload.
+    pub const VAR_BYTE: i32 = -1;
+    /// The length of a packet with a two byte length before its payload.
+    pub const VAR_SHORT: i32 = -2;
+
+    /// The server end of a connection, reading what the client sends.
+    pub fn server(stream: S, seed: [i32; 4]) -> IsaacCodec<S> {
+        let (decoder, encoder) = IsaacCipher::login(seed);
+        return IsaacCodec {
+            stream,
+            decoder,
+            encoder,
+        };
+    }
+
+    /// The client end of a connection, like a bot or a test client.
+    pub fn client(stream: S, seed: [i32; 4]) -> IsaacCodec<S> {
+        let (encoder, decoder) = IsaacCipher::login(seed);
+        return IsaacCodec {
+            stream,
+            decoder,
+            encoder,
+        };
+    }
+
+    pub fn get_ref(&self) -> &S {
+        return &self.stream;
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        return &mut self.stream;
+    }
+
+    pub fn into_inner(self) -> S {
+        return self.stream;
+    }
+}
+
+impl<S: Read> IsaacCodec<S> {
+    /// Reads the next packet, `lengths` being the length of every opcode the other
+    /// end sends, `VAR_BYTE` or `VAR_SHORT` for the ones with a length before them.
+    ///
+    /// # Returns
+    ///
+    /// The decrypted opcode and the payload, or an error when the stream ends or
+    /// the opcode isn't in `lengths`. The cipher has moved on by then, so the
+    /// connection can't be read from after an error.
+    pub fn read_packet(&mut self, lengths: &[i32]) -> Result<(u8, Vec<u8>), String> {
+        let [byte] = self.read::<1>()?;
+        let opcode: u8 = self.decoder.decrypt(byte);
+        let length: usize = match lengths.get(opcode as usize) {
+            Some(&length) if length >= 0 => length as usize,
+            Some(&length) if length == Self::VAR_BYTE => self.read::<1>()?[0] as usize,
+            Some(&length) if length == Self::VAR_SHORT => u16::from_be_bytes(self.read()?) as usize,
+            _ => return Err(format!("Unknown opcode {}", opcode)),
+        };
+        let mut payload: Vec<u8> = vec![0; length];
+        self.stream
+            .read_exact(&mut payload)
+            .map_err(|e| format!("Failed to read packet {}: {}", opcode, e))?;
+        return Ok((opcode, payload));
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut bytes: [u8; N] = [0; N];
+        self.stream
+            .read_exact(&mut bytes)
+            .map_err(|e| format!("Failed to read packet header: {}", e))?;
+        return Ok(bytes);
+    }
+}
+
+impl<S: Write> IsaacCodec<S> {
+    /// Writes a packet, `length` being the length of its opcode as the other end
+    /// expects it: the payload length itself, `VAR_BYTE` or `VAR_SHORT`.
+    ///
+    /// # Returns
+    ///
+    /// An error when the payload doesn't fit `length`, before anything is written
+    /// or the cipher moves on, or when the stream fails.
+    pub fn write_packet(&mut self, opcode: u8, length: i32, payload: &[u8]) -> Result<(), String> {
+        let mut header: Vec<u8> = Vec::with_capacity(3);
+        match length {
+            length if length == Self::VAR_BYTE && payload.len() <= u8::MAX as usize => {
+                header.push(payload.len() as u8);
+            }
+            length if length == Self::VAR_SHORT && payload.len() <= u16::MAX as usize => {
+                header.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+            }
+            length if length >= 0 && length as usize == payload.len() => {}
+            _ => {
+                return Err(format!(
+                    "Packet {} of {} bytes doesn't fit length {}",
+                    opcode,
+                    payload.len(),
+                    length
+                ));
+            }
+        }
+        header.insert(0, self.encoder.encrypt(opcode));
+        self.stream
+            .write_all(&header)
+            .and_then(|_| self.stream.write_all(payload))
+            .map_err(|e| format!("Failed to write packet {}: {}", opcode, e))?;
+        return Ok(());
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        return self
+            .stream
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e));
+    }
+}
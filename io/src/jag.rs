@@ -1,4 +1,6 @@
-use crate::{bz2_decompress, Packet};
+use std::borrow::Cow;
+
+use crate::{bz2_compress_headerless, bz2_try_decompress, Packet, PacketReader};
 
 pub struct JagFile {
     pub file_count: usize,
@@ -46,18 +48,38 @@ impl JagFile {
     /// # Return
     /// - Returns a new `JagFile` instance.
     pub fn new(bytes: Vec<u8>) -> JagFile {
-        let mut buf: Packet = Packet::from(bytes);
-        let unpacked: i32 = buf.g3();
-        let packed: i32 = buf.g3();
+        return match JagFile::try_new(bytes) {
+            Ok(jag) => jag,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    /// Creates a new `JagFile` like `new`, returning an error instead of panicking when the header
+    /// or file table is truncated or a file runs past the end of the data.
+    ///
+    /// # Arguments
+    /// - `bytes`: A `Vec<u8>` containing the raw byte data of the `JagFile`.
+    ///
+    /// # Return
+    /// - Returns a new `JagFile` instance, or an error describing what is wrong with the data.
+    pub fn try_new(bytes: Vec<u8>) -> Result<JagFile, String> {
+        let mut header: PacketReader = PacketReader::new(&bytes);
+        let unpacked: i32 = header.try_g3()?;
+        let packed: i32 = header.try_g3()?;
 
-        let mut decompressed: bool = false;
+        let (data, decompressed): (Vec<u8>, bool) = if packed != unpacked {
+            let data: Vec<u8> = bz2_try_decompress(&bytes[6..], unpacked as usize, true)
+                .map_err(|e| format!("Failed to decompress archive: {}", e))?;
+            (data, true)
+        } else {
+            (bytes, false)
+        };
 
-        if packed != unpacked {
-            buf = Packet::from(bz2_decompress(buf.data, unpacked as usize, true, 6));
-            decompressed = true;
+        let mut buf: PacketReader = PacketReader::new(&data);
+        if !decompressed {
+            buf.pos = 6;
         }
-
-        let file_count: usize = buf.g2() as usize;
+        let file_count: usize = buf.try_g2()? as usize;
 
         let mut file_hashes: Vec<i32> = vec![0; file_count];
         let mut file_unpacks: Vec<i32> = vec![0; file_count];
@@ -66,22 +88,88 @@ impl JagFile {
 
         let mut pos: usize = buf.pos + file_count * 10;
         for index in 0..file_count {
-            file_hashes[index] = buf.g4s();
-            file_unpacks[index] = buf.g3();
-            file_packs[index] = buf.g3();
+            file_hashes[index] = buf.try_g4s()?;
+            file_unpacks[index] = buf.try_g3()?;
+            file_packs[index] = buf.try_g3()?;
             file_offsets[index] = pos;
             pos += file_packs[index] as usize;
         }
+        if pos > data.len() {
+            return Err(format!(
+                "Jag files end at {} but there are only {} bytes",
+                pos,
+                data.len()
+            ));
+        }
 
-        return JagFile {
+        return Ok(JagFile {
             file_count,
             file_hashes,
             file_unpacks,
             file_packs,
             file_offsets,
-            data: buf.data,
+            data,
             unpacked: decompressed,
+        });
+    }
+
+    /// Packs named files into the raw byte data of a new `.jag` file.
+    ///
+    /// This is the inverse of `JagFile::new`. Each name is hashed with `JagFile::hash` and the files are written
+    /// in the order given, packing the same files twice produces the same bytes. Compressed data is written
+    /// without its `BZh1` header, the client prepends it before decompressing.
+    ///
+    /// The output is meant to be byte-identical to the original client archives when given the same files in the
+    /// same order. That is unverified until a genuine client archive is checked in for `test_pack_client_parity`,
+    /// which is ignored until then.
+    ///
+    /// # Arguments
+    /// - `files`: The name and uncompressed data of every file to pack.
+    /// - `compress_whole`: Whether to compress the archive as a whole rather than compressing each file on its own.
+    ///
+    /// # Return
+    /// - Returns a `Vec<u8>` containing the raw byte data of the `JagFile`.
+    pub fn pack(files: &[(&str, Vec<u8>)], compress_whole: bool) -> Vec<u8> {
+        let entries: Vec<(i32, usize, Vec<u8>)> = files
+            .iter()
+            .map(|(name, data)| {
+                let packed: Vec<u8> = if compress_whole {
+                    data.clone()
+                } else {
+                    bz2_compress_headerless(data.clone())
+                };
+                (JagFile::hash(name), data.len(), packed)
+            })
+            .collect();
+
+        let size: usize = 2
+            + entries.len() * 10
+            + entries
+                .iter()
+                .map(|(_, _, packed)| packed.len())
+                .sum::<usize>();
+        let mut buf: Packet = Packet::new(size);
+        buf.p2(entries.len() as i32);
+        for (hash, unpacked, packed) in &entries {
+            buf.p4(*hash);
+            buf.p3(*unpacked as i32);
+            buf.p3(packed.len() as i32);
+        }
+        for (_, _, packed) in &entries {
+            buf.pdata(packed, 0, packed.len());
+        }
+
+        let body: Vec<u8> = if compress_whole {
+            bz2_compress_headerless(buf.data)
+        } else {
+            buf.data
         };
+
+        let mut jag: Packet = Packet::new(6 + body.len());
+        jag.p3(size as i32);
+        jag.p3(body.len() as i32);
+        jag.pdata(&body, 0, body.len());
+        return jag.data;
     }
 
     /// Reads a file from the `JagFile` by its name.
@@ -95,14 +183,33 @@ impl JagFile {
     ///
     /// # Return
     /// - Returns an `Option<Packet>`. If the file is found, it returns `Some(Packet)` containing the file's data.
-    /// - If the file is not found, it returns `None`.
+    /// - If the file is not found or can't be decompressed, it returns `None`.
     pub fn read(&self, name: &str) -> Option<Packet> {
+        return self.try_read(name).ok().flatten();
+    }
+
+    /// Reads a file from the `JagFile` by its name like `read`, telling a missing file apart from
+    /// one that can't be decompressed.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the file to read.
+    ///
+    /// # Return
+    /// - Returns `Ok(None)` if there is no file with the name, or an error naming the file if its
+    ///   data is out of bounds or corrupt.
+    pub fn try_read(&self, name: &str) -> Result<Option<Packet>, String> {
         let hash: i32 = JagFile::hash(name);
-        return self
+        let index: Option<usize> = self
             .file_hashes
             .iter()
-            .position(|&file_hash| file_hash == hash)
-            .and_then(|index| self.get(index));
+            .position(|&file_hash| file_hash == hash);
+        return match index {
+            Some(index) => self
+                .try_file(index)
+                .map(|data| Some(Packet::from(data.into_owned())))
+                .map_err(|e| format!("Failed to read {}: {}", name, e)),
+            None => Ok(None),
+        };
     }
 
     /// Retrieves a file from the `JagFile` by its index.
@@ -118,25 +225,57 @@ impl JagFile {
     /// - Returns an `Option<Packet>`. If the file exists and the data is valid, it returns `Some(Packet)` with
     ///   the file's data. Otherwise, it returns `None`.
     pub fn get(&self, index: usize) -> Option<Packet> {
-        if index >= self.file_count {
-            return None;
-        }
+        return self.file(index).map(|data| Packet::from(data.into_owned()));
+    }
 
-        if self.file_offsets[index] >= self.data.len() {
-            return None;
+    /// Retrieves the data of a file from the `JagFile` by its index.
+    ///
+    /// Files of an archive that was compressed as a whole are borrowed from the archive without copying,
+    /// read them with a `PacketReader`. Files compressed on their own are decompressed into a new buffer.
+    ///
+    /// # Arguments
+    /// - `index`: The index of the file to retrieve.
+    ///
+    /// # Return
+    /// - Returns an `Option<Cow<[u8]>>` with the file's data, or `None` if the index or data is out of bounds
+    ///   or the data can't be decompressed.
+    pub fn file(&self, index: usize) -> Option<Cow<'_, [u8]>> {
+        return self.try_file(index).ok();
+    }
+
+    /// Retrieves the data of a file from the `JagFile` by its index like `file`.
+    ///
+    /// # Arguments
+    /// - `index`: The index of the file to retrieve.
+    ///
+    /// # Return
+    /// - Returns the file's data, or an error if the index or data is out of bounds or the data can't
+    ///   be decompressed to its unpacked size.
+    pub fn try_file(&self, index: usize) -> Result<Cow<'_, [u8]>, String> {
+        if index >= self.file_count {
+            return Err(format!("There is no file {} of {}", index, self.file_count));
         }
 
         let start: usize = self.file_offsets[index];
         let end: usize = start + self.file_packs[index] as usize;
+        if end > self.data.len() {
+            return Err(format!(
+                "File {} ends at {} but there are only {} bytes",
+                index,
+                end,
+                self.data.len()
+            ));
+        }
+
         return if self.unpacked {
-            Some(Packet::from(self.data[start..end].to_vec()))
+            Ok(Cow::Borrowed(&self.data[start..end]))
         } else {
-            Some(Packet::from(bz2_decompress(
-                self.data[start..end].to_vec(),
+            bz2_try_decompress(
+                &self.data[start..end],
                 self.file_unpacks[index] as usize,
                 true,
-                0,
-            )))
+            )
+            .map(Cow::Owned)
         };
     }
 }
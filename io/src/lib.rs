@@ -1,10 +1,28 @@
+pub use bits::{BitReader, BitWriter};
 pub use bz2::bz2_compress;
+pub use bz2::bz2_compress_headerless;
 pub use bz2::bz2_decompress;
+pub use bz2::bz2_decompress_into;
+pub use bz2::bz2_try_decompress;
+pub use crc32::{crc32, Crc32};
+pub use gzip::{gzip_compress, gzip_decompress};
 pub use isaac::Isaac;
+pub use isaac_cipher::{IsaacCipher, IsaacCodec};
 pub use jag::JagFile;
 pub use packet::Packet;
+pub use pool::PacketPool;
+pub use reader::PacketReader;
+pub use rsa_key::RsaKeyPair;
 
+mod bits;
 mod bz2;
+mod crc32;
+mod gzip;
 mod isaac;
+mod isaac_cipher;
 mod jag;
+mod message;
 mod packet;
+mod pool;
+mod reader;
+mod rsa_key;
@@ -0,0 +1,270 @@
+/// Defines a struct along with how each of its fields is written to a `Packet`, so the
+/// encoder, decoder and size of a message are generated from a single schema.
+///
+/// Every field is written as `name: type as encoding`, in the order they are sent. The
+/// encodings are named after the `Packet` methods that write them:
+///
+/// * `p1`, `p2`, `ip2`, `p3`, `p4`, `ip4` and `p8` for fixed size numbers.
+/// * `smart` and `smarts` for numbers sent in one or two bytes depending on their size.
+/// * `jstr` for a `String`, terminated by a newline.
+/// * `bits(n)` for the lowest `n` bits, up to 32. Consecutive bit fields share bytes
+///   and the packet is back in byte mode after the last of them.
+///
+/// Numbers are converted to and from the field type with `as`, so any integer type works.
+///
+/// The struct gets:
+///
+/// * `encode(&self, packet: &mut Packet)` to write it at the packet's position.
+/// * `try_decode(reader: &mut PacketReader) -> Result<Self, String>` to read it from
+///   untrusted bytes like client packets, leaving the reader where it was when they
+///   are cut short or malformed.
+/// * `decode(packet: &mut Packet) -> Self` to read it back from trusted data, like the
+///   cache, panicking when it is cut short.
+/// * `size(&self) -> usize`, the exact number of bytes `encode` writes, and `MIN_SIZE`,
+///   the fewest bytes any value can take up.
+///
+/// # Example
+///
+/// ```rust
+/// use io::{Packet, PacketReader};
+///
+/// io::packet! {
+///     /// Moves a player to a new zone.
+///     #[derive(Clone, PartialEq, Debug)]
+///     pub struct Teleport {
+///         pub zone_x: u16 as p2,
+///         pub zone_z: u16 as p2,
+///         pub level: u8 as bits(2),
+///         pub run: u8 as bits(1),
+///         pub message: String as jstr,
+///     }
+/// }
+///
+/// let teleport: Teleport = Teleport {
+///     zone_x: 400,
+///     zone_z: 401,
+///     level: 1,
+///     run: 0,
+///     message: "Welcome".to_string(),
+/// };
+/// let mut packet: Packet = Packet::new(teleport.size());
+/// teleport.encode(&mut packet);
+/// assert_eq!(13, packet.pos);
+///
+/// packet.pos = 0;
+/// assert_eq!(teleport, Teleport::decode(&mut packet));
+///
+/// let mut reader: PacketReader = PacketReader::new(&packet.data[..12]);
+/// assert!(Teleport::try_decode(&mut reader).is_err());
+/// assert_eq!(0, reader.pos);
+/// ```
+#[macro_export]
+macro_rules! packet {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident: $ty:ty as $encoding:ident $(($bits:expr))?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $name {
+            /// The fewest bytes a value of this message can be encoded in.
+            pub const MIN_SIZE: usize = {
+                let mut size: usize = 0;
+                let mut bits: usize = 0;
+                $(
+                    $crate::__packet_field!(min_size size, bits, $encoding $(($bits))?);
+                )*
+                size + bits.div_ceil(8)
+            };
+
+            /// Writes this message at the position of `packet`.
+            pub fn encode(&self, packet: &mut $crate::Packet) {
+                let mut bit_mode: bool = false;
+                $(
+                    $crate::__packet_field!(encode packet, bit_mode, self.$field, $encoding $(($bits))?);
+                )*
+                if bit_mode {
+                    packet.bytes();
+                }
+            }
+
+            /// Reads a message at the position of `reader`, which is left where it was
+            /// when the message is cut short or malformed.
+            pub fn try_decode(reader: &mut $crate::PacketReader) -> Result<$name, String> {
+                let mut message: $crate::PacketReader = *reader;
+                let mut bit_mode: bool = false;
+                $(
+                    let $field: $ty = $crate::__packet_field!(
+                        try_decode message, bit_mode, $ty, $encoding $(($bits))?
+                    );
+                )*
+                *reader = message;
+                return Ok($name { $($field),* });
+            }
+
+            /// Reads a message at the position of `packet`, trusting it to be complete.
+            pub fn decode(packet: &mut $crate::Packet) -> $name {
+                let mut bit_mode: bool = false;
+                $(
+                    let $field: $ty =
+                        $crate::__packet_field!(decode packet, bit_mode, $ty, $encoding $(($bits))?);
+                )*
+                if bit_mode {
+                    packet.bytes();
+                }
+                return $name { $($field),* };
+            }
+
+            /// The number of bytes `encode` writes for this message.
+            pub fn size(&self) -> usize {
+                let mut size: usize = 0;
+                let mut bits: usize = 0;
+                $(
+                    $crate::__packet_field!(size size, bits, self.$field, $encoding $(($bits))?);
+                )*
+                return size + bits.div_ceil(8);
+            }
+        }
+    };
+}
+
+// The per field parts of `packet!`. The first of a group of bit fields switches the
+// packet into bit mode and every bit field leaves it after its last partly used byte,
+// so the group shares its bytes and the next byte field can follow it as is.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __packet_field {
+    (encode $packet:ident, $bit_mode:ident, $value:expr, bits($bits:expr)) => {
+        if !$bit_mode {
+            $packet.bits();
+            $bit_mode = true;
+        }
+        $crate::BitWriter::resume($packet).pbit($bits, $value as u32);
+    };
+    (encode $packet:ident, $bit_mode:ident, $value:expr, $encoding:ident) => {
+        $bit_mode = false;
+        $crate::__packet_field!(put $packet, $value, $encoding);
+    };
+
+    (put $packet:ident, $value:expr, p1) => { $packet.p1($value as i32) };
+    (put $packet:ident, $value:expr, p2) => { $packet.p2($value as i32) };
+    (put $packet:ident, $value:expr, ip2) => { $packet.ip2($value as i32) };
+    (put $packet:ident, $value:expr, p3) => { $packet.p3($value as i32) };
+    (put $packet:ident, $value:expr, p4) => { $packet.p4($value as i32) };
+    (put $packet:ident, $value:expr, ip4) => { $packet.ip4($value as i32) };
+    (put $packet:ident, $value:expr, p8) => { $packet.p8($value as i64) };
+    (put $packet:ident, $value:expr, smart) => { $packet.psmart($value as i32) };
+    (put $packet:ident, $value:expr, smarts) => { $packet.psmarts($value as i32) };
+    (put $packet:ident, $value:expr, jstr) => { $packet.pjstr(&$value, 10) };
+
+    (decode $packet:ident, $bit_mode:ident, $ty:ty, bits($bits:expr)) => {{
+        if !$bit_mode {
+            $packet.bits();
+            $bit_mode = true;
+        }
+        $crate::BitReader::resume($packet).gbit($bits) as $ty
+    }};
+    (decode $packet:ident, $bit_mode:ident, $ty:ty, $encoding:ident) => {{
+        $bit_mode = false;
+        $crate::__packet_field!(get $packet, $ty, $encoding)
+    }};
+
+    (get $packet:ident, $ty:ty, p1) => { $packet.g1() as $ty };
+    (get $packet:ident, $ty:ty, p2) => { $packet.g2() as $ty };
+    (get $packet:ident, $ty:ty, ip2) => { $packet.ig2s() as $ty };
+    (get $packet:ident, $ty:ty, p3) => { $packet.g3() as $ty };
+    (get $packet:ident, $ty:ty, p4) => { $packet.g4s() as $ty };
+    (get $packet:ident, $ty:ty, ip4) => { $packet.ig4s() as $ty };
+    (get $packet:ident, $ty:ty, p8) => { $packet.g8s() as $ty };
+    (get $packet:ident, $ty:ty, smart) => { $packet.gsmart() as $ty };
+    (get $packet:ident, $ty:ty, smarts) => { $packet.gsmarts() as $ty };
+    (get $packet:ident, $ty:ty, jstr) => { $packet.gjstr(10) };
+
+    (try_decode $reader:ident, $bit_mode:ident, $ty:ty, bits($bits:expr)) => {{
+        if !$bit_mode {
+            $reader.bits();
+            $bit_mode = true;
+        }
+        $reader.try_gbit($bits)? as $ty
+    }};
+    (try_decode $reader:ident, $bit_mode:ident, $ty:ty, $encoding:ident) => {{
+        $bit_mode = false;
+        $crate::__packet_field!(try_get $reader, $ty, $encoding)
+    }};
+
+    (try_get $reader:ident, $ty:ty, p1) => { $reader.try_g1()? as $ty };
+    (try_get $reader:ident, $ty:ty, p2) => { $reader.try_g2()? as $ty };
+    (try_get $reader:ident, $ty:ty, ip2) => { $reader.try_ig2s()? as $ty };
+    (try_get $reader:ident, $ty:ty, p3) => { $reader.try_g3()? as $ty };
+    (try_get $reader:ident, $ty:ty, p4) => { $reader.try_g4s()? as $ty };
+    (try_get $reader:ident, $ty:ty, ip4) => { $reader.try_ig4s()? as $ty };
+    (try_get $reader:ident, $ty:ty, p8) => { $reader.try_g8s()? as $ty };
+    (try_get $reader:ident, $ty:ty, smart) => { $reader.try_gsmart()? as $ty };
+    (try_get $reader:ident, $ty:ty, smarts) => { $reader.try_gsmarts()? as $ty };
+    (try_get $reader:ident, $ty:ty, jstr) => { $reader.try_gjstr(10)? };
+
+    (size $size:ident, $bits:ident, $value:expr, bits($count:expr)) => {
+        $bits += $count;
+    };
+    (size $size:ident, $bits:ident, $value:expr, smart) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += if ($value as i32) >= 0 && ($value as i32) < 128 { 1 } else { 2 };
+    };
+    (size $size:ident, $bits:ident, $value:expr, smarts) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += if ($value as i32) >= -64 && ($value as i32) < 64 { 1 } else { 2 };
+    };
+    (size $size:ident, $bits:ident, $value:expr, jstr) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += $value.len() + 1;
+    };
+    (size $size:ident, $bits:ident, $value:expr, $encoding:ident) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += $crate::__packet_field!(fixed $encoding);
+    };
+
+    (min_size $size:ident, $bits:ident, bits($count:expr)) => {
+        $bits += $count;
+    };
+    (min_size $size:ident, $bits:ident, smart) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += 1;
+    };
+    (min_size $size:ident, $bits:ident, smarts) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += 1;
+    };
+    (min_size $size:ident, $bits:ident, jstr) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += 1;
+    };
+    (min_size $size:ident, $bits:ident, $encoding:ident) => {
+        $crate::__packet_field!(bytes $size, $bits);
+        $size += $crate::__packet_field!(fixed $encoding);
+    };
+
+    // closes a group of bit fields
+    (bytes $size:ident, $bits:ident) => {
+        $size += $bits.div_ceil(8);
+        $bits = 0;
+    };
+
+    (fixed p1) => { 1 };
+    (fixed p2) => { 2 };
+    (fixed ip2) => { 2 };
+    (fixed p3) => { 3 };
+    (fixed p4) => { 4 };
+    (fixed ip4) => { 4 };
+    (fixed p8) => { 8 };
+}
@@ -1,9 +1,4 @@
-use num_bigint::BigInt;
-use num_traits::identities::One;
-use pem::{parse, Pem};
-use rsa::pkcs8::DecodePrivateKey;
-use rsa::RsaPrivateKey;
-use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+use crate::{IsaacCipher, PacketReader, RsaKeyPair};
 
 #[derive(Clone)]
 pub struct Packet {
@@ -56,6 +51,23 @@ impl Packet {
         return Packet::from(std::fs::read(path).unwrap());
     }
 
+    /// Borrows the bytes from the current position on as a bounds checked `PacketReader`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use io::{Packet, PacketReader};
+    ///
+    /// let packet: Packet = Packet::from(vec![0, 5]);
+    /// let mut reader: PacketReader = packet.reader();
+    /// assert_eq!(Ok(5), reader.try_g2());
+    /// ```
+    pub fn reader(&self) -> PacketReader<'_> {
+        let mut reader: PacketReader = PacketReader::new(&self.data);
+        reader.pos = self.pos;
+        return reader;
+    }
+
     /// Returns the remaining amount of storage available for this `Packet`.
     /// This is calculated by the difference of the total length with the current
     /// position of this packet.
@@ -119,6 +131,17 @@ impl Packet {
         self.pos += 1;
     }
 
+    /// Writes a packet opcode encrypted with `cipher`, the way the client
+    /// starts every packet it sends after the login.
+    ///
+    /// # Safety
+    ///
+    /// Like `p1`, the caller must ensure there is space for 1 byte at the current position.
+    #[inline(always)]
+    pub fn p1isaac(&mut self, opcode: u8, cipher: &mut IsaacCipher) {
+        self.p1(cipher.encrypt(opcode) as i32);
+    }
+
     /// Writes the lower 16 bits of the given `value` into the internal buffer
     /// at the current position, and increments the byte position of this buffer by 2.
     ///
@@ -472,6 +495,16 @@ impl Packet {
         return unsafe { *self.data.get_unchecked(self.pos - 1) };
     }
 
+    /// Reads a packet opcode encrypted with `cipher`.
+    ///
+    /// # Safety
+    ///
+    /// Like `g1`, the caller must ensure there is at least 1 byte remaining.
+    #[inline(always)]
+    pub fn g1isaac(&mut self, cipher: &mut IsaacCipher) -> u8 {
+        return cipher.decrypt(self.g1());
+    }
+
     /// Reads one byte from the internal buffer, interprets them as a signed 8-bit integer (`i8`),
     /// and increments the internal position by 1.
     ///
@@ -1137,6 +1170,8 @@ impl Packet {
 
     /// Performs RSA encryption using the provided PEM-encoded public key.
     ///
+    /// The key is parsed on every call, use `RsaKeyPair::encrypt` to reuse it.
+    ///
     /// # Arguments
     ///
     /// * `pem_key`: A string slice containing the PEM-encoded RSA private key.
@@ -1158,31 +1193,14 @@ impl Packet {
     /// - The encryption is carried out using the public key modulus and exponent.
     /// - The encrypted data is written back to the buffer.
     pub fn rsaenc(&mut self, pem_key: &str) {
-        let pem: Pem = parse(pem_key).expect("Failed to parse PEM key");
-        let private_key: RsaPrivateKey =
-            RsaPrivateKey::from_pkcs8_der(&pem.contents()).expect("Failed to decode private key");
-
-        let n: BigInt =
-            BigInt::from_bytes_be(num_bigint::Sign::Plus, &private_key.n().to_bytes_be());
-        let e: BigInt =
-            BigInt::from_bytes_be(num_bigint::Sign::Plus, &private_key.e().to_bytes_be());
-
-        let length = self.pos;
-        self.pos = 0;
-        let mut dec = vec![0u8; length];
-        self.gdata(&mut dec, 0, length);
-
-        let big_raw: BigInt = BigInt::from_bytes_be(num_bigint::Sign::Plus, &dec);
-        let big_enc: BigInt = big_raw.modpow(&e, &n);
-        let enc: Vec<u8> = big_enc.to_bytes_be().1;
-
-        self.pos = 0;
-        self.p1(enc.len() as i32);
-        self.pdata(&enc, 0, enc.len());
+        let key: RsaKeyPair = RsaKeyPair::from_pem(pem_key).expect("Failed to parse PEM key");
+        key.encrypt(self, 0).expect("Failed to encrypt");
     }
 
     /// Performs RSA decryption using the Chinese Remainder Theorem (CRT) and the provided PEM-encoded private key.
     ///
+    /// The key is parsed on every call, use `RsaKeyPair::decrypt` to reuse it.
+    ///
     /// # Arguments
     ///
     /// * `pem_key`: A string slice containing the PEM-encoded RSA private key.
@@ -1209,40 +1227,8 @@ impl Packet {
     /// - The decryption is carried out using CRT, which computes two partial results `m1` and `m2`.
     /// - The final result is reconstructed from `m1` and `m2`, and the decrypted data is written back to the buffer.
     pub fn rsadec(&mut self, pem_key: &str) {
-        let pem: Pem = parse(pem_key).expect("Failed to parse PEM key");
-        let private_key: RsaPrivateKey =
-            RsaPrivateKey::from_pkcs8_der(&pem.contents()).expect("Failed to decode private key");
-
-        let p: BigInt = BigInt::from_bytes_be(
-            num_bigint::Sign::Plus,
-            &private_key.primes()[0].to_bytes_be(),
-        );
-        let q: BigInt = BigInt::from_bytes_be(
-            num_bigint::Sign::Plus,
-            &private_key.primes()[1].to_bytes_be(),
-        );
-        let d_p: BigInt = BigInt::from_bytes_be(
-            num_bigint::Sign::Plus,
-            &private_key.dp().unwrap().to_bytes_be(),
-        );
-        let d_q: BigInt = BigInt::from_bytes_be(
-            num_bigint::Sign::Plus,
-            &private_key.dq().unwrap().to_bytes_be(),
-        );
-        let q_inv: BigInt = BigInt::from_bytes_be(
-            num_bigint::Sign::Plus,
-            &private_key.qinv().unwrap().to_bytes_be().1,
-        );
-
-        let length = self.g1() as usize;
-        let mut enc: Vec<u8> = vec![0u8; length];
-        self.gdata(&mut enc, 0, length);
-
-        let big_raw: BigInt = BigInt::from_bytes_be(num_bigint::Sign::Plus, &enc);
-        let m1: BigInt = big_raw.modpow(&d_p, &p);
-        let m2: BigInt = big_raw.modpow(&d_q, &q);
-        let h: BigInt = (q_inv * (&m1 - &m2)).modpow(&BigInt::one(), &p);
-        let dec: Vec<u8> = (&m2 + &h * &q).to_bytes_be().1;
+        let key: RsaKeyPair = RsaKeyPair::from_pem(pem_key).expect("Failed to parse PEM key");
+        let dec: Vec<u8> = key.decrypt(self).expect("Failed to decrypt");
 
         self.pos = 0;
         self.pdata(&dec, 0, dec.len());
@@ -0,0 +1,100 @@
+use crate::Packet;
+
+/// Reuses the buffers of packets that have been sent, so building the output of
+/// every player each tick doesn't allocate a new buffer for every packet.
+///
+/// Buffers are kept in size classes. `take` hands out a packet backed by a free
+/// buffer of the smallest class that fits, and `give` puts its buffer back once the
+/// packet has been sent. Packets bigger than the largest class are allocated as
+/// usual and dropped when given back. The pool keeps as many buffers as the busiest
+/// tick needed, so a full world settles into allocating nothing after a few ticks.
+///
+/// # Example
+///
+/// ```rust
+/// use io::{Packet, PacketPool};
+///
+/// let mut pool: PacketPool = PacketPool::new();
+/// let packet: Packet = pool.take(10);
+/// assert_eq!(10, packet.len());
+/// pool.give(packet);
+///
+/// let packet: Packet = pool.take(12);
+/// assert_eq!(1, pool.allocations());
+/// assert_eq!(1, pool.reuses());
+/// ```
+pub struct PacketPool {
+    free: Vec<Vec<Vec<u8>>>,
+    allocations: usize,
+    reuses: usize,
+}
+
+impl PacketPool {
+    /// The capacity of the buffers in each size class, from zone events and small
+    /// packets up to player and npc info.
+    pub const SIZE_CLASSES: [usize; 6] = [16, 64, 256, 1024, 5000, 40000];
+
+    pub fn new() -> PacketPool {
+        return PacketPool {
+            free: vec![Vec::new(); PacketPool::SIZE_CLASSES.len()],
+            allocations: 0,
+            reuses: 0,
+        };
+    }
+
+    /// Checks out a zeroed packet of `size` bytes, the same as `Packet::new(size)`.
+    pub fn take(&mut self, size: usize) -> Packet {
+        let class: Option<usize> = PacketPool::SIZE_CLASSES
+            .iter()
+            .position(|&capacity| capacity >= size);
+        let mut data: Vec<u8> = match class.and_then(|class| self.free[class].pop()) {
+            Some(data) => {
+                self.reuses += 1;
+                data
+            }
+            None => {
+                self.allocations += 1;
+                Vec::with_capacity(class.map_or(size, |class| PacketPool::SIZE_CLASSES[class]))
+            }
+        };
+        data.resize(size, 0);
+        return Packet::from(data);
+    }
+
+    /// Returns the buffer of `packet` to the pool, to be handed out by `take` again.
+    pub fn give(&mut self, packet: Packet) {
+        let mut data: Vec<u8> = packet.data;
+        if data.capacity() > PacketPool::SIZE_CLASSES[PacketPool::SIZE_CLASSES.len() - 1] {
+            return;
+        }
+        // the largest class the buffer can serve, packets not from the pool may fit none
+        let class: Option<usize> = PacketPool::SIZE_CLASSES
+            .iter()
+            .rposition(|&capacity| capacity <= data.capacity());
+        if let Some(class) = class {
+            data.clear();
+            self.free[class].push(data);
+        }
+    }
+
+    /// The number of buffers `take` has had to allocate.
+    pub fn allocations(&self) -> usize {
+        return self.allocations;
+    }
+
+    /// The number of packets `take` has handed out with a reused buffer.
+    pub fn reuses(&self) -> usize {
+        return self.reuses;
+    }
+
+    /// The number of buffers waiting to be reused.
+    pub fn available(&self) -> usize {
+        return self.free.iter().map(|buffers| buffers.len()).sum();
+    }
+}
+
+impl Default for PacketPool {
+    fn default() -> PacketPool {
+        return PacketPool::new();
+    }
+}
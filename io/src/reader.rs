@@ -0,0 +1,187 @@
+use crate::bits::read_bits;
+
+/// A bounds checked reader over borrowed bytes.
+///
+/// Unlike `Packet` this never copies the bytes it reads from and every read
+/// returns an error instead of reading past the end, so it is safe to use on
+/// client packets and cache data that may be truncated or malformed. A failed
+/// read leaves the position where it was.
+///
+/// # Example
+///
+/// ```rust
+/// use io::PacketReader;
+///
+/// let mut reader: PacketReader = PacketReader::new(&[0, 1, 2]);
+/// assert_eq!(Ok(1), reader.try_g2());
+/// assert!(reader.try_g2().is_err());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PacketReader<'a> {
+    data: &'a [u8],
+    pub pos: usize,
+    bit_pos: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    /// Creates a reader starting at the first byte of `data`.
+    pub fn new(data: &'a [u8]) -> PacketReader<'a> {
+        return PacketReader {
+            data,
+            pos: 0,
+            bit_pos: 0,
+        };
+    }
+
+    /// The number of bytes left to read.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        return self.data.len().saturating_sub(self.pos);
+    }
+
+    /// The total number of bytes, read or not.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        return self.data.len();
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        return self.data.is_empty();
+    }
+
+    /// Takes the next `length` bytes without copying them.
+    ///
+    /// # Returns
+    ///
+    /// An error when fewer than `length` bytes remain.
+    #[inline(always)]
+    pub fn try_gdata(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if length > self.remaining() {
+            return Err(format!(
+                "Tried to read {} bytes at {} but only {} remain",
+                length,
+                self.pos,
+                self.remaining()
+            ));
+        }
+        let data: &'a [u8] = &self.data[self.pos..self.pos + length];
+        self.pos += length;
+        return Ok(data);
+    }
+
+    #[inline(always)]
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        return Ok(self.try_gdata(N)?.try_into().unwrap());
+    }
+
+    #[inline(always)]
+    pub fn try_g1(&mut self) -> Result<u8, String> {
+        return Ok(self.take::<1>()?[0]);
+    }
+
+    #[inline(always)]
+    pub fn try_g1s(&mut self) -> Result<i8, String> {
+        return Ok(self.take::<1>()?[0] as i8);
+    }
+
+    #[inline(always)]
+    pub fn try_g2(&mut self) -> Result<u16, String> {
+        return Ok(u16::from_be_bytes(self.take()?));
+    }
+
+    #[inline(always)]
+    pub fn try_g2s(&mut self) -> Result<i16, String> {
+        return Ok(i16::from_be_bytes(self.take()?));
+    }
+
+    #[inline(always)]
+    pub fn try_ig2s(&mut self) -> Result<i16, String> {
+        return Ok(i16::from_le_bytes(self.take()?));
+    }
+
+    // java ints are always signed (java 8 added unsigned)
+    #[inline(always)]
+    pub fn try_g3(&mut self) -> Result<i32, String> {
+        let bytes: [u8; 3] = self.take()?;
+        return Ok((bytes[0] as i32) << 16 | (bytes[1] as i32) << 8 | bytes[2] as i32);
+    }
+
+    #[inline(always)]
+    pub fn try_g4s(&mut self) -> Result<i32, String> {
+        return Ok(i32::from_be_bytes(self.take()?));
+    }
+
+    #[inline(always)]
+    pub fn try_ig4s(&mut self) -> Result<i32, String> {
+        return Ok(i32::from_le_bytes(self.take()?));
+    }
+
+    #[inline(always)]
+    pub fn try_g8s(&mut self) -> Result<i64, String> {
+        return Ok(i64::from_be_bytes(self.take()?));
+    }
+
+    /// Reads a string up to `terminator`, skipping over the terminator.
+    ///
+    /// # Returns
+    ///
+    /// An error when the terminator is missing or the string is not valid UTF-8.
+    #[inline(always)]
+    pub fn try_gjstr(&mut self, terminator: u8) -> Result<String, String> {
+        let rest: &'a [u8] = &self.data[self.pos.min(self.data.len())..];
+        let length: usize = rest
+            .iter()
+            .position(|&byte| byte == terminator)
+            .ok_or_else(|| format!("Unterminated string at {}", self.pos))?;
+        let str: &str = std::str::from_utf8(&rest[..length])
+            .map_err(|e| format!("Invalid string at {}: {}", self.pos, e))?;
+        self.pos += length + 1;
+        return Ok(str.to_owned());
+    }
+
+    /// Starts reading bits at the byte position, like `Packet::bits`.
+    #[inline(always)]
+    pub fn bits(&mut self) {
+        self.bit_pos = self.pos << 3;
+    }
+
+    /// Reads `n` bits, most significant first, continuing from the last bit read since
+    /// `bits`. The byte position follows along, after the last partly read byte, so a
+    /// byte read can come straight after the last of a group of bit reads.
+    ///
+    /// # Returns
+    ///
+    /// The bits, or an error when `n` is more than 32 or fewer than `n` bits are left.
+    pub fn try_gbit(&mut self, n: usize) -> Result<u32, String> {
+        let value: u32 = read_bits(self.data, &mut self.bit_pos, n)?;
+        self.pos = self.bit_pos.div_ceil(8);
+        return Ok(value);
+    }
+
+    /// Reads a smart, one byte for values below `128` and two bytes otherwise.
+    #[inline(always)]
+    pub fn try_gsmart(&mut self) -> Result<i32, String> {
+        return match self.data.get(self.pos) {
+            Some(&byte) if byte < 128 => Ok(self.try_g1()? as i32),
+            Some(_) => Ok(self.try_g2()? as i32 - 32768),
+            None => Err(format!(
+                "Tried to read a smart at {} past the end",
+                self.pos
+            )),
+        };
+    }
+
+    /// Reads a signed smart, one byte for values in `-64..64` and two bytes otherwise.
+    #[inline(always)]
+    pub fn try_gsmarts(&mut self) -> Result<i32, String> {
+        return match self.data.get(self.pos) {
+            Some(&byte) if byte < 128 => Ok(self.try_g1()? as i32 - 64),
+            Some(_) => Ok(self.try_g2()? as i32 - 49152),
+            None => Err(format!(
+                "Tried to read a smart at {} past the end",
+                self.pos
+            )),
+        };
+    }
+}
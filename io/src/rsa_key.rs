@@ -0,0 +1,154 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::identities::One;
+use rand::rngs::OsRng;
+use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+use rsa::{BigUint, RsaPrivateKey};
+
+use crate::{Packet, PacketReader};
+
+/// An RSA key for the login block, parsed once and kept as `BigInt`s.
+///
+/// The client encrypts its login block with the public half, `n` and `e`, and
+/// the server decrypts it with the private half using the Chinese Remainder Theorem.
+/// Either side writes the block the same way: a single length byte followed by the
+/// big-endian ciphertext.
+#[derive(Clone, Debug)]
+pub struct RsaKeyPair {
+    n: BigInt,
+    e: BigInt,
+    p: BigInt,
+    q: BigInt,
+    d_p: BigInt,
+    d_q: BigInt,
+    q_inv: BigInt,
+    key: RsaPrivateKey,
+}
+
+impl RsaKeyPair {
+    /// Reads a PKCS#8 PEM private key from disk, like `data/config/private.pem`.
+    pub fn io(path: &str) -> Result<RsaKeyPair, String> {
+        let pem: String =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        return RsaKeyPair::from_pem(&pem);
+    }
+
+    /// Parses a PKCS#8 PEM private key, indentation before the lines is ignored.
+    pub fn from_pem(pem: &str) -> Result<RsaKeyPair, String> {
+        let pem: String = pem
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let key: RsaPrivateKey = RsaPrivateKey::from_pkcs8_pem(&pem)
+            .map_err(|e| format!("Failed to decode private key: {}", e))?;
+        return RsaKeyPair::from_key(key);
+    }
+
+    /// Generates a new key with a modulus of `bits` bits.
+    ///
+    /// The 225 client sends its login block in a single length byte, so `bits`
+    /// can't be more than 2040.
+    pub fn generate(bits: usize) -> Result<RsaKeyPair, String> {
+        if bits > 255 * 8 {
+            return Err(format!("A {} bit key doesn't fit the login block", bits));
+        }
+        let key: RsaPrivateKey = RsaPrivateKey::new(&mut OsRng, bits)
+            .map_err(|e| format!("Failed to generate key: {}", e))?;
+        return RsaKeyPair::from_key(key);
+    }
+
+    fn from_key(key: RsaPrivateKey) -> Result<RsaKeyPair, String> {
+        if key.primes().len() != 2 {
+            return Err("Only keys with two primes are supported".to_string());
+        }
+        let q_inv: BigInt = match key.qinv() {
+            Some(q_inv) => BigInt::from_bytes_be(Sign::Plus, &q_inv.to_bytes_be().1),
+            None => return Err("Private key has no CRT values".to_string()),
+        };
+        return Ok(RsaKeyPair {
+            n: RsaKeyPair::big(key.n()),
+            e: RsaKeyPair::big(key.e()),
+            p: RsaKeyPair::big(&key.primes()[0]),
+            q: RsaKeyPair::big(&key.primes()[1]),
+            d_p: RsaKeyPair::big(key.dp().unwrap()),
+            d_q: RsaKeyPair::big(key.dq().unwrap()),
+            q_inv,
+            key,
+        });
+    }
+
+    fn big(value: &BigUint) -> BigInt {
+        return BigInt::from_bytes_be(Sign::Plus, &value.to_bytes_be());
+    }
+
+    /// The key as a PKCS#8 PEM, to be saved as `private.pem`.
+    pub fn to_pem(&self) -> Result<String, String> {
+        return self
+            .key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map(|pem| pem.to_string())
+            .map_err(|e| format!("Failed to encode private key: {}", e));
+    }
+
+    /// The modulus in decimal, as the client's `BigInteger` constant.
+    pub fn client_modulus(&self) -> String {
+        return self.n.to_string();
+    }
+
+    /// The public exponent in decimal, as the client's `BigInteger` constant.
+    pub fn client_exponent(&self) -> String {
+        return self.e.to_string();
+    }
+
+    /// Encrypts the bytes of `packet` from `start` up to its position with the public
+    /// key, replacing them with the length-prefixed block and moving the position past it.
+    /// The buffer grows when the block is longer than the bytes it replaces.
+    pub fn encrypt(&self, packet: &mut Packet, start: usize) -> Result<(), String> {
+        if start > packet.pos || packet.pos > packet.data.len() {
+            return Err(format!(
+                "Invalid region {}..{} to encrypt",
+                start, packet.pos
+            ));
+        }
+        let raw: BigInt = BigInt::from_bytes_be(Sign::Plus, &packet.data[start..packet.pos]);
+        let enc: Vec<u8> = raw.modpow(&self.e, &self.n).to_bytes_be().1;
+        if enc.len() > 255 {
+            return Err(format!(
+                "Encrypted block of {} bytes is too long",
+                enc.len()
+            ));
+        }
+
+        let capacity: usize = packet.data.len();
+        packet.data.truncate(start);
+        packet.data.push(enc.len() as u8);
+        packet.data.extend_from_slice(&enc);
+        packet.pos = packet.data.len();
+        if packet.data.len() < capacity {
+            packet.data.resize(capacity, 0);
+        }
+        return Ok(());
+    }
+
+    /// Decrypts the length-prefixed block at the position of `packet`, moving past it.
+    ///
+    /// # Returns
+    ///
+    /// The decrypted bytes, or an error when the block runs past the end of the packet.
+    pub fn decrypt(&self, packet: &mut Packet) -> Result<Vec<u8>, String> {
+        let mut reader: PacketReader = packet.reader();
+        let length: usize = reader.try_g1()? as usize;
+        let enc: &[u8] = reader.try_gdata(length)?;
+
+        let raw: BigInt = BigInt::from_bytes_be(Sign::Plus, enc);
+        let m1: BigInt = raw.modpow(&self.d_p, &self.p);
+        let m2: BigInt = raw.modpow(&self.d_q, &self.q);
+        let h: BigInt = (&self.q_inv * (&m1 - &m2)).modpow(&BigInt::one(), &self.p);
+        let dec: Vec<u8> = (&m2 + &h * &self.q).to_bytes_be().1;
+
+        packet.pos = reader.pos;
+        return Ok(dec);
+    }
+}
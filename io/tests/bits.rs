@@ -0,0 +1,124 @@
+use io::{BitReader, BitWriter, Packet};
+
+#[test]
+fn test_matches_pbit() {
+    let mut expected: Packet = Packet::new(4);
+    expected.p1(9);
+    expected.bits();
+    expected.pbit(1, 1);
+    expected.pbit(4, 3);
+    expected.pbit(7, 13);
+    expected.pbit(3, 5);
+    expected.bytes();
+    expected.p1(200);
+
+    let mut packet: Packet = Packet::new(4);
+    packet.p1(9);
+    let mut bits: BitWriter = BitWriter::new(&mut packet);
+    bits.pbit(1, 1);
+    bits.pbit(4, 3);
+    bits.pbit(7, 13);
+    bits.pbit(3, 5);
+    bits.finish();
+    packet.p1(200);
+
+    assert_eq!(expected.pos, packet.pos);
+    assert_eq!(expected.data, packet.data);
+}
+
+#[test]
+fn test_wide_values() {
+    let mut packet: Packet = Packet::new(0);
+    let mut bits: BitWriter = BitWriter::new(&mut packet);
+    bits.pbit(3, 5);
+    bits.pbit(11, 2047);
+    bits.pbit(11, 1234);
+    bits.pbit(32, 0xdeadbeef);
+    bits.pbit(16, 0x1234);
+    bits.pbit(5, 0xffffffff); // only the lowest bits are written
+    bits.pbit(0, 1);
+    assert_eq!(78, bits.bit_pos());
+    bits.finish();
+    assert_eq!(10, packet.pos);
+
+    packet.pos = 0;
+    let mut bits: BitReader = BitReader::new(&mut packet);
+    assert_eq!(5, bits.gbit(3));
+    assert_eq!(2047, bits.gbit(11));
+    assert_eq!(1234, bits.gbit(11));
+    assert_eq!(0xdeadbeef, bits.gbit(32));
+    assert_eq!(0x1234, bits.gbit(16));
+    assert_eq!(31, bits.gbit(5));
+    assert_eq!(0, bits.gbit(0));
+    bits.finish();
+    assert_eq!(10, packet.pos);
+}
+
+#[test]
+fn test_grows() {
+    let mut packet: Packet = Packet::new(1);
+    let mut bits: BitWriter = BitWriter::new(&mut packet);
+    for _ in 0..100 {
+        bits.pbit(11, 1);
+    }
+    bits.finish();
+    assert_eq!(138, packet.pos);
+    assert!(packet.data.len() >= 138);
+}
+
+#[test]
+fn test_overwrites_stale_bits() {
+    let mut packet: Packet = Packet::from(vec![255; 3]);
+    let mut bits: BitWriter = BitWriter::new(&mut packet);
+    bits.pbit(12, 0);
+    bits.finish();
+    assert_eq!(vec![0, 15, 255], packet.data);
+}
+
+#[test]
+fn test_capacity() {
+    let mut packet: Packet = Packet::new(8);
+    packet.p2(0);
+    let mut bits: BitWriter = BitWriter::with_capacity(&mut packet, 4);
+    assert_eq!(16, bits.remaining());
+    bits.pbit(11, 1);
+    assert_eq!(5, bits.remaining());
+    assert!(bits.try_pbit(6, 1).is_err());
+    assert_eq!(5, bits.remaining());
+    bits.pbit(5, 1);
+    assert_eq!(0, bits.remaining());
+    assert!(bits.try_pbit(1, 1).is_err());
+    bits.finish();
+    assert_eq!(4, packet.pos);
+}
+
+#[test]
+#[should_panic]
+fn test_pbit_too_wide() {
+    let mut packet: Packet = Packet::new(8);
+    BitWriter::new(&mut packet).pbit(33, 0);
+}
+
+#[test]
+fn test_reader_bounds() {
+    let mut packet: Packet = Packet::from(vec![1, 2]);
+    packet.pos = 1;
+    let mut bits: BitReader = BitReader::new(&mut packet);
+    assert_eq!(8, bits.remaining());
+    assert!(bits.try_gbit(9).is_err());
+    assert!(bits.try_gbit(33).is_err());
+    assert_eq!(Ok(0), bits.try_gbit(6));
+    assert_eq!(Ok(2), bits.try_gbit(2));
+    assert!(bits.try_gbit(1).is_err());
+}
+
+#[test]
+fn test_drop_flushes() {
+    let mut packet: Packet = Packet::new(4);
+    {
+        let mut bits: BitWriter = BitWriter::new(&mut packet);
+        bits.pbit(9, 1);
+    }
+    assert_eq!(2, packet.pos);
+    assert_eq!(9, packet.bit_pos);
+}
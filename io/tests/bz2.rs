@@ -1,4 +1,6 @@
-use io::{bz2_compress, bz2_decompress};
+use io::{
+    bz2_compress, bz2_compress_headerless, bz2_decompress, bz2_decompress_into, bz2_try_decompress,
+};
 
 #[test]
 fn test_decompress() {
@@ -13,3 +15,74 @@ fn test_compress() {
     let decompressed: Vec<u8> = bz2_decompress(compressed, 12, false, 0);
     assert_eq!(String::from_utf8(decompressed).unwrap(), "Hello world!");
 }
+
+#[test]
+fn test_compress_headerless() {
+    let compressed: Vec<u8> = bz2_compress_headerless("Hello world!".as_bytes().to_vec());
+    assert_ne!(b"BZh", &compressed[..3]);
+    let decompressed: Vec<u8> = bz2_decompress(compressed, 12, true, 0);
+    assert_eq!(String::from_utf8(decompressed).unwrap(), "Hello world!");
+}
+
+fn noise(length: usize) -> Vec<u8> {
+    let mut seed: u32 = 1;
+    return (0..length)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        })
+        .collect();
+}
+
+#[test]
+fn test_try_decompress_length() {
+    let compressed: Vec<u8> = bz2_compress_headerless("Hello world!".as_bytes().to_vec());
+    assert_eq!(
+        b"Hello world!".to_vec(),
+        bz2_try_decompress(&compressed, 12, true).unwrap()
+    );
+    assert!(bz2_try_decompress(&compressed, 11, true).is_err());
+    assert!(bz2_try_decompress(&compressed, 13, true).is_err());
+}
+
+#[test]
+fn test_try_decompress_corrupt() {
+    let compressed: Vec<u8> = bz2_compress_headerless(noise(1000));
+    assert!(bz2_try_decompress(&compressed[..compressed.len() / 2], 1000, true).is_err());
+    assert!(bz2_try_decompress(&compressed, 1000, false).is_err());
+
+    let mut corrupt: Vec<u8> = compressed.clone();
+    corrupt[20] ^= 0xff;
+    assert!(bz2_try_decompress(&corrupt, 1000, true).is_err());
+    assert!(bz2_try_decompress(&[], 1000, true).is_err());
+}
+
+#[test]
+fn test_decompress_into() {
+    let compressed: Vec<u8> = bz2_compress("Hello world!".as_bytes().to_vec());
+    let mut out: [u8; 12] = [0; 12];
+    bz2_decompress_into(&compressed, &mut out, false).unwrap();
+    assert_eq!(b"Hello world!", &out);
+}
+
+#[test]
+fn test_decompress_multi_block() {
+    // more than a single 100k block at the `BZh1` block size
+    let data: Vec<u8> = noise(350_000);
+    let compressed: Vec<u8> = bz2_compress_headerless(data.clone());
+    assert_eq!(
+        data,
+        bz2_try_decompress(&compressed, data.len(), true).unwrap()
+    );
+}
+
+#[test]
+fn test_decompress_concatenated() {
+    let mut compressed: Vec<u8> = bz2_compress_headerless(b"Hello ".to_vec());
+    compressed.extend(bz2_compress(b"world!".to_vec()));
+    assert_eq!(
+        b"Hello world!".to_vec(),
+        bz2_try_decompress(&compressed, 12, true).unwrap()
+    );
+    assert!(bz2_try_decompress(&compressed, 6, true).is_err());
+}
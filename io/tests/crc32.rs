@@ -0,0 +1,27 @@
+use io::{crc32, Crc32};
+
+#[test]
+fn test_crc32() {
+    assert_eq!(0, crc32(&[]));
+    assert_eq!(-873187034, crc32(b"123456789"));
+    assert_eq!(
+        0x414fa339,
+        crc32(b"The quick brown fox jumps over the lazy dog")
+    );
+    assert_eq!(-162091, crc32(&[4; 1200]));
+}
+
+#[test]
+fn test_crc32_update() {
+    let data: Vec<u8> = (0..=255).cycle().take(5000).collect();
+    let mut crc: Crc32 = Crc32::new();
+    for chunk in data.chunks(333) {
+        crc.update(chunk);
+    }
+    assert_eq!(crc32(&data), crc.value() as i32);
+
+    crc.reset();
+    assert_eq!(0, crc.value());
+    crc.update(b"123456789");
+    assert_eq!(0xcbf43926, crc.value());
+}
@@ -0,0 +1,75 @@
+use io::{gzip_compress, gzip_decompress};
+
+// `gzip.compress(b"Hello world!", 9, mtime=0)`
+const HELLO: [u8; 32] = [
+    31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 243, 72, 205, 201, 201, 87, 40, 207, 47, 202, 73, 81, 4, 0,
+    149, 25, 133, 27, 12, 0, 0, 0,
+];
+
+#[test]
+fn test_decompress() {
+    assert_eq!(
+        b"Hello world!".to_vec(),
+        gzip_decompress(&HELLO, 12).unwrap()
+    );
+    let empty: [u8; 20] = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(Vec::<u8>::new(), gzip_decompress(&empty, 0).unwrap());
+}
+
+#[test]
+fn test_compress_deterministic() {
+    let compressed: Vec<u8> = gzip_compress(b"Hello world!", true);
+    assert_eq!(&HELLO[..8], &compressed[..8]);
+    assert_eq!(&HELLO[10..], &compressed[10..]);
+    assert_eq!(compressed, gzip_compress(b"Hello world!", true));
+}
+
+#[test]
+fn test_roundtrip() {
+    let text: Vec<u8> = "the quick brown fox jumps over the lazy dog. "
+        .repeat(2000)
+        .into_bytes();
+    let mut noise: Vec<u8> = Vec::with_capacity(100_000);
+    let mut seed: u32 = 1;
+    for _ in 0..100_000 {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        noise.push((seed >> 16) as u8);
+    }
+    for data in [Vec::new(), vec![7], text, noise, vec![0; 200_000]] {
+        let compressed: Vec<u8> = gzip_compress(&data, false);
+        assert_eq!(data, gzip_decompress(&compressed, data.len()).unwrap());
+    }
+}
+
+#[test]
+fn test_decompress_invalid() {
+    assert!(gzip_decompress(&[], 12).is_err());
+    assert!(gzip_decompress(b"BZh1", 12).is_err());
+    assert!(gzip_decompress(&HELLO[..20], 12).is_err());
+
+    let mut crc: Vec<u8> = HELLO.to_vec();
+    crc[24] ^= 1;
+    assert!(gzip_decompress(&crc, 12).is_err());
+
+    let mut length: Vec<u8> = HELLO.to_vec();
+    length[28] = 13;
+    assert!(gzip_decompress(&length, 12).is_err());
+
+    let mut block: Vec<u8> = HELLO.to_vec();
+    block[10] |= 0b110; // reserved block type
+    assert!(gzip_decompress(&block, 12).is_err());
+}
+
+#[test]
+fn test_decompress_max_length() {
+    let compressed: Vec<u8> = gzip_compress(&vec![0; 200_000], true);
+    assert!(compressed.len() < 1000);
+    assert!(gzip_decompress(&compressed, 199_999).is_err());
+    assert_eq!(
+        200_000,
+        gzip_decompress(&compressed, 200_000).unwrap().len()
+    );
+    assert!(gzip_decompress(&HELLO, 11).is_err());
+}
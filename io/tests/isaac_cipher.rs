@@ -0,0 +1,111 @@
+use std::io::Cursor;
+
+use io::{IsaacCipher, IsaacCodec, Packet};
+
+// the seed of a 225 login block, the client's two random ints then the server's
+const SEED: [i32; 4] = [1439836234, -1213374622, 738193741, 1054863041];
+
+// opcodes and what the client's Isaac turns them into for `SEED`
+const CLIENT_OPCODES: [u8; 7] = [206, 4, 150, 86, 35, 206, 4];
+const CLIENT_BYTES: [u8; 7] = [28, 35, 125, 68, 184, 83, 166];
+const SERVER_OPCODES: [u8; 6] = [184, 168, 142, 1, 44, 184];
+const SERVER_BYTES: [u8; 6] = [189, 53, 66, 75, 250, 11];
+
+#[test]
+fn test_client_to_server() {
+    let (mut client, _) = IsaacCipher::login(SEED);
+    let encrypted: Vec<u8> = CLIENT_OPCODES
+        .iter()
+        .map(|&opcode| client.encrypt(opcode))
+        .collect();
+    assert_eq!(CLIENT_BYTES.to_vec(), encrypted);
+
+    let (mut server, _) = IsaacCipher::login(SEED);
+    let decrypted: Vec<u8> = CLIENT_BYTES
+        .iter()
+        .map(|&byte| server.decrypt(byte))
+        .collect();
+    assert_eq!(CLIENT_OPCODES.to_vec(), decrypted);
+}
+
+#[test]
+fn test_server_to_client() {
+    let (_, mut server) = IsaacCipher::login(SEED);
+    let encrypted: Vec<u8> = SERVER_OPCODES
+        .iter()
+        .map(|&opcode| server.encrypt(opcode))
+        .collect();
+    assert_eq!(SERVER_BYTES.to_vec(), encrypted);
+
+    // the server direction is the same as a cipher seeded 50 higher
+    let mut offset: IsaacCipher = IsaacCipher::new(SEED.map(|value| value + 50));
+    let decrypted: Vec<u8> = SERVER_BYTES
+        .iter()
+        .map(|&byte| offset.decrypt(byte))
+        .collect();
+    assert_eq!(SERVER_OPCODES.to_vec(), decrypted);
+}
+
+#[test]
+fn test_packet_opcodes() {
+    let (mut client, _) = IsaacCipher::login(SEED);
+    let mut packet: Packet = Packet::new(CLIENT_OPCODES.len());
+    for opcode in CLIENT_OPCODES {
+        packet.p1isaac(opcode, &mut client);
+    }
+    assert_eq!(CLIENT_BYTES.to_vec(), packet.data);
+
+    let (mut server, _) = IsaacCipher::login(SEED);
+    packet.pos = 0;
+    for opcode in CLIENT_OPCODES {
+        assert_eq!(opcode, packet.g1isaac(&mut server));
+    }
+}
+
+#[test]
+fn test_codec() {
+    let mut lengths: Vec<i32> = vec![0; 256];
+    lengths[4] = 2;
+    lengths[150] = IsaacCodec::<()>::VAR_BYTE;
+    lengths[86] = IsaacCodec::<()>::VAR_SHORT;
+
+    let mut client: IsaacCodec<Vec<u8>> = IsaacCodec::client(Vec::new(), SEED);
+    client.write_packet(206, 0, &[]).unwrap();
+    client.write_packet(4, 2, &[1, 2]).unwrap();
+    client
+        .write_packet(150, IsaacCodec::<()>::VAR_BYTE, b"hi")
+        .unwrap();
+    client
+        .write_packet(86, IsaacCodec::<()>::VAR_SHORT, &[9; 300])
+        .unwrap();
+    assert!(client.write_packet(4, 2, &[1]).is_err());
+
+    let bytes: Vec<u8> = client.into_inner();
+    assert_eq!(
+        vec![28, 35, 1, 2, 125, 2, b'h', b'i', 68, 1, 44],
+        bytes[..11].to_vec()
+    );
+    assert_eq!(11 + 300, bytes.len());
+
+    let mut server: IsaacCodec<Cursor<Vec<u8>>> = IsaacCodec::server(Cursor::new(bytes), SEED);
+    assert_eq!((206, vec![]), server.read_packet(&lengths).unwrap());
+    assert_eq!((4, vec![1, 2]), server.read_packet(&lengths).unwrap());
+    assert_eq!((150, b"hi".to_vec()), server.read_packet(&lengths).unwrap());
+    assert_eq!((86, vec![9; 300]), server.read_packet(&lengths).unwrap());
+    assert!(server.read_packet(&lengths).is_err());
+}
+
+#[test]
+fn test_codec_truncated() {
+    let mut server: IsaacCodec<Cursor<Vec<u8>>> =
+        IsaacCodec::server(Cursor::new(vec![28, 1]), SEED);
+    let mut lengths: Vec<i32> = vec![-3; 256];
+    lengths[206] = 2;
+    assert!(server.read_packet(&lengths).is_err());
+
+    // the second opcode decrypts to 4, which isn't in the lengths
+    let mut server: IsaacCodec<Cursor<Vec<u8>>> =
+        IsaacCodec::server(Cursor::new(vec![28, 1, 2, 35]), SEED);
+    assert_eq!((206, vec![1, 2]), server.read_packet(&lengths).unwrap());
+    assert!(server.read_packet(&lengths).is_err());
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use io::JagFile;
 
 #[test]
@@ -14,3 +16,99 @@ fn test_hash_headicons() {
 fn test_hash_hitmarks() {
     assert_eq!(-1502153170, JagFile::hash("hitmarks.dat"));
 }
+
+#[test]
+fn test_pack_per_file() {
+    let files: Vec<(&str, Vec<u8>)> =
+        vec![("obj.dat", vec![1, 2, 3, 4]), ("obj.idx", vec![5; 100])];
+    let bytes: Vec<u8> = JagFile::pack(&files, false);
+    let jag: JagFile = JagFile::new(bytes);
+    assert!(!jag.unpacked);
+    assert_eq!(2, jag.file_count);
+    assert_eq!(JagFile::hash("obj.dat"), jag.file_hashes[0]);
+    assert_eq!(vec![1, 2, 3, 4], jag.read("obj.dat").unwrap().data);
+    assert_eq!(vec![5; 100], jag.read("obj.idx").unwrap().data);
+}
+
+#[test]
+fn test_pack_whole() {
+    let files: Vec<(&str, Vec<u8>)> = vec![("loc.dat", vec![7; 300]), ("loc.idx", vec![8, 9])];
+    let bytes: Vec<u8> = JagFile::pack(&files, true);
+    let jag: JagFile = JagFile::new(bytes);
+    assert!(jag.unpacked);
+    assert_eq!(vec![7; 300], jag.read("loc.dat").unwrap().data);
+    assert_eq!(vec![8, 9], jag.read("loc.idx").unwrap().data);
+    assert!(jag.read("npc.dat").is_none());
+}
+
+#[test]
+fn test_pack_strips_header() {
+    let bytes: Vec<u8> = JagFile::pack(&[("data", vec![1; 50])], true);
+    assert_ne!(b"BZh1", &bytes[6..10]);
+    // repacking the same files produces the same bytes
+    assert_eq!(bytes, JagFile::pack(&[("data", vec![1; 50])], true));
+}
+
+#[test]
+#[ignore = "needs a genuine 225 client config archive at io/tests/data/config"]
+fn test_pack_client_parity() {
+    let original: Vec<u8> = std::fs::read("tests/data/config").unwrap();
+    let jag: JagFile = JagFile::new(original.clone());
+    let mut names: Vec<String> = ["flo", "idk", "loc", "npc", "obj", "seq", "spotanim", "varp"]
+        .iter()
+        .flat_map(|kind| [format!("{}.dat", kind), format!("{}.idx", kind)])
+        .collect();
+    // repacked in the archive's own order
+    names.sort_by_key(|name| {
+        jag.file_hashes
+            .iter()
+            .position(|hash| *hash == JagFile::hash(name))
+    });
+    let files: Vec<(&str, Vec<u8>)> = names
+        .iter()
+        .map(|name| (name.as_str(), jag.read(name).unwrap().data))
+        .collect();
+    assert_eq!(jag.file_count, files.len());
+    assert_eq!(original, JagFile::pack(&files, jag.unpacked));
+}
+
+#[test]
+fn test_file_borrows_whole() {
+    let bytes: Vec<u8> = JagFile::pack(&[("loc.dat", vec![7; 300])], true);
+    let jag: JagFile = JagFile::new(bytes);
+    assert!(matches!(jag.file(0), Some(Cow::Borrowed(_))));
+    assert_eq!(&[7; 300][..], &*jag.file(0).unwrap());
+    assert!(jag.file(1).is_none());
+}
+
+#[test]
+fn test_try_new_truncated() {
+    let mut bytes: Vec<u8> = JagFile::pack(&[("obj.dat", vec![1, 2, 3, 4])], false);
+    bytes.truncate(bytes.len() - 1);
+    assert!(JagFile::try_new(bytes).is_err());
+    assert!(JagFile::try_new(vec![0, 0]).is_err());
+}
+
+#[test]
+fn test_try_read_corrupt() {
+    let mut bytes: Vec<u8> = JagFile::pack(
+        &[("obj.dat", vec![1; 100]), ("loc.dat", vec![2; 100])],
+        false,
+    );
+    let length: usize = bytes.len();
+    bytes[length - 10] ^= 0xff; // inside of loc.dat
+    let jag: JagFile = JagFile::new(bytes);
+
+    assert_eq!(vec![1; 100], jag.try_read("obj.dat").unwrap().unwrap().data);
+    assert!(jag.try_read("npc.dat").unwrap().is_none());
+    assert!(jag.try_read("loc.dat").err().unwrap().contains("loc.dat"));
+    assert!(jag.read("loc.dat").is_none());
+    assert!(jag.try_file(1).is_err());
+}
+
+#[test]
+fn test_try_new_corrupt() {
+    let mut bytes: Vec<u8> = JagFile::pack(&[("obj.dat", vec![1; 100])], true);
+    bytes[10] ^= 0xff;
+    assert!(JagFile::try_new(bytes).is_err());
+}
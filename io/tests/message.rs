@@ -0,0 +1,180 @@
+use io::{Packet, PacketReader};
+
+io::packet! {
+    #[derive(Clone, PartialEq, Debug)]
+    struct Numbers {
+        a: u8 as p1,
+        b: u16 as p2,
+        c: i16 as ip2,
+        d: i32 as p3,
+        e: i32 as p4,
+        f: i32 as ip4,
+        g: i64 as p8,
+    }
+}
+
+io::packet! {
+    #[derive(Clone, PartialEq, Debug)]
+    struct Mixed {
+        id: u16 as smart,
+        offset: i16 as smarts,
+        name: String as jstr,
+        level: u8 as bits(2),
+        flag: u8 as bits(1),
+        colour: u8 as bits(5),
+        direction: u8 as bits(3),
+        trailer: u8 as p1,
+    }
+}
+
+fn mixed(id: u16, offset: i16) -> Mixed {
+    return Mixed {
+        id,
+        offset,
+        name: "Hans".to_string(),
+        level: 3,
+        flag: 1,
+        colour: 21,
+        direction: 6,
+        trailer: 200,
+    };
+}
+
+#[test]
+fn test_numbers() {
+    let numbers: Numbers = Numbers {
+        a: 255,
+        b: 65535,
+        c: -2,
+        d: 16777215,
+        e: -1,
+        f: 123456789,
+        g: 0x0102030405060708,
+    };
+    assert_eq!(24, numbers.size());
+    assert_eq!(24, Numbers::MIN_SIZE);
+
+    let mut packet: Packet = Packet::new(numbers.size());
+    numbers.encode(&mut packet);
+    assert_eq!(24, packet.pos);
+    assert_eq!(&[255, 255, 255, 254, 255], &packet.data[..5]);
+    assert_eq!(&[1, 2, 3, 4, 5, 6, 7, 8], &packet.data[16..24]);
+
+    packet.pos = 0;
+    assert_eq!(numbers, Numbers::decode(&mut packet));
+    assert_eq!(24, packet.pos);
+
+    let mut reader: PacketReader = PacketReader::new(&packet.data);
+    assert_eq!(Ok(numbers), Numbers::try_decode(&mut reader));
+    assert_eq!(24, reader.pos);
+}
+
+#[test]
+fn test_encode_matches_hand_written() {
+    let message: Mixed = mixed(300, -5);
+    let mut packet: Packet = Packet::new(message.size());
+    message.encode(&mut packet);
+
+    let mut expected: Packet = Packet::new(message.size());
+    expected.psmart(300);
+    expected.psmarts(-5);
+    expected.pjstr("Hans", 10);
+    expected.bits();
+    expected.pbit(2, 3);
+    expected.pbit(1, 1);
+    expected.pbit(5, 21);
+    expected.pbit(3, 6);
+    expected.bytes();
+    expected.p1(200);
+
+    assert_eq!(expected.pos, packet.pos);
+    assert_eq!(expected.data, packet.data);
+}
+
+#[test]
+fn test_mixed_round_trip() {
+    for (id, offset) in [(0, 0), (127, 63), (128, -64), (32767, -65), (5, 16383)] {
+        let message: Mixed = mixed(id, offset);
+        let mut packet: Packet = Packet::new(message.size());
+        message.encode(&mut packet);
+        assert_eq!(message.size(), packet.pos);
+
+        packet.pos = 0;
+        assert_eq!(message, Mixed::decode(&mut packet));
+        assert_eq!(message.size(), packet.pos);
+
+        let mut reader: PacketReader = PacketReader::new(&packet.data);
+        assert_eq!(Ok(message.clone()), Mixed::try_decode(&mut reader));
+        assert_eq!(message.size(), reader.pos);
+    }
+}
+
+#[test]
+fn test_try_decode_truncated() {
+    let message: Mixed = mixed(300, -5);
+    let mut packet: Packet = Packet::new(message.size());
+    message.encode(&mut packet);
+
+    // cut short in every field, including between the bit fields
+    for length in 0..message.size() {
+        let mut reader: PacketReader = PacketReader::new(&packet.data[..length]);
+        assert!(Mixed::try_decode(&mut reader).is_err());
+        assert_eq!(0, reader.pos);
+    }
+
+    let mut unterminated: Vec<u8> = packet.data.clone();
+    unterminated[7] = b'!'; // the newline after "Hans"
+    let mut reader: PacketReader = PacketReader::new(&unterminated);
+    assert!(Mixed::try_decode(&mut reader).is_err());
+}
+
+#[test]
+fn test_size() {
+    // smart, smarts, "Hans\n", 11 bits in 2 bytes, p1
+    assert_eq!(1 + 1 + 5 + 2 + 1, mixed(127, 63).size());
+    assert_eq!(2 + 2 + 5 + 2 + 1, mixed(128, -65).size());
+    assert_eq!(1 + 1 + 1 + 2 + 1, Mixed::MIN_SIZE);
+}
+
+#[test]
+fn test_encode_at_position() {
+    let message: Mixed = mixed(1, 1);
+    let mut packet: Packet = Packet::new(message.size() + 3);
+    packet.p3(0x123456);
+    message.encode(&mut packet);
+    assert_eq!(message.size() + 3, packet.pos);
+
+    packet.pos = 0;
+    assert_eq!(0x123456, packet.g3());
+    assert_eq!(message, Mixed::decode(&mut packet));
+}
+
+io::packet! {
+    #[derive(Clone, PartialEq, Debug)]
+    struct Wide {
+        index: u16 as bits(11),
+        hash: u32 as bits(32),
+        length: u8 as p1,
+    }
+}
+
+#[test]
+fn test_wide_bits() {
+    let message: Wide = Wide {
+        index: 2047,
+        hash: 0xcafebabe,
+        length: 7,
+    };
+    assert_eq!(7, message.size());
+
+    let mut packet: Packet = Packet::new(message.size());
+    message.encode(&mut packet);
+    assert_eq!(7, packet.pos);
+
+    packet.pos = 0;
+    assert_eq!(message, Wide::decode(&mut packet));
+
+    let mut reader: PacketReader = PacketReader::new(&packet.data);
+    assert_eq!(Ok(message), Wide::try_decode(&mut reader));
+    assert_eq!(7, reader.pos);
+}
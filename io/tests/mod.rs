@@ -1,8 +1,24 @@
 #[cfg(test)]
+mod bits;
+#[cfg(test)]
 mod bz2;
 #[cfg(test)]
+mod crc32;
+#[cfg(test)]
+mod gzip;
+#[cfg(test)]
 mod isaac;
 #[cfg(test)]
+mod isaac_cipher;
+#[cfg(test)]
 mod jag;
 #[cfg(test)]
+mod message;
+#[cfg(test)]
 mod packet;
+#[cfg(test)]
+mod pool;
+#[cfg(test)]
+mod reader;
+#[cfg(test)]
+mod rsa_key;
@@ -0,0 +1,73 @@
+use io::{Packet, PacketPool};
+
+#[test]
+fn test_take_is_zeroed() {
+    let mut pool: PacketPool = PacketPool::new();
+    let mut packet: Packet = pool.take(4);
+    packet.p4(-1);
+    pool.give(packet);
+
+    let packet: Packet = pool.take(6);
+    assert_eq!(vec![0; 6], packet.data);
+    assert_eq!(0, packet.pos);
+    assert_eq!(0, packet.bit_pos);
+}
+
+#[test]
+fn test_reuse_by_size_class() {
+    let mut pool: PacketPool = PacketPool::new();
+    let small: Packet = pool.take(3);
+    let large: Packet = pool.take(4000);
+    assert_eq!(2, pool.allocations());
+    pool.give(small);
+    pool.give(large);
+    assert_eq!(2, pool.available());
+
+    // a bigger packet can't use the small buffer
+    let medium: Packet = pool.take(100);
+    assert_eq!(3, pool.allocations());
+    assert!(medium.data.capacity() >= 256);
+
+    let small: Packet = pool.take(16);
+    let large: Packet = pool.take(5000);
+    assert_eq!(3, pool.allocations());
+    assert_eq!(2, pool.reuses());
+    assert_eq!(16, small.len());
+    assert_eq!(5000, large.len());
+    assert_eq!(0, pool.available());
+}
+
+#[test]
+fn test_oversized() {
+    let mut pool: PacketPool = PacketPool::new();
+    let packet: Packet = pool.take(50000);
+    assert_eq!(50000, packet.len());
+    pool.give(packet);
+    assert_eq!(0, pool.available());
+}
+
+#[test]
+fn test_give_foreign_packet() {
+    let mut pool: PacketPool = PacketPool::new();
+    pool.give(Packet::new(3));
+    assert_eq!(0, pool.available());
+
+    pool.give(Packet::new(300));
+    assert_eq!(1, pool.available());
+    let packet: Packet = pool.take(256);
+    assert_eq!(1, pool.reuses());
+    assert_eq!(256, packet.len());
+}
+
+#[test]
+fn test_steady_state() {
+    let mut pool: PacketPool = PacketPool::new();
+    for _ in 0..10 {
+        let packets: Vec<Packet> = (1..100).map(|size| pool.take(size * 50)).collect();
+        for packet in packets {
+            pool.give(packet);
+        }
+    }
+    assert_eq!(99, pool.allocations());
+    assert_eq!(99 * 9, pool.reuses());
+}
@@ -0,0 +1,95 @@
+use io::{Packet, PacketReader};
+
+#[test]
+fn test_try_g() {
+    let mut packet: Packet = Packet::new(24);
+    packet.p1(-1);
+    packet.p2(-2);
+    packet.ip2(-3);
+    packet.p3(16777215);
+    packet.p4(-4);
+    packet.ip4(-5);
+    packet.p8(-6);
+    let mut reader: PacketReader = PacketReader::new(&packet.data);
+    assert_eq!(Ok(-1), reader.try_g1s());
+    assert_eq!(Ok(-2), reader.try_g2s());
+    assert_eq!(Ok(-3), reader.try_ig2s());
+    assert_eq!(Ok(16777215), reader.try_g3());
+    assert_eq!(Ok(-4), reader.try_g4s());
+    assert_eq!(Ok(-5), reader.try_ig4s());
+    assert_eq!(Ok(-6), reader.try_g8s());
+    assert_eq!(0, reader.remaining());
+}
+
+#[test]
+fn test_try_g_truncated() {
+    let mut reader: PacketReader = PacketReader::new(&[1, 2, 3]);
+    assert!(reader.try_g4s().is_err());
+    assert_eq!(0, reader.pos); // a failed read doesn't move
+    assert_eq!(Ok(258), reader.try_g2());
+    assert!(reader.try_g2().is_err());
+    assert_eq!(Ok(3), reader.try_g1());
+    assert!(reader.try_g1().is_err());
+}
+
+#[test]
+fn test_try_gjstr() {
+    let mut reader: PacketReader = PacketReader::new(b"Hello\nWorld");
+    assert_eq!(Ok("Hello".to_string()), reader.try_gjstr(10));
+    assert_eq!(6, reader.pos);
+    assert!(reader.try_gjstr(10).is_err());
+    assert_eq!(6, reader.pos);
+
+    let mut reader: PacketReader = PacketReader::new(&[0xff, 0xfe, 10]);
+    assert!(reader.try_gjstr(10).is_err());
+}
+
+#[test]
+fn test_try_gsmart() {
+    let mut packet: Packet = Packet::new(8);
+    packet.psmart(127);
+    packet.psmart(128);
+    packet.psmarts(-64);
+    packet.psmarts(100);
+    let mut reader: PacketReader = PacketReader::new(&packet.data[..packet.pos]);
+    assert_eq!(Ok(127), reader.try_gsmart());
+    assert_eq!(Ok(128), reader.try_gsmart());
+    assert_eq!(Ok(-64), reader.try_gsmarts());
+    assert_eq!(Ok(100), reader.try_gsmarts());
+    assert!(reader.try_gsmart().is_err());
+
+    // the first byte says two bytes follow but only one is there.
+    let mut reader: PacketReader = PacketReader::new(&[0x80]);
+    assert!(reader.try_gsmart().is_err());
+}
+
+#[test]
+fn test_try_gdata_borrows() {
+    let data: Vec<u8> = vec![1, 2, 3, 4, 5];
+    let mut reader: PacketReader = PacketReader::new(&data);
+    let slice: &[u8] = reader.try_gdata(3).unwrap();
+    assert_eq!(data.as_ptr(), slice.as_ptr());
+    assert!(reader.try_gdata(3).is_err());
+    assert_eq!(Ok(&[4, 5][..]), reader.try_gdata(2));
+}
+
+#[test]
+fn test_packet_reader() {
+    let mut packet: Packet = Packet::from(vec![9, 0, 7]);
+    packet.pos = 1;
+    let mut reader: PacketReader = packet.reader();
+    assert_eq!(Ok(7), reader.try_g2());
+}
+
+#[test]
+fn test_try_gbit() {
+    let mut reader: PacketReader = PacketReader::new(&[255, 240, 255]);
+    reader.bits();
+    assert_eq!(Ok(1), reader.try_gbit(1));
+    assert_eq!(Ok(2047), reader.try_gbit(11));
+    assert_eq!(2, reader.pos);
+    assert!(reader.try_gbit(13).is_err());
+    assert!(reader.try_gbit(33).is_err());
+    assert_eq!(Ok(255), reader.try_gbit(12));
+    assert_eq!(3, reader.pos);
+}
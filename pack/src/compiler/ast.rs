@@ -0,0 +1,112 @@
+/// A script as written in a `.rs2` file, `[trigger,subject](params)(returns)`
+/// followed by its statements.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Script {
+    pub trigger: String,
+    pub subject: String,
+    pub params: Vec<Param>,
+    pub returns: Vec<String>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+}
+
+impl Script {
+    /// The name the script is known by, `[proc,fib]`.
+    pub fn name(&self) -> String {
+        return format!("[{},{}]", self.trigger, self.subject);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Param {
+    pub kind: String,
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub line: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum StmtKind {
+    Define {
+        kind: String,
+        name: String,
+        size: Option<Expr>, // only for arrays, `def_int $arr(10)`
+        value: Option<Expr>,
+    },
+    Assign {
+        targets: Vec<Expr>,
+        values: Vec<Expr>,
+    },
+    If {
+        condition: Expr,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+    },
+    Switch {
+        kind: String,
+        value: Expr,
+        cases: Vec<Case>,
+    },
+    Return(Vec<Expr>),
+    Expr(Expr),
+    Block(Vec<Stmt>),
+}
+
+/// A `case` of a switch, a case without any keys is `case default`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Case {
+    pub keys: Vec<Expr>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub line: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExprKind {
+    Int(i32),
+    Char(i32),
+    Str(String),
+    Join(Vec<Expr>), // an interpolated string
+    Local {
+        name: String,
+        index: Option<Box<Expr>>,
+    },
+    GameVar {
+        name: String,
+        secondary: bool,
+    },
+    Constant(String),
+    Ident(String), // a literal of the expected type or a command without arguments
+    Call {
+        name: String,
+        secondary: bool,
+        args: Vec<Expr>,
+    },
+    Proc {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Jump {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Calc(Box<Expr>),
+    Binary {
+        op: &'static str,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
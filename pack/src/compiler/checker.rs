@@ -0,0 +1,1014 @@
+use std::collections::{HashMap, HashSet};
+
+use cache::ScriptOpcode;
+
+use crate::compiler::ast::{Expr, ExprKind, Script, Stmt, StmtKind};
+use crate::compiler::codegen::{
+    ends_with_return, Code, CodeKind, CompiledScript, Condition, Target, Value,
+};
+use crate::compiler::symbols::{
+    npc_mode, subject, trigger, DbColumn, GameVar, ScriptSymbol, Subject, Symbols, FONTMETRICS,
+    LOC_SHAPES, NPC_STATS,
+};
+use crate::context::PackContext;
+
+/// Every type a local, param or return can be declared as.
+const TYPES: [&str; 40] = [
+    "int",
+    "string",
+    "boolean",
+    "char",
+    "coord",
+    "obj",
+    "namedobj",
+    "npc",
+    "loc",
+    "seq",
+    "inv",
+    "stat",
+    "npc_stat",
+    "category",
+    "component",
+    "interface",
+    "spotanim",
+    "synth",
+    "enum",
+    "struct",
+    "param",
+    "dbtable",
+    "dbrow",
+    "dbcolumn",
+    "queue",
+    "timer",
+    "softtimer",
+    "walktrigger",
+    "proc",
+    "label",
+    "locshape",
+    "npc_mode",
+    "hunt",
+    "idkit",
+    "idk",
+    "fontmetrics",
+    "model",
+    "mesanim",
+    "player_uid",
+    "npc_uid",
+];
+
+struct Local {
+    kind: String,
+    index: usize,
+    array: bool,
+}
+
+/// Type checks a script and lowers it to `Code` for the codegen.
+///
+/// Names are resolved against the type they are expected to be, so `coins`
+/// is an obj where an obj is expected and a command anywhere else.
+pub struct Checker<'a> {
+    ctx: &'a mut PackContext,
+    symbols: &'a Symbols,
+    path: &'a str,
+    scopes: Vec<HashMap<String, Local>>,
+    int_locals: usize,
+    string_locals: usize,
+    arrays: usize,
+    returns: Vec<String>,
+}
+
+fn is_type(kind: &str) -> bool {
+    return TYPES.contains(&kind);
+}
+
+fn is_string(kind: &str) -> bool {
+    return kind == "string";
+}
+
+/// Whether a value of type `found` can be used where `expected` is wanted,
+/// a namedobj is always an obj but not the other way around.
+fn compatible(expected: &str, found: &str) -> bool {
+    return expected == found || (expected == "obj" && found == "namedobj");
+}
+
+fn type_char(kind: &str) -> i32 {
+    return PackContext::type_char(kind).unwrap_or(if is_string(kind) {
+        's' as i32
+    } else {
+        'i' as i32
+    });
+}
+
+/// The value of a local that was declared without one.
+fn default_value(kind: &str) -> Value {
+    return match kind {
+        "string" => Value::Str(String::new()),
+        "int" | "boolean" => Value::Int(0),
+        _ => Value::Int(-1),
+    };
+}
+
+fn branch(op: &str) -> Option<ScriptOpcode> {
+    return match op {
+        "=" => Some(ScriptOpcode::BranchEquals),
+        "!" => Some(ScriptOpcode::BranchNot),
+        "<" => Some(ScriptOpcode::BranchLessThan),
+        ">" => Some(ScriptOpcode::BranchGreaterThan),
+        "<=" => Some(ScriptOpcode::BranchLessThanOrEquals),
+        ">=" => Some(ScriptOpcode::BranchGreaterThanOrEquals),
+        _ => None,
+    };
+}
+
+fn math(op: &str) -> Option<ScriptOpcode> {
+    return match op {
+        "+" => Some(ScriptOpcode::Add),
+        "-" => Some(ScriptOpcode::Sub),
+        "*" => Some(ScriptOpcode::Multiply),
+        "/" => Some(ScriptOpcode::Divide),
+        "%" => Some(ScriptOpcode::Modulo),
+        "&" => Some(ScriptOpcode::And),
+        "|" => Some(ScriptOpcode::Or),
+        _ => None,
+    };
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(ctx: &'a mut PackContext, symbols: &'a Symbols, path: &'a str) -> Checker<'a> {
+        return Checker {
+            ctx,
+            symbols,
+            path,
+            scopes: Vec::new(),
+            int_locals: 0,
+            string_locals: 0,
+            arrays: 0,
+            returns: Vec::new(),
+        };
+    }
+
+    fn error(&self, line: usize, message: &str) -> String {
+        return format!("{}:{}: {}", self.path, line, message);
+    }
+
+    /// Checks a whole script, returning its header and the body to generate.
+    pub fn script(mut self, script: &Script) -> Result<(CompiledScript, Vec<Code>), String> {
+        let lookup: i32 = self.lookup(script)?;
+
+        self.scopes.push(HashMap::new());
+        let mut params: Vec<u8> = Vec::with_capacity(script.params.len());
+        for param in &script.params {
+            if !is_type(&param.kind) {
+                return Err(self.error(script.line, &format!("Unknown type: {}", param.kind)));
+            }
+            self.declare(script.line, &param.kind, &param.name, false)?;
+            params.push(type_char(&param.kind) as u8);
+        }
+        let int_arg_count: usize = self.int_locals;
+        let string_arg_count: usize = self.string_locals;
+
+        for kind in &script.returns {
+            if !is_type(kind) {
+                return Err(self.error(script.line, &format!("Unknown type: {}", kind)));
+            }
+        }
+        self.returns = script.returns.clone();
+
+        let mut body: Vec<Code> = self.block(&script.body)?;
+        // falling off the end still returns what the script declares, the defaults
+        // of its return types, so the caller's stack stays in step.
+        if !self.returns.is_empty() && !ends_with_return(&body) {
+            body.push(Code {
+                kind: CodeKind::Return(
+                    self.returns
+                        .iter()
+                        .map(|kind| default_value(kind))
+                        .collect(),
+                ),
+                line: script.body.last().map_or(script.line, |stmt| stmt.line),
+            });
+        }
+        let compiled: CompiledScript = CompiledScript {
+            name: script.name(),
+            path: self.path.to_string(),
+            lookup,
+            params,
+            lines: Vec::new(),
+            code: Vec::new(),
+            switches: Vec::new(),
+            int_local_count: self.int_locals,
+            string_local_count: self.string_locals,
+            int_arg_count,
+            string_arg_count,
+        };
+        return Ok((compiled, body));
+    }
+
+    /// The id the engine finds a triggered script by.
+    ///
+    /// A specific subject is `trigger | 0x2 << 8 | id << 10`, a `_category`
+    /// is `trigger | 0x1 << 8 | category << 10` and `_` is the trigger alone.
+    /// Scripts that are only called by name have no lookup.
+    fn lookup(&mut self, script: &Script) -> Result<i32, String> {
+        let id: i32 = trigger(&script.trigger)
+            .ok_or(self.error(script.line, &format!("Unknown trigger: {}", script.trigger)))?;
+        let name: &str = &script.subject;
+        return match subject(&script.trigger) {
+            Subject::Name => Ok(-1),
+            Subject::Global if name == "_" => Ok(id),
+            Subject::Global => Err(self.error(
+                script.line,
+                &format!(
+                    "{} can only be written as [{},_]",
+                    script.trigger, script.trigger
+                ),
+            )),
+            Subject::Type(_) if name == "_" => Ok(id),
+            Subject::Type(_) if name.starts_with('_') => {
+                let category: i32 = self.ctx.assign("category", &name[1..])?;
+                Ok(id | (0x1 << 8) | category.wrapping_shl(10))
+            }
+            Subject::Type(kind) => {
+                let subject: Option<i32> = if kind == "mapzone" {
+                    PackContext::coord(&format!("{}_0_0", name)).ok()
+                } else {
+                    self.literal(kind, name)
+                };
+                let subject: i32 = subject
+                    .ok_or(self.error(script.line, &format!("Unknown {}: {}", kind, name)))?;
+                Ok(id | (0x2 << 8) | subject.wrapping_shl(10))
+            }
+        };
+    }
+
+    fn declare(&mut self, line: usize, kind: &str, name: &str, array: bool) -> Result<(), String> {
+        if self.scopes.iter().any(|scope| scope.contains_key(name)) {
+            return Err(self.error(line, &format!("${} is already defined", name)));
+        }
+        let index: usize = if array {
+            self.arrays += 1;
+            self.arrays - 1
+        } else if is_string(kind) {
+            self.string_locals += 1;
+            self.string_locals - 1
+        } else {
+            self.int_locals += 1;
+            self.int_locals - 1
+        };
+        let local: Local = Local {
+            kind: kind.to_string(),
+            index,
+            array,
+        };
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), local);
+        return Ok(());
+    }
+
+    fn local(&self, line: usize, name: &str) -> Result<&Local, String> {
+        return self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .ok_or(self.error(line, &format!("Unknown local: ${}", name)));
+    }
+
+    fn var(&self, line: usize, name: &str, secondary: bool) -> Result<(&GameVar, i32), String> {
+        let var: &GameVar = self
+            .symbols
+            .vars
+            .get(name)
+            .ok_or(self.error(line, &format!("Unknown var: %{}", name)))?;
+        return Ok((var, ((secondary as i32) << 16) | var.id));
+    }
+
+    fn script_symbol(&self, name: &str) -> Option<&'a ScriptSymbol> {
+        let symbols: &'a Symbols = self.symbols;
+        return symbols.scripts.get(name);
+    }
+
+    fn dbcolumn(&self, line: usize, expr: &Expr) -> Result<&'a DbColumn, String> {
+        let symbols: &'a Symbols = self.symbols;
+        return match &expr.kind {
+            ExprKind::Ident(name) => symbols
+                .dbcolumns
+                .get(name)
+                .ok_or(self.error(line, &format!("Unknown dbcolumn: {}", name))),
+            _ => Err(self.error(line, "Expected a dbcolumn")),
+        };
+    }
+
+    fn pack_id(&mut self, kind: &str, name: &str) -> Option<i32> {
+        return self.ctx.pack(kind).ok().and_then(|pack| pack.id(name));
+    }
+
+    /// Resolves a name written as a value of the given type.
+    fn literal(&mut self, kind: &str, name: &str) -> Option<i32> {
+        return match kind {
+            "int" => match name.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().map(|value| value as i32),
+                None => name.parse::<i32>().ok(),
+            },
+            "boolean" => match name {
+                "true" => Some(1),
+                "false" => Some(0),
+                _ => None,
+            },
+            "coord" => PackContext::coord(name).ok(),
+            "stat" => PackContext::stat(name).ok(),
+            "npc_stat" => NPC_STATS
+                .iter()
+                .position(|&stat| stat == name)
+                .map(|id| id as i32),
+            "locshape" => LOC_SHAPES
+                .iter()
+                .position(|&shape| shape == name)
+                .map(|id| id as i32),
+            "fontmetrics" => FONTMETRICS
+                .iter()
+                .position(|&font| font == name)
+                .map(|id| id as i32),
+            "npc_mode" => npc_mode(name),
+            "category" => self.ctx.assign("category", name).ok(),
+            "component" if name.contains(':') => self.pack_id("interface", name),
+            "interface" if !name.contains(':') => self.pack_id("interface", name),
+            "obj" | "namedobj" => self.pack_id("obj", name),
+            "synth" => self.pack_id("sound", name),
+            "idkit" | "idk" => self.pack_id("idk", name),
+            "npc" | "loc" | "seq" | "inv" | "spotanim" | "enum" | "struct" | "param" | "model"
+            | "dbtable" | "dbrow" | "hunt" | "mesanim" => self.pack_id(kind, name),
+            "dbcolumn" => self.symbols.dbcolumns.get(name).map(|column| column.id),
+            "queue" => self
+                .script_symbol(&format!("[queue,{}]", name))
+                .or(self.script_symbol(&format!("[weakqueue,{}]", name)))
+                .map(|script| script.id),
+            "timer" | "softtimer" | "walktrigger" | "proc" | "label" => self
+                .script_symbol(&format!("[{},{}]", kind, name))
+                .map(|script| script.id),
+            _ => None,
+        };
+    }
+
+    /// Resolves a `^constant`, its value is read as the expected type.
+    fn constant(
+        &mut self,
+        line: usize,
+        name: &str,
+        expected: Option<&str>,
+    ) -> Result<(Value, String), String> {
+        let value: String = self
+            .ctx
+            .constants
+            .get(name)
+            .cloned()
+            .ok_or(self.error(line, &format!("Unknown constant: ^{}", name)))?;
+        let kind: &str = expected.unwrap_or("int");
+        if is_string(kind) {
+            return Ok((Value::Str(value), kind.to_string()));
+        }
+        if value == "null" {
+            return Ok((Value::Int(-1), kind.to_string()));
+        }
+        if let Some(int) = self.literal(kind, &value) {
+            return Ok((Value::Int(int), kind.to_string()));
+        }
+        if let Ok(int) = value.parse::<i32>() {
+            return Ok((Value::Int(int), "int".to_string()));
+        }
+        return Err(self.error(line, &format!("^{} is not a {}: {}", name, kind, value)));
+    }
+
+    fn block(&mut self, body: &[Stmt]) -> Result<Vec<Code>, String> {
+        self.scopes.push(HashMap::new());
+        let mut codes: Vec<Code> = Vec::with_capacity(body.len());
+        for stmt in body {
+            self.statement(stmt, &mut codes)?;
+        }
+        self.scopes.pop();
+        return Ok(codes);
+    }
+
+    fn statement(&mut self, stmt: &Stmt, codes: &mut Vec<Code>) -> Result<(), String> {
+        let line: usize = stmt.line;
+        let kind: CodeKind = match &stmt.kind {
+            StmtKind::Define {
+                kind,
+                name,
+                size,
+                value,
+            } => {
+                if !is_type(kind) {
+                    return Err(self.error(line, &format!("Unknown type: {}", kind)));
+                }
+                if let Some(size) = size {
+                    if is_string(kind) {
+                        return Err(self.error(line, "Arrays can only hold ints"));
+                    }
+                    let (size, _) = self.single(size, Some("int"))?;
+                    self.declare(line, kind, name, true)?;
+                    let array: usize = self.local(line, name)?.index;
+                    CodeKind::DefineArray {
+                        array,
+                        kind: type_char(kind),
+                        size,
+                    }
+                } else {
+                    let value: Value = match value {
+                        Some(value) => self.single(value, Some(kind))?.0,
+                        None => default_value(kind),
+                    };
+                    self.declare(line, kind, name, false)?;
+                    let index: usize = self.local(line, name)?.index;
+                    CodeKind::Store {
+                        targets: vec![Target::Local {
+                            index,
+                            string: is_string(kind),
+                        }],
+                        values: vec![value],
+                    }
+                }
+            }
+            StmtKind::Assign { targets, values } => {
+                let mut resolved: Vec<Target> = Vec::with_capacity(targets.len());
+                let mut kinds: Vec<String> = Vec::with_capacity(targets.len());
+                for target in targets {
+                    let (target, kind) = self.target(target)?;
+                    resolved.push(target);
+                    kinds.push(kind);
+                }
+                let (values, found) = self.values(line, values, &kinds)?;
+                self.expect_types(line, &kinds, &found)?;
+                CodeKind::Store {
+                    targets: resolved,
+                    values,
+                }
+            }
+            StmtKind::If {
+                condition,
+                then,
+                otherwise,
+            } => CodeKind::If {
+                condition: self.condition(condition)?,
+                then: self.block(then)?,
+                otherwise: self.block(otherwise)?,
+            },
+            StmtKind::While { condition, body } => CodeKind::While {
+                condition: self.condition(condition)?,
+                body: self.block(body)?,
+            },
+            StmtKind::Switch { kind, value, cases } => {
+                if !is_type(kind) || is_string(kind) {
+                    return Err(self.error(line, &format!("Can't switch on type: {}", kind)));
+                }
+                let (value, _) = self.single(value, Some(kind))?;
+                let mut seen: HashSet<i32> = HashSet::new();
+                let mut checked: Vec<(Vec<i32>, Vec<Code>)> = Vec::with_capacity(cases.len());
+                let mut default: Option<Vec<Code>> = None;
+                for case in cases {
+                    if case.keys.is_empty() {
+                        if default.is_some() {
+                            return Err(self.error(case.line, "Switch has more than one default"));
+                        }
+                        default = Some(self.block(&case.body)?);
+                        continue;
+                    }
+                    let mut keys: Vec<i32> = Vec::with_capacity(case.keys.len());
+                    for key in &case.keys {
+                        let key: i32 = match self.single(key, Some(kind))?.0 {
+                            Value::Int(key) => key,
+                            _ => return Err(self.error(case.line, "Case keys must be constant")),
+                        };
+                        // a repeated key can never be reached, the first case keeps it.
+                        if seen.insert(key) {
+                            keys.push(key);
+                        }
+                    }
+                    checked.push((keys, self.block(&case.body)?));
+                }
+                CodeKind::Switch {
+                    value,
+                    cases: checked,
+                    default: default.unwrap_or_default(),
+                }
+            }
+            StmtKind::Return(values) => {
+                let expected: Vec<String> = self.returns.clone();
+                let (values, found) = self.values(line, values, &expected)?;
+                self.expect_types(line, &expected, &found)?;
+                CodeKind::Return(values)
+            }
+            StmtKind::Expr(Expr {
+                kind: ExprKind::Jump { name, args },
+                line,
+            }) => {
+                let label: &ScriptSymbol = self
+                    .script_symbol(&format!("[label,{}]", name))
+                    .ok_or(self.error(*line, &format!("Unknown label: @{}", name)))?;
+                CodeKind::Jump {
+                    script: label.id,
+                    args: self.args(*line, args, &label.signature.params)?,
+                }
+            }
+            StmtKind::Expr(expr) => {
+                if !matches!(
+                    expr.kind,
+                    ExprKind::Call { .. } | ExprKind::Proc { .. } | ExprKind::Ident(_)
+                ) {
+                    return Err(self.error(line, "Expected a statement"));
+                }
+                let (value, kinds) = self.expr(expr, None)?;
+                let strings: usize = kinds.iter().filter(|kind| is_string(kind)).count();
+                CodeKind::Discard {
+                    value,
+                    ints: kinds.len() - strings,
+                    strings,
+                }
+            }
+            StmtKind::Block(body) => {
+                codes.extend(self.block(body)?);
+                return Ok(());
+            }
+        };
+        codes.push(Code { kind, line });
+        return Ok(());
+    }
+
+    /// Resolves the left side of an assignment.
+    fn target(&mut self, expr: &Expr) -> Result<(Target, String), String> {
+        return match &expr.kind {
+            ExprKind::Local { name, index } => {
+                let local: &Local = self.local(expr.line, name)?;
+                let (kind, slot, array) = (local.kind.clone(), local.index, local.array);
+                match index {
+                    Some(index) if array => {
+                        let (index, _) = self.single(index, Some("int"))?;
+                        Ok((Target::Array { array: slot, index }, kind))
+                    }
+                    None if !array => Ok((
+                        Target::Local {
+                            index: slot,
+                            string: is_string(&kind),
+                        },
+                        kind,
+                    )),
+                    _ => Err(self.error(expr.line, &format!("${} is used incorrectly", name))),
+                }
+            }
+            ExprKind::GameVar { name, secondary } => {
+                let (var, operand) = self.var(expr.line, name, *secondary)?;
+                Ok((
+                    Target::Var {
+                        pop: var.pop.clone(),
+                        operand,
+                    },
+                    var.kind.clone(),
+                ))
+            }
+            _ => Err(self.error(expr.line, "Only locals and vars can be assigned")),
+        };
+    }
+
+    /// Checks a list of values against the types they are expected to be,
+    /// a single call may return more than one value.
+    fn values(
+        &mut self,
+        line: usize,
+        exprs: &[Expr],
+        expected: &[String],
+    ) -> Result<(Vec<Value>, Vec<String>), String> {
+        let mut values: Vec<Value> = Vec::with_capacity(exprs.len());
+        let mut kinds: Vec<String> = Vec::with_capacity(expected.len());
+        for expr in exprs {
+            let wanted: Option<&str> = expected.get(kinds.len()).map(|kind| kind.as_str());
+            let (value, found) = self.expr(expr, wanted)?;
+            if found.is_empty() {
+                return Err(self.error(line, "Expected a value"));
+            }
+            values.push(value);
+            kinds.extend(found);
+        }
+        return Ok((values, kinds));
+    }
+
+    fn expect_types(
+        &self,
+        line: usize,
+        expected: &[String],
+        found: &[String],
+    ) -> Result<(), String> {
+        let matches: bool = expected.len() == found.len()
+            && expected
+                .iter()
+                .zip(found)
+                .all(|(expected, found)| compatible(expected, found));
+        if !matches {
+            return Err(self.error(
+                line,
+                &format!(
+                    "Expected ({}) but found ({})",
+                    expected.join(", "),
+                    found.join(", ")
+                ),
+            ));
+        }
+        return Ok(());
+    }
+
+    /// Checks the arguments of a call against its params.
+    fn args(
+        &mut self,
+        line: usize,
+        args: &[Expr],
+        params: &[String],
+    ) -> Result<Vec<Value>, String> {
+        let (values, found) = self.values(line, args, params)?;
+        self.expect_types(line, params, &found)?;
+        return Ok(values);
+    }
+
+    /// Checks an expression that must produce exactly one value.
+    fn single(&mut self, expr: &Expr, expected: Option<&str>) -> Result<(Value, String), String> {
+        let (value, kinds) = self.expr(expr, expected)?;
+        if kinds.len() != 1 {
+            return Err(self.error(
+                expr.line,
+                &format!("Expected one value but found {}", kinds.len()),
+            ));
+        }
+        let kind: String = kinds.into_iter().next().unwrap();
+        if let Some(expected) = expected {
+            if !compatible(expected, &kind) {
+                return Err(self.error(
+                    expr.line,
+                    &format!("Expected {} but found {}", expected, kind),
+                ));
+            }
+        }
+        return Ok((value, kind));
+    }
+
+    fn expr(
+        &mut self,
+        expr: &Expr,
+        expected: Option<&str>,
+    ) -> Result<(Value, Vec<String>), String> {
+        let line: usize = expr.line;
+        let one = |value: Value, kind: &str| Ok((value, vec![kind.to_string()]));
+        return match &expr.kind {
+            ExprKind::Int(value) => one(Value::Int(*value), "int"),
+            ExprKind::Char(value) => one(Value::Int(*value), "char"),
+            ExprKind::Str(value) => one(Value::Str(value.clone()), "string"),
+            ExprKind::Join(parts) => {
+                let mut values: Vec<Value> = Vec::with_capacity(parts.len());
+                for part in parts {
+                    values.push(self.single(part, Some("string"))?.0);
+                }
+                one(Value::Join(values), "string")
+            }
+            ExprKind::Local { name, index } => {
+                let local: &Local = self.local(line, name)?;
+                let (kind, slot, array) = (local.kind.clone(), local.index, local.array);
+                match index {
+                    Some(index) if array => {
+                        let (index, _) = self.single(index, Some("int"))?;
+                        one(
+                            Value::Array {
+                                array: slot,
+                                index: Box::new(index),
+                            },
+                            &kind,
+                        )
+                    }
+                    None if !array => one(
+                        Value::Local {
+                            index: slot,
+                            string: is_string(&kind),
+                        },
+                        &kind,
+                    ),
+                    _ => Err(self.error(line, &format!("${} is used incorrectly", name))),
+                }
+            }
+            ExprKind::GameVar { name, secondary } => {
+                let (var, operand) = self.var(line, name, *secondary)?;
+                let push: ScriptOpcode = var.push.clone();
+                one(Value::Var { push, operand }, &var.kind.clone())
+            }
+            ExprKind::Constant(name) => {
+                let (value, kind) = self.constant(line, name, expected)?;
+                Ok((value, vec![kind]))
+            }
+            ExprKind::Ident(name) => self.ident(line, name, expected),
+            ExprKind::Call {
+                name,
+                secondary,
+                args,
+            } => self.command(line, name, *secondary, args),
+            ExprKind::Proc { name, args } => {
+                let proc: &ScriptSymbol = self
+                    .script_symbol(&format!("[proc,{}]", name))
+                    .ok_or(self.error(line, &format!("Unknown proc: ~{}", name)))?;
+                let args: Vec<Value> = self.args(line, args, &proc.signature.params)?;
+                Ok((
+                    Value::GoSub {
+                        script: proc.id,
+                        args,
+                    },
+                    proc.signature.returns.clone(),
+                ))
+            }
+            ExprKind::Jump { name, args } if args.is_empty() && expected == Some("label") => {
+                let label: &ScriptSymbol = self
+                    .script_symbol(&format!("[label,{}]", name))
+                    .ok_or(self.error(line, &format!("Unknown label: @{}", name)))?;
+                one(Value::Int(label.id), "label")
+            }
+            ExprKind::Jump { .. } => Err(self.error(line, "A jump can't be used as a value")),
+            ExprKind::Calc(inner) => one(self.calc(inner)?, "int"),
+            ExprKind::Binary { op, .. } => Err(self.error(
+                line,
+                &format!("{} can only be used in a condition or calc", op),
+            )),
+        };
+    }
+
+    /// A name is a literal of the expected type, or else a command without arguments.
+    fn ident(
+        &mut self,
+        line: usize,
+        name: &str,
+        expected: Option<&str>,
+    ) -> Result<(Value, Vec<String>), String> {
+        let command: bool = self.symbols.commands.contains_key(name);
+        match (name, expected) {
+            ("null", Some(kind)) if !is_string(kind) => {
+                return Ok((Value::Int(-1), vec![kind.to_string()]));
+            }
+            ("true" | "false", None) => {
+                return Ok((
+                    Value::Int((name == "true") as i32),
+                    vec!["boolean".to_string()],
+                ));
+            }
+            // a category is any name, prefer the command when there is one.
+            (_, Some("category")) if command => {}
+            (_, Some(kind)) => {
+                if let Some(id) = self.literal(kind, name) {
+                    return Ok((Value::Int(id), vec![kind.to_string()]));
+                }
+            }
+            _ => {}
+        }
+        if command || name.starts_with('.') {
+            let (name, secondary) = match name.strip_prefix('.') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            return self.command(line, name, secondary, &[]);
+        }
+        return Err(match expected {
+            Some(kind) => self.error(line, &format!("Unknown {}: {}", kind, name)),
+            None => self.error(line, &format!("Unknown symbol: {}", name)),
+        });
+    }
+
+    fn calc(&mut self, expr: &Expr) -> Result<Value, String> {
+        if let ExprKind::Binary { op, left, right } = &expr.kind {
+            let opcode: ScriptOpcode = math(op)
+                .ok_or(self.error(expr.line, &format!("{} can't be used in a calc", op)))?;
+            return Ok(Value::Math {
+                opcode,
+                left: Box::new(self.calc(left)?),
+                right: Box::new(self.calc(right)?),
+            });
+        }
+        return Ok(self.single(expr, Some("int"))?.0);
+    }
+
+    /// Whether the type of an expression depends on what it is compared to.
+    fn contextual(&self, expr: &Expr) -> bool {
+        return match &expr.kind {
+            ExprKind::Constant(_) => true,
+            ExprKind::Ident(name) => !self.symbols.commands.contains_key(name),
+            _ => false,
+        };
+    }
+
+    fn condition(&mut self, expr: &Expr) -> Result<Condition, String> {
+        let (op, left, right) = match &expr.kind {
+            ExprKind::Binary { op, left, right } => (*op, left, right),
+            _ => return Err(self.error(expr.line, "Expected a condition")),
+        };
+        if op == "&" {
+            let left: Condition = self.condition(left)?;
+            return Ok(Condition::And(
+                Box::new(left),
+                Box::new(self.condition(right)?),
+            ));
+        }
+        if op == "|" {
+            let left: Condition = self.condition(left)?;
+            return Ok(Condition::Or(
+                Box::new(left),
+                Box::new(self.condition(right)?),
+            ));
+        }
+        let branch: ScriptOpcode = branch(op)
+            .ok_or(self.error(expr.line, &format!("{} can only be used in a calc", op)))?;
+        let (left, right, kind) = if self.contextual(left) && !self.contextual(right) {
+            let (right, kind) = self.single(right, None)?;
+            let (left, _) = self.single(left, Some(&kind))?;
+            (left, right, kind)
+        } else {
+            let (left, kind) = self.single(left, None)?;
+            let (right, _) = self.single(right, Some(&kind))?;
+            (left, right, kind)
+        };
+        if is_string(&kind) {
+            return Err(self.error(expr.line, "Strings can't be compared, use compare"));
+        }
+        return Ok(Condition::Compare {
+            branch,
+            left,
+            right,
+        });
+    }
+
+    /// Checks a command call, a few commands take or return types that depend
+    /// on their arguments and are checked by hand.
+    fn command(
+        &mut self,
+        line: usize,
+        name: &str,
+        secondary: bool,
+        args: &[Expr],
+    ) -> Result<(Value, Vec<String>), String> {
+        let key: String = if secondary {
+            format!(".{}", name)
+        } else {
+            name.to_string()
+        };
+        let symbols: &'a Symbols = self.symbols;
+        let command = symbols
+            .commands
+            .get(&key)
+            .ok_or(self.error(line, &format!("Unknown command: {}", key)))?;
+        let (values, returns) = match name {
+            "enum" => self.enum_command(line, args)?,
+            "struct_param" | "nc_param" | "lc_param" | "oc_param" | "npc_param" | "loc_param"
+            | "obj_param" => self.param_command(line, name, args)?,
+            "db_getfield" => {
+                if args.len() != 3 {
+                    return Err(self.error(line, "db_getfield takes (dbrow, dbcolumn, int)"));
+                }
+                let row: Value = self.single(&args[0], Some("dbrow"))?.0;
+                let column: &DbColumn = self.dbcolumn(line, &args[1])?;
+                let field: Value = self.single(&args[2], Some("int"))?.0;
+                (
+                    vec![row, Value::Int(column.id), field],
+                    column.types.clone(),
+                )
+            }
+            "db_find" | "db_find_with_count" | "db_find_refine" | "db_find_refine_with_count" => {
+                if args.len() != 2 {
+                    return Err(self.error(line, &format!("{} takes (dbcolumn, value)", name)));
+                }
+                let column: &DbColumn = self.dbcolumn(line, &args[0])?;
+                let kind: &str = column.types.first().map_or("int", |kind| kind.as_str());
+                let value: Value = self.single(&args[1], Some(kind))?.0;
+                let returns: Vec<String> = if name.ends_with("_with_count") {
+                    vec!["int".to_string()]
+                } else {
+                    Vec::new()
+                };
+                (
+                    vec![Value::Int(column.id), value, Value::Int(type_char(kind))],
+                    returns,
+                )
+            }
+            "queue" | "strongqueue" | "weakqueue" | "softtimer" | "settimer" => {
+                self.queue_command(line, name, args)?
+            }
+            _ => (
+                self.args(line, args, &command.signature.params)?,
+                command.signature.returns.clone(),
+            ),
+        };
+        return Ok((
+            Value::Command {
+                opcode: command.opcode.clone(),
+                secondary,
+                args: values,
+            },
+            returns,
+        ));
+    }
+
+    /// `enum(input, output, enum, key)`, the types are pushed as their type chars.
+    fn enum_command(
+        &mut self,
+        line: usize,
+        args: &[Expr],
+    ) -> Result<(Vec<Value>, Vec<String>), String> {
+        let types: Vec<&str> = args
+            .iter()
+            .take(2)
+            .filter_map(|arg| match &arg.kind {
+                ExprKind::Ident(kind) if is_type(kind) => Some(kind.as_str()),
+                _ => None,
+            })
+            .collect();
+        if args.len() != 4 || types.len() != 2 {
+            return Err(self.error(line, "enum takes (type, type, enum, key)"));
+        }
+        let (input, output) = (types[0], types[1]);
+        let enum_id: Value = self.single(&args[2], Some("enum"))?.0;
+        let key: Value = self.single(&args[3], Some(input))?.0;
+        let values: Vec<Value> = vec![
+            Value::Int(type_char(input)),
+            Value::Int(type_char(output)),
+            enum_id,
+            key,
+        ];
+        return Ok((values, vec![output.to_string()]));
+    }
+
+    /// The `*_param` commands return whatever type the param was declared as.
+    fn param_command(
+        &mut self,
+        line: usize,
+        name: &str,
+        args: &[Expr],
+    ) -> Result<(Vec<Value>, Vec<String>), String> {
+        let subject: Option<&str> = match name {
+            "struct_param" => Some("struct"),
+            "nc_param" => Some("npc"),
+            "lc_param" => Some("loc"),
+            "oc_param" => Some("obj"),
+            _ => None,
+        };
+        let count: usize = subject.map_or(1, |_| 2);
+        if args.len() != count {
+            return Err(self.error(line, &format!("{} takes {} arguments", name, count)));
+        }
+        let mut values: Vec<Value> = Vec::with_capacity(count);
+        if let Some(subject) = subject {
+            values.push(self.single(&args[0], Some(subject))?.0);
+        }
+        let param: &Expr = &args[count - 1];
+        let param_name: String = match &param.kind {
+            ExprKind::Ident(param) => param.clone(),
+            ExprKind::Constant(constant) => self.ctx.constant(&format!("^{}", constant))?,
+            _ => return Err(self.error(line, "Expected a param")),
+        };
+        let kind: String = self
+            .ctx
+            .params
+            .get(&param_name)
+            .cloned()
+            .ok_or(self.error(line, &format!("Unknown param: {}", param_name)))?;
+        values.push(self.single(param, Some("param"))?.0);
+        return Ok((values, vec![kind]));
+    }
+
+    /// `queue(script, delay, args...)`, the args are checked against the queued
+    /// script and followed by a string of their type chars.
+    fn queue_command(
+        &mut self,
+        line: usize,
+        name: &str,
+        args: &[Expr],
+    ) -> Result<(Vec<Value>, Vec<String>), String> {
+        let kind: &str = match name {
+            "softtimer" => "softtimer",
+            "settimer" => "timer",
+            _ => "queue",
+        };
+        let target: &str = match args.first().map(|arg| &arg.kind) {
+            Some(ExprKind::Ident(target)) if args.len() >= 2 => target,
+            _ => return Err(self.error(line, &format!("{} takes ({}, int, ...)", name, kind))),
+        };
+        let script: &ScriptSymbol = match kind {
+            "queue" => self
+                .script_symbol(&format!("[queue,{}]", target))
+                .or(self.script_symbol(&format!("[weakqueue,{}]", target))),
+            _ => self.script_symbol(&format!("[{},{}]", kind, target)),
+        }
+        .ok_or(self.error(line, &format!("Unknown {}: {}", kind, target)))?;
+
+        let mut values: Vec<Value> =
+            vec![Value::Int(script.id), self.single(&args[1], Some("int"))?.0];
+        values.extend(self.args(line, &args[2..], &script.signature.params)?);
+        let types: String = script
+            .signature
+            .params
+            .iter()
+            .map(|kind| char::from_u32(type_char(kind) as u32).unwrap_or('i'))
+            .collect();
+        values.push(Value::Str(types));
+        return Ok((values, Vec::new()));
+    }
+}
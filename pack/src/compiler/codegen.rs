@@ -0,0 +1,490 @@
+use cache::{ScriptFile, ScriptOpcode};
+use io::Packet;
+
+/// A type checked expression, pushes its values onto the int and string stacks.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Int(i32),
+    Str(String),
+    Join(Vec<Value>),
+    Local {
+        index: usize,
+        string: bool,
+    },
+    Array {
+        array: usize,
+        index: Box<Value>,
+    },
+    Var {
+        push: ScriptOpcode,
+        operand: i32,
+    },
+    Command {
+        opcode: ScriptOpcode,
+        secondary: bool,
+        args: Vec<Value>,
+    },
+    GoSub {
+        script: i32,
+        args: Vec<Value>,
+    },
+    Math {
+        opcode: ScriptOpcode,
+        left: Box<Value>,
+        right: Box<Value>,
+    },
+}
+
+/// Where an assignment stores a value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Target {
+    Local { index: usize, string: bool },
+    Array { array: usize, index: Value },
+    Var { pop: ScriptOpcode, operand: i32 },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Condition {
+    Compare {
+        branch: ScriptOpcode,
+        left: Value,
+        right: Value,
+    },
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+/// A type checked statement along with the source line it came from.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Code {
+    pub kind: CodeKind,
+    pub line: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CodeKind {
+    Discard {
+        value: Value,
+        ints: usize,
+        strings: usize,
+    },
+    Store {
+        targets: Vec<Target>,
+        values: Vec<Value>,
+    },
+    DefineArray {
+        array: usize,
+        kind: i32,
+        size: Value,
+    },
+    If {
+        condition: Condition,
+        then: Vec<Code>,
+        otherwise: Vec<Code>,
+    },
+    While {
+        condition: Condition,
+        body: Vec<Code>,
+    },
+    Switch {
+        value: Value,
+        cases: Vec<(Vec<i32>, Vec<Code>)>,
+        default: Vec<Code>,
+    },
+    Return(Vec<Value>),
+    Jump {
+        script: i32,
+        args: Vec<Value>,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operand {
+    Int(i32),
+    Str(String),
+    Label(usize), // a branch target, resolved once every label is placed
+}
+
+/// The compiled form of a script, everything `ScriptFile::new` reads.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompiledScript {
+    pub name: String,
+    pub path: String,
+    pub lookup: i32,
+    pub params: Vec<u8>,
+    pub lines: Vec<(i32, i32)>, // (pc, line) whenever the line changes
+    pub code: Vec<(ScriptOpcode, Operand)>,
+    pub switches: Vec<Vec<(i32, i32)>>,
+    pub int_local_count: usize,
+    pub string_local_count: usize,
+    pub int_arg_count: usize,
+    pub string_arg_count: usize,
+}
+
+/// Whether the last statement of `body` is a return, so its end can't be reached.
+pub fn ends_with_return(body: &[Code]) -> bool {
+    return matches!(body.last(), Some(code) if matches!(code.kind, CodeKind::Return(_)));
+}
+
+/// Turns the type checked statements of a script into instructions.
+pub struct Codegen {
+    code: Vec<(ScriptOpcode, Operand)>,
+    lines: Vec<(i32, i32)>,
+    labels: Vec<Option<usize>>,
+    switches: Vec<Vec<(i32, usize)>>,
+    switch_pcs: Vec<usize>,
+    line: usize,
+}
+
+impl Codegen {
+    pub fn new() -> Codegen {
+        return Codegen {
+            code: Vec::new(),
+            lines: Vec::new(),
+            labels: Vec::new(),
+            switches: Vec::new(),
+            switch_pcs: Vec::new(),
+            line: 0,
+        };
+    }
+
+    /// Generates the instructions, line table and switch tables of a script body
+    /// into `script`. A script always ends with a return.
+    pub fn generate(
+        mut self,
+        body: &[Code],
+        end: usize,
+        script: &mut CompiledScript,
+    ) -> Result<(), String> {
+        self.block(body);
+        if !ends_with_return(body) {
+            self.line = end;
+            self.emit(ScriptOpcode::Return, Operand::Int(0));
+        }
+
+        script.code = Vec::with_capacity(self.code.len());
+        for (pc, (opcode, operand)) in self.code.iter().enumerate() {
+            let operand: Operand = match operand {
+                Operand::Label(label) => Operand::Int(self.offset(pc, *label)?),
+                operand => operand.clone(),
+            };
+            script.code.push((opcode.clone(), operand));
+        }
+        script.switches = Vec::with_capacity(self.switches.len());
+        for (table, cases) in self.switches.iter().enumerate() {
+            let pc: usize = self.switch_pcs[table];
+            let mut resolved: Vec<(i32, i32)> = Vec::with_capacity(cases.len());
+            for (key, label) in cases {
+                resolved.push((*key, self.offset(pc, *label)?));
+            }
+            script.switches.push(resolved);
+        }
+        script.lines = self.lines;
+        return Ok(());
+    }
+
+    /// The pc is incremented after every instruction, so a jump lands one past its offset.
+    fn offset(&self, pc: usize, label: usize) -> Result<i32, String> {
+        let target: usize = self.labels[label].ok_or("Label was never placed")?;
+        return Ok(target as i32 - pc as i32 - 1);
+    }
+
+    fn emit(&mut self, opcode: ScriptOpcode, operand: Operand) {
+        let pc: i32 = self.code.len() as i32;
+        if self
+            .lines
+            .last()
+            .is_none_or(|&(_, line)| line != self.line as i32)
+        {
+            self.lines.push((pc, self.line as i32));
+        }
+        self.code.push((opcode, operand));
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        return self.labels.len() - 1;
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len());
+    }
+
+    fn block(&mut self, body: &[Code]) {
+        for code in body {
+            self.statement(code);
+        }
+    }
+
+    fn statement(&mut self, code: &Code) {
+        self.line = code.line;
+        match &code.kind {
+            CodeKind::Discard {
+                value,
+                ints,
+                strings,
+            } => {
+                self.value(value);
+                for _ in 0..*ints {
+                    self.emit(ScriptOpcode::PopIntDiscard, Operand::Int(0));
+                }
+                for _ in 0..*strings {
+                    self.emit(ScriptOpcode::PopStringDiscard, Operand::Int(0));
+                }
+            }
+            CodeKind::Store { targets, values } => {
+                for target in targets {
+                    if let Target::Array { index, .. } = target {
+                        self.value(index);
+                    }
+                }
+                for value in values {
+                    self.value(value);
+                }
+                for target in targets.iter().rev() {
+                    match target {
+                        Target::Local { index, string } => {
+                            let opcode: ScriptOpcode = if *string {
+                                ScriptOpcode::PopStringLocal
+                            } else {
+                                ScriptOpcode::PopIntLocal
+                            };
+                            self.emit(opcode, Operand::Int(*index as i32));
+                        }
+                        Target::Array { array, .. } => {
+                            self.emit(ScriptOpcode::PopArrayInt, Operand::Int(*array as i32));
+                        }
+                        Target::Var { pop, operand } => {
+                            self.emit(pop.clone(), Operand::Int(*operand));
+                        }
+                    }
+                }
+            }
+            CodeKind::DefineArray { array, kind, size } => {
+                self.value(size);
+                self.emit(
+                    ScriptOpcode::DefineArray,
+                    Operand::Int(((*array as i32) << 16) | kind),
+                );
+            }
+            CodeKind::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let (then_label, else_label) = (self.label(), self.label());
+                self.condition(condition, then_label, else_label);
+                self.place(then_label);
+                self.block(then);
+                if otherwise.is_empty() {
+                    self.place(else_label);
+                } else {
+                    let end: usize = self.label();
+                    self.line = code.line;
+                    self.emit(ScriptOpcode::Branch, Operand::Label(end));
+                    self.place(else_label);
+                    self.block(otherwise);
+                    self.place(end);
+                }
+            }
+            CodeKind::While { condition, body } => {
+                let (start, body_label, end) = (self.label(), self.label(), self.label());
+                self.place(start);
+                self.condition(condition, body_label, end);
+                self.place(body_label);
+                self.block(body);
+                self.line = code.line;
+                self.emit(ScriptOpcode::Branch, Operand::Label(start));
+                self.place(end);
+            }
+            CodeKind::Switch {
+                value,
+                cases,
+                default,
+            } => {
+                self.value(value);
+                let table: usize = self.switches.len();
+                self.switches.push(Vec::new());
+                self.switch_pcs.push(self.code.len());
+                self.emit(ScriptOpcode::Switch, Operand::Int(table as i32));
+
+                let end: usize = self.label();
+                self.block(default);
+                self.line = code.line;
+                self.emit(ScriptOpcode::Branch, Operand::Label(end));
+                for (keys, body) in cases {
+                    let label: usize = self.label();
+                    self.place(label);
+                    for key in keys {
+                        self.switches[table].push((*key, label));
+                    }
+                    self.block(body);
+                    self.line = code.line;
+                    self.emit(ScriptOpcode::Branch, Operand::Label(end));
+                }
+                self.place(end);
+            }
+            CodeKind::Return(values) => {
+                for value in values {
+                    self.value(value);
+                }
+                self.emit(ScriptOpcode::Return, Operand::Int(0));
+            }
+            CodeKind::Jump { script, args } => {
+                for arg in args {
+                    self.value(arg);
+                }
+                self.emit(ScriptOpcode::JumpWithParams, Operand::Int(*script));
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Condition, then: usize, otherwise: usize) {
+        match condition {
+            Condition::Compare {
+                branch,
+                left,
+                right,
+            } => {
+                self.value(left);
+                self.value(right);
+                self.emit(branch.clone(), Operand::Label(then));
+                self.emit(ScriptOpcode::Branch, Operand::Label(otherwise));
+            }
+            Condition::And(left, right) => {
+                let next: usize = self.label();
+                self.condition(left, next, otherwise);
+                self.place(next);
+                self.condition(right, then, otherwise);
+            }
+            Condition::Or(left, right) => {
+                let next: usize = self.label();
+                self.condition(left, then, next);
+                self.place(next);
+                self.condition(right, then, otherwise);
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Int(value) => self.emit(ScriptOpcode::PushConstantInt, Operand::Int(*value)),
+            Value::Str(value) => self.emit(
+                ScriptOpcode::PushConstantString,
+                Operand::Str(value.clone()),
+            ),
+            Value::Join(parts) => {
+                for part in parts {
+                    self.value(part);
+                }
+                self.emit(ScriptOpcode::JoinString, Operand::Int(parts.len() as i32));
+            }
+            Value::Local { index, string } => {
+                let opcode: ScriptOpcode = if *string {
+                    ScriptOpcode::PushStringLocal
+                } else {
+                    ScriptOpcode::PushIntLocal
+                };
+                self.emit(opcode, Operand::Int(*index as i32));
+            }
+            Value::Array { array, index } => {
+                self.value(index);
+                self.emit(ScriptOpcode::PushArrayInt, Operand::Int(*array as i32));
+            }
+            Value::Var { push, operand } => self.emit(push.clone(), Operand::Int(*operand)),
+            Value::Command {
+                opcode,
+                secondary,
+                args,
+            } => {
+                for arg in args {
+                    self.value(arg);
+                }
+                self.emit(opcode.clone(), Operand::Int(*secondary as i32));
+            }
+            Value::GoSub { script, args } => {
+                for arg in args {
+                    self.value(arg);
+                }
+                self.emit(ScriptOpcode::GoSubWithParams, Operand::Int(*script));
+            }
+            Value::Math {
+                opcode,
+                left,
+                right,
+            } => {
+                self.value(left);
+                self.value(right);
+                self.emit(opcode.clone(), Operand::Int(0));
+            }
+        }
+    }
+}
+
+impl Default for Codegen {
+    fn default() -> Codegen {
+        return Codegen::new();
+    }
+}
+
+impl CompiledScript {
+    /// Encodes the script in the layout `ScriptFile::new` reads, the header,
+    /// the instructions and then the trailer with the local counts and switch tables.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut size: usize = self.name.len() + 1 + self.path.len() + 1 + 4;
+        size += 1 + self.params.len() + 2 + self.lines.len() * 8;
+        for (opcode, operand) in &self.code {
+            size += 2 + match operand {
+                Operand::Str(value) => value.len() + 1,
+                _ if ScriptFile::is_large_operand(opcode) => 4,
+                _ => 1,
+            };
+        }
+        let trailer: usize = 1 + self
+            .switches
+            .iter()
+            .map(|cases| 2 + cases.len() * 8)
+            .sum::<usize>();
+        size += 12 + trailer + 2;
+
+        let mut buf: Packet = Packet::new(size);
+        buf.pjstr(&self.name, 0);
+        buf.pjstr(&self.path, 0);
+        buf.p4(self.lookup);
+        buf.p1(self.params.len() as i32);
+        for param in &self.params {
+            buf.p1(*param as i32);
+        }
+        buf.p2(self.lines.len() as i32);
+        for (pc, line) in &self.lines {
+            buf.p4(*pc);
+            buf.p4(*line);
+        }
+
+        for (opcode, operand) in &self.code {
+            buf.p2(opcode.clone() as i32);
+            match operand {
+                Operand::Str(value) => buf.pjstr(value, 0),
+                Operand::Int(value) if ScriptFile::is_large_operand(opcode) => buf.p4(*value),
+                Operand::Int(value) => buf.p1(*value),
+                Operand::Label(_) => unreachable!("labels are resolved by the codegen"),
+            }
+        }
+
+        buf.p4(self.code.len() as i32);
+        buf.p2(self.int_local_count as i32);
+        buf.p2(self.string_local_count as i32);
+        buf.p2(self.int_arg_count as i32);
+        buf.p2(self.string_arg_count as i32);
+        buf.p1(self.switches.len() as i32);
+        for cases in &self.switches {
+            buf.p2(cases.len() as i32);
+            for (key, offset) in cases {
+                buf.p4(*key);
+                buf.p4(*offset);
+            }
+        }
+        buf.p2(trailer as i32);
+        return buf.data;
+    }
+}
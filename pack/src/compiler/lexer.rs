@@ -0,0 +1,286 @@
+/// A single piece of an interpolated string, either literal text or the
+/// tokens of an expression written between `<` and `>`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum StringPart {
+    Text(String),
+    Expr(Vec<Token>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TokenKind {
+    Ident(String), // names, commands and coords, `.name` for secondary commands
+    Int(i32),      // decimal or hex
+    Char(i32),     // 'c'
+    Str(Vec<StringPart>),
+    Local(String),    // $name
+    GameVar(String),  // %name or .%name
+    Constant(String), // ^name
+    Proc(String),     // ~name
+    Label(String),    // @name
+    Symbol(&'static str),
+    Eof,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub line: usize,
+}
+
+const SYMBOLS: [&str; 21] = [
+    "<=", ">=", "[", "]", "(", ")", "{", "}", ",", ";", ":", "=", "!", "<", ">", "&", "|", "+",
+    "-", "*", "/",
+];
+
+/// Splits RuneScript source into tokens.
+///
+/// Strings are lexed as a whole with their `<expr>` interpolations lexed
+/// recursively, a `<p,mesanim>` tag is kept as text for the client.
+pub struct Lexer<'src> {
+    path: &'src str,
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+fn is_ident(char: char) -> bool {
+    return char.is_ascii_alphanumeric() || char == '_';
+}
+
+impl<'src> Lexer<'src> {
+    pub fn new(path: &'src str, src: &str) -> Lexer<'src> {
+        return Lexer {
+            path,
+            chars: src.chars().collect(),
+            pos: 0,
+            line: 1,
+        };
+    }
+
+    /// Lexes the whole source, the last token is always `Eof`.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, String> {
+        let mut tokens: Vec<Token> = Vec::new();
+        loop {
+            let token: Token = self.next_token(tokens.last())?;
+            let eof: bool = token.kind == TokenKind::Eof;
+            tokens.push(token);
+            if eof {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        return format!("{}:{}: {}", self.path, self.line, message);
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        return self.chars.get(self.pos + offset).copied();
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), String> {
+        while let Some(char) = self.peek(0) {
+            if char == '\n' {
+                self.line += 1;
+                self.pos += 1;
+            } else if char.is_whitespace() {
+                self.pos += 1;
+            } else if char == '/' && self.peek(1) == Some('/') {
+                while self.peek(0).is_some_and(|char| char != '\n') {
+                    self.pos += 1;
+                }
+            } else if char == '/' && self.peek(1) == Some('*') {
+                self.pos += 2;
+                loop {
+                    match self.peek(0) {
+                        Some('*') if self.peek(1) == Some('/') => {
+                            self.pos += 2;
+                            break;
+                        }
+                        Some(char) => {
+                            if char == '\n' {
+                                self.line += 1;
+                            }
+                            self.pos += 1;
+                        }
+                        None => return Err(self.error("Unterminated block comment")),
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Reads a name, a `:` is part of the name only when another name follows it,
+    /// so `interface:com` is one name but `case obj:` is not.
+    fn name(&mut self) -> String {
+        let start: usize = self.pos;
+        while let Some(char) = self.peek(0) {
+            if is_ident(char) || (char == ':' && self.peek(1).is_some_and(is_ident)) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        return self.chars[start..self.pos].iter().collect();
+    }
+
+    fn next_token(&mut self, previous: Option<&Token>) -> Result<Token, String> {
+        self.skip_whitespace()?;
+        let line: usize = self.line;
+        let token = |kind: TokenKind| Token { kind, line };
+
+        let char: char = match self.peek(0) {
+            Some(char) => char,
+            None => return Ok(token(TokenKind::Eof)),
+        };
+        let next: Option<char> = self.peek(1);
+
+        // a minus is part of a number unless it follows a value.
+        let after_value: bool = previous.is_some_and(|previous| match &previous.kind {
+            TokenKind::Symbol(symbol) => *symbol == ")",
+            TokenKind::Eof => false,
+            _ => true,
+        });
+
+        if char == '"' {
+            self.pos += 1;
+            return Ok(token(TokenKind::Str(self.string()?)));
+        }
+        if char == '\'' {
+            let value: char = next.ok_or(self.error("Unterminated char"))?;
+            if self.peek(2) != Some('\'') {
+                return Err(self.error("Invalid char literal"));
+            }
+            self.pos += 3;
+            return Ok(token(TokenKind::Char(value as i32)));
+        }
+        if char == '$' || char == '^' || char == '~' || char == '@' {
+            self.pos += 1;
+            // procs and labels can be named for the secondary entity, `~.name`.
+            let secondary: bool = (char == '~' || char == '@') && self.peek(0) == Some('.');
+            if secondary {
+                self.pos += 1;
+            }
+            let mut name: String = self.name();
+            if name.is_empty() {
+                return Err(self.error(&format!("Expected a name after {}", char)));
+            }
+            if secondary {
+                name.insert(0, '.');
+            }
+            return Ok(token(match char {
+                '$' => TokenKind::Local(name),
+                '^' => TokenKind::Constant(name),
+                '~' => TokenKind::Proc(name),
+                _ => TokenKind::Label(name),
+            }));
+        }
+        if char == '%' && next.is_some_and(is_ident) {
+            self.pos += 1;
+            return Ok(token(TokenKind::GameVar(self.name())));
+        }
+        if char == '%' {
+            self.pos += 1;
+            return Ok(token(TokenKind::Symbol("%")));
+        }
+        if char == '.' && next == Some('%') {
+            self.pos += 2;
+            return Ok(token(TokenKind::GameVar(format!(".{}", self.name()))));
+        }
+        if char == '.' && next.is_some_and(is_ident) {
+            self.pos += 1;
+            return Ok(token(TokenKind::Ident(format!(".{}", self.name()))));
+        }
+        if char == '-' && !after_value && next.is_some_and(|char| char.is_ascii_digit()) {
+            self.pos += 1;
+            let name: String = self.name();
+            return Ok(token(TokenKind::Int(
+                self.number(&name).map(|value| value.wrapping_neg())?,
+            )));
+        }
+        if is_ident(char) {
+            let name: String = self.name();
+            if name.chars().all(|char| char.is_ascii_digit()) || name.starts_with("0x") {
+                return Ok(token(TokenKind::Int(self.number(&name)?)));
+            }
+            return Ok(token(TokenKind::Ident(name)));
+        }
+        for symbol in SYMBOLS {
+            if self.chars[self.pos..].starts_with(&symbol.chars().collect::<Vec<char>>()) {
+                self.pos += symbol.len();
+                return Ok(token(TokenKind::Symbol(symbol)));
+            }
+        }
+        return Err(self.error(&format!("Unexpected character: {}", char)));
+    }
+
+    fn number(&self, text: &str) -> Result<i32, String> {
+        let value: Option<i32> = match text.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok().map(|value| value as i32),
+            None => text.parse::<i32>().ok(),
+        };
+        return value.ok_or(self.error(&format!("Invalid number: {}", text)));
+    }
+
+    /// Lexes the rest of a string after its opening quote.
+    fn string(&mut self) -> Result<Vec<StringPart>, String> {
+        let mut parts: Vec<StringPart> = Vec::new();
+        let mut text: String = String::new();
+        loop {
+            let char: char = match self.peek(0) {
+                Some('\n') | None => return Err(self.error("Unterminated string")),
+                Some(char) => char,
+            };
+            self.pos += 1;
+            match char {
+                '"' => break,
+                '\\' => {
+                    text.push(self.peek(0).ok_or(self.error("Unterminated string"))?);
+                    self.pos += 1;
+                }
+                '<' if self.peek(0) == Some('p') && self.peek(1) == Some(',') => {
+                    text.push('<');
+                }
+                '<' => {
+                    if !text.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(StringPart::Expr(self.interpolation()?));
+                }
+                char => text.push(char),
+            }
+        }
+        if !text.is_empty() || parts.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+        return Ok(parts);
+    }
+
+    /// Lexes the tokens of an interpolated expression up to its closing `>`.
+    fn interpolation(&mut self) -> Result<Vec<Token>, String> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut depth: usize = 0;
+        loop {
+            let token: Token = self.next_token(tokens.last())?;
+            match &token.kind {
+                TokenKind::Eof => return Err(self.error("Unterminated string")),
+                TokenKind::Symbol(">") if depth == 0 => break,
+                TokenKind::Symbol("(") => depth += 1,
+                TokenKind::Symbol(")") => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            tokens.push(token);
+        }
+        if tokens.is_empty() {
+            return Err(self.error("Empty string interpolation"));
+        }
+        tokens.push(Token {
+            kind: TokenKind::Eof,
+            line: self.line,
+        });
+        return Ok(tokens);
+    }
+}
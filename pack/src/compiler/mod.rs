@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use cache::{ScriptOpcode, COMPILER_VERSION};
+use io::Packet;
+
+use crate::compiler::ast::Script;
+use crate::compiler::checker::Checker;
+use crate::compiler::codegen::{Code, Codegen, CompiledScript};
+use crate::compiler::lexer::Lexer;
+use crate::compiler::parser::Parser;
+use crate::compiler::symbols::{
+    load_commands, DbColumn, GameVar, ScriptSymbol, Signature, Symbols,
+};
+use crate::config::{find_files, read_configs};
+use crate::context::PackContext;
+
+pub mod ast;
+pub mod checker;
+pub mod codegen;
+pub mod lexer;
+pub mod parser;
+pub mod symbols;
+
+/// A `.rs2` file after parsing, `path` is relative to the source directory.
+pub struct SourceFile {
+    pub path: String,
+    pub scripts: Vec<Script>,
+}
+
+/// Parses every `.rs2` file under `src/scripts` except the command declarations in `engine.rs2`.
+pub fn parse_scripts(src: &Path) -> Result<Vec<SourceFile>, String> {
+    let mut files: Vec<SourceFile> = Vec::new();
+    for path in find_files(&src.join("scripts"), "rs2") {
+        if path.file_name().is_some_and(|name| name == "engine.rs2") {
+            continue;
+        }
+        let relative: String = path
+            .strip_prefix(src)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let source: String = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let tokens = Lexer::new(&relative, &source).tokenize()?;
+        let scripts: Vec<Script> = Parser::new(&relative, tokens).scripts()?;
+        files.push(SourceFile {
+            path: relative,
+            scripts,
+        });
+    }
+    return Ok(files);
+}
+
+/// Reads the `%vars`, varps are already given ids by the config pack,
+/// varns and varss only exist for scripts and are given ids here.
+fn load_vars(ctx: &mut PackContext, src: &Path) -> Result<HashMap<String, GameVar>, String> {
+    let mut vars: HashMap<String, GameVar> = HashMap::new();
+    let kinds: [(&str, ScriptOpcode, ScriptOpcode); 3] = [
+        ("varp", ScriptOpcode::PushVarp, ScriptOpcode::PopVarp),
+        ("varn", ScriptOpcode::PushVarn, ScriptOpcode::PopVarn),
+        ("vars", ScriptOpcode::PushVars, ScriptOpcode::PopVars),
+    ];
+    for (kind, push, pop) in kinds {
+        for config in read_configs(src, kind)? {
+            let var: GameVar = GameVar {
+                push: push.clone(),
+                pop: pop.clone(),
+                id: ctx.assign(kind, &config.name)?,
+                kind: config.get("type").unwrap_or("int").to_string(),
+            };
+            vars.insert(config.name, var);
+        }
+    }
+    return Ok(vars);
+}
+
+/// Gives every dbtable and dbrow an id and reads the columns of each table.
+///
+/// A column is `column=name,type,...` and its id is `table << 12 | column << 4`.
+fn load_dbcolumns(ctx: &mut PackContext, src: &Path) -> Result<HashMap<String, DbColumn>, String> {
+    let mut columns: HashMap<String, DbColumn> = HashMap::new();
+    for table in read_configs(src, "dbtable")? {
+        let table_id: i32 = ctx.assign("dbtable", &table.name)?;
+        let mut index: i32 = 0;
+        for (key, value) in &table.lines {
+            if key != "column" {
+                continue;
+            }
+            let mut parts = value.split(',').map(|part| part.trim());
+            let name: &str = parts.next().unwrap_or_default();
+            let column: DbColumn = DbColumn {
+                id: (table_id << 12) | (index << 4),
+                types: parts.map(|kind| kind.to_string()).collect(),
+            };
+            columns.insert(format!("{}:{}", table.name, name), column);
+            index += 1;
+        }
+    }
+    for row in read_configs(src, "dbrow")? {
+        ctx.assign("dbrow", &row.name)?;
+    }
+    for hunt in read_configs(src, "hunt")? {
+        ctx.assign("hunt", &hunt.name)?;
+    }
+    return Ok(columns);
+}
+
+/// Compiles every script under `src/scripts` into `server/script.dat` and `server/script.idx`.
+///
+/// Script ids are kept in `src/pack/script.pack`. Every script is checked before
+/// the pack fails, so one run reports all of the scripts that don't type check.
+/// Nothing is written when there are no scripts or any of them fail.
+pub fn compile(ctx: &mut PackContext, src: &Path, out: &Path) -> Result<(), String> {
+    let start: Instant = Instant::now();
+    let files: Vec<SourceFile> = parse_scripts(src)?;
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let mut scripts: HashMap<String, ScriptSymbol> = HashMap::new();
+    for file in &files {
+        for script in &file.scripts {
+            let name: String = script.name();
+            let symbol: ScriptSymbol = ScriptSymbol {
+                id: ctx.assign("script", &name)?,
+                signature: Signature::of(script),
+            };
+            if scripts.insert(name.clone(), symbol).is_some() {
+                return Err(format!(
+                    "{}:{}: Duplicate script {}",
+                    file.path, script.line, name
+                ));
+            }
+        }
+    }
+
+    let symbols: Symbols = Symbols {
+        commands: load_commands(&src.join("scripts").join("engine.rs2"))?,
+        scripts,
+        vars: load_vars(ctx, src)?,
+        dbcolumns: load_dbcolumns(ctx, src)?,
+    };
+
+    let count: usize = ctx.pack("script")?.count();
+    let mut compiled: Vec<Option<Vec<u8>>> = vec![None; count];
+    let mut errors: Vec<String> = Vec::new();
+    for file in &files {
+        for script in &file.scripts {
+            match compile_script(ctx, &symbols, &file.path, script) {
+                Ok(data) => compiled[symbols.scripts[&script.name()].id as usize] = Some(data),
+                Err(e) => errors.push(format!("{}: {}", script.name(), e)),
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "{} scripts failed to compile:\n{}",
+            errors.len(),
+            errors.join("\n")
+        ));
+    }
+
+    let (dat, idx) = encode_scripts(&compiled)?;
+    let server: PathBuf = out.join("server");
+    std::fs::write(server.join("script.dat"), dat)
+        .map_err(|e| format!("Could not write script.dat: {}", e))?;
+    std::fs::write(server.join("script.idx"), idx)
+        .map_err(|e| format!("Could not write script.idx: {}", e))?;
+
+    println!(
+        "Compiled {} scripts in: {:?}",
+        compiled.iter().flatten().count(),
+        start.elapsed()
+    );
+    return Ok(());
+}
+
+/// Type checks and generates a single script.
+pub fn compile_script(
+    ctx: &mut PackContext,
+    symbols: &Symbols,
+    path: &str,
+    script: &Script,
+) -> Result<Vec<u8>, String> {
+    let (mut compiled, body): (CompiledScript, Vec<Code>) =
+        Checker::new(ctx, symbols, path).script(script)?;
+    let end: usize = body.last().map_or(script.line, |code| code.line);
+    Codegen::new().generate(&body, end, &mut compiled)?;
+    return Ok(compiled.encode());
+}
+
+/// Writes `script.dat` as the count and compiler version followed by each
+/// script, and `script.idx` as the count followed by the length of each.
+/// Ids without a script have a length of 0.
+pub fn encode_scripts(scripts: &[Option<Vec<u8>>]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let size: usize = scripts.iter().flatten().map(|data| data.len()).sum();
+    let mut dat: Packet = Packet::new(2 + 4 + size);
+    let mut idx: Packet = Packet::new(2 + scripts.len() * 2);
+    dat.p2(scripts.len() as i32);
+    dat.p4(COMPILER_VERSION);
+    idx.p2(scripts.len() as i32);
+    let empty: Vec<u8> = Vec::new();
+    for script in scripts {
+        let data: &Vec<u8> = script.as_ref().unwrap_or(&empty);
+        if data.len() > u16::MAX as usize {
+            return Err(format!("Script is too large: {} bytes", data.len()));
+        }
+        dat.pdata(data, 0, data.len());
+        idx.p2(data.len() as i32);
+    }
+    return Ok((dat.data, idx.data));
+}
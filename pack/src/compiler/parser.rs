@@ -0,0 +1,482 @@
+use crate::compiler::ast::{Case, Expr, ExprKind, Param, Script, Stmt, StmtKind};
+use crate::compiler::lexer::{StringPart, Token, TokenKind};
+
+/// Builds the scripts of a source file from its tokens.
+pub struct Parser<'src> {
+    path: &'src str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'src> Parser<'src> {
+    pub fn new(path: &'src str, tokens: Vec<Token>) -> Parser<'src> {
+        return Parser {
+            path,
+            tokens,
+            pos: 0,
+        };
+    }
+
+    /// Parses every script in the file.
+    pub fn scripts(&mut self) -> Result<Vec<Script>, String> {
+        let mut scripts: Vec<Script> = Vec::new();
+        while !self.at_eof() {
+            scripts.push(self.script()?);
+        }
+        return Ok(scripts);
+    }
+
+    fn peek(&self) -> &Token {
+        return &self.tokens[self.pos.min(self.tokens.len() - 1)];
+    }
+
+    fn next(&mut self) -> Token {
+        let token: Token = self.peek().clone();
+        if self.pos < self.tokens.len() {
+            self.pos += 1;
+        }
+        return token;
+    }
+
+    fn at_eof(&self) -> bool {
+        return self.peek().kind == TokenKind::Eof;
+    }
+
+    fn at_symbol(&self, symbol: &str) -> bool {
+        return matches!(&self.peek().kind, TokenKind::Symbol(found) if *found == symbol);
+    }
+
+    fn at_ident(&self, name: &str) -> bool {
+        return matches!(&self.peek().kind, TokenKind::Ident(found) if found == name);
+    }
+
+    fn error(&self, token: &Token, message: &str) -> String {
+        return format!("{}:{}: {}", self.path, token.line, message);
+    }
+
+    fn describe(token: &Token) -> String {
+        return match &token.kind {
+            TokenKind::Ident(name) => name.clone(),
+            TokenKind::Int(value) | TokenKind::Char(value) => value.to_string(),
+            TokenKind::Str(_) => "string".to_string(),
+            TokenKind::Local(name) => format!("${}", name),
+            TokenKind::GameVar(name) => format!("%{}", name),
+            TokenKind::Constant(name) => format!("^{}", name),
+            TokenKind::Proc(name) => format!("~{}", name),
+            TokenKind::Label(name) => format!("@{}", name),
+            TokenKind::Symbol(symbol) => symbol.to_string(),
+            TokenKind::Eof => "end of file".to_string(),
+        };
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<Token, String> {
+        if !self.at_symbol(symbol) {
+            let token: &Token = self.peek();
+            return Err(self.error(
+                token,
+                &format!("Expected {} but found {}", symbol, Parser::describe(token)),
+            ));
+        }
+        return Ok(self.next());
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        let token: Token = self.next();
+        return match token.kind {
+            TokenKind::Ident(name) => Ok(name),
+            // coords and numbers are valid subjects, `[mapzone,0_50_50]`.
+            TokenKind::Int(value) => Ok(value.to_string()),
+            _ => Err(self.error(
+                &token,
+                &format!("Expected a name but found {}", Parser::describe(&token)),
+            )),
+        };
+    }
+
+    fn local(&mut self) -> Result<String, String> {
+        let token: Token = self.next();
+        return match token.kind {
+            TokenKind::Local(name) => Ok(name),
+            _ => Err(self.error(
+                &token,
+                &format!("Expected a local but found {}", Parser::describe(&token)),
+            )),
+        };
+    }
+
+    /// Whether the `(` at the current token starts a list of types without names.
+    fn at_returns(&self) -> bool {
+        let after = |offset: usize| self.tokens.get(self.pos + offset).map(|token| &token.kind);
+        return matches!(after(1), Some(TokenKind::Ident(_)))
+            && !matches!(after(2), Some(TokenKind::Local(_)));
+    }
+
+    fn script(&mut self) -> Result<Script, String> {
+        let line: usize = self.expect("[")?.line;
+        let trigger: String = self.ident()?;
+        self.expect(",")?;
+        let subject: String = self.ident()?;
+        self.expect("]")?;
+
+        // the params can be left out, `[proc,name](int)` only has returns.
+        let mut params: Vec<Param> = Vec::new();
+        let mut returns: Vec<String> = Vec::new();
+        if self.at_symbol("(") && !self.at_returns() {
+            self.next();
+            while !self.at_symbol(")") {
+                if !params.is_empty() {
+                    self.expect(",")?;
+                }
+                let kind: String = self.ident()?;
+                let name: String = self.local()?;
+                params.push(Param { kind, name });
+            }
+            self.expect(")")?;
+        }
+        if self.at_symbol("(") {
+            self.next();
+            while !self.at_symbol(")") {
+                if !returns.is_empty() {
+                    self.expect(",")?;
+                }
+                returns.push(self.ident()?);
+            }
+            self.expect(")")?;
+        }
+
+        let mut body: Vec<Stmt> = Vec::new();
+        while !self.at_eof() && !self.at_symbol("[") {
+            body.push(self.statement()?);
+        }
+        return Ok(Script {
+            trigger,
+            subject,
+            params,
+            returns,
+            body,
+            line,
+        });
+    }
+
+    /// Parses a statement, a block is returned as its statements.
+    fn body(&mut self) -> Result<Vec<Stmt>, String> {
+        let stmt: Stmt = self.statement()?;
+        return Ok(match stmt.kind {
+            StmtKind::Block(stmts) => stmts,
+            _ => vec![stmt],
+        });
+    }
+
+    fn condition(&mut self) -> Result<Expr, String> {
+        self.expect("(")?;
+        let condition: Expr = self.expr()?;
+        self.expect(")")?;
+        return Ok(condition);
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        let token: Token = self.peek().clone();
+        let line: usize = token.line;
+        let stmt = |kind: StmtKind| Stmt { kind, line };
+
+        match &token.kind {
+            TokenKind::Symbol("{") => {
+                self.next();
+                let mut stmts: Vec<Stmt> = Vec::new();
+                while !self.at_symbol("}") {
+                    if self.at_eof() {
+                        return Err(self.error(&token, "Unterminated block"));
+                    }
+                    stmts.push(self.statement()?);
+                }
+                self.next();
+                return Ok(stmt(StmtKind::Block(stmts)));
+            }
+            TokenKind::Symbol(";") => {
+                self.next();
+                return Ok(stmt(StmtKind::Block(Vec::new())));
+            }
+            TokenKind::Ident(name) if name == "if" => {
+                self.next();
+                let condition: Expr = self.condition()?;
+                let then: Vec<Stmt> = self.body()?;
+                let mut otherwise: Vec<Stmt> = Vec::new();
+                if self.at_ident("else") {
+                    self.next();
+                    otherwise = self.body()?;
+                }
+                return Ok(stmt(StmtKind::If {
+                    condition,
+                    then,
+                    otherwise,
+                }));
+            }
+            TokenKind::Ident(name) if name == "while" => {
+                self.next();
+                let condition: Expr = self.condition()?;
+                let body: Vec<Stmt> = self.body()?;
+                return Ok(stmt(StmtKind::While { condition, body }));
+            }
+            TokenKind::Ident(name) if name == "return" => {
+                self.next();
+                let mut values: Vec<Expr> = Vec::new();
+                if self.at_symbol("(") {
+                    self.next();
+                    values = self.args()?;
+                }
+                self.expect(";")?;
+                return Ok(stmt(StmtKind::Return(values)));
+            }
+            TokenKind::Ident(name) if name.starts_with("def_") => {
+                let kind: String = name["def_".len()..].to_string();
+                self.next();
+                let name: String = self.local()?;
+                let mut size: Option<Expr> = None;
+                let mut value: Option<Expr> = None;
+                if self.at_symbol("(") {
+                    self.next();
+                    size = Some(self.expr()?);
+                    self.expect(")")?;
+                } else if self.at_symbol("=") {
+                    self.next();
+                    value = Some(self.expr()?);
+                }
+                self.expect(";")?;
+                return Ok(stmt(StmtKind::Define {
+                    kind,
+                    name,
+                    size,
+                    value,
+                }));
+            }
+            TokenKind::Ident(name) if name.starts_with("switch_") => {
+                let kind: String = name["switch_".len()..].to_string();
+                self.next();
+                let value: Expr = self.condition()?;
+                self.expect("{")?;
+                let mut cases: Vec<Case> = Vec::new();
+                while !self.at_symbol("}") {
+                    cases.push(self.case()?);
+                }
+                self.next();
+                return Ok(stmt(StmtKind::Switch { kind, value, cases }));
+            }
+            TokenKind::Local(_) | TokenKind::GameVar(_) => {
+                let mut targets: Vec<Expr> = vec![self.primary()?];
+                while self.at_symbol(",") {
+                    self.next();
+                    targets.push(self.primary()?);
+                }
+                self.expect("=")?;
+                let values: Vec<Expr> = self.list(";")?;
+                self.expect(";")?;
+                return Ok(stmt(StmtKind::Assign { targets, values }));
+            }
+            _ => {
+                let expr: Expr = self.expr()?;
+                self.expect(";")?;
+                return Ok(stmt(StmtKind::Expr(expr)));
+            }
+        }
+    }
+
+    fn case(&mut self) -> Result<Case, String> {
+        let token: Token = self.next();
+        if !matches!(&token.kind, TokenKind::Ident(name) if name == "case") {
+            return Err(self.error(
+                &token,
+                &format!("Expected case but found {}", Parser::describe(&token)),
+            ));
+        }
+        let mut keys: Vec<Expr> = Vec::new();
+        if self.at_ident("default") {
+            self.next();
+        } else {
+            keys.push(self.expr()?);
+            while self.at_symbol(",") {
+                self.next();
+                keys.push(self.expr()?);
+            }
+        }
+        self.expect(":")?;
+        let mut body: Vec<Stmt> = Vec::new();
+        while !self.at_ident("case") && !self.at_symbol("}") {
+            if self.at_eof() {
+                return Err(self.error(&token, "Unterminated switch"));
+            }
+            body.push(self.statement()?);
+        }
+        return Ok(Case {
+            keys,
+            body,
+            line: token.line,
+        });
+    }
+
+    /// Parses a comma separated list of expressions up to the given symbol,
+    /// the symbol itself is not consumed.
+    fn list(&mut self, end: &str) -> Result<Vec<Expr>, String> {
+        let mut exprs: Vec<Expr> = Vec::new();
+        while !self.at_symbol(end) {
+            if !exprs.is_empty() {
+                self.expect(",")?;
+            }
+            exprs.push(self.expr()?);
+        }
+        return Ok(exprs);
+    }
+
+    /// Parses the arguments of a call after its opening `(`.
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        let args: Vec<Expr> = self.list(")")?;
+        self.expect(")")?;
+        return Ok(args);
+    }
+
+    pub fn expr(&mut self) -> Result<Expr, String> {
+        return self.binary(0);
+    }
+
+    /// Parses binary operators by precedence, lowest first.
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[&str]; 5] = [
+            &["|"],
+            &["&"],
+            &["=", "!", "<", ">", "<=", ">="],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        if level == LEVELS.len() {
+            return self.primary();
+        }
+        let mut left: Expr = self.binary(level + 1)?;
+        loop {
+            let op: &'static str = match &self.peek().kind {
+                TokenKind::Symbol(symbol) if LEVELS[level].contains(symbol) => symbol,
+                _ => return Ok(left),
+            };
+            let line: usize = self.next().line;
+            let right: Expr = self.binary(level + 1)?;
+            left = Expr {
+                kind: ExprKind::Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                line,
+            };
+        }
+    }
+
+    /// Parses the optional argument list of a call.
+    fn call_args(&mut self) -> Result<Vec<Expr>, String> {
+        if !self.at_symbol("(") {
+            return Ok(Vec::new());
+        }
+        self.next();
+        return self.args();
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token: Token = self.next();
+        let line: usize = token.line;
+        let expr = |kind: ExprKind| Expr { kind, line };
+
+        return match token.kind {
+            TokenKind::Int(value) => Ok(expr(ExprKind::Int(value))),
+            TokenKind::Char(value) => Ok(expr(ExprKind::Char(value))),
+            TokenKind::Str(parts) => self.string(parts, line),
+            TokenKind::Local(name) => {
+                let mut index: Option<Box<Expr>> = None;
+                if self.at_symbol("(") {
+                    self.next();
+                    index = Some(Box::new(self.expr()?));
+                    self.expect(")")?;
+                }
+                Ok(expr(ExprKind::Local { name, index }))
+            }
+            TokenKind::GameVar(name) => Ok(expr(match name.strip_prefix('.') {
+                Some(name) => ExprKind::GameVar {
+                    name: name.to_string(),
+                    secondary: true,
+                },
+                None => ExprKind::GameVar {
+                    name,
+                    secondary: false,
+                },
+            })),
+            TokenKind::Constant(name) => Ok(expr(ExprKind::Constant(name))),
+            TokenKind::Proc(name) => Ok(expr(ExprKind::Proc {
+                name,
+                args: self.call_args()?,
+            })),
+            TokenKind::Label(name) => Ok(expr(ExprKind::Jump {
+                name,
+                args: self.call_args()?,
+            })),
+            TokenKind::Symbol("(") => {
+                let inner: Expr = self.expr()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            TokenKind::Ident(name) if name == "calc" && self.at_symbol("(") => {
+                self.next();
+                let inner: Expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr(ExprKind::Calc(Box::new(inner))))
+            }
+            TokenKind::Ident(name) if self.at_symbol("(") => {
+                self.next();
+                let args: Vec<Expr> = self.args()?;
+                Ok(expr(match name.strip_prefix('.') {
+                    Some(name) => ExprKind::Call {
+                        name: name.to_string(),
+                        secondary: true,
+                        args,
+                    },
+                    None => ExprKind::Call {
+                        name,
+                        secondary: false,
+                        args,
+                    },
+                }))
+            }
+            TokenKind::Ident(name) => Ok(expr(ExprKind::Ident(name))),
+            _ => Err(self.error(&token, &format!("Unexpected {}", Parser::describe(&token)))),
+        };
+    }
+
+    fn string(&mut self, parts: Vec<StringPart>, line: usize) -> Result<Expr, String> {
+        if let [StringPart::Text(text)] = parts.as_slice() {
+            return Ok(Expr {
+                kind: ExprKind::Str(text.clone()),
+                line,
+            });
+        }
+        let mut exprs: Vec<Expr> = Vec::new();
+        for part in parts {
+            match part {
+                StringPart::Text(text) => exprs.push(Expr {
+                    kind: ExprKind::Str(text),
+                    line,
+                }),
+                StringPart::Expr(tokens) => {
+                    let mut parser: Parser = Parser::new(self.path, tokens);
+                    let expr: Expr = parser.expr()?;
+                    if !parser.at_eof() {
+                        let token: &Token = parser.peek();
+                        return Err(parser.error(
+                            token,
+                            &format!("Unexpected {} in string", Parser::describe(token)),
+                        ));
+                    }
+                    exprs.push(expr);
+                }
+            }
+        }
+        return Ok(Expr {
+            kind: ExprKind::Join(exprs),
+            line,
+        });
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use cache::ScriptOpcode;
+
+use crate::compiler::ast::Script;
+use crate::compiler::lexer::Lexer;
+use crate::compiler::parser::Parser;
+
+/// Every server trigger, the id of a trigger is its index.
+pub const TRIGGERS: [&str; 165] = [
+    "proc",
+    "label",
+    "debugproc",
+    "apnpc1",
+    "apnpc2",
+    "apnpc3",
+    "apnpc4",
+    "apnpc5",
+    "apnpcu",
+    "apnpct",
+    "opnpc1",
+    "opnpc2",
+    "opnpc3",
+    "opnpc4",
+    "opnpc5",
+    "opnpcu",
+    "opnpct",
+    "ai_apnpc1",
+    "ai_apnpc2",
+    "ai_apnpc3",
+    "ai_apnpc4",
+    "ai_apnpc5",
+    "ai_apnpcu",
+    "ai_apnpct",
+    "ai_opnpc1",
+    "ai_opnpc2",
+    "ai_opnpc3",
+    "ai_opnpc4",
+    "ai_opnpc5",
+    "ai_opnpcu",
+    "ai_opnpct",
+    "apobj1",
+    "apobj2",
+    "apobj3",
+    "apobj4",
+    "apobj5",
+    "apobju",
+    "apobjt",
+    "opobj1",
+    "opobj2",
+    "opobj3",
+    "opobj4",
+    "opobj5",
+    "opobju",
+    "opobjt",
+    "ai_apobj1",
+    "ai_apobj2",
+    "ai_apobj3",
+    "ai_apobj4",
+    "ai_apobj5",
+    "ai_apobju",
+    "ai_apobjt",
+    "ai_opobj1",
+    "ai_opobj2",
+    "ai_opobj3",
+    "ai_opobj4",
+    "ai_opobj5",
+    "ai_opobju",
+    "ai_opobjt",
+    "aploc1",
+    "aploc2",
+    "aploc3",
+    "aploc4",
+    "aploc5",
+    "aplocu",
+    "aploct",
+    "oploc1",
+    "oploc2",
+    "oploc3",
+    "oploc4",
+    "oploc5",
+    "oplocu",
+    "oploct",
+    "ai_aploc1",
+    "ai_aploc2",
+    "ai_aploc3",
+    "ai_aploc4",
+    "ai_aploc5",
+    "ai_aplocu",
+    "ai_aploct",
+    "ai_oploc1",
+    "ai_oploc2",
+    "ai_oploc3",
+    "ai_oploc4",
+    "ai_oploc5",
+    "ai_oplocu",
+    "ai_oploct",
+    "applayer1",
+    "applayer2",
+    "applayer3",
+    "applayer4",
+    "applayer5",
+    "applayeru",
+    "applayert",
+    "opplayer1",
+    "opplayer2",
+    "opplayer3",
+    "opplayer4",
+    "opplayer5",
+    "opplayeru",
+    "opplayert",
+    "ai_applayer1",
+    "ai_applayer2",
+    "ai_applayer3",
+    "ai_applayer4",
+    "ai_applayer5",
+    "ai_applayeru",
+    "ai_applayert",
+    "ai_opplayer1",
+    "ai_opplayer2",
+    "ai_opplayer3",
+    "ai_opplayer4",
+    "ai_opplayer5",
+    "ai_opplayeru",
+    "ai_opplayert",
+    "queue",
+    "weakqueue",
+    "ai_queue1",
+    "ai_queue2",
+    "ai_queue3",
+    "ai_queue4",
+    "ai_queue5",
+    "ai_queue6",
+    "ai_queue7",
+    "ai_queue8",
+    "ai_queue9",
+    "ai_queue10",
+    "ai_queue11",
+    "ai_queue12",
+    "ai_queue13",
+    "ai_queue14",
+    "ai_queue15",
+    "ai_queue16",
+    "ai_queue17",
+    "ai_queue18",
+    "ai_queue19",
+    "ai_queue20",
+    "softtimer",
+    "timer",
+    "ai_timer",
+    "opheld1",
+    "opheld2",
+    "opheld3",
+    "opheld4",
+    "opheld5",
+    "opheldu",
+    "opheldt",
+    "if_button",
+    "if_close",
+    "inv_button1",
+    "inv_button2",
+    "inv_button3",
+    "inv_button4",
+    "inv_button5",
+    "inv_buttond",
+    "walktrigger",
+    "ai_walktrigger",
+    "login",
+    "logout",
+    "tutorial",
+    "advancestat",
+    "mapzone",
+    "mapzoneexit",
+    "zone",
+    "zoneexit",
+];
+
+pub const LOC_SHAPES: [&str; 23] = [
+    "wall_straight",
+    "wall_diagonalcorner",
+    "wall_l",
+    "wall_squarecorner",
+    "walldecor_straight_nooffset",
+    "walldecor_straight_offset",
+    "walldecor_diagonal_offset",
+    "walldecor_diagonal_nooffset",
+    "walldecor_diagonal_both",
+    "wall_diagonal",
+    "centrepiece_straight",
+    "centrepiece_diagonal",
+    "roof_straight",
+    "roof_diagonal_with_roofedge",
+    "roof_diagonal",
+    "roof_l_concave",
+    "roof_l_convex",
+    "roof_flat",
+    "roofedge_straight",
+    "roofedge_diagonalcorner",
+    "roofedge_l",
+    "roofedge_squarecorner",
+    "grounddecor",
+];
+
+pub const NPC_STATS: [&str; 6] = [
+    "attack",
+    "defence",
+    "strength",
+    "hitpoints",
+    "ranged",
+    "magic",
+];
+
+pub const FONTMETRICS: [&str; 4] = ["p11", "p12", "b12", "q8"];
+
+/// The modes an npc can be set to, `null` is -1.
+pub fn npc_mode(name: &str) -> Option<i32> {
+    const MODES: [&str; 7] = [
+        "none",
+        "wander",
+        "patrol",
+        "playerescape",
+        "playerfollow",
+        "playerface",
+        "playerfaceclose",
+    ];
+    const TARGETS: [&str; 8] = [
+        "opplayer", "applayer", "oploc", "aploc", "opobj", "apobj", "opnpc", "apnpc",
+    ];
+    if let Some(mode) = MODES.iter().position(|&mode| mode == name) {
+        return Some(mode as i32);
+    }
+    if let Some(queue) = name.strip_prefix("queue") {
+        return queue
+            .parse::<i32>()
+            .ok()
+            .filter(|queue| (1..=20).contains(queue))
+            .map(|queue| (MODES.len() + TARGETS.len() * 5) as i32 + queue - 1);
+    }
+    for (index, target) in TARGETS.iter().enumerate() {
+        if let Some(op) = name
+            .strip_prefix(target)
+            .and_then(|op| op.parse::<i32>().ok())
+        {
+            if (1..=5).contains(&op) {
+                return Some(MODES.len() as i32 + index as i32 * 5 + op - 1);
+            }
+        }
+    }
+    return None;
+}
+
+pub fn trigger(name: &str) -> Option<i32> {
+    return TRIGGERS
+        .iter()
+        .position(|&trigger| trigger == name)
+        .map(|trigger| trigger as i32);
+}
+
+/// What the subject of a trigger refers to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Subject {
+    Name,               // only called by name, `[proc,name]`
+    Global,             // only `_`
+    Type(&'static str), // a config of the type, a `_category` or `_` for all
+}
+
+pub fn subject(trigger: &str) -> Subject {
+    return match trigger {
+        "proc" | "label" | "debugproc" | "queue" | "weakqueue" | "softtimer" | "timer"
+        | "walktrigger" => Subject::Name,
+        "login" | "logout" | "tutorial" => Subject::Global,
+        "if_button" => Subject::Type("component"),
+        "if_close" => Subject::Type("interface"),
+        "advancestat" => Subject::Type("stat"),
+        "mapzone" | "mapzoneexit" => Subject::Type("mapzone"),
+        "zone" | "zoneexit" => Subject::Type("coord"),
+        _ if trigger.starts_with("ai_") => Subject::Type("npc"),
+        _ if trigger.starts_with("inv_button") => Subject::Type("component"),
+        _ if trigger.ends_with('t') => Subject::Type("component"),
+        _ if trigger.contains("npc") => Subject::Type("npc"),
+        _ if trigger.contains("loc") => Subject::Type("loc"),
+        _ if trigger.contains("obj") || trigger.starts_with("opheld") => Subject::Type("obj"),
+        _ if trigger.ends_with('u') => Subject::Type("obj"),
+        _ => Subject::Global,
+    };
+}
+
+/// The param and return types of a command or script.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Signature {
+    pub params: Vec<String>,
+    pub returns: Vec<String>,
+}
+
+impl Signature {
+    pub fn of(script: &Script) -> Signature {
+        return Signature {
+            params: script
+                .params
+                .iter()
+                .map(|param| param.kind.clone())
+                .collect(),
+            returns: script.returns.clone(),
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Command {
+    pub opcode: ScriptOpcode,
+    pub signature: Signature,
+}
+
+/// Reads the `[command,name](params)(returns)` declarations of engine.rs2.
+///
+/// Secondary commands are keyed with their `.`, commands without an opcode
+/// are left out so using one is an unknown command.
+pub fn load_commands(path: &Path) -> Result<HashMap<String, Command>, String> {
+    let display: String = path.display().to_string();
+    let src: String =
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", display, e))?;
+    let tokens = Lexer::new(&display, &src).tokenize()?;
+    let mut commands: HashMap<String, Command> = HashMap::new();
+    for script in Parser::new(&display, tokens).scripts()? {
+        if script.trigger != "command" {
+            return Err(format!("{}:{}: Expected a command", display, script.line));
+        }
+        let name: &str = script.subject.trim_start_matches('.');
+        if let Some(opcode) = ScriptOpcode::from_command(name) {
+            let signature: Signature = Signature::of(&script);
+            commands.insert(script.subject, Command { opcode, signature });
+        }
+    }
+    return Ok(commands);
+}
+
+/// A `%var`, a varp belongs to the player, a varn to the npc and a vars to the world.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameVar {
+    pub push: ScriptOpcode,
+    pub pop: ScriptOpcode,
+    pub id: i32,
+    pub kind: String,
+}
+
+/// A script that can be called, `[proc,name]` or `[label,name]`, or queued.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScriptSymbol {
+    pub id: i32,
+    pub signature: Signature,
+}
+
+/// A `table:column`, its id and the types of its fields.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DbColumn {
+    pub id: i32,
+    pub types: Vec<String>,
+}
+
+/// Everything a script can refer to besides configs and constants.
+pub struct Symbols {
+    pub commands: HashMap<String, Command>,
+    pub scripts: HashMap<String, ScriptSymbol>, // keyed by `[trigger,subject]`
+    pub vars: HashMap<String, GameVar>,
+    pub dbcolumns: HashMap<String, DbColumn>,
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+/// A single `[name]` block from a config source file along with its
+/// `key=value` lines, kept in the order they were written.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfigSection {
+    pub name: String,
+    pub lines: Vec<(String, String)>,
+    pub path: String, // empty for sections the packer generates
+    pub line: usize,  // line of the `[name]` header
+}
+
+impl ConfigSection {
+    pub fn new(name: &str) -> ConfigSection {
+        return ConfigSection {
+            name: name.to_string(),
+            lines: Vec::new(),
+            path: String::new(),
+            line: 0,
+        };
+    }
+
+    /// Where the section was written, to point errors at the file and line.
+    pub fn location(&self) -> String {
+        if self.path.is_empty() {
+            return format!("[{}]", self.name);
+        }
+        return format!("{}:{}: [{}]", self.path, self.line, self.name);
+    }
+
+    /// Returns the value of the first line with the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        return self
+            .lines
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str());
+    }
+}
+
+/// Parses the contents of a config source file into its sections.
+///
+/// Lines starting with `//` are comments and blank lines are ignored.
+/// Any `key=value` line before the first `[name]` header is an error.
+pub fn parse_configs(src: &str) -> Result<Vec<ConfigSection>, String> {
+    let mut sections: Vec<ConfigSection> = Vec::new();
+    for (number, raw) in src.lines().enumerate() {
+        let line: &str = raw.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            let mut section: ConfigSection = ConfigSection::new(&line[1..line.len() - 1]);
+            section.line = number + 1;
+            sections.push(section);
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some(pair) => pair,
+            None => return Err(format!("Invalid config line {}: {}", number + 1, line)),
+        };
+        match sections.last_mut() {
+            Some(section) => section
+                .lines
+                .push((key.trim().to_string(), value.trim().to_string())),
+            None => {
+                return Err(format!(
+                    "Config line {} is outside of a section: {}",
+                    number + 1,
+                    line
+                ))
+            }
+        }
+    }
+    return Ok(sections);
+}
+
+/// Recursively finds every file under `dir` with the given extension.
+/// Paths are sorted so packing is deterministic across platforms.
+pub fn find_files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut entries: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(read) => read.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => return files,
+    };
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            files.extend(find_files(&path, extension));
+        } else if path.extension().is_some_and(|ext| ext == extension) {
+            files.push(path);
+        }
+    }
+    return files;
+}
+
+/// Reads and parses every config source file with the given extension under `dir`.
+pub fn read_configs(dir: &Path, extension: &str) -> Result<Vec<ConfigSection>, String> {
+    let mut sections: Vec<ConfigSection> = Vec::new();
+    for path in find_files(dir, extension) {
+        let src: String = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let mut parsed: Vec<ConfigSection> =
+            parse_configs(&src).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for section in &mut parsed {
+            section.path = path.display().to_string();
+        }
+        sections.extend(parsed);
+    }
+    return Ok(sections);
+}
@@ -0,0 +1,70 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::{PackContext, TypedValue};
+
+/// Enums are written with their input and output types first, the values
+/// are resolved against those types and written as one table at the end.
+/// An `autoint` input numbers the values from 0 in the order they are written.
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, _client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let inputtype: &str = config.get("inputtype").ok_or("Enum is missing an inputtype")?;
+    let outputtype: &str = config.get("outputtype").ok_or("Enum is missing an outputtype")?;
+
+    let mut vals: Vec<(i32, TypedValue)> = Vec::new();
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "inputtype" => {
+                server.p1(1);
+                server.p1(PackContext::type_char(value)?);
+            }
+            "outputtype" => {
+                server.p1(2);
+                server.p1(PackContext::type_char(value)?);
+            }
+            "default" => match ctx.typed(outputtype, value)? {
+                TypedValue::String(value) => {
+                    server.p1(3);
+                    server.pjstr(&value, 10);
+                }
+                TypedValue::Int(value) => {
+                    server.p1(4);
+                    server.p4(value);
+                }
+            },
+            "val" => {
+                let (input, output) = if inputtype == "autoint" {
+                    (vals.len() as i32, value)
+                } else {
+                    let (input, output) = value.split_once(',').ok_or(format!("Invalid val: {}", value))?;
+                    let input: i32 = match ctx.typed(inputtype, input)? {
+                        TypedValue::Int(input) => input,
+                        TypedValue::String(_) => return Err("Enum inputs can not be strings".to_string()),
+                    };
+                    (input, output)
+                };
+                vals.push((input, ctx.typed(outputtype, output)?));
+            }
+            _ => return Err(format!("Unknown enum property: {}", key)),
+        }
+    }
+
+    if vals.is_empty() {
+        return Ok(());
+    }
+    if outputtype == "string" {
+        server.p1(5);
+    } else {
+        server.p1(6);
+    }
+    server.p2(vals.len() as i32);
+    for (input, output) in vals {
+        server.p4(input);
+        match output {
+            TypedValue::String(output) => server.pjstr(&output, 10),
+            TypedValue::Int(output) => server.p4(output),
+        }
+    }
+    return Ok(());
+}
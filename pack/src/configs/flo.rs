@@ -0,0 +1,30 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::PackContext;
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, _server: &mut Packet) -> Result<(), String> {
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "rgb" => {
+                client.p1(1);
+                client.p3(i32::from_str_radix(value.trim_start_matches("0x"), 16)
+                    .map_err(|_| format!("Invalid rgb: {}", value))?);
+            }
+            "texture" => {
+                client.p1(2);
+                client.p1(ctx.id("texture", value)?);
+            }
+            "overlay" => if ctx.bool(value)? { client.p1(3) },
+            "occlude" => if !ctx.bool(value)? { client.p1(5) },
+            _ => return Err(format!("Unknown flo property: {}", key)),
+        }
+    }
+
+    // floors are looked up by name in the map editor.
+    client.p1(6);
+    client.pjstr(&config.name, 10);
+    return Ok(());
+}
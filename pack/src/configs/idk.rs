@@ -0,0 +1,65 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::{key_index, recol, write_recols};
+use crate::context::PackContext;
+
+pub const BODYPARTS: [&str; 14] = [
+    "man_hair",
+    "man_jaw",
+    "man_torso",
+    "man_arms",
+    "man_hands",
+    "man_legs",
+    "man_feet",
+    "woman_hair",
+    "woman_jaw",
+    "woman_torso",
+    "woman_arms",
+    "woman_hands",
+    "woman_legs",
+    "woman_feet",
+];
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, _server: &mut Packet) -> Result<(), String> {
+    let mut recols: Vec<(i32, i32)> = Vec::new();
+    let mut models: Vec<i32> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "type" => {
+                client.p1(1);
+                client.p1(BODYPARTS
+                    .iter()
+                    .position(|&part| part == value)
+                    .ok_or(format!("Unknown idk type: {}", value))? as i32);
+            }
+            "disable" => if ctx.bool(value)? { client.p1(3) },
+            key => {
+                if let Some(index) = key_index(key, "model").filter(|&index| index > 0) {
+                    if models.len() < index {
+                        models.resize(index, 0);
+                    }
+                    models[index - 1] = ctx.id("model", value)?;
+                } else if let Some(index) = key_index(key, "head").filter(|index| (1..=10).contains(index)) {
+                    client.p1(59 + index as i32);
+                    client.p2(ctx.id("model", value)?);
+                } else if !recol(ctx, key, value, &mut recols)? {
+                    return Err(format!("Unknown idk property: {}", key));
+                }
+            }
+        }
+    }
+
+    if !models.is_empty() {
+        client.p1(2);
+        client.p1(models.len() as i32);
+        for model in models {
+            client.p2(model);
+        }
+    }
+    write_recols(&recols, client);
+    return Ok(());
+}
@@ -0,0 +1,67 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::key_index;
+use crate::context::PackContext;
+
+fn scope(value: &str) -> Result<i32, String> {
+    return match value {
+        "temp" => Ok(0),
+        "perm" => Ok(1),
+        "shared" => Ok(2),
+        _ => Err(format!("Unknown scope: {}", value)),
+    };
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, _client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let mut stock: Vec<(i32, i32, i32)> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "scope" => {
+                server.p1(1);
+                server.p1(scope(value)?);
+            }
+            "size" => {
+                server.p1(2);
+                server.p2(ctx.int(value)?);
+            }
+            "stackall" => if ctx.bool(value)? { server.p1(3) },
+            "restock" => if ctx.bool(value)? { server.p1(5) },
+            "allstock" => if ctx.bool(value)? { server.p1(6) },
+            "protect" => if !ctx.bool(value)? { server.p1(7) },
+            "runweight" => if ctx.bool(value)? { server.p1(8) },
+            "dummyinv" => if ctx.bool(value)? { server.p1(9) },
+            key => {
+                let index: usize = key_index(key, "stock")
+                    .filter(|&index| index > 0)
+                    .ok_or(format!("Unknown inv property: {}", key))?;
+                let parts: Vec<&str> = value.split(',').collect();
+                if parts.len() < 2 {
+                    return Err(format!("Invalid {}: {}", key, value));
+                }
+                if stock.len() < index {
+                    stock.resize(index, (0, 0, 0));
+                }
+                stock[index - 1] = (
+                    ctx.id("obj", parts[0])?,
+                    ctx.int(parts[1])?,
+                    parts.get(2).map_or(Ok(0), |rate| ctx.int(rate))?,
+                );
+            }
+        }
+    }
+
+    if !stock.is_empty() {
+        server.p1(4);
+        server.p1(stock.len() as i32);
+        for (obj, count, rate) in stock {
+            server.p2(obj);
+            server.p2(count);
+            server.p4(rate);
+        }
+    }
+    return Ok(());
+}
@@ -0,0 +1,157 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::{key_index, recol, write_recols};
+use crate::context::PackContext;
+
+/// The model name suffix for every loc shape, indexed by shape.
+pub const SHAPE_SUFFIXES: [&str; 23] = [
+    "_1", "_2", "_3", "_4", "_q", "_w", "_r", "_e", "_t", "_5", "_8", "_9", "_a", "_s", "_d", "_f",
+    "_g", "_h", "_z", "_x", "_c", "_v", "_0",
+];
+
+/// Writes the models for a loc.
+///
+/// A loc model is a family of models sharing a name with a suffix per shape,
+/// `wooden_chest` finds `wooden_chest_8` as a centrepiece. A model without
+/// any shaped variants is written as an unshaped model.
+fn models(ctx: &mut PackContext, value: &str, buf: &mut Packet) -> Result<(), String> {
+    let mut shaped: Vec<(i32, i32)> = Vec::new();
+    for (shape, suffix) in SHAPE_SUFFIXES.iter().enumerate() {
+        if let Some(id) = ctx.pack("model")?.id(&format!("{}{}", value, suffix)) {
+            shaped.push((id, shape as i32));
+        }
+    }
+
+    if shaped.is_empty() {
+        buf.p1(5);
+        buf.p1(1);
+        buf.p2(ctx.id("model", value)?);
+        return Ok(());
+    }
+
+    buf.p1(1);
+    buf.p1(shaped.len() as i32);
+    for (model, shape) in shaped {
+        buf.p2(model);
+        buf.p1(shape);
+    }
+    return Ok(());
+}
+
+fn forceapproach(value: &str) -> Result<i32, String> {
+    let mut flags: i32 = 0b1111;
+    match value {
+        "north" => flags &= !0b0001,
+        "east" => flags &= !0b0010,
+        "south" => flags &= !0b0100,
+        "west" => flags &= !0b1000,
+        _ => return Err(format!("Unknown forceapproach: {}", value)),
+    }
+    return Ok(flags);
+}
+
+fn int(ctx: &PackContext, code: i32, value: &str, buf: &mut Packet) -> Result<(), String> {
+    buf.p1(code);
+    buf.p1(ctx.int(value)?);
+    return Ok(());
+}
+
+fn int2(ctx: &PackContext, code: i32, value: &str, buf: &mut Packet) -> Result<(), String> {
+    buf.p1(code);
+    buf.p2(ctx.int(value)?);
+    return Ok(());
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let mut recols: Vec<(i32, i32)> = Vec::new();
+    let mut retexs: Vec<(i32, i32)> = Vec::new();
+    let mut params: Vec<&str> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "model" => models(ctx, value, client)?,
+            "name" => {
+                client.p1(2);
+                client.pjstr(value, 10);
+            }
+            "desc" => {
+                client.p1(3);
+                client.pjstr(value, 10);
+            }
+            "width" => int(ctx, 14, value, client)?,
+            "length" => int(ctx, 15, value, client)?,
+            "blockwalk" => if !ctx.bool(value)? { client.p1(17) },
+            "blockrange" => if !ctx.bool(value)? { client.p1(18) },
+            "active" => {
+                client.p1(19);
+                client.p1(ctx.bool(value)? as i32);
+            }
+            "hillskew" => if ctx.bool(value)? { client.p1(21) },
+            "sharelight" => if ctx.bool(value)? { client.p1(22) },
+            "occlude" => if ctx.bool(value)? { client.p1(23) },
+            "anim" => {
+                client.p1(24);
+                client.p2(ctx.id("seq", value)?);
+            }
+            "hasalpha" => if ctx.bool(value)? { client.p1(25) },
+            "wallwidth" => int(ctx, 28, value, client)?,
+            "ambient" => int(ctx, 29, value, client)?,
+            "contrast" => int(ctx, 39, value, client)?,
+            "mapfunction" => int2(ctx, 60, value, client)?,
+            "mirror" => if ctx.bool(value)? { client.p1(62) },
+            "shadow" => if !ctx.bool(value)? { client.p1(64) },
+            "resizex" => int2(ctx, 65, value, client)?,
+            "resizey" => int2(ctx, 66, value, client)?,
+            "resizez" => int2(ctx, 67, value, client)?,
+            "mapscene" => int2(ctx, 68, value, client)?,
+            "forceapproach" => {
+                client.p1(69);
+                client.p1(forceapproach(value)?);
+            }
+            "xoff" => int2(ctx, 70, value, client)?,
+            "yoff" => int2(ctx, 71, value, client)?,
+            "zoff" => int2(ctx, 72, value, client)?,
+            "forcedecor" => if ctx.bool(value)? { client.p1(73) },
+            "category" => {
+                server.p1(200);
+                server.p2(ctx.assign("category", value)?);
+            }
+            "param" => params.push(value),
+            key => {
+                if let Some(index) = key_index(key, "op").filter(|index| (1..=5).contains(index)) {
+                    client.p1(29 + index as i32);
+                    client.pjstr(value, 10);
+                } else if let Some(retex) = key.strip_prefix("retex") {
+                    // textures are recoloured by id, so they share the recol list.
+                    let texture: i32 = ctx.id("texture", value)?;
+                    let (index, source) = match (retex.strip_suffix('s'), retex.strip_suffix('d')) {
+                        (Some(index), _) => (index, true),
+                        (_, Some(index)) => (index, false),
+                        _ => return Err(format!("Unknown loc property: {}", key)),
+                    };
+                    let index: usize = index.parse::<usize>().map_err(|_| format!("Unknown loc property: {}", key))?;
+                    if index == 0 {
+                        return Err(format!("Unknown loc property: {}", key));
+                    }
+                    if retexs.len() < index {
+                        retexs.resize(index, (0, 0));
+                    }
+                    if source {
+                        retexs[index - 1].0 = texture;
+                    } else {
+                        retexs[index - 1].1 = texture;
+                    }
+                } else if !recol(ctx, key, value, &mut recols)? {
+                    return Err(format!("Unknown loc property: {}", key));
+                }
+            }
+        }
+    }
+
+    recols.extend(retexs);
+    write_recols(&recols, client);
+    return ctx.params(&params, server);
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::PackContext;
+
+pub mod enum_type;
+pub mod flo;
+pub mod idk;
+pub mod inv;
+pub mod loc;
+pub mod npc;
+pub mod obj;
+pub mod param;
+pub mod seq;
+pub mod spotanim;
+pub mod struct_type;
+pub mod varp;
+
+/// Encodes the properties of a single config into the client and server buffers.
+/// The terminating opcode is written by `pack_configs`.
+pub type ConfigEncoder =
+    fn(&mut PackContext, &ConfigSection, &mut Packet, &mut Packet) -> Result<(), String>;
+
+/// The encoded configs of a single type.
+///
+/// `client_dat` and `server_dat` start with the config count followed by every
+/// config in id order. `client_idx` is the count followed by the size of each config.
+pub struct PackedConfigs {
+    pub client_dat: Vec<u8>,
+    pub client_idx: Vec<u8>,
+    pub server_dat: Vec<u8>,
+}
+
+// the largest a single encoded config is allowed to be, the client idx stores sizes as a p2.
+const CONFIG_LIMIT: usize = 65535;
+
+/// An upper bound on the bytes a section is encoded in, no line takes up more than its
+/// own length plus 16 bytes. `Packet` writes are unchecked, so encode buffers are at least
+/// this big.
+pub fn encoded_size(section: &ConfigSection) -> usize {
+    return 64
+        + section.name.len()
+        + section
+            .lines
+            .iter()
+            .map(|(key, value)| key.len() + value.len() + 16)
+            .sum::<usize>();
+}
+
+/// Encodes every config of a type in id order. Ids without a config are written
+/// as empty configs so the ids of everything after them are unchanged.
+pub fn pack_configs(
+    ctx: &mut PackContext,
+    kind: &str,
+    sections: &[ConfigSection],
+    encode: ConfigEncoder,
+) -> Result<PackedConfigs, String> {
+    let by_name: HashMap<&str, &ConfigSection> = sections
+        .iter()
+        .map(|section| (section.name.as_str(), section))
+        .collect();
+
+    let count: usize = ctx.pack(kind)?.count();
+    let names: Vec<Option<String>> = (0..count)
+        .map(|id| ctx.packs[kind].name(id).map(|name| name.to_string()))
+        .collect();
+
+    let mut client_dat: Vec<u8> = vec![(count >> 8) as u8, count as u8];
+    let mut client_idx: Vec<u8> = vec![(count >> 8) as u8, count as u8];
+    let mut server_dat: Vec<u8> = vec![(count >> 8) as u8, count as u8];
+
+    let mut client: Packet = Packet::new(CONFIG_LIMIT);
+    let mut server: Packet = Packet::new(CONFIG_LIMIT);
+    for name in names {
+        let section: Option<&ConfigSection> = name
+            .as_ref()
+            .and_then(|name| by_name.get(name.as_str()).copied());
+        // the buffers only grow for a config that could take up more than the limit
+        let size: usize = CONFIG_LIMIT + section.map_or(0, encoded_size);
+        if client.data.len() < size {
+            client.data.resize(size, 0);
+            server.data.resize(size, 0);
+        }
+        client.pos = 0;
+        server.pos = 0;
+        if let Some(name) = &name {
+            if let Some(section) = section {
+                encode(ctx, section, &mut client, &mut server)
+                    .map_err(|e| format!("{} {}", section.location(), e))?;
+            }
+            server.p1(250);
+            server.pjstr(name, 10);
+        }
+        client.p1(0);
+        server.p1(0);
+        if client.pos > CONFIG_LIMIT {
+            return Err(format!(
+                "[{}] {} is {} bytes, more than the client can index",
+                name.unwrap_or_default(),
+                kind,
+                client.pos
+            ));
+        }
+
+        client_dat.extend_from_slice(&client.data[..client.pos]);
+        client_idx.push((client.pos >> 8) as u8);
+        client_idx.push(client.pos as u8);
+        server_dat.extend_from_slice(&server.data[..server.pos]);
+    }
+
+    return Ok(PackedConfigs {
+        client_dat,
+        client_idx,
+        server_dat,
+    });
+}
+
+/// Collects `recolNs`/`recolNd` pairs, returns false if the key is not a recol.
+pub fn recol(
+    ctx: &PackContext,
+    key: &str,
+    value: &str,
+    recols: &mut Vec<(i32, i32)>,
+) -> Result<bool, String> {
+    let (index, source) = match key.strip_prefix("recol").and_then(|rest| {
+        rest.strip_suffix('s')
+            .map(|n| (n, true))
+            .or(rest.strip_suffix('d').map(|n| (n, false)))
+    }) {
+        Some((index, source)) => match index.parse::<usize>() {
+            Ok(index) if index > 0 => (index - 1, source),
+            _ => return Ok(false),
+        },
+        None => return Ok(false),
+    };
+    if recols.len() <= index {
+        recols.resize(index + 1, (0, 0));
+    }
+    let colour: i32 = ctx.int(value)?;
+    if source {
+        recols[index].0 = colour;
+    } else {
+        recols[index].1 = colour;
+    }
+    return Ok(true);
+}
+
+/// Writes the collected recols as opcode 40.
+pub fn write_recols(recols: &[(i32, i32)], buf: &mut Packet) {
+    if recols.is_empty() {
+        return;
+    }
+    buf.p1(40);
+    buf.p1(recols.len() as i32);
+    for (source, dest) in recols {
+        buf.p2(*source);
+        buf.p2(*dest);
+    }
+}
+
+/// Returns the number at the end of a numbered key, `op3` -> `3`.
+pub fn key_index(key: &str, prefix: &str) -> Option<usize> {
+    return key
+        .strip_prefix(prefix)
+        .and_then(|index| index.parse::<usize>().ok());
+}
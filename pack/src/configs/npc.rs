@@ -0,0 +1,205 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::{key_index, recol, write_recols};
+use crate::context::PackContext;
+
+fn moverestrict(value: &str) -> Result<i32, String> {
+    return match value {
+        "normal" => Ok(0),
+        "blocked" => Ok(1),
+        "blocked+normal" => Ok(2),
+        "indoors" => Ok(3),
+        "outdoors" => Ok(4),
+        "nomove" => Ok(5),
+        "passthru" => Ok(6),
+        _ => Err(format!("Unknown moverestrict: {}", value)),
+    };
+}
+
+fn blockwalk(value: &str) -> Result<i32, String> {
+    return match value {
+        "none" => Ok(0),
+        "all" => Ok(1),
+        "npc" => Ok(2),
+        _ => Err(format!("Unknown blockwalk: {}", value)),
+    };
+}
+
+fn defaultmode(value: &str) -> Result<i32, String> {
+    return match value {
+        "none" => Ok(0),
+        "wander" => Ok(1),
+        "patrol" => Ok(2),
+        _ => Err(format!("Unknown defaultmode: {}", value)),
+    };
+}
+
+/// Writes a list of models as a count followed by each model id.
+fn models(
+    ctx: &mut PackContext,
+    code: i32,
+    models: &[String],
+    buf: &mut Packet,
+) -> Result<(), String> {
+    if models.is_empty() {
+        return Ok(());
+    }
+    buf.p1(code);
+    buf.p1(models.len() as i32);
+    for model in models {
+        buf.p2(ctx.id("model", model)?);
+    }
+    return Ok(());
+}
+
+/// Stores a numbered value like `model3` at index 2.
+fn numbered(list: &mut Vec<String>, index: usize, value: &str) {
+    if list.len() < index {
+        list.resize(index, String::new());
+    }
+    list[index - 1] = value.to_string();
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let mut recols: Vec<(i32, i32)> = Vec::new();
+    let mut params: Vec<&str> = Vec::new();
+    let mut body: Vec<String> = Vec::new();
+    let mut heads: Vec<String> = Vec::new();
+    let mut patrol: Vec<String> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "name" => {
+                client.p1(2);
+                client.pjstr(value, 10);
+            }
+            "desc" => {
+                client.p1(3);
+                client.pjstr(value, 10);
+            }
+            "size" => {
+                client.p1(12);
+                client.p1(ctx.int(value)?);
+            }
+            "readyanim" => {
+                client.p1(13);
+                client.p2(ctx.id("seq", value)?);
+            }
+            "walkanim" => {
+                let anims: Vec<&str> = value.split(',').collect();
+                match anims.len() {
+                    1 => client.p1(14),
+                    4 => client.p1(17),
+                    _ => return Err(format!("Invalid walkanim: {}", value)),
+                }
+                for anim in anims {
+                    client.p2(ctx.id("seq", anim)?);
+                }
+            }
+            "hasalpha" => if ctx.bool(value)? { client.p1(16) },
+            "category" => {
+                server.p1(18);
+                server.p2(ctx.assign("category", value)?);
+            }
+            "attack" | "defence" | "strength" | "hitpoints" | "ranged" | "magic" => {
+                let stat: usize = ["attack", "defence", "strength", "hitpoints", "ranged", "magic"]
+                    .iter()
+                    .position(|&stat| stat == key)
+                    .unwrap();
+                server.p1(74 + stat as i32);
+                server.p2(ctx.int(value)?);
+            }
+            "minimap" => if !ctx.bool(value)? { client.p1(93) },
+            "vislevel" => {
+                client.p1(95);
+                client.p2(if value == "hide" { 0 } else { ctx.int(value)? });
+            }
+            "resizeh" => {
+                client.p1(97);
+                client.p2(ctx.int(value)?);
+            }
+            "resizev" => {
+                client.p1(98);
+                client.p2(ctx.int(value)?);
+            }
+            "wanderrange" => {
+                server.p1(200);
+                server.p1(ctx.int(value)?);
+            }
+            "maxrange" => {
+                server.p1(201);
+                server.p1(ctx.int(value)?);
+            }
+            "huntrange" => {
+                server.p1(202);
+                server.p1(ctx.int(value)?);
+            }
+            "timer" => {
+                server.p1(203);
+                server.p2(ctx.int(value)?);
+            }
+            "respawnrate" => {
+                server.p1(204);
+                server.p2(ctx.int(value)?);
+            }
+            "moverestrict" => {
+                server.p1(206);
+                server.p1(moverestrict(value)?);
+            }
+            "attackrange" => {
+                server.p1(207);
+                server.p1(ctx.int(value)?);
+            }
+            "blockwalk" => {
+                server.p1(208);
+                server.p1(blockwalk(value)?);
+            }
+            "huntmode" => {
+                server.p1(209);
+                server.p1(ctx.assign("hunt", value)?);
+            }
+            "defaultmode" => {
+                server.p1(210);
+                server.p1(defaultmode(value)?);
+            }
+            "members" => if ctx.bool(value)? { server.p1(211) },
+            "givechase" => {
+                server.p1(213);
+                server.p1(ctx.bool(value)? as i32);
+            }
+            "param" => params.push(value),
+            key => {
+                if let Some(index) = key_index(key, "model").filter(|&index| index > 0) {
+                    numbered(&mut body, index, value);
+                } else if let Some(index) = key_index(key, "head").filter(|&index| index > 0) {
+                    numbered(&mut heads, index, value);
+                } else if let Some(index) = key_index(key, "patrol").filter(|&index| index > 0) {
+                    numbered(&mut patrol, index, &ctx.constant(value)?);
+                } else if let Some(index) = key_index(key, "op").filter(|index| (1..=5).contains(index)) {
+                    client.p1(29 + index as i32);
+                    client.pjstr(value, 10);
+                } else if !recol(ctx, key, value, &mut recols)? {
+                    return Err(format!("Unknown npc property: {}", key));
+                }
+            }
+        }
+    }
+
+    models(ctx, 1, &body, client)?;
+    write_recols(&recols, client);
+    models(ctx, 60, &heads, client)?;
+
+    if !patrol.is_empty() {
+        server.p1(212);
+        server.p1(patrol.len() as i32);
+        for point in &patrol {
+            let (coord, delay) = point.split_once(',').unwrap_or((point, "0"));
+            server.p4(PackContext::coord(coord)?);
+            server.p1(ctx.int(delay)?);
+        }
+    }
+    return ctx.params(&params, server);
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::{key_index, recol, write_recols};
+use crate::context::PackContext;
+
+pub const WEARPOS: [&str; 14] = [
+    "hat",
+    "back",
+    "front",
+    "righthand",
+    "torso",
+    "lefthand",
+    "arms",
+    "legs",
+    "head",
+    "hands",
+    "feet",
+    "jaw",
+    "ring",
+    "quiver",
+];
+
+fn wearpos(value: &str) -> Result<i32, String> {
+    return WEARPOS
+        .iter()
+        .position(|&pos| pos == value)
+        .map(|pos| pos as i32)
+        .ok_or(format!("Unknown wearpos: {}", value));
+}
+
+/// Writes a `model,offset` pair, the offset defaults to 0.
+fn wear(ctx: &mut PackContext, code: i32, value: &str, buf: &mut Packet) -> Result<(), String> {
+    let (model, offset) = value.split_once(',').unwrap_or((value, "0"));
+    buf.p1(code);
+    buf.p2(ctx.id("model", model)?);
+    buf.p1(ctx.int(offset)?);
+    return Ok(());
+}
+
+fn model(ctx: &mut PackContext, code: i32, value: &str, buf: &mut Packet) -> Result<(), String> {
+    buf.p1(code);
+    buf.p2(ctx.id("model", value)?);
+    return Ok(());
+}
+
+fn int2(ctx: &PackContext, code: i32, value: &str, buf: &mut Packet) -> Result<(), String> {
+    buf.p1(code);
+    buf.p2(ctx.int(value)?);
+    return Ok(());
+}
+
+/// Certificates are never written by hand, every `cert_<name>` in obj.pack
+/// without a config of its own is generated from the obj it notes.
+pub fn certs(
+    ctx: &mut PackContext,
+    sections: &[ConfigSection],
+) -> Result<Vec<ConfigSection>, String> {
+    let defined: HashSet<&str> = sections
+        .iter()
+        .map(|section| section.name.as_str())
+        .collect();
+    let mut certs: Vec<ConfigSection> = Vec::new();
+    for name in ctx.pack("obj")?.names.iter().flatten() {
+        if defined.contains(name.as_str()) {
+            continue;
+        }
+        if let Some(link) = name.strip_prefix("cert_") {
+            let mut cert: ConfigSection = ConfigSection::new(name);
+            cert.lines.push(("certlink".to_string(), link.to_string()));
+            cert.lines
+                .push(("certtemplate".to_string(), "template_for_cert".to_string()));
+            certs.push(cert);
+        }
+    }
+    return Ok(certs);
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let mut recols: Vec<(i32, i32)> = Vec::new();
+    let mut params: Vec<&str> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "model" => model(ctx, 1, value, client)?,
+            "name" => {
+                client.p1(2);
+                client.pjstr(value, 10);
+            }
+            "desc" => {
+                client.p1(3);
+                client.pjstr(value, 10);
+            }
+            "2dzoom" => int2(ctx, 4, value, client)?,
+            "2dxan" => int2(ctx, 5, value, client)?,
+            "2dyan" => int2(ctx, 6, value, client)?,
+            "2dxof" => int2(ctx, 7, value, client)?,
+            "2dyof" => int2(ctx, 8, value, client)?,
+            "code9" => if ctx.bool(value)? { client.p1(9) },
+            "code10" => {
+                client.p1(10);
+                client.p2(ctx.id("seq", value)?);
+            }
+            "stackable" => if ctx.bool(value)? { client.p1(11) },
+            "cost" => {
+                client.p1(12);
+                client.p4(ctx.int(value)?);
+            }
+            "wearpos" => {
+                server.p1(13);
+                server.p1(wearpos(value)?);
+            }
+            "wearpos2" => {
+                server.p1(14);
+                server.p1(wearpos(value)?);
+            }
+            "members" => if ctx.bool(value)? { client.p1(16) },
+            "manwear" => wear(ctx, 23, value, client)?,
+            "manwear2" => model(ctx, 24, value, client)?,
+            "womanwear" => wear(ctx, 25, value, client)?,
+            "womanwear2" => model(ctx, 26, value, client)?,
+            "wearpos3" => {
+                server.p1(27);
+                server.p1(wearpos(value)?);
+            }
+            "weight" => {
+                server.p1(75);
+                server.p2(PackContext::weight(value)?);
+            }
+            "manwear3" => model(ctx, 78, value, client)?,
+            "womanwear3" => model(ctx, 79, value, client)?,
+            "manhead" => model(ctx, 90, value, client)?,
+            "womanhead" => model(ctx, 91, value, client)?,
+            "manhead2" => model(ctx, 92, value, client)?,
+            "womanhead2" => model(ctx, 93, value, client)?,
+            "category" => {
+                server.p1(94);
+                server.p2(ctx.assign("category", value)?);
+            }
+            "2dzan" => int2(ctx, 95, value, client)?,
+            "dummyitem" => {
+                server.p1(96);
+                server.p1(match value {
+                    "graphic_only" => 1,
+                    "inv_only" => 2,
+                    _ => return Err(format!("Unknown dummyitem: {}", value)),
+                });
+            }
+            "certlink" => {
+                client.p1(97);
+                client.p2(ctx.id("obj", value)?);
+            }
+            "certtemplate" => {
+                client.p1(98);
+                client.p2(ctx.id("obj", value)?);
+            }
+            "tradeable" => if ctx.bool(value)? { server.p1(200) },
+            "respawnrate" => {
+                server.p1(201);
+                server.p2(ctx.int(value)?);
+            }
+            "param" => params.push(value),
+            key => {
+                if let Some(index) = key_index(key, "op").filter(|index| (1..=5).contains(index)) {
+                    client.p1(29 + index as i32);
+                    client.pjstr(value, 10);
+                } else if let Some(index) = key_index(key, "iop").filter(|index| (1..=5).contains(index)) {
+                    client.p1(34 + index as i32);
+                    client.pjstr(value, 10);
+                } else if let Some(index) = key_index(key, "count").filter(|index| (1..=10).contains(index)) {
+                    let (obj, count) = value.split_once(',').ok_or(format!("Invalid {}: {}", key, value))?;
+                    client.p1(99 + index as i32);
+                    client.p2(ctx.id("obj", obj)?);
+                    client.p2(ctx.int(count)?);
+                } else if !recol(ctx, key, value, &mut recols)? {
+                    return Err(format!("Unknown obj property: {}", key));
+                }
+            }
+        }
+    }
+
+    write_recols(&recols, client);
+    return ctx.params(&params, server);
+}
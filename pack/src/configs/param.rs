@@ -0,0 +1,32 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::{PackContext, TypedValue};
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, _client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    let kind: &str = config.get("type").ok_or("Param is missing a type")?;
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "type" => {
+                server.p1(1);
+                server.p1(PackContext::type_char(value)?);
+            }
+            "default" => match ctx.typed(kind, value)? {
+                TypedValue::Int(value) => {
+                    server.p1(2);
+                    server.p4(value);
+                }
+                TypedValue::String(value) => {
+                    server.p1(5);
+                    server.pjstr(&value, 10);
+                }
+            },
+            "autodisable" => if !ctx.bool(value)? { server.p1(4) },
+            _ => return Err(format!("Unknown param property: {}", key)),
+        }
+    }
+    return Ok(());
+}
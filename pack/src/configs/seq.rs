@@ -0,0 +1,85 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::key_index;
+use crate::context::PackContext;
+
+/// Held objs are written as `obj + 512`, `hide` hides the hand entirely.
+fn hand(ctx: &mut PackContext, value: &str) -> Result<i32, String> {
+    if value == "hide" {
+        return Ok(0);
+    }
+    return Ok(ctx.id("obj", value)? + 512);
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, _server: &mut Packet) -> Result<(), String> {
+    let mut frames: Vec<i32> = Vec::new();
+    let mut iframes: Vec<i32> = Vec::new();
+    let mut delays: Vec<i32> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "replayoff" => {
+                client.p1(2);
+                client.p2(ctx.int(value)?);
+            }
+            "walkmerge" => {
+                let labels: Vec<&str> = value.split(',').collect();
+                client.p1(3);
+                client.p1(labels.len() as i32);
+                for label in labels {
+                    client.p1(ctx.int(label.trim_start_matches("label_"))?);
+                }
+            }
+            "stretches" => if ctx.bool(value)? { client.p1(4) },
+            "priority" => {
+                client.p1(5);
+                client.p1(ctx.int(value)?);
+            }
+            "righthand" => {
+                client.p1(6);
+                client.p2(hand(ctx, value)?);
+            }
+            "lefthand" => {
+                client.p1(7);
+                client.p2(hand(ctx, value)?);
+            }
+            "replaycount" => {
+                client.p1(8);
+                client.p1(ctx.int(value)?);
+            }
+            key => {
+                let (list, index, id) = if let Some(index) = key_index(key, "frame") {
+                    (&mut frames, index, ctx.id("anim", value)?)
+                } else if let Some(index) = key_index(key, "iframe") {
+                    (&mut iframes, index, ctx.id("anim", value)?)
+                } else if let Some(index) = key_index(key, "delay") {
+                    (&mut delays, index, ctx.int(value)?)
+                } else {
+                    return Err(format!("Unknown seq property: {}", key));
+                };
+                if index == 0 {
+                    return Err(format!("Unknown seq property: {}", key));
+                }
+                if list.len() < index {
+                    list.resize(index, -1);
+                }
+                list[index - 1] = id;
+            }
+        }
+    }
+
+    if !frames.is_empty() {
+        client.p1(1);
+        client.p1(frames.len() as i32);
+        for (index, frame) in frames.iter().enumerate() {
+            // a delay of 0 tells the client to use the delay stored in the frame.
+            client.p2(*frame);
+            client.p2(iframes.get(index).copied().unwrap_or(-1));
+            client.p2(delays.get(index).copied().filter(|&delay| delay != -1).unwrap_or(0));
+        }
+    }
+    return Ok(());
+}
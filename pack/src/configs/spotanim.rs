@@ -0,0 +1,53 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::configs::{recol, write_recols};
+use crate::context::PackContext;
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, _server: &mut Packet) -> Result<(), String> {
+    let mut recols: Vec<(i32, i32)> = Vec::new();
+
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "model" => {
+                client.p1(1);
+                client.p2(ctx.id("model", value)?);
+            }
+            "anim" => {
+                client.p1(2);
+                client.p2(ctx.id("seq", value)?);
+            }
+            "hasalpha" => if ctx.bool(value)? { client.p1(3) },
+            "resizeh" => {
+                client.p1(4);
+                client.p2(ctx.int(value)?);
+            }
+            "resizev" => {
+                client.p1(5);
+                client.p2(ctx.int(value)?);
+            }
+            "orientation" => {
+                client.p1(6);
+                client.p2(ctx.int(value)?);
+            }
+            "ambient" => {
+                client.p1(7);
+                client.p1(ctx.int(value)?);
+            }
+            "contrast" => {
+                client.p1(8);
+                client.p1(ctx.int(value)?);
+            }
+            key => {
+                if !recol(ctx, key, value, &mut recols)? {
+                    return Err(format!("Unknown spotanim property: {}", key));
+                }
+            }
+        }
+    }
+
+    write_recols(&recols, client);
+    return Ok(());
+}
@@ -0,0 +1,20 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::PackContext;
+
+pub fn encode(
+    ctx: &mut PackContext,
+    config: &ConfigSection,
+    _client: &mut Packet,
+    server: &mut Packet,
+) -> Result<(), String> {
+    let mut params: Vec<&str> = Vec::new();
+    for (key, value) in &config.lines {
+        match key.as_str() {
+            "param" => params.push(value),
+            _ => return Err(format!("Unknown struct property: {}", key)),
+        }
+    }
+    return ctx.params(&params, server);
+}
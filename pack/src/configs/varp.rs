@@ -0,0 +1,37 @@
+use io::Packet;
+
+use crate::config::ConfigSection;
+use crate::context::PackContext;
+
+fn scope(value: &str) -> Result<i32, String> {
+    return match value {
+        "temp" => Ok(0),
+        "perm" => Ok(1),
+        _ => Err(format!("Unknown scope: {}", value)),
+    };
+}
+
+#[rustfmt::skip]
+pub fn encode(ctx: &mut PackContext, config: &ConfigSection, client: &mut Packet, server: &mut Packet) -> Result<(), String> {
+    for (key, value) in &config.lines {
+        let value: &str = value.as_str();
+        match key.as_str() {
+            "scope" => {
+                server.p1(1);
+                server.p1(scope(value)?);
+            }
+            "type" => {
+                server.p1(2);
+                server.p1(PackContext::type_char(value)?);
+            }
+            "protect" => if !ctx.bool(value)? { server.p1(4) },
+            "clientcode" => {
+                client.p1(5);
+                client.p2(ctx.int(value)?);
+            }
+            "transmit" => if ctx.bool(value)? { server.p1(6) },
+            _ => return Err(format!("Unknown varp property: {}", key)),
+        }
+    }
+    return Ok(());
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use io::Packet;
+
+use crate::config::{find_files, ConfigSection};
+use crate::pack_file::PackFile;
+
+/// A config value after it has been resolved against its type.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypedValue {
+    Int(i32),
+    String(String),
+}
+
+/// Shared state while packing, the id maps for every config type,
+/// the `^constants` and the declared type of every param.
+pub struct PackContext {
+    pub src: PathBuf,
+    pub packs: HashMap<String, PackFile>,
+    pub constants: HashMap<String, String>,
+    pub params: HashMap<String, String>,
+}
+
+impl PackContext {
+    pub const STATS: [&'static str; 21] = [
+        "attack",
+        "defence",
+        "strength",
+        "hitpoints",
+        "ranged",
+        "prayer",
+        "magic",
+        "cooking",
+        "woodcutting",
+        "fletching",
+        "fishing",
+        "firemaking",
+        "crafting",
+        "smithing",
+        "mining",
+        "herblore",
+        "agility",
+        "thieving",
+        "stat18",
+        "stat19",
+        "runecraft",
+    ];
+
+    pub fn new(src: &Path) -> PackContext {
+        return PackContext {
+            src: src.to_path_buf(),
+            packs: HashMap::new(),
+            constants: HashMap::new(),
+            params: HashMap::new(),
+        };
+    }
+
+    /// Reads every `^name = value` from the `.constant` files under the source directory.
+    pub fn load_constants(&mut self) -> Result<(), String> {
+        for path in find_files(&self.src, "constant") {
+            let src: String = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            for line in src.lines() {
+                let line: &str = line.trim();
+                if line.is_empty() || line.starts_with("//") {
+                    continue;
+                }
+                if let Some((name, value)) = line.split_once('=') {
+                    self.constants.insert(
+                        name.trim().trim_start_matches('^').to_string(),
+                        value.trim().trim_end_matches(';').trim().to_string(),
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    /// Records the declared type of every param so param values can be resolved.
+    pub fn load_params(&mut self, params: &[ConfigSection]) {
+        for param in params {
+            if let Some(kind) = param.get("type") {
+                self.params.insert(param.name.clone(), kind.to_string());
+            }
+        }
+    }
+
+    /// The id map for a config type, loaded from `pack/<kind>.pack` on first use.
+    pub fn pack(&mut self, kind: &str) -> Result<&mut PackFile, String> {
+        if !self.packs.contains_key(kind) {
+            let path: PathBuf = self.src.join("pack").join(format!("{}.pack", kind));
+            let pack: PackFile = PackFile::io(&path.to_string_lossy())?;
+            self.packs.insert(kind.to_string(), pack);
+        }
+        return Ok(self.packs.get_mut(kind).unwrap());
+    }
+
+    /// The ids listed in `pack/<kind>.order`, the order the client expects them to be
+    /// written in. Returns `None` when the type has no order file.
+    pub fn order(&self, kind: &str) -> Result<Option<Vec<usize>>, String> {
+        let path: PathBuf = self.src.join("pack").join(format!("{}.order", kind));
+        let src: String = match std::fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(_) => return Ok(None),
+        };
+        let mut order: Vec<usize> = Vec::new();
+        for line in src.lines() {
+            let line: &str = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            order.push(
+                line.parse::<usize>()
+                    .map_err(|_| format!("Invalid id in {}: {}", path.display(), line))?,
+            );
+        }
+        return Ok(Some(order));
+    }
+
+    /// Looks up the id of an existing name.
+    pub fn id(&mut self, kind: &str, name: &str) -> Result<i32, String> {
+        let name: String = self.constant(name)?;
+        return self
+            .pack(kind)?
+            .id(&name)
+            .ok_or(format!("Unknown {}: {}", kind, name));
+    }
+
+    /// Looks up the id of a name, assigning a new id if it has never been seen.
+    pub fn assign(&mut self, kind: &str, name: &str) -> Result<i32, String> {
+        let name: String = self.constant(name)?;
+        return Ok(self.pack(kind)?.assign(&name));
+    }
+
+    /// Writes every pack file that had new ids assigned.
+    pub fn save(&mut self) -> Result<(), String> {
+        for pack in self.packs.values_mut() {
+            pack.save()?;
+        }
+        return Ok(());
+    }
+
+    /// Substitutes a `^constant` for its value, anything else is returned as is.
+    pub fn constant(&self, value: &str) -> Result<String, String> {
+        return match value.strip_prefix('^') {
+            Some(name) => self
+                .constants
+                .get(name)
+                .cloned()
+                .ok_or(format!("Unknown constant: ^{}", name)),
+            None => Ok(value.to_string()),
+        };
+    }
+
+    pub fn int(&self, value: &str) -> Result<i32, String> {
+        let value: String = self.constant(value)?;
+        return value
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid int: {}", value));
+    }
+
+    pub fn bool(&self, value: &str) -> Result<bool, String> {
+        return match self.constant(value)?.as_str() {
+            "yes" | "true" | "1" => Ok(true),
+            "no" | "false" | "0" => Ok(false),
+            other => Err(format!("Invalid boolean: {}", other)),
+        };
+    }
+
+    /// Converts a weight like `600g`, `4lb`, `10oz` or `1kg` into grams.
+    pub fn weight(value: &str) -> Result<i32, String> {
+        let (number, scale) = if let Some(number) = value.strip_suffix("kg") {
+            (number, 1000.0)
+        } else if let Some(number) = value.strip_suffix("lb") {
+            (number, 453.592)
+        } else if let Some(number) = value.strip_suffix("oz") {
+            (number, 28.3495)
+        } else if let Some(number) = value.strip_suffix('g') {
+            (number, 1.0)
+        } else {
+            return Err(format!("Invalid weight: {}", value));
+        };
+        let number: f64 = number
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid weight: {}", value))?;
+        return Ok((number * scale) as i32);
+    }
+
+    /// Converts a coord written as `level_mx_mz_lx_lz` into its packed form.
+    pub fn coord(value: &str) -> Result<i32, String> {
+        let parts: Vec<i32> = value
+            .split('_')
+            .map(|part| part.parse::<i32>())
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|_| format!("Invalid coord: {}", value))?;
+        if parts.len() != 5 {
+            return Err(format!("Invalid coord: {}", value));
+        }
+        let x: i32 = (parts[1] << 6) + parts[3];
+        let z: i32 = (parts[2] << 6) + parts[4];
+        return Ok(((parts[0] & 0x3) << 28) | ((x & 0x3fff) << 14) | (z & 0x3fff));
+    }
+
+    pub fn stat(value: &str) -> Result<i32, String> {
+        return PackContext::STATS
+            .iter()
+            .position(|&stat| stat == value)
+            .map(|stat| stat as i32)
+            .ok_or(format!("Unknown stat: {}", value));
+    }
+
+    /// The character the client and server use to identify a script var type.
+    pub fn type_char(kind: &str) -> Result<i32, String> {
+        let char: char = match kind {
+            "int" | "autoint" => 'i',
+            "boolean" => '1',
+            "string" => 's',
+            "obj" => 'o',
+            "namedobj" => 'O',
+            "npc" => 'n',
+            "loc" => 'l',
+            "seq" => 'A',
+            "struct" => 'J',
+            "enum" => 'g',
+            "category" => 'y',
+            "coord" => 'c',
+            "stat" => 'S',
+            "inv" => 'v',
+            "synth" => 'P',
+            "idk" => 'K',
+            "spotanim" => 't',
+            "interface" => 'a',
+            "component" => 'I',
+            "model" => 'm',
+            "char" => 'z',
+            "npc_uid" => 'N',
+            "player_uid" => 'p',
+            "locshape" => 'H',
+            "dbrow" => 'Ð',
+            _ => return Err(format!("Unknown type: {}", kind)),
+        };
+        return Ok(char as i32);
+    }
+
+    /// Resolves a value of the given script var type, references to
+    /// other configs are converted to their ids.
+    pub fn typed(&mut self, kind: &str, value: &str) -> Result<TypedValue, String> {
+        if kind == "string" {
+            return Ok(TypedValue::String(self.constant(value)?));
+        }
+        let value: String = self.constant(value)?;
+        if value == "null" {
+            return Ok(TypedValue::Int(-1));
+        }
+        let int: i32 = match kind {
+            "int" | "autoint" => self.int(&value)?,
+            "boolean" => self.bool(&value)? as i32,
+            "obj" | "namedobj" => self.id("obj", &value)?,
+            "npc" | "loc" | "seq" | "inv" | "struct" | "enum" | "idk" | "spotanim" => {
+                self.id(kind, &value)?
+            }
+            "synth" => self.id("sound", &value)?,
+            "model" => self.id("model", &value)?,
+            "interface" => self.id("interface", &value)?,
+            "category" => self.assign("category", &value)?,
+            "coord" => PackContext::coord(&value)?,
+            "stat" => PackContext::stat(&value)?,
+            "char" => value.chars().next().map_or(0, |char| char as i32),
+            _ => return Err(format!("Unknown type: {}", kind)),
+        };
+        return Ok(TypedValue::Int(int));
+    }
+
+    /// Writes the params of a config as opcode 249.
+    ///
+    /// Each param is `param=name,value`, the value is resolved using the type
+    /// declared in the `.param` file.
+    pub fn params(&mut self, params: &[&str], buf: &mut Packet) -> Result<(), String> {
+        if params.is_empty() {
+            return Ok(());
+        }
+        buf.p1(249);
+        buf.p1(params.len() as i32);
+        for param in params {
+            let (name, value) = param
+                .split_once(',')
+                .ok_or(format!("Invalid param: {}", param))?;
+            let kind: String = self
+                .params
+                .get(name)
+                .cloned()
+                .ok_or(format!("Unknown param: {}", name))?;
+            buf.p3(self.id("param", name)?);
+            match self.typed(&kind, value)? {
+                TypedValue::String(value) => {
+                    buf.p1(1);
+                    buf.pjstr(&value, 10);
+                }
+                TypedValue::Int(value) => {
+                    buf.p1(0);
+                    buf.p4(value);
+                }
+            }
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use io::Packet;
+
+use crate::config::{find_files, parse_configs, ConfigSection};
+use crate::configs::encoded_size;
+use crate::context::PackContext;
+
+/// A `.if` source file. The lines before the first `[name]` are the properties of the
+/// interface itself, every section after them is one of its components.
+pub struct InterfaceSource {
+    pub name: String,
+    pub root: ConfigSection,
+    pub components: Vec<ConfigSection>,
+}
+
+impl InterfaceSource {
+    /// The name a component is given in `interface.pack`, `tradeconfirm:com_0`.
+    pub fn component_name(&self, component: &str) -> String {
+        return format!("{}:{}", self.name, component);
+    }
+
+    /// The components inside of `layer`, or inside of the interface itself for `None`.
+    fn children(&self, layer: Option<&str>) -> Vec<&ConfigSection> {
+        return self
+            .components
+            .iter()
+            .filter(|component| component.get("layer") == layer)
+            .collect();
+    }
+}
+
+/// Parses a `.if` source file, named after the file it was read from.
+pub fn parse_interface(name: &str, src: &str) -> Result<InterfaceSource, String> {
+    let mut sections: Vec<ConfigSection> = parse_configs(&format!("[{}]\n{}", name, src))?;
+    let root: ConfigSection = sections.remove(0);
+    return Ok(InterfaceSource {
+        name: name.to_string(),
+        root,
+        components: sections,
+    });
+}
+
+/// Reads every `.if` file under `dir`.
+pub fn read_interfaces(dir: &Path) -> Result<Vec<InterfaceSource>, String> {
+    let mut interfaces: Vec<InterfaceSource> = Vec::new();
+    for path in find_files(dir, "if") {
+        let src: String = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let name: String = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        interfaces
+            .push(parse_interface(&name, &src).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    return Ok(interfaces);
+}
+
+/// Encodes every interface into the `data` file of the client `interface` archive.
+///
+/// The file starts with the number of ids followed by every component, in the order of
+/// `pack/interface.order` and then by id for anything not in it. A component is written
+/// as its id and its properties, whenever the interface changes the id is preceded by
+/// 65535 and the id of the interface. Each interface is written as a layer holding the
+/// components that have no `layer` of their own. Ids are assigned to new names like configs.
+pub fn pack_interfaces(
+    ctx: &mut PackContext,
+    interfaces: &[InterfaceSource],
+) -> Result<Vec<u8>, String> {
+    // every id with a source, the interface and its component, none for the interface itself
+    let mut sources: HashMap<usize, (usize, Option<usize>)> = HashMap::new();
+    for (index, interface) in interfaces.iter().enumerate() {
+        let id: i32 = ctx.assign("interface", &interface.name)?;
+        sources.insert(id as usize, (index, None));
+        for (component, section) in interface.components.iter().enumerate() {
+            let id: i32 = ctx.assign("interface", &interface.component_name(&section.name))?;
+            sources.insert(id as usize, (index, Some(component)));
+        }
+    }
+
+    let count: usize = ctx.pack("interface")?.count();
+    let mut order: Vec<usize> = ctx.order("interface")?.unwrap_or_default();
+    order.retain(|id| sources.contains_key(id));
+    let mut unordered: Vec<usize> = sources
+        .keys()
+        .filter(|id| !order.contains(id))
+        .copied()
+        .collect();
+    unordered.sort();
+    order.extend(unordered);
+
+    let mut data: Vec<u8> = vec![(count >> 8) as u8, count as u8];
+    let mut layer: Option<usize> = None;
+    for id in order {
+        let (index, component) = sources[&id];
+        let interface: &InterfaceSource = &interfaces[index];
+        if layer != Some(index) {
+            let root: i32 = ctx.id("interface", &interface.name)?;
+            data.extend_from_slice(&[0xff, 0xff, (root >> 8) as u8, root as u8]);
+            layer = Some(index);
+        }
+        let section: &ConfigSection = match component {
+            Some(component) => &interface.components[component],
+            None => &interface.root,
+        };
+        let children: Vec<&ConfigSection> = match component {
+            Some(_) => interface.children(Some(&section.name)),
+            None => interface.children(None),
+        };
+        let mut buf: Packet = Packet::new(encoded_size(section) + children.len() * 6 + 256);
+        buf.p2(id as i32);
+        encode_component(
+            ctx,
+            interface,
+            section,
+            component.is_none(),
+            &children,
+            &mut buf,
+        )
+        .map_err(|e| format!("[{}] {}", interface.component_name(&section.name), e))?;
+        data.extend_from_slice(&buf.data[..buf.pos]);
+    }
+    return Ok(data);
+}
+
+fn encode_component(
+    ctx: &mut PackContext,
+    interface: &InterfaceSource,
+    com: &ConfigSection,
+    root: bool,
+    children: &[&ConfigSection],
+    buf: &mut Packet,
+) -> Result<(), String> {
+    for (key, _) in &com.lines {
+        if !known_property(key) {
+            return Err(format!("Unknown interface property: {}", key));
+        }
+    }
+
+    // the interface itself is always a layer as big as the main viewport, an `overlay`
+    // interface only differs in how the server opens it
+    let kind: i32 = if root {
+        0
+    } else {
+        match com.get("type") {
+            Some("layer") => 0,
+            Some("overlay") => 1,
+            Some("inv") => 2,
+            Some("rect") => 3,
+            Some("text") => 4,
+            Some("graphic") => 5,
+            Some("model") => 6,
+            Some("invtext") => 7,
+            Some(other) => return Err(format!("Invalid interface type: {}", other)),
+            None => return Err("Missing interface type".to_string()),
+        }
+    };
+    let button: i32 = match com.get("buttontype") {
+        None => 0,
+        Some("normal") => 1,
+        Some("target") => 2,
+        Some("close") => 3,
+        Some("toggle") => 4,
+        Some("select") => 5,
+        Some("pause") => 6,
+        Some(other) => return Err(format!("Invalid buttontype: {}", other)),
+    };
+
+    buf.p1(kind);
+    buf.p1(button);
+    buf.p2(int_or(ctx, com, "clientcode", 0)?);
+    if root {
+        buf.p2(512);
+        buf.p2(334);
+    } else {
+        buf.p2(int_or(ctx, com, "width", 0)?);
+        buf.p2(int_or(ctx, com, "height", 0)?);
+    }
+    match com.get("overlayer") {
+        Some(overlayer) => {
+            let id: i32 = ctx.id("interface", &interface.component_name(overlayer))?;
+            buf.p1((id >> 8) + 1);
+            buf.p1(id & 0xff);
+        }
+        None => buf.p1(0),
+    }
+
+    let comparators: Vec<(i32, i32)> = (1..=5)
+        .map_while(|index| com.get(&format!("script{}", index)))
+        .map(|script| comparator(ctx, script))
+        .collect::<Result<Vec<(i32, i32)>, String>>()?;
+    buf.p1(comparators.len() as i32);
+    for (comparator, operand) in &comparators {
+        buf.p1(*comparator);
+        buf.p2(*operand);
+    }
+
+    let mut scripts: Vec<Vec<i32>> = Vec::new();
+    for index in 1..=5 {
+        let ops: Vec<&str> = (1..=10)
+            .filter_map(|op| com.get(&format!("script{}op{}", index, op)))
+            .collect();
+        if ops.is_empty() {
+            break;
+        }
+        let mut script: Vec<i32> = Vec::new();
+        for op in ops.iter().filter(|op| !op.is_empty()) {
+            script.extend(script_op(ctx, op)?);
+        }
+        script.push(0);
+        scripts.push(script);
+    }
+    buf.p1(scripts.len() as i32);
+    for script in &scripts {
+        buf.p2(script.len() as i32);
+        for value in script {
+            buf.p2(*value);
+        }
+    }
+
+    if kind == 0 {
+        buf.p2(int_or(ctx, com, "scroll", 0)?);
+        buf.p1(bool_or(ctx, com, "hide")? as i32);
+        buf.p2(children.len() as i32);
+        for child in children {
+            buf.p2(ctx.id("interface", &interface.component_name(&child.name))?);
+            buf.p2(int_or(ctx, child, "x", 0)?);
+            buf.p2(int_or(ctx, child, "y", 0)?);
+        }
+    }
+    if kind == 1 {
+        buf.p2(0);
+        buf.p1(0);
+    }
+    if kind == 2 {
+        let (margin_x, margin_y) = pair(ctx, com.get("margin").unwrap_or("0,0"))?;
+        buf.p1(bool_or(ctx, com, "draggable")? as i32);
+        buf.p1(bool_or(ctx, com, "interactable")? as i32);
+        buf.p1(bool_or(ctx, com, "usable")? as i32);
+        buf.p1(margin_x);
+        buf.p1(margin_y);
+        for index in 1..=20 {
+            match com.get(&format!("slot{}", index)) {
+                Some(slot) => {
+                    // `graphic:x,y`, the offset is optional
+                    let (graphic, offset) = slot.split_once(':').unwrap_or((slot, "0,0"));
+                    let (x, y) = pair(ctx, offset)?;
+                    buf.p1(1);
+                    buf.p2(x);
+                    buf.p2(y);
+                    buf.pjstr(graphic, 10);
+                }
+                None => buf.p1(0),
+            }
+        }
+        options(com, buf);
+    }
+    if kind == 3 {
+        buf.p1(bool_or(ctx, com, "fill")? as i32);
+    }
+    if kind == 4 || kind == 1 {
+        buf.p1(bool_or(ctx, com, "center")? as i32);
+        buf.p1(font(com.get("font"))?);
+        buf.p1(bool_or(ctx, com, "shadowed")? as i32);
+    }
+    if kind == 4 {
+        buf.pjstr(com.get("text").unwrap_or(""), 10);
+        buf.pjstr(com.get("activetext").unwrap_or(""), 10);
+    }
+    if kind == 1 || kind == 3 || kind == 4 {
+        buf.p4(colour(ctx, com, "colour")?);
+    }
+    if kind == 3 || kind == 4 {
+        buf.p4(colour(ctx, com, "activecolour")?);
+        buf.p4(colour(ctx, com, "overcolour")?);
+    }
+    if kind == 5 {
+        buf.pjstr(com.get("graphic").unwrap_or(""), 10);
+        buf.pjstr(com.get("activegraphic").unwrap_or(""), 10);
+    }
+    if kind == 6 {
+        reference(ctx, buf, "model", com.get("model"))?;
+        reference(ctx, buf, "model", com.get("activemodel"))?;
+        reference(ctx, buf, "seq", com.get("anim"))?;
+        reference(ctx, buf, "seq", com.get("activeanim"))?;
+        buf.p2(int_or(ctx, com, "zoom", 0)?);
+        buf.p2(int_or(ctx, com, "xan", 0)?);
+        buf.p2(int_or(ctx, com, "yan", 0)?);
+    }
+    if kind == 7 {
+        let (margin_x, margin_y) = pair(ctx, com.get("margin").unwrap_or("0,0"))?;
+        buf.p1(bool_or(ctx, com, "center")? as i32);
+        buf.p1(font(com.get("font"))?);
+        buf.p1(bool_or(ctx, com, "shadowed")? as i32);
+        buf.p4(colour(ctx, com, "colour")?);
+        buf.p2(margin_x);
+        buf.p2(margin_y);
+        buf.p1(bool_or(ctx, com, "interactable")? as i32);
+        options(com, buf);
+    }
+    if button == 2 || kind == 2 {
+        buf.pjstr(com.get("actionverb").unwrap_or(""), 10);
+        buf.pjstr(com.get("action").unwrap_or(""), 10);
+        buf.p2(action_target(com.get("actiontarget").unwrap_or(""))?);
+    }
+    if button == 1 || button == 4 || button == 5 || button == 6 {
+        buf.pjstr(com.get("option").unwrap_or(""), 10);
+    }
+    return Ok(());
+}
+
+fn known_property(key: &str) -> bool {
+    if let Some(rest) = key.strip_prefix("script") {
+        // scriptN and scriptNopM
+        let (index, op) = rest.split_once("op").unwrap_or((rest, "1"));
+        return index
+            .parse::<u8>()
+            .is_ok_and(|index| (1..=5).contains(&index))
+            && op.parse::<u8>().is_ok_and(|op| (1..=10).contains(&op));
+    }
+    if let Some(index) = key.strip_prefix("slot") {
+        return index
+            .parse::<u8>()
+            .is_ok_and(|index| (1..=20).contains(&index));
+    }
+    if let Some(index) = key.strip_prefix("option") {
+        return index.is_empty()
+            || index
+                .parse::<u8>()
+                .is_ok_and(|index| (1..=5).contains(&index));
+    }
+    return matches!(
+        key,
+        "type"
+            | "layer"
+            | "x"
+            | "y"
+            | "width"
+            | "height"
+            | "buttontype"
+            | "clientcode"
+            | "overlayer"
+            | "scroll"
+            | "hide"
+            | "draggable"
+            | "interactable"
+            | "usable"
+            | "margin"
+            | "fill"
+            | "center"
+            | "font"
+            | "shadowed"
+            | "text"
+            | "activetext"
+            | "colour"
+            | "activecolour"
+            | "overcolour"
+            | "graphic"
+            | "activegraphic"
+            | "model"
+            | "activemodel"
+            | "anim"
+            | "activeanim"
+            | "zoom"
+            | "xan"
+            | "yan"
+            | "actionverb"
+            | "action"
+            | "actiontarget"
+    );
+}
+
+fn int_or(ctx: &PackContext, com: &ConfigSection, key: &str, default: i32) -> Result<i32, String> {
+    return match com.get(key) {
+        Some(value) => ctx.int(value),
+        None => Ok(default),
+    };
+}
+
+fn bool_or(ctx: &PackContext, com: &ConfigSection, key: &str) -> Result<bool, String> {
+    return match com.get(key) {
+        Some(value) => ctx.bool(value),
+        None => Ok(false),
+    };
+}
+
+fn pair(ctx: &PackContext, value: &str) -> Result<(i32, i32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or(format!("Invalid pair: {}", value))?;
+    return Ok((ctx.int(x)?, ctx.int(y)?));
+}
+
+fn colour(ctx: &PackContext, com: &ConfigSection, key: &str) -> Result<i32, String> {
+    let value: String = match com.get(key) {
+        Some(value) => ctx.constant(value)?,
+        None => return Ok(0),
+    };
+    return match value.strip_prefix("0x") {
+        Some(hex) => i32::from_str_radix(hex, 16).map_err(|_| format!("Invalid colour: {}", value)),
+        None => ctx.int(&value),
+    };
+}
+
+fn font(value: Option<&str>) -> Result<i32, String> {
+    return match value {
+        None | Some("p11") => Ok(0),
+        Some("p12") => Ok(1),
+        Some("b12") => Ok(2),
+        Some("q8") => Ok(3),
+        Some(other) => Err(format!("Invalid font: {}", other)),
+    };
+}
+
+/// Writes a model or seq as its id plus one over two bytes, or a single 0 for none.
+fn reference(
+    ctx: &mut PackContext,
+    buf: &mut Packet,
+    kind: &str,
+    name: Option<&str>,
+) -> Result<(), String> {
+    match name {
+        Some(name) => {
+            let id: i32 = ctx.id(kind, name)?;
+            buf.p1((id >> 8) + 1);
+            buf.p1(id & 0xff);
+        }
+        None => buf.p1(0),
+    }
+    return Ok(());
+}
+
+fn options(com: &ConfigSection, buf: &mut Packet) {
+    for index in 1..=5 {
+        buf.pjstr(com.get(&format!("option{}", index)).unwrap_or(""), 10);
+    }
+}
+
+fn action_target(value: &str) -> Result<i32, String> {
+    let mut flags: i32 = 0;
+    for target in value.split(',').filter(|target| !target.is_empty()) {
+        flags |= match target {
+            "obj" => 0x1,
+            "npc" => 0x2,
+            "loc" => 0x4,
+            "player" => 0x8,
+            "heldobj" => 0x10,
+            other => return Err(format!("Invalid actiontarget: {}", other)),
+        };
+    }
+    return Ok(flags);
+}
+
+/// `scriptN=comparator,operand`, the script is active when its value compares true.
+fn comparator(ctx: &PackContext, script: &str) -> Result<(i32, i32), String> {
+    let (comparator, operand) = script
+        .split_once(',')
+        .ok_or(format!("Invalid script comparator: {}", script))?;
+    let comparator: i32 = match comparator {
+        "eq" => 1,
+        "lt" => 2,
+        "gt" => 3,
+        "neq" => 4,
+        other => return Err(format!("Invalid script comparator: {}", other)),
+    };
+    return Ok((comparator, ctx.int(operand)?));
+}
+
+/// `scriptNopM=op,args`, the values of every op of a script are added together.
+fn script_op(ctx: &mut PackContext, op: &str) -> Result<Vec<i32>, String> {
+    let args: Vec<&str> = op.split(',').collect();
+    let arg = |index: usize| -> Result<&str, String> {
+        return args
+            .get(index)
+            .copied()
+            .ok_or(format!("Missing script op argument: {}", op));
+    };
+    return match args[0] {
+        "stat_level" => Ok(vec![1, PackContext::stat(arg(1)?)?]),
+        "stat_base_level" => Ok(vec![2, PackContext::stat(arg(1)?)?]),
+        "stat_xp" => Ok(vec![3, PackContext::stat(arg(1)?)?]),
+        "inv_count" => Ok(vec![
+            4,
+            ctx.id("interface", arg(1)?)?,
+            ctx.id("obj", arg(2)?)?,
+        ]),
+        "pushvar" => Ok(vec![5, ctx.id("varp", arg(1)?)?]),
+        "stat_xp_remaining" => Ok(vec![6, PackContext::stat(arg(1)?)?]),
+        "op7" => Ok(vec![7]),
+        "op8" => Ok(vec![8]),
+        "op9" => Ok(vec![9]),
+        "inv_contains" => Ok(vec![
+            10,
+            ctx.id("interface", arg(1)?)?,
+            ctx.id("obj", arg(2)?)?,
+        ]),
+        "runenergy" => Ok(vec![11]),
+        "runweight" => Ok(vec![12]),
+        "testbit" => Ok(vec![13, ctx.id("varp", arg(1)?)?, ctx.int(arg(2)?)?]),
+        other => Err(format!("Unknown script op: {}", other)),
+    };
+}
@@ -0,0 +1,23 @@
+pub use config::find_files;
+pub use config::parse_configs;
+pub use config::read_configs;
+pub use config::ConfigSection;
+pub use context::PackContext;
+pub use context::TypedValue;
+pub use interface::parse_interface;
+pub use interface::InterfaceSource;
+pub use pack_file::PackFile;
+pub use packer::missing_archives;
+pub use packer::pack;
+pub use packer::ConfigType;
+pub use packer::CONFIG_TYPES;
+pub use packer::UNPACKED_ARCHIVES;
+
+pub mod compiler;
+pub mod configs;
+
+mod config;
+mod context;
+mod interface;
+mod pack_file;
+mod packer;
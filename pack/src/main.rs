@@ -0,0 +1,10 @@
+use std::path::Path;
+
+fn main() {
+    let src: String = std::env::args().nth(1).unwrap_or("./data/src".to_string());
+    let out: String = std::env::args().nth(2).unwrap_or("./data/pack".to_string());
+    if let Err(e) = pack::pack(Path::new(&src), Path::new(&out)) {
+        eprintln!("Pack failed: {}", e);
+        std::process::exit(1);
+    }
+}
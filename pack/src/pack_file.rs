@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+/// A `*.pack` id map, one `id=name` per line.
+///
+/// Ids are stable once assigned, new names are given the next free id
+/// and written back to the file so the client and server never disagree.
+pub struct PackFile {
+    pub path: Option<String>,
+    pub ids: HashMap<String, i32>,
+    pub names: Vec<Option<String>>,
+    dirty: bool,
+}
+
+impl PackFile {
+    pub fn new() -> PackFile {
+        return PackFile {
+            path: None,
+            ids: HashMap::new(),
+            names: Vec::new(),
+            dirty: false,
+        };
+    }
+
+    /// Loads a pack file from disk, a missing file is treated as empty
+    /// and will be created on `save` if any ids were assigned.
+    pub fn io(path: &str) -> Result<PackFile, String> {
+        let mut pack: PackFile = match std::fs::read_to_string(path) {
+            Ok(src) => PackFile::parse(&src)?,
+            Err(_) => PackFile::new(),
+        };
+        pack.path = Some(path.to_string());
+        return Ok(pack);
+    }
+
+    pub fn parse(src: &str) -> Result<PackFile, String> {
+        let mut pack: PackFile = PackFile::new();
+        for line in src.lines() {
+            let line: &str = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (id, name) = line
+                .split_once('=')
+                .ok_or(format!("Invalid pack line: {}", line))?;
+            let id: usize = id
+                .parse()
+                .map_err(|_| format!("Invalid pack id: {}", line))?;
+            pack.insert(id, name);
+        }
+        return Ok(pack);
+    }
+
+    fn insert(&mut self, id: usize, name: &str) {
+        if id >= self.names.len() {
+            self.names.resize(id + 1, None);
+        }
+        self.names[id] = Some(name.to_string());
+        self.ids.insert(name.to_string(), id as i32);
+    }
+
+    /// The number of ids, including any gaps.
+    pub fn count(&self) -> usize {
+        return self.names.len();
+    }
+
+    pub fn id(&self, name: &str) -> Option<i32> {
+        return self.ids.get(name).copied();
+    }
+
+    pub fn name(&self, id: usize) -> Option<&str> {
+        return self.names.get(id).and_then(|name| name.as_deref());
+    }
+
+    /// Returns the id for `name`, assigning the next free id if it is new.
+    pub fn assign(&mut self, name: &str) -> i32 {
+        if let Some(id) = self.id(name) {
+            return id;
+        }
+        let id: usize = self.names.len();
+        self.insert(id, name);
+        self.dirty = true;
+        return id as i32;
+    }
+
+    /// Writes the pack file back to disk if any new ids were assigned.
+    pub fn save(&mut self) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(path) = &self.path {
+            let mut out: String = String::new();
+            for (id, name) in self.names.iter().enumerate() {
+                if let Some(name) = name {
+                    out.push_str(&format!("{}={}\n", id, name));
+                }
+            }
+            std::fs::write(path, out).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        }
+        self.dirty = false;
+        return Ok(());
+    }
+}
+
+impl Default for PackFile {
+    fn default() -> PackFile {
+        return PackFile::new();
+    }
+}
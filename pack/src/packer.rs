@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+use io::JagFile;
+
+use crate::compiler;
+use crate::config::{read_configs, ConfigSection};
+use crate::configs::{
+    enum_type, flo, idk, inv, loc, npc, obj, pack_configs, param, seq, spotanim, struct_type, varp,
+    ConfigEncoder, PackedConfigs,
+};
+use crate::context::PackContext;
+use crate::interface::{pack_interfaces, read_interfaces, InterfaceSource};
+
+pub struct ConfigType {
+    pub kind: &'static str,
+    pub encode: ConfigEncoder,
+    pub client: bool, // whether the type is part of the client config archive
+}
+
+/// Every config type the packer knows about, the source extension matches the kind.
+pub const CONFIG_TYPES: [ConfigType; 12] = [
+    ConfigType {
+        kind: "flo",
+        encode: flo::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "idk",
+        encode: idk::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "loc",
+        encode: loc::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "npc",
+        encode: npc::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "obj",
+        encode: obj::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "seq",
+        encode: seq::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "spotanim",
+        encode: spotanim::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "varp",
+        encode: varp::encode,
+        client: true,
+    },
+    ConfigType {
+        kind: "inv",
+        encode: inv::encode,
+        client: false,
+    },
+    ConfigType {
+        kind: "param",
+        encode: param::encode,
+        client: false,
+    },
+    ConfigType {
+        kind: "enum",
+        encode: enum_type::encode,
+        client: false,
+    },
+    ConfigType {
+        kind: "struct",
+        encode: struct_type::encode,
+        client: false,
+    },
+];
+
+/// The client archives `pack` has no sources for, they come from the original
+/// cache and have to be copied into `client` by hand. The update server serves
+/// whichever are missing with a crc of 0, which clients won't finish loading with.
+pub const UNPACKED_ARCHIVES: [&str; 6] =
+    ["title", "media", "models", "textures", "wordenc", "sounds"];
+
+/// The `UNPACKED_ARCHIVES` not yet copied into `out/client`.
+pub fn missing_archives(out: &Path) -> Vec<&'static str> {
+    return UNPACKED_ARCHIVES
+        .iter()
+        .filter(|name| !out.join("client").join(name).is_file())
+        .copied()
+        .collect();
+}
+
+/// Packs the config sources under `src` into `out`.
+///
+/// Writes `server/<kind>.dat` for every config type and the `client/config`
+/// archive with the `<kind>.dat` and `<kind>.idx` of every client type.
+/// Names without an id in `src/pack/<kind>.pack` are given one and the
+/// pack file is updated. The `.if` interfaces are written to the `client/interface`
+/// archive in the order of `src/pack/interface.order`. The `UNPACKED_ARCHIVES`
+/// aren't generated, any still missing from `out/client` are warned about.
+pub fn pack(src: &Path, out: &Path) -> Result<(), String> {
+    let start: Instant = Instant::now();
+    let mut ctx: PackContext = PackContext::new(src);
+    ctx.load_constants()?;
+
+    let mut sections: HashMap<&str, Vec<ConfigSection>> = HashMap::new();
+    for config in &CONFIG_TYPES {
+        let configs: Vec<ConfigSection> = read_configs(src, config.kind)?;
+        let mut seen: HashSet<&str> = HashSet::new();
+        for section in &configs {
+            if !seen.insert(section.name.as_str()) {
+                return Err(format!("Duplicate {}: {}", config.kind, section.name));
+            }
+            ctx.assign(config.kind, &section.name)?;
+        }
+        sections.insert(config.kind, configs);
+    }
+
+    ctx.load_params(&sections["param"]);
+    let certs: Vec<ConfigSection> = obj::certs(&mut ctx, &sections["obj"])?;
+    sections.get_mut("obj").unwrap().extend(certs);
+
+    std::fs::create_dir_all(out.join("server")).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(out.join("client")).map_err(|e| e.to_string())?;
+
+    let mut archive: Vec<(String, Vec<u8>)> = Vec::new();
+    for config in &CONFIG_TYPES {
+        let packed: PackedConfigs =
+            pack_configs(&mut ctx, config.kind, &sections[config.kind], config.encode)?;
+        let server: std::path::PathBuf = out.join("server").join(format!("{}.dat", config.kind));
+        std::fs::write(&server, &packed.server_dat)
+            .map_err(|e| format!("Could not write {}: {}", server.display(), e))?;
+        if config.client {
+            archive.push((format!("{}.dat", config.kind), packed.client_dat));
+            archive.push((format!("{}.idx", config.kind), packed.client_idx));
+        }
+    }
+
+    let files: Vec<(&str, Vec<u8>)> = archive
+        .iter()
+        .map(|(name, data)| (name.as_str(), data.clone()))
+        .collect();
+    let config: std::path::PathBuf = out.join("client").join("config");
+    std::fs::write(&config, JagFile::pack(&files, false))
+        .map_err(|e| format!("Could not write {}: {}", config.display(), e))?;
+
+    let interfaces: Vec<InterfaceSource> = read_interfaces(src)?;
+    let interface: Vec<u8> = pack_interfaces(&mut ctx, &interfaces)?;
+    let path: std::path::PathBuf = out.join("client").join("interface");
+    std::fs::write(&path, JagFile::pack(&[("data", interface)], false))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+    println!("Packed configs in: {:?}", start.elapsed());
+    let missing: Vec<&str> = missing_archives(out);
+    if !missing.is_empty() {
+        println!(
+            "Not packed, copy them into {} from a client cache: {}",
+            out.join("client").display(),
+            missing.join(", ")
+        );
+    }
+
+    compiler::compile(&mut ctx, src, out)?;
+    ctx.save()?;
+    return Ok(());
+}
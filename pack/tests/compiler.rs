@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use cache::{ScriptFile, ScriptOpcode, ScriptProvider, COMPILER_VERSION};
+use pack::compiler::ast::Script;
+use pack::compiler::lexer::Lexer;
+use pack::compiler::parser::Parser;
+
+const ENGINE: &str = "[command,mes](string $message)\n[command,tostring](int $num)(string)\n[command,stat](stat $stat)(int)\n";
+
+/// Writes `src` as the only script file of a source tree in the temp dir.
+fn mock_src(name: &str, src: &str) -> PathBuf {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("compiler_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src/pack")).unwrap();
+    std::fs::create_dir_all(dir.join("src/scripts/test")).unwrap();
+    std::fs::write(
+        dir.join("src/pack/obj.pack"),
+        "0=bronze_dagger\n1=iron_dagger\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src/scripts/engine.rs2"), ENGINE).unwrap();
+    std::fs::write(
+        dir.join("src/scripts/test/test.constant"),
+        "^max_level = 99;\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src/scripts/test/test.rs2"), src).unwrap();
+    return dir;
+}
+
+fn compile(name: &str, src: &str) -> ScriptProvider {
+    let dir: PathBuf = mock_src(name, src);
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+    let scripts: ScriptProvider = ScriptProvider::io(
+        dir.join("pack").to_str().unwrap(),
+        COMPILER_VERSION.to_string(),
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+    return scripts;
+}
+
+fn parse(src: &str) -> Vec<Script> {
+    let tokens = Lexer::new("test.rs2", src).tokenize().unwrap();
+    return Parser::new("test.rs2", tokens).scripts().unwrap();
+}
+
+fn codes(script: &ScriptFile) -> Vec<ScriptOpcode> {
+    return script
+        .codes
+        .as_ref()
+        .unwrap()
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+}
+
+#[test]
+fn test_compile_proc() {
+    let scripts: ScriptProvider = compile(
+        "proc",
+        "[proc,fib](int $n)(int)\nif ($n <= 1) {\n    return($n);\n}\nreturn(calc(~fib(calc($n - 1)) + ~fib(calc($n - 2))));\n",
+    );
+    let fib: &ScriptFile = scripts.get_by_name("[proc,fib]").unwrap();
+    assert_eq!(-1, fib.info.lookup);
+    assert_eq!("scripts/test/test.rs2", fib.info.path);
+    assert_eq!(vec![b'i'], fib.info.params);
+    assert_eq!(1, fib.int_arg_count);
+    assert_eq!(
+        vec![
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchLessThanOrEquals,
+            ScriptOpcode::Branch,
+            ScriptOpcode::PushIntLocal,
+            ScriptOpcode::Return,
+        ],
+        codes(fib)[..6]
+    );
+    // branches are relative to the next instruction.
+    assert_eq!(1, fib.int_operands[2]);
+    assert_eq!(2, fib.int_operands[3]);
+    assert_eq!(vec![0, 4], fib.info.pcs[..2]);
+    assert_eq!(vec![2, 3], fib.info.lines[..2]);
+}
+
+#[test]
+fn test_compile_trigger() {
+    let scripts: ScriptProvider = compile(
+        "trigger",
+        "[opheld1,iron_dagger]\nif (stat(attack) < ^max_level) {\n    mes(\"Level: <tostring(stat(attack))>\");\n}\n",
+    );
+    let script: &ScriptFile = scripts.get_by_name("[opheld1,iron_dagger]").unwrap();
+    assert_eq!(140 | (0x2 << 8) | (1 << 10), script.info.lookup);
+    assert_eq!(
+        vec![
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Stat,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::BranchLessThan,
+            ScriptOpcode::Branch,
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Stat,
+            ScriptOpcode::ToString,
+            ScriptOpcode::JoinString,
+            ScriptOpcode::Mes,
+            ScriptOpcode::Return,
+        ],
+        codes(script)
+    );
+    assert_eq!(99, script.int_operands[2]);
+    assert_eq!("Level: ", script.string_operands[5]);
+    assert_eq!(2, script.int_operands[9]);
+}
+
+#[test]
+fn test_compile_switch() {
+    let scripts: ScriptProvider = compile(
+        "switch",
+        "[proc,size](int $n)(string)\nswitch_int ($n) {\n    case 1, 2 : return(\"small\");\n    case default : return(\"large\");\n}\nreturn(\"none\");\n",
+    );
+    let script: &ScriptFile = scripts.get_by_name("[proc,size]").unwrap();
+    let table = &script.switch_tables[0];
+    assert_eq!(2, table.len());
+    assert_eq!(table.get(&1), table.get(&2));
+    assert_eq!(ScriptOpcode::Switch, codes(script)[1]);
+}
+
+#[test]
+fn test_compile_switches() {
+    let scripts: ScriptProvider = compile(
+        "switches",
+        "[proc,sizes](int $a, int $b)(int)\nswitch_int ($a) {\n    case 1 : return(10);\n}\nswitch_int ($b) {\n    case 2 : return(20);\n    case 3 : return(30);\n}\nreturn(0);\n",
+    );
+    let script: &ScriptFile = scripts.get_by_name("[proc,sizes]").unwrap();
+    assert_eq!(2, script.switch_tables.len());
+    assert_eq!(1, script.switch_tables[0].len());
+    assert_eq!(2, script.switch_tables[1].len());
+    assert!(script.switch_tables[0].contains_key(&1));
+    assert!(script.switch_tables[1].contains_key(&3));
+    let switches: Vec<i32> = codes(script)
+        .iter()
+        .enumerate()
+        .filter(|(_, code)| **code == ScriptOpcode::Switch)
+        .map(|(pc, _)| script.int_operands[pc])
+        .collect();
+    assert_eq!(vec![0, 1], switches);
+}
+
+#[test]
+fn test_compile_fails_on_type_errors() {
+    let dir: PathBuf = mock_src(
+        "errors",
+        "[proc,good]()(int)\nreturn(1);\n\n[proc,bad]\ndef_int $x = \"one\";\n\n[proc,worse]\ndef_int $y = \"two\";\n",
+    );
+    let error: String = pack::pack(&dir.join("src"), &dir.join("pack")).unwrap_err();
+    assert!(error.starts_with("2 scripts failed to compile"));
+    assert!(error.contains("[proc,bad]"));
+    assert!(error.contains("[proc,worse]"));
+    assert!(!dir.join("pack/server/script.dat").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compile_missing_return() {
+    let scripts: ScriptProvider = compile(
+        "missing_return",
+        "[proc,a](int $x)(int, string, obj)\nmes(\"hi\");\n\n[proc,b](int $x)(int)\nreturn($x);\n",
+    );
+    // the end returns the defaults of the return types.
+    let a: &ScriptFile = scripts.get_by_name("[proc,a]").unwrap();
+    assert_eq!(
+        vec![
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::Mes,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::PushConstantString,
+            ScriptOpcode::PushConstantInt,
+            ScriptOpcode::Return,
+        ],
+        codes(a)
+    );
+    assert_eq!(0, a.int_operands[2]);
+    assert_eq!("", a.string_operands[3]);
+    assert_eq!(-1, a.int_operands[4]);
+
+    let b: &ScriptFile = scripts.get_by_name("[proc,b]").unwrap();
+    assert_eq!(
+        vec![ScriptOpcode::PushIntLocal, ScriptOpcode::Return],
+        codes(b)
+    );
+}
+
+#[test]
+fn test_parse_header() {
+    let scripts: Vec<Script> =
+        parse("[proc,a](int $x, coord $y)(boolean)\n[proc,b](coord)\n[proc,c]\n");
+    assert_eq!(3, scripts.len());
+    assert_eq!(2, scripts[0].params.len());
+    assert_eq!(vec!["boolean".to_string()], scripts[0].returns);
+    assert!(scripts[1].params.is_empty());
+    assert_eq!(vec!["coord".to_string()], scripts[1].returns);
+    assert_eq!("[proc,c]", scripts[2].name());
+}
+
+#[test]
+fn test_lex_errors() {
+    assert!(Lexer::new("test.rs2", "mes(\"unterminated);")
+        .tokenize()
+        .is_err());
+    assert!(Lexer::new("test.rs2", "/* unterminated")
+        .tokenize()
+        .is_err());
+}
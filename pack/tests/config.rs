@@ -0,0 +1,30 @@
+use pack::{parse_configs, ConfigSection};
+
+#[test]
+fn test_parse_configs() {
+    let src: &str = "//comment\n[bronze_dagger]\nname=Bronze dagger\ncost=10\n\n[iron_dagger]\nname=Iron dagger\n";
+    let sections: Vec<ConfigSection> = parse_configs(src).unwrap();
+    assert_eq!(2, sections.len());
+    assert_eq!("bronze_dagger", sections[0].name);
+    assert_eq!(Some("Bronze dagger"), sections[0].get("name"));
+    assert_eq!(Some("10"), sections[0].get("cost"));
+    assert_eq!(None, sections[1].get("cost"));
+}
+
+#[test]
+fn test_parse_configs_keeps_order() {
+    let sections: Vec<ConfigSection> = parse_configs("[a]\nparam=x,1\nparam=y,2\n").unwrap();
+    assert_eq!(
+        vec![
+            ("param".to_string(), "x,1".to_string()),
+            ("param".to_string(), "y,2".to_string())
+        ],
+        sections[0].lines
+    );
+}
+
+#[test]
+fn test_parse_configs_outside_section() {
+    assert!(parse_configs("name=Bronze dagger\n").is_err());
+    assert!(parse_configs("[a]\nnot a property\n").is_err());
+}
@@ -0,0 +1,57 @@
+use pack::{PackContext, TypedValue};
+
+#[test]
+fn test_weight() {
+    assert_eq!(600, PackContext::weight("600g").unwrap());
+    assert_eq!(2000, PackContext::weight("2kg").unwrap());
+    assert_eq!(1814, PackContext::weight("4lb").unwrap());
+    assert_eq!(141, PackContext::weight("5oz").unwrap());
+    assert!(PackContext::weight("5").is_err());
+}
+
+#[test]
+fn test_coord() {
+    // 0_50_50_22_22 is 3222,3222 on level 0
+    assert_eq!(
+        (3222 << 14) | 3222,
+        PackContext::coord("0_50_50_22_22").unwrap()
+    );
+    assert_eq!(
+        (1 << 28) | (3200 << 14) | 3200,
+        PackContext::coord("1_50_50_0_0").unwrap()
+    );
+    assert!(PackContext::coord("0_50_50").is_err());
+}
+
+#[test]
+fn test_constants() {
+    let mut ctx: PackContext = PackContext::new(std::path::Path::new("."));
+    ctx.constants
+        .insert("stab_style".to_string(), "0".to_string());
+    assert_eq!(0, ctx.int("^stab_style").unwrap());
+    assert!(ctx.int("^slash_style").is_err());
+}
+
+#[test]
+fn test_typed() {
+    let mut ctx: PackContext = PackContext::new(std::path::Path::new("."));
+    assert_eq!(TypedValue::Int(1), ctx.typed("boolean", "yes").unwrap());
+    assert_eq!(TypedValue::Int(-1), ctx.typed("obj", "null").unwrap());
+    assert_eq!(TypedValue::Int(2), ctx.typed("stat", "strength").unwrap());
+    assert_eq!(
+        TypedValue::String("an Apple Pie".to_string()),
+        ctx.typed("string", "an Apple Pie").unwrap()
+    );
+}
+
+#[test]
+fn test_order() {
+    let dir: std::path::PathBuf =
+        std::env::temp_dir().join(format!("pack_context_order_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("pack")).unwrap();
+    std::fs::write(dir.join("pack/model.order"), "3\n1\n\n2\n").unwrap();
+    let ctx: PackContext = PackContext::new(&dir);
+    assert_eq!(Some(vec![3, 1, 2]), ctx.order("model").unwrap());
+    assert_eq!(None, ctx.order("anim").unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,10 @@
+#[cfg(test)]
+mod compiler;
+#[cfg(test)]
+mod config;
+#[cfg(test)]
+mod context;
+#[cfg(test)]
+mod pack_file;
+#[cfg(test)]
+mod packer;
@@ -0,0 +1,34 @@
+use pack::PackFile;
+
+#[test]
+fn test_parse() {
+    let pack: PackFile = PackFile::parse("0=dwarf_remains\n1=tool_kit\n3=ammo_mould\n").unwrap();
+    assert_eq!(4, pack.count());
+    assert_eq!(Some(1), pack.id("tool_kit"));
+    assert_eq!(Some("ammo_mould"), pack.name(3));
+    assert_eq!(None, pack.name(2));
+}
+
+#[test]
+fn test_assign() {
+    let mut pack: PackFile = PackFile::parse("0=dwarf_remains\n1=tool_kit\n").unwrap();
+    assert_eq!(1, pack.assign("tool_kit"));
+    assert_eq!(2, pack.assign("mcannonball"));
+    assert_eq!(3, pack.count());
+}
+
+#[test]
+fn test_save() {
+    let path: std::path::PathBuf =
+        std::env::temp_dir().join(format!("pack_file_test_save_{}.pack", std::process::id()));
+    std::fs::write(&path, "0=dwarf_remains\n").unwrap();
+
+    let mut pack: PackFile = PackFile::io(path.to_str().unwrap()).unwrap();
+    pack.assign("tool_kit");
+    pack.save().unwrap();
+    assert_eq!(
+        "0=dwarf_remains\n1=tool_kit\n",
+        std::fs::read_to_string(&path).unwrap()
+    );
+    std::fs::remove_file(&path).unwrap();
+}
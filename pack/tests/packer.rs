@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+
+use cache::{CacheProvider, ObjProvider, ObjType, ParamValue, ScriptChanges, COMPILER_VERSION};
+use io::JagFile;
+
+/// A source tree in the temp dir, removed once the test is done with it.
+struct MockSrc(PathBuf);
+
+impl std::ops::Deref for MockSrc {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        return &self.0;
+    }
+}
+
+impl Drop for MockSrc {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn mock_src(name: &str) -> MockSrc {
+    let dir: PathBuf = std::env::temp_dir().join(format!("pack_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src/pack")).unwrap();
+    std::fs::create_dir_all(dir.join("src/scripts/test")).unwrap();
+    std::fs::write(
+        dir.join("src/pack/obj.pack"),
+        "0=bronze_dagger\n1=cert_bronze_dagger\n2=template_for_cert\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/pack/model.pack"),
+        "0=model_dagger\n1=model_cert\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/scripts/test/test.obj"),
+        "[bronze_dagger]\nname=Bronze dagger\ndesc=Short but pointy.\nmodel=model_dagger\ncost=10\nweight=1lb\nwearpos=righthand\ntradeable=yes\nparam=damage,^dagger_damage\n\n[template_for_cert]\nmodel=model_cert\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/scripts/test/test.param"),
+        "[damage]\ntype=int\ndefault=0\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/scripts/test/test.constant"),
+        "^dagger_damage = 4\n",
+    )
+    .unwrap();
+    return MockSrc(dir);
+}
+
+#[test]
+fn test_pack_objs() {
+    let dir: MockSrc = mock_src("objs");
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+
+    let objs: ObjProvider = ObjProvider::io(dir.join("pack").to_str().unwrap(), true);
+    assert_eq!(3, objs.objs.len());
+
+    let dagger: &ObjType = objs.get_by_name("bronze_dagger").unwrap();
+    assert_eq!(Some("Bronze dagger".to_string()), dagger.name);
+    assert_eq!(0, dagger.model);
+    assert_eq!(10, dagger.cost);
+    assert_eq!(453, dagger.weight);
+    assert_eq!(Some(3), dagger.wearpos);
+    assert!(dagger.tradeable);
+    match dagger.params.as_ref().and_then(|params| params.get(&0)) {
+        Some(ParamValue::Int(damage)) => assert_eq!(4, *damage),
+        _ => panic!("Missing damage param!"),
+    }
+}
+
+#[test]
+fn test_pack_certs() {
+    let dir: MockSrc = mock_src("certs");
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+
+    let objs: ObjProvider = ObjProvider::io(dir.join("pack").to_str().unwrap(), true);
+    let cert: &ObjType = objs.get_by_name("cert_bronze_dagger").unwrap();
+    assert_eq!(Some(0), cert.certlink);
+    assert_eq!(Some(2), cert.certtemplate);
+    assert_eq!(1, cert.model);
+    assert!(cert.stackable);
+    assert_eq!(
+        Some("Swap this note at any bank for a Bronze dagger.".to_string()),
+        cert.desc
+    );
+}
+
+#[test]
+fn test_pack_client_config() {
+    let dir: MockSrc = mock_src("client_config");
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+
+    let config: JagFile = JagFile::new(std::fs::read(dir.join("pack/client/config")).unwrap());
+    for kind in ["flo", "idk", "loc", "npc", "obj", "seq", "spotanim", "varp"] {
+        assert!(config.read(&format!("{}.dat", kind)).is_some());
+        assert!(config.read(&format!("{}.idx", kind)).is_some());
+    }
+    assert!(config.read("inv.dat").is_none());
+
+    let mut idx: io::Packet = config.read("obj.idx").unwrap();
+    assert_eq!(3, idx.g2());
+    assert_eq!(idx.len(), 2 + 3 * 2);
+}
+
+#[test]
+fn test_pack_missing_archives() {
+    let dir: MockSrc = mock_src("missing_archives");
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+    assert_eq!(
+        pack::UNPACKED_ARCHIVES.to_vec(),
+        pack::missing_archives(&dir.join("pack"))
+    );
+
+    std::fs::write(dir.join("pack/client/title"), [0]).unwrap();
+    assert!(!pack::missing_archives(&dir.join("pack")).contains(&"title"));
+}
+
+#[test]
+fn test_pack_assigns_ids() {
+    let dir: MockSrc = mock_src("assigns_ids");
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+    // params have no pack file yet so one is created.
+    assert_eq!(
+        "0=damage\n",
+        std::fs::read_to_string(dir.join("src/pack/param.pack")).unwrap()
+    );
+}
+
+#[test]
+fn test_pack_unknown_property() {
+    let dir: MockSrc = mock_src("unknown_property");
+    std::fs::write(
+        dir.join("src/scripts/test/bad.obj"),
+        "[bad_obj]\ncolour=red\n",
+    )
+    .unwrap();
+    let result: Result<(), String> = pack::pack(&dir.join("src"), &dir.join("pack"));
+    assert_eq!(
+        Err(format!(
+            "{}:1: [bad_obj] Unknown obj property: colour",
+            dir.join("src/scripts/test/bad.obj").display()
+        )),
+        result
+    );
+}
+
+#[test]
+fn test_pack_reload_scripts() {
+    let dir: MockSrc = mock_src("reload");
+    let scripts: PathBuf = dir.join("src/scripts/test/test.rs2");
+    std::fs::write(dir.join("src/scripts/engine.rs2"), "").unwrap();
+    std::fs::write(
+        &scripts,
+        "[proc,kept]\nreturn;\n\n[proc,edited]\nreturn;\n\n[proc,deleted]\nreturn;\n",
+    )
+    .unwrap();
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+    let mut cache: CacheProvider = CacheProvider::new(
+        dir.join("pack").to_str().unwrap(),
+        COMPILER_VERSION.to_string(),
+        true,
+    );
+    assert!(cache.script_provider.get_by_name("[proc,deleted]").is_ok());
+
+    std::fs::write(
+        &scripts,
+        "[proc,kept]\nreturn;\n\n[proc,edited]\ndef_int $x = 1;\nreturn;\n\n[proc,created]\nreturn;\n",
+    )
+    .unwrap();
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+    let changes: ScriptChanges = cache.reload_scripts().unwrap();
+    assert_eq!(vec!["[proc,created]"], changes.added);
+    assert_eq!(vec!["[proc,edited]"], changes.changed);
+    assert_eq!(vec!["[proc,deleted]"], changes.removed);
+    assert!(cache.script_provider.get_by_name("[proc,created]").is_ok());
+    assert!(cache.script_provider.get_by_name("[proc,deleted]").is_err());
+
+    // a broken reload keeps the scripts already loaded.
+    std::fs::remove_file(dir.join("pack/server/script.dat")).unwrap();
+    assert!(cache.reload_scripts().is_err());
+    assert!(cache.script_provider.get_by_name("[proc,created]").is_ok());
+}
+
+#[test]
+fn test_pack_interfaces() {
+    let dir: MockSrc = mock_src("interfaces");
+    std::fs::write(
+        dir.join("src/pack/interface.pack"),
+        "0=bank\n1=bank:com_0\n2=bank:title\n",
+    )
+    .unwrap();
+    // written in the order of the order file, not by id
+    std::fs::write(dir.join("src/pack/interface.order"), "0\n2\n1\n").unwrap();
+    std::fs::write(
+        dir.join("src/scripts/test/bank.if"),
+        "type=overlay\n\n[com_0]\ntype=layer\nx=10\ny=20\nwidth=100\nheight=50\n\n[title]\ntype=text\nlayer=com_0\nx=5\ny=-4\nwidth=100\nheight=12\nfont=b12\ntext=Bank\ncolour=0xFF9040\nbuttontype=normal\noption=Close\n",
+    )
+    .unwrap();
+    pack::pack(&dir.join("src"), &dir.join("pack")).unwrap();
+
+    let archive: JagFile = JagFile::new(std::fs::read(dir.join("pack/client/interface")).unwrap());
+    let mut data: io::Packet = archive.read("data").unwrap();
+    assert_eq!(3, data.g2());
+
+    // the interface itself, a layer holding com_0
+    assert_eq!(65535, data.g2());
+    assert_eq!(0, data.g2());
+    assert_eq!(0, data.g2());
+    assert_eq!(0, data.g1()); // layer
+    assert_eq!(0, data.g1()); // buttontype
+    assert_eq!(0, data.g2());
+    assert_eq!(512, data.g2());
+    assert_eq!(334, data.g2());
+    assert_eq!([0, 0, 0], [data.g1(), data.g1(), data.g1()]);
+    assert_eq!(0, data.g2()); // scroll
+    assert_eq!(0, data.g1()); // hide
+    assert_eq!(1, data.g2());
+    assert_eq!([1, 10, 20], [data.g2(), data.g2(), data.g2()]);
+
+    // title comes before com_0
+    assert_eq!(2, data.g2());
+    assert_eq!(4, data.g1()); // text
+    assert_eq!(1, data.g1()); // normal
+    assert_eq!(0, data.g2());
+    assert_eq!(100, data.g2());
+    assert_eq!(12, data.g2());
+    assert_eq!([0, 0, 0], [data.g1(), data.g1(), data.g1()]);
+    assert_eq!([0, 2, 0], [data.g1(), data.g1(), data.g1()]); // center, font, shadowed
+    assert_eq!("Bank", data.gjstr(10));
+    assert_eq!("", data.gjstr(10));
+    assert_eq!(0xFF9040, data.g4s());
+    assert_eq!([0, 0], [data.g4s(), data.g4s()]);
+    assert_eq!("Close", data.gjstr(10));
+
+    assert_eq!(1, data.g2());
+    assert_eq!(0, data.g1()); // layer
+    data.pos += 1 + 2 + 2 + 2 + 3 + 2 + 1;
+    assert_eq!(1, data.g2());
+    assert_eq!([2, 5, 65532], [data.g2(), data.g2(), data.g2()]);
+    assert_eq!(data.len(), data.pos);
+}
+
+#[test]
+fn test_pack_interface_unknown_property() {
+    let dir: MockSrc = mock_src("interface_unknown_property");
+    std::fs::write(
+        dir.join("src/scripts/test/bank.if"),
+        "[com_0]\ntype=rect\nborder=yes\n",
+    )
+    .unwrap();
+    let result: Result<(), String> = pack::pack(&dir.join("src"), &dir.join("pack"));
+    assert_eq!(
+        Err("[bank:com_0] Unknown interface property: border".to_string()),
+        result
+    );
+}
+
+#[test]
+fn test_pack_config_too_large() {
+    let dir: MockSrc = mock_src("config_too_large");
+    std::fs::write(
+        dir.join("src/scripts/test/big.obj"),
+        format!("[big_obj]\ndesc={}\n", "a".repeat(70000)),
+    )
+    .unwrap();
+    let result: Result<(), String> = pack::pack(&dir.join("src"), &dir.join("pack"));
+    assert_eq!(
+        Err("[big_obj] obj is 70003 bytes, more than the client can index".to_string()),
+        result
+    );
+}
+
+#[test]
+fn test_pack_enum_invalid_default() {
+    let dir: MockSrc = mock_src("enum_invalid_default");
+    std::fs::write(
+        dir.join("src/scripts/test/test.enum"),
+        "[dagger_stats]\ninputtype=obj\noutputtype=int\n\n[stat_members]\ninputtype=stat\noutputtype=int\ndefault=stat\nval=attack,0\n",
+    )
+    .unwrap();
+    let result: Result<(), String> = pack::pack(&dir.join("src"), &dir.join("pack"));
+    assert_eq!(
+        Err(format!(
+            "{}:5: [stat_members] Invalid int: stat",
+            dir.join("src/scripts/test/test.enum").display()
+        )),
+        result
+    );
+}